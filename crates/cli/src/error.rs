@@ -23,9 +23,17 @@ pub enum Error {
     #[error("Xml Error: {0}")]
     Xml(#[from] quick_xml::Error),
 
-    #[error("Unknown Game: {0}")]
-    UnknownGame(String),
+    #[error("Unknown Game: {id}{}", did_you_mean(.suggestions))]
+    UnknownGame {
+        id: String,
+        suggestions: Vec<String>,
+    },
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        return String::new();
+    }
 
-    #[error("Invalid hostname: {0}")]
-    InvalidHostname(String),
+    format!(" (did you mean {}?)", suggestions.join(", "))
 }
@@ -1,9 +1,7 @@
-use std::net::{IpAddr, ToSocketAddrs};
-
 use clap::{Parser, Subcommand, ValueEnum};
 use gamedig::{
     games::*,
-    protocols::types::{CommonResponse, ExtraRequestSettings, TimeoutSettings},
+    protocols::types::{CommonResponse, ExtraRequestSettings, SocketSettings, TimeoutSettings},
 };
 
 mod error;
@@ -35,6 +33,8 @@ struct Cli {
     action: Action,
 }
 
+// Only one is ever built, so the size of the query options doesn't matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 enum Action {
     /// Query game server information
@@ -44,6 +44,13 @@ enum Action {
         #[arg(short, long)]
         game: String,
 
+        /// Optional file of additional game definitions, overriding the
+        /// library ones with the same identifier. Parsed as TOML if its name
+        /// ends with `.toml` (requires the `toml` feature), as JSON otherwise.
+        #[cfg(feature = "games_file")]
+        #[arg(long)]
+        games_file: Option<std::path::PathBuf>,
+
         /// Hostname or IP address of the server.
         #[arg(short, long)]
         ip: String,
@@ -53,6 +60,11 @@ enum Action {
         #[arg(short, long)]
         port: Option<u16>,
 
+        /// Optional port players connect to, the query port being derived
+        /// from it for games that answer queries on another port.
+        #[arg(long, conflicts_with = "port")]
+        game_port: Option<u16>,
+
         /// Specifies the output format
         #[arg(short, long, default_value = "debug", value_enum)]
         format: OutputFormat,
@@ -77,6 +89,10 @@ enum Action {
         #[command(flatten, next_help_heading = "Timeouts")]
         timeout_settings: Option<TimeoutSettings>,
 
+        /// Optional settings of the sockets the query uses
+        #[command(flatten, next_help_heading = "Network")]
+        socket_settings: Option<SocketSettings>,
+
         /// Optional extra settings for the server query
         #[command(flatten, next_help_heading = "Query options")]
         extra_options: Option<ExtraRequestSettings>,
@@ -119,79 +135,39 @@ enum OutputFormat {
     BsonBase64,
 }
 
-/// Attempt to find a game from the [library game definitions](GAMES) based on
-/// its unique identifier.
+/// Attempt to find a game from the game definitions (the [library
+/// ones](GAMES) and the loaded ones) based on its unique identifier or one of
+/// its aliases.
 ///
 /// # Arguments
+/// * `registry` - The game definitions.
 /// * `game_id` - A string slice containing the unique game identifier.
 ///
 /// # Returns
-/// * Result<&'static [Game]> - On sucess returns a reference to the game
-///   definition; on failure returns a [Error::UnknownGame] error.
-fn find_game(game_id: &str) -> Result<&'static Game> {
-    // Attempt to retrieve the game from the predefined game list
-    GAMES
-        .get(game_id)
-        .ok_or_else(|| Error::UnknownGame(game_id.to_string()))
-}
-
-/// Resolve an IP address by either parsing an IP address or doing a DNS lookup.
-/// In the case of DNS lookup update extra request options with the hostname.
-///
-/// # Arguments
-/// * `host` - A string slice containing the IP address or hostname of a server
-///   to resolve.
-/// * `extra_options` - Mutable reference to extra options for the game query.
-///
-/// # Returns
-/// * `Result<IpAddr>` - On sucess returns a resolved IP address; on failure
-///   returns an [Error::InvalidHostname] error.
-fn resolve_ip_or_domain<T: AsRef<str>>(host: T, extra_options: &mut Option<ExtraRequestSettings>) -> Result<IpAddr> {
-    let host_str = host.as_ref();
-    if let Ok(parsed_ip) = host_str.parse() {
-        Ok(parsed_ip)
-    } else {
-        set_hostname_if_missing(host_str, extra_options);
-        resolve_domain(host_str)
-    }
-}
-
-/// Resolve a domain name to one of its IP addresses (the first one returned).
-///
-/// # Arguments
-/// * `domain` - A string slice containing the domain name to lookup.
-///
-/// # Returns
-/// * `Result<IpAddr>` - On success, returns one of the resolved IP addresses;
-///   on failure returns an [Error::InvalidHostname] error.
-fn resolve_domain(domain: &str) -> Result<IpAddr> {
-    // Append a dummy port to perform socket address resolution and then extract the
-    // IP
-    Ok(format!("{}:0", domain)
-        .to_socket_addrs()
-        .map_err(|_| Error::InvalidHostname(domain.to_string()))?
-        .next()
-        .ok_or_else(|| Error::InvalidHostname(domain.to_string()))?
-        .ip())
-}
-
-/// Sets the hostname on extra request settings if it is not already set.
-///
-/// # Arguments
-/// * `host` - A string slice containing the hostname.
-/// * `extra_options` - A mutable reference to optional [ExtraRequestSettings].
-fn set_hostname_if_missing(host: &str, extra_options: &mut Option<ExtraRequestSettings>) {
-    if let Some(extra_options) = extra_options {
-        if extra_options.hostname.is_none() {
-            // If extra_options exists but hostname is None overwrite hostname in place
-            extra_options.hostname = Some(host.to_string())
+/// * Result<&Game> - On sucess returns a reference to the game definition; on
+///   failure returns a [Error::UnknownGame] error with the closest matching
+///   games.
+fn find_game<'a>(registry: &'a GameRegistry, game_id: &str) -> Result<&'a Game> {
+    // Attempt to retrieve the game from the registry
+    match registry.lookup(game_id) {
+        Some((_, game)) => Ok(game),
+        None => {
+            Err(Error::UnknownGame {
+                id: game_id.to_string(),
+                suggestions: registry
+                    .search(game_id)
+                    .into_iter()
+                    .take(MAX_SUGGESTIONS)
+                    .map(|game_match| game_match.id.to_string())
+                    .collect(),
+            })
         }
-    } else {
-        // If extra_options is None create default settings with hostname
-        *extra_options = Some(ExtraRequestSettings::default().set_hostname(host.to_string()));
     }
 }
 
+/// How many games to suggest for an unknown game identifier.
+const MAX_SUGGESTIONS: usize = 3;
+
 /// Output the result of a query to stdout.
 ///
 /// # Arguments
@@ -425,24 +401,45 @@ fn main() -> Result<()> {
     match args.action {
         Action::Query {
             game,
+            #[cfg(feature = "games_file")]
+            games_file,
             ip,
             port,
+            game_port,
             format,
             output_mode,
             #[cfg(feature = "packet_capture")]
             capture,
             timeout_settings,
+            socket_settings,
             extra_options,
         } => {
             // Process the query command
-            let game = find_game(&game)?;
-            let mut extra_options = extra_options;
-            let ip = resolve_ip_or_domain(&ip, &mut extra_options)?;
+            #[cfg_attr(not(feature = "games_file"), allow(unused_mut))]
+            let mut registry = GameRegistry::new();
+            #[cfg(feature = "games_file")]
+            if let Some(games_file) = games_file {
+                registry.load_file(games_file)?;
+            }
+
+            let game = find_game(&registry, &game)?;
+            let port = match game_port {
+                Some(game_port) => Some(game.query_port(game_port)?),
+                None => port,
+            };
 
             #[cfg(feature = "packet_capture")]
             gamedig::capture::setup_capture(capture);
 
-            let result = query_with_timeout_and_extra_settings(game, &ip, port, timeout_settings, extra_options)?;
+            let result = query_host_with_resolver(
+                &gamedig::dns::SystemResolver::default(),
+                game,
+                &ip,
+                port,
+                timeout_settings,
+                extra_options,
+                &socket_settings.unwrap_or_default(),
+            )?;
             output_result(output_mode, format, result.as_ref());
         }
         Action::Source => {
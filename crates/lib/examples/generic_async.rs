@@ -0,0 +1,41 @@
+use gamedig::{query_with_timeout_and_extra_settings_async, TimeoutSettings, GAMES};
+
+use std::net::{SocketAddr, ToSocketAddrs};
+
+/// Same as the `generic` example, but using the async query functions.
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+
+    let game_name = args.next().expect("Must provide a game");
+    let hostname = args.next().expect("Must provide an address");
+    // Use to_socket_addrs to resolve hostname to IP
+    let addr: SocketAddr = format!("{}:0", hostname)
+        .to_socket_addrs()
+        .unwrap()
+        .next()
+        .expect("Could not lookup host");
+    let port: Option<u16> = args.next().map(|s| s.parse().unwrap());
+
+    let game = GAMES
+        .get(&game_name)
+        .expect("Game doesn't exist, run the generic example without arguments to see a list of games");
+
+    let extra_settings = game
+        .request_settings
+        .clone()
+        .set_hostname(hostname.to_string())
+        .set_check_app_id(false);
+
+    let response = query_with_timeout_and_extra_settings_async(
+        game,
+        &addr.ip(),
+        port,
+        Some(TimeoutSettings::default()),
+        Some(extra_settings),
+    )
+    .await
+    .unwrap();
+
+    println!("Response: {:#?}", response.as_json());
+}
@@ -23,7 +23,8 @@ pub(crate) enum Direction {
 /// Defines the protocol of a network packet.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum Protocol {
-    /// Transmission Control Protocol.
+    /// Transmission Control Protocol (only used by games).
+    #[cfg_attr(not(feature = "games"), allow(dead_code))]
     Tcp,
     /// User Datagram Protocol.
     Udp,
@@ -6,8 +6,8 @@ use crate::{
         packet::{Direction, Protocol},
         writer::{Writer, CAPTURE_WRITER},
     },
-    protocols::types::TimeoutSettings,
-    socket::{Socket, TcpSocketImpl, UdpSocketImpl},
+    protocols::types::{QueryScope, TimeoutSettings},
+    socket::{Socket, UdpSocketImpl},
     GDResult,
 };
 
@@ -35,7 +35,9 @@ pub(crate) trait ProtocolProvider {
 }
 
 /// Represents the TCP protocol provider.
+#[cfg(feature = "games")]
 pub(crate) struct ProtocolTCP;
+#[cfg(feature = "games")]
 impl ProtocolProvider for ProtocolTCP {
     fn protocol() -> Protocol { Protocol::Tcp }
 }
@@ -59,6 +61,8 @@ pub(crate) struct WrappedCaptureSocket<I: Socket, P: ProtocolProvider> {
 }
 
 impl<I: Socket, P: ProtocolProvider> Socket for WrappedCaptureSocket<I, P> {
+    const KIND: crate::transport::TransportKind = I::KIND;
+
     /// Creates a new wrapped socket for capturing packets.
     ///
     /// Initializes a new socket of type `I`, wrapping it to enable packet
@@ -68,13 +72,14 @@ impl<I: Socket, P: ProtocolProvider> Socket for WrappedCaptureSocket<I, P> {
     /// # Arguments
     /// * `address` - The address to connect the socket to.
     /// * `timeout_settings` - Optional timeout settings for the socket.
+    /// * `scope` - The query the socket is used for.
     ///
     /// # Returns
     /// A `GDResult` containing either the wrapped socket or an error.
-    fn new(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>) -> GDResult<Self>
+    fn new(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self>
     where Self: Sized {
         let v = Self {
-            inner: I::new(address, timeout_settings)?,
+            inner: I::new(address, timeout_settings, scope)?,
             remote_address: *address,
             _protocol: PhantomData,
         };
@@ -166,8 +171,17 @@ impl<I: Socket, P: ProtocolProvider> Socket for WrappedCaptureSocket<I, P> {
     ///
     /// # Returns
     /// The remote port number.
+    #[cfg(feature = "games")]
     fn port(&self) -> u16 { self.inner.port() }
 
+    /// Returns the latency of the wrapped socket's first exchange.
+    ///
+    /// Delegates the operation to the inner socket implementation.
+    ///
+    /// # Returns
+    /// The latency, if a response was received.
+    fn latency(&self) -> Option<std::time::Duration> { self.inner.latency() }
+
     /// Returns the local SocketAddr of the wrapped socket.
     ///
     /// Delegates the operation to the inner socket implementation.
@@ -211,4 +225,5 @@ pub(crate) type CapturedUdpSocket = WrappedCaptureSocket<UdpSocketImpl, Protocol
 /// This type captures and processes TCP packets, wrapping around standard
 /// TCP socket functionalities with additional packet capture
 /// capabilities.
-pub(crate) type CapturedTcpSocket = WrappedCaptureSocket<TcpSocketImpl, ProtocolTCP>;
+#[cfg(feature = "games")]
+pub(crate) type CapturedTcpSocket = WrappedCaptureSocket<crate::socket::TcpSocketImpl, ProtocolTCP>;
@@ -0,0 +1,482 @@
+//! Hostname resolution, to query servers by their hostname (see
+//! [query_host](crate::games::query::query_host)).
+//!
+//! Lookups go through a [Resolver], the [SystemResolver] by default, another
+//! one (a stub in tests, a caching or DNS-over-HTTPS one...) can be given to
+//! [resolve_host](crate::games::query::resolve_host).
+
+use crate::buffer::Buffer;
+use crate::protocols::types::TimeoutSettings;
+use crate::utils::random;
+use crate::GDErrorKind::{HostLookup, InvalidInput, PacketBad, PacketReceive, PacketSend, SocketBind, SocketConnect};
+use crate::GDResult;
+
+use byteorder::BigEndian;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+
+/// A SRV record, pointing a service to the host and port providing it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SrvRecord {
+    /// Records with the lowest priority are to be used first.
+    pub priority: u16,
+    /// Among records of the same priority, higher weights are to be used more.
+    pub weight: u16,
+    /// The port of the service.
+    pub port: u16,
+    /// The host of the service (`.` meaning that the service isn't
+    /// available).
+    pub target: String,
+}
+
+/// Looks up the records needed to query servers by hostname.
+///
+/// The timeout settings are the ones of the query (for the resolver to apply
+/// as it sees fit), the lookups count towards their query deadline.
+pub trait Resolver: Send + Sync {
+    /// The addresses (A and AAAA records) of `host`.
+    fn lookup_ip(&self, host: &str, timeout_settings: &Option<TimeoutSettings>) -> GDResult<Vec<IpAddr>>;
+
+    /// The SRV records of `name` (e.g. `_minecraft._tcp.example.com`), empty
+    /// if there are none.
+    fn lookup_srv(&self, name: &str, timeout_settings: &Option<TimeoutSettings>) -> GDResult<Vec<SrvRecord>>;
+}
+
+/// Resolves addresses with the operating system and SRV records by asking a
+/// nameserver, by default the first one of `/etc/resolv.conf`.
+///
+/// Address lookups can't be timed out, SRV ones use the read, write and
+/// connect timeouts (shortened to the query deadline). SRV lookups fail with
+/// GDErrorKind::[HostLookup] on systems without `/etc/resolv.conf` unless a
+/// nameserver is given.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemResolver {
+    nameserver: Option<SocketAddr>,
+}
+
+impl SystemResolver {
+    /// Use `nameserver` for SRV lookups.
+    pub const fn with_nameserver(nameserver: SocketAddr) -> Self {
+        Self {
+            nameserver: Some(nameserver),
+        }
+    }
+}
+
+impl Resolver for SystemResolver {
+    fn lookup_ip(&self, host: &str, _timeout_settings: &Option<TimeoutSettings>) -> GDResult<Vec<IpAddr>> {
+        // A port is needed for the lookup, it isn't used.
+        let addresses = (host, 0)
+            .to_socket_addrs()
+            .map_err(|e| HostLookup.context(e))?;
+
+        Ok(addresses.map(|address| address.ip()).collect())
+    }
+
+    fn lookup_srv(&self, name: &str, timeout_settings: &Option<TimeoutSettings>) -> GDResult<Vec<SrvRecord>> {
+        let nameserver = self
+            .nameserver
+            .or_else(system_nameserver)
+            .ok_or_else(|| HostLookup.context("No nameserver found to look up SRV records"))?;
+
+        query_srv(&nameserver, name, timeout_settings)
+    }
+}
+
+/// The first (usable) nameserver of `/etc/resolv.conf`.
+fn system_nameserver() -> Option<SocketAddr> {
+    let resolv_conf = std::fs::read_to_string("/etc/resolv.conf").ok()?;
+    parse_nameserver(&resolv_conf)
+}
+
+fn parse_nameserver(resolv_conf: &str) -> Option<SocketAddr> {
+    resolv_conf.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            // Scoped IPv6 addresses (`fe80::1%eth0`) don't parse and are skipped.
+            Some("nameserver") => words.next()?.parse().ok().map(|ip| SocketAddr::new(ip, 53)),
+            _ => None,
+        }
+    })
+}
+
+/// The first address of `host`, which may also be an IP address.
+pub fn lookup_host(
+    resolver: &dyn Resolver,
+    host: &str,
+    timeout_settings: &Option<TimeoutSettings>,
+) -> GDResult<IpAddr> {
+    if let Ok(ip) = host.parse() {
+        return Ok(ip);
+    }
+
+    resolver
+        .lookup_ip(host, timeout_settings)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| HostLookup.context(format!("No addresses found for {host}")))
+}
+
+/// The record to use among `records`: one of the lowest priority, the
+/// heaviest of them, ignoring those saying that the service isn't available.
+pub fn pick_srv(records: &[SrvRecord]) -> Option<&SrvRecord> {
+    records
+        .iter()
+        .filter(|record| !record.target.is_empty() && record.target != ".")
+        .min_by_key(|record| (record.priority, u16::MAX - record.weight))
+}
+
+const SRV: u16 = 33;
+const CLASS_IN: u16 = 1;
+/// Flag of the responses that didn't fit in a datagram, to be asked again
+/// over TCP.
+const TRUNCATED: u16 = 0x0200;
+
+fn srv_request(id: u16, name: &str) -> GDResult<Vec<u8>> {
+    let mut request = Vec::with_capacity(12 + name.len() + 6);
+    request.extend(id.to_be_bytes());
+    // Recursion desired, one question.
+    request.extend([0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(InvalidInput.context(format!("Invalid domain name {name:?}")));
+        }
+
+        request.push(label.len() as u8);
+        request.extend(label.as_bytes());
+    }
+
+    request.push(0);
+    request.extend(SRV.to_be_bytes());
+    request.extend(CLASS_IN.to_be_bytes());
+
+    Ok(request)
+}
+
+/// Skip a (possibly compressed) name.
+fn skip_name(buffer: &mut Buffer<BigEndian>) -> GDResult<()> {
+    loop {
+        match buffer.read::<u8>()? {
+            0 => return Ok(()),
+            // A pointer ends the name.
+            length if length & 0xC0 == 0xC0 => return buffer.move_cursor(1),
+            length => buffer.move_cursor(length.into())?,
+        }
+    }
+}
+
+/// Read the (possibly compressed) name at `offset` of a response.
+fn read_name(response: &[u8], mut offset: usize) -> GDResult<String> {
+    let byte_at = |offset: usize| {
+        response
+            .get(offset)
+            .copied()
+            .ok_or_else(|| PacketBad.context("DNS name out of bounds"))
+    };
+
+    let mut labels = Vec::new();
+    // Bounded, as pointers could loop.
+    for _ in 0 .. 128 {
+        match byte_at(offset)? {
+            0 => return Ok(labels.join(".")),
+            length if length & 0xC0 == 0xC0 => {
+                offset = usize::from(length & 0x3F) << 8 | usize::from(byte_at(offset + 1)?);
+            }
+            length => {
+                let label = response
+                    .get(offset + 1 .. offset + 1 + usize::from(length))
+                    .ok_or_else(|| PacketBad.context("DNS name out of bounds"))?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                offset += 1 + usize::from(length);
+            }
+        }
+    }
+
+    Err(PacketBad.context("DNS name has too many labels"))
+}
+
+fn parse_srv_response(id: u16, response: &[u8]) -> GDResult<Vec<SrvRecord>> {
+    let mut buffer = Buffer::<BigEndian>::new(response);
+
+    if buffer.read::<u16>()? != id {
+        return Err(PacketBad.context("DNS response ID did not match"));
+    }
+
+    match buffer.read::<u16>()? & 0x000F {
+        0 => {}
+        // No such name.
+        3 => return Ok(Vec::new()),
+        code => return Err(HostLookup.context(format!("DNS server answered with error {code}"))),
+    }
+
+    let questions = buffer.read::<u16>()?;
+    let answers = buffer.read::<u16>()?;
+    // Authority and additional records.
+    buffer.move_cursor(4)?;
+
+    for _ in 0 .. questions {
+        skip_name(&mut buffer)?;
+        // Type and class.
+        buffer.move_cursor(4)?;
+    }
+
+    let mut records = Vec::new();
+    for _ in 0 .. answers {
+        skip_name(&mut buffer)?;
+        let kind = buffer.read::<u16>()?;
+        // Class and TTL.
+        buffer.move_cursor(6)?;
+        let length = buffer.read::<u16>()?;
+        let end = buffer.current_position() + usize::from(length);
+
+        if kind == SRV {
+            records.push(SrvRecord {
+                priority: buffer.read()?,
+                weight: buffer.read()?,
+                port: buffer.read()?,
+                target: read_name(response, buffer.current_position())?,
+            });
+        }
+
+        buffer.move_cursor(end as isize - buffer.current_position() as isize)?;
+    }
+
+    Ok(records)
+}
+
+fn is_truncated(response: &[u8]) -> bool {
+    response
+        .get(2 .. 4)
+        .is_some_and(|flags| u16::from_be_bytes([flags[0], flags[1]]) & TRUNCATED != 0)
+}
+
+/// Ask `nameserver` for the SRV records of `name`, over UDP then over TCP if
+/// the answer was truncated.
+fn query_srv(
+    nameserver: &SocketAddr,
+    name: &str,
+    timeout_settings: &Option<TimeoutSettings>,
+) -> GDResult<Vec<SrvRecord>> {
+    let id = random() as u16;
+    let request = srv_request(id, name)?;
+
+    let mut response = exchange_udp(nameserver, &request, timeout_settings)?;
+    if is_truncated(&response) {
+        response = exchange_tcp(nameserver, &request, timeout_settings)?;
+    }
+
+    parse_srv_response(id, &response)
+}
+
+fn exchange_udp(
+    nameserver: &SocketAddr,
+    request: &[u8],
+    timeout_settings: &Option<TimeoutSettings>,
+) -> GDResult<Vec<u8>> {
+    let (read, write) = TimeoutSettings::get_read_and_write_or_defaults(timeout_settings);
+
+    let local: IpAddr = match nameserver {
+        SocketAddr::V4(_) => Ipv4Addr::UNSPECIFIED.into(),
+        SocketAddr::V6(_) => Ipv6Addr::UNSPECIFIED.into(),
+    };
+    let socket = UdpSocket::bind((local, 0)).map_err(|e| SocketBind.context(e))?;
+    socket
+        .set_read_timeout(read)
+        .map_err(|e| SocketConnect.context(e))?;
+    socket
+        .set_write_timeout(write)
+        .map_err(|e| SocketConnect.context(e))?;
+    socket
+        .connect(nameserver)
+        .map_err(|e| SocketConnect.context(e))?;
+
+    socket.send(request).map_err(|e| PacketSend.context(e))?;
+
+    let mut response = vec![0; 4096];
+    let size = socket
+        .recv(&mut response)
+        .map_err(|e| PacketReceive.context(e))?;
+    response.truncate(size);
+
+    Ok(response)
+}
+
+/// Messages over TCP are prefixed by their length.
+fn exchange_tcp(
+    nameserver: &SocketAddr,
+    request: &[u8],
+    timeout_settings: &Option<TimeoutSettings>,
+) -> GDResult<Vec<u8>> {
+    let (read, write) = TimeoutSettings::get_read_and_write_or_defaults(timeout_settings);
+
+    let stream = match TimeoutSettings::get_connect_or_default(timeout_settings) {
+        Some(timeout) => TcpStream::connect_timeout(nameserver, timeout),
+        None => TcpStream::connect(nameserver),
+    };
+    let mut stream = stream.map_err(|e| SocketConnect.context(e))?;
+    stream
+        .set_read_timeout(read)
+        .map_err(|e| SocketConnect.context(e))?;
+    stream
+        .set_write_timeout(write)
+        .map_err(|e| SocketConnect.context(e))?;
+
+    let length = u16::try_from(request.len()).map_err(|e| InvalidInput.context(e))?;
+    stream
+        .write_all(&[&length.to_be_bytes(), request].concat())
+        .map_err(|e| PacketSend.context(e))?;
+
+    let mut length = [0; 2];
+    stream
+        .read_exact(&mut length)
+        .map_err(|e| PacketReceive.context(e))?;
+    let mut response = vec![0; usize::from(u16::from_be_bytes(length))];
+    stream
+        .read_exact(&mut response)
+        .map_err(|e| PacketReceive.context(e))?;
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn nameserver_from_resolv_conf() {
+        let resolv_conf =
+            "# comment\nsearch lan\nnameserver fe80::1%eth0\nnameserver 192.168.1.1\nnameserver 1.1.1.1\n";
+        assert_eq!(
+            parse_nameserver(resolv_conf),
+            Some("192.168.1.1:53".parse().unwrap())
+        );
+        assert_eq!(parse_nameserver("search lan\n"), None);
+    }
+
+    #[test]
+    fn pick_record() {
+        let record = |priority, weight, target: &str| {
+            SrvRecord {
+                priority,
+                weight,
+                port: 25565,
+                target: target.to_string(),
+            }
+        };
+
+        let records = [
+            record(10, 5, "b.example.com"),
+            record(5, 1, "c.example.com"),
+            record(5, 9, "a.example.com"),
+            record(0, 0, "."),
+        ];
+        assert_eq!(pick_srv(&records).unwrap().target, "a.example.com");
+        assert!(pick_srv(&records[3 ..]).is_none());
+    }
+
+    /// The answer to `request` with a record whose target is compressed.
+    fn srv_response(request: &[u8]) -> Vec<u8> {
+        let mut response = request.to_vec();
+        // A response without errors, with one answer.
+        response[2 .. 4].copy_from_slice(&[0x81, 0x80]);
+        response[6 .. 8].copy_from_slice(&[0, 1]);
+        // Name pointing to the question, type SRV, class IN, TTL.
+        response.extend([0xC0, 12, 0, 33, 0, 1, 0, 0, 0, 60]);
+        // Length, priority 0, weight 5, port 25566, target "mc." + "example.com" of
+        // the question.
+        response.extend([0, 11, 0, 0, 0, 5, 0x63, 0xDE, 2, b'm', b'c', 0xC0, 12 + 16]);
+        response
+    }
+
+    fn assert_srv_record(records: &[SrvRecord]) {
+        assert_eq!(
+            records,
+            [SrvRecord {
+                priority: 0,
+                weight: 5,
+                port: 25566,
+                target: "mc.example.com".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn srv_lookup() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let nameserver = server.local_addr().unwrap();
+        let server_thread = std::thread::spawn(move || {
+            let mut request = [0; 512];
+            let (size, client) = server.recv_from(&mut request).unwrap();
+            server
+                .send_to(&srv_response(&request[.. size]), client)
+                .unwrap();
+        });
+
+        let resolver = SystemResolver::with_nameserver(nameserver);
+        let records = resolver
+            .lookup_srv("_minecraft._tcp.example.com", &None)
+            .unwrap();
+        server_thread.join().unwrap();
+
+        assert_srv_record(&records);
+    }
+
+    #[test]
+    fn truncated_srv_lookup() {
+        // A nameserver answering over UDP with a truncated response, then over TCP.
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let nameserver = server.local_addr().unwrap();
+        let listener = std::net::TcpListener::bind(nameserver).unwrap();
+        let server_thread = std::thread::spawn(move || {
+            let mut request = [0; 512];
+            let (size, client) = server.recv_from(&mut request).unwrap();
+            let mut truncated = request[.. size].to_vec();
+            truncated[2 .. 4].copy_from_slice(&[0x83, 0x80]);
+            server.send_to(&truncated, client).unwrap();
+
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut length = [0; 2];
+            stream.read_exact(&mut length).unwrap();
+            let mut request = vec![0; usize::from(u16::from_be_bytes(length))];
+            stream.read_exact(&mut request).unwrap();
+
+            let response = srv_response(&request);
+            stream
+                .write_all(&(response.len() as u16).to_be_bytes())
+                .unwrap();
+            stream.write_all(&response).unwrap();
+        });
+
+        let resolver = SystemResolver::with_nameserver(nameserver);
+        let records = resolver
+            .lookup_srv("_minecraft._tcp.example.com", &None)
+            .unwrap();
+        server_thread.join().unwrap();
+
+        assert_srv_record(&records);
+    }
+
+    #[test]
+    fn srv_lookup_timeout() {
+        // Never answers.
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let timeout_settings = TimeoutSettings::new(Some(Duration::from_millis(50)), None, None, 0).unwrap();
+
+        let resolver = SystemResolver::with_nameserver(server.local_addr().unwrap());
+        let started = std::time::Instant::now();
+        let error = resolver
+            .lookup_srv("_minecraft._tcp.example.com", &Some(timeout_settings))
+            .unwrap_err();
+
+        assert_eq!(error.kind, PacketReceive);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn invalid_name() {
+        assert!(srv_request(1, "a..b").is_err());
+        assert!(srv_request(1, &"a".repeat(64)).is_err());
+    }
+}
@@ -1,6 +1,8 @@
+use crate::protocols::types::CommonResponse;
 use crate::GDErrorKind;
 use std::error::Error;
 use std::fmt::Formatter;
+use std::net::SocketAddr;
 use std::{backtrace, fmt};
 
 pub(crate) type ErrorSource = Box<dyn Error + 'static + Send + Sync>;
@@ -32,10 +34,43 @@ pub(crate) type ErrorSource = Box<dyn Error + 'static + Send + Sync>;
 /// use gamedig::{GDError, GDErrorKind};
 /// let _: GDError = GDError::new(GDErrorKind::PacketBad, Some("Reason the packet was bad".into()));
 /// ```
+///
+/// Errors of queries also carry an [ErrorContext] telling where they happened.
 pub struct GDError {
     pub kind: GDErrorKind,
     pub source: Option<ErrorSource>,
     pub backtrace: Option<backtrace::Backtrace>,
+    pub context: Box<ErrorContext>,
+    /// What was gathered before a section requested with
+    /// [Enforce](crate::protocols::types::GatherToggle::Enforce) failed (such
+    /// as Valve's server info), its outcome telling the failure. Get the
+    /// concrete response through [CommonResponse::as_original].
+    pub partial_response: Option<Box<dyn CommonResponse + Send + Sync>>,
+}
+
+/// Where an error happened, as much as is known of it.
+///
+/// Each field is set by the innermost code knowing it (the `with_*` methods
+/// don't replace what is already set).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// The address of the server being queried.
+    pub address: Option<SocketAddr>,
+    /// The protocol used (such as `"valve"` or `"minecraft"`).
+    pub protocol: Option<&'static str>,
+    /// The step of the query that failed (such as `"challenge"`, `"info"`,
+    /// `"players"` or `"rules"`).
+    pub stage: Option<&'static str>,
+    /// The try of the request that failed, starting at 1 (retries are
+    /// counted).
+    pub attempt: Option<usize>,
+    /// The raw data that failed to be parsed.
+    pub data: Option<Vec<u8>>,
+}
+
+impl ErrorContext {
+    /// Whether nothing is known.
+    pub fn is_empty(&self) -> bool { self == &Self::default() }
 }
 
 impl From<GDErrorKind> for GDError {
@@ -45,6 +80,8 @@ impl From<GDErrorKind> for GDError {
             kind: value,
             source: None,
             backtrace,
+            context: Box::default(),
+            partial_response: None,
         }
     }
 }
@@ -63,6 +100,25 @@ impl fmt::Debug for GDError {
         if let Some(source) = &self.source {
             writeln!(f, "  source={source:?}")?;
         }
+        if let Some(address) = &self.context.address {
+            writeln!(f, "  address={address}")?;
+        }
+        if let Some(protocol) = &self.context.protocol {
+            writeln!(f, "  protocol={protocol}")?;
+        }
+        if let Some(stage) = &self.context.stage {
+            writeln!(f, "  stage={stage}")?;
+        }
+        if let Some(attempt) = &self.context.attempt {
+            writeln!(f, "  attempt={attempt}")?;
+        }
+        if let Some(data) = &self.context.data {
+            let hex: String = data.iter().map(|byte| format!("{byte:02x}")).collect();
+            writeln!(f, "  data={hex}")?;
+        }
+        if let Some(response) = &self.partial_response {
+            writeln!(f, "  partial_response={:?}", response.as_original())?;
+        }
         if let Some(backtrace) = &self.backtrace {
             let bt = format!("{backtrace:#?}");
             writeln!(f, "  backtrace={}", bt.replace('\n', "\n  "))?;
@@ -84,6 +140,8 @@ impl GDError {
             kind,
             source,
             backtrace,
+            context: Box::default(),
+            partial_response: None,
         }
     }
 
@@ -91,6 +149,44 @@ impl GDError {
     pub fn from_error<E: Into<ErrorSource>>(kind: GDErrorKind, source: E) -> Self {
         Self::new(kind, Some(source.into()))
     }
+
+    /// Set the address of the server, if not already set.
+    pub fn with_address(mut self, address: SocketAddr) -> Self {
+        self.context.address.get_or_insert(address);
+        self
+    }
+
+    /// Set the protocol, if not already set.
+    pub fn with_protocol(mut self, protocol: &'static str) -> Self {
+        self.context.protocol.get_or_insert(protocol);
+        self
+    }
+
+    /// Set the stage of the query, if not already set.
+    pub fn with_stage(mut self, stage: &'static str) -> Self {
+        self.context.stage.get_or_insert(stage);
+        self
+    }
+
+    /// Set the attempt number, if not already set.
+    pub fn with_attempt(mut self, attempt: usize) -> Self {
+        self.context.attempt.get_or_insert(attempt);
+        self
+    }
+
+    /// Set the data that failed to be parsed, if not already set.
+    pub fn with_data(mut self, data: &[u8]) -> Self {
+        self.context.data.get_or_insert_with(|| data.to_vec());
+        self
+    }
+
+    /// Set the [partial response](GDError#structfield.partial_response), if
+    /// not already set.
+    pub fn with_partial_response<R: CommonResponse + Send + Sync + 'static>(mut self, response: R) -> Self {
+        self.partial_response
+            .get_or_insert_with(|| Box::new(response));
+        self
+    }
 }
 
 #[cfg(test)]
@@ -132,6 +228,36 @@ mod tests {
         assert!(error_from_context.source.is_some());
     }
 
+    // Test the context only being set by the innermost call
+    #[test]
+    fn test_context() {
+        let address = "127.0.0.1:27015".parse().unwrap();
+        let error = GDErrorKind::PacketBad
+            .context("test")
+            .with_data(&[0xff, 0x01])
+            .with_stage("info")
+            .with_stage("query")
+            .with_address(address)
+            .with_protocol("valve")
+            .with_attempt(2);
+
+        assert_eq!(
+            *error.context,
+            ErrorContext {
+                address: Some(address),
+                protocol: Some("valve"),
+                stage: Some("info"),
+                attempt: Some(2),
+                data: Some(vec![0xff, 0x01]),
+            }
+        );
+        assert!(format!("{error}")
+            .contains("  address=127.0.0.1:27015\n  protocol=valve\n  stage=info\n  attempt=2\n  data=ff01\n"));
+
+        let error: GDError = GDErrorKind::PacketBad.into();
+        assert!(error.context.is_empty());
+    }
+
     // Test creating GDError with From<GDErrorKind> for GDError
     #[test]
     fn test_create_into() {
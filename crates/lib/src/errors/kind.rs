@@ -2,7 +2,8 @@ use crate::error::ErrorSource;
 use crate::GDError;
 
 /// All GameDig Error kinds.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GDErrorKind {
     /// The received packet was bigger than the buffer size.
     PacketOverflow,
@@ -38,9 +39,32 @@ pub enum GDErrorKind {
     TypeParse,
     /// Couldn't find the host specified.
     HostLookup,
+    /// The query deadline expired before the query completed.
+    DeadlineExceeded,
 }
 
 impl GDErrorKind {
+    /// Every kind, in declaration order.
+    pub(crate) const ALL: [Self; 17] = [
+        Self::PacketOverflow,
+        Self::PacketUnderflow,
+        Self::PacketBad,
+        Self::PacketSend,
+        Self::PacketReceive,
+        Self::Decompress,
+        Self::SocketConnect,
+        Self::SocketBind,
+        Self::InvalidInput,
+        Self::BadGame,
+        Self::AutoQuery,
+        Self::ProtocolFormat,
+        Self::UnknownEnumCast,
+        Self::JsonParse,
+        Self::TypeParse,
+        Self::HostLookup,
+        Self::DeadlineExceeded,
+    ];
+
     /// Convert error kind into a full error with a source (and implicit
     /// backtrace)
     ///
@@ -57,12 +81,40 @@ mod tests {
 
     // Testing cloning the GDErrorKind type
     #[test]
+    #[allow(clippy::clone_on_copy)]
     fn test_cloning() {
         let error = GDErrorKind::BadGame;
         let cloned_error = error.clone();
         assert_eq!(error, cloned_error);
     }
 
+    // Test every kind being listed at the index of its discriminant
+    #[test]
+    fn test_all_kinds() {
+        for (index, kind) in GDErrorKind::ALL.iter().enumerate() {
+            // Fails to compile once a kind is added, so that it's listed too.
+            match kind {
+                GDErrorKind::PacketOverflow
+                | GDErrorKind::PacketUnderflow
+                | GDErrorKind::PacketBad
+                | GDErrorKind::PacketSend
+                | GDErrorKind::PacketReceive
+                | GDErrorKind::Decompress
+                | GDErrorKind::SocketConnect
+                | GDErrorKind::SocketBind
+                | GDErrorKind::InvalidInput
+                | GDErrorKind::BadGame
+                | GDErrorKind::AutoQuery
+                | GDErrorKind::ProtocolFormat
+                | GDErrorKind::UnknownEnumCast
+                | GDErrorKind::JsonParse
+                | GDErrorKind::TypeParse
+                | GDErrorKind::HostLookup
+                | GDErrorKind::DeadlineExceeded => assert_eq!(*kind as usize, index),
+            }
+        }
+    }
+
     // test display GDError
     #[test]
     fn test_display() {
@@ -1,8 +1,40 @@
 use crate::GDError;
+use std::net::SocketAddr;
 
 /// `Result` of `T` and `GDError`.
 pub type GDResult<T> = Result<T, GDError>;
 
+/// Add [context](crate::ErrorContext) to the error of a [GDResult], see the
+/// `with_*` methods of [GDError].
+pub trait ErrorContextExt {
+    /// Set the address of the server, if not already set.
+    fn with_address(self, address: SocketAddr) -> Self;
+
+    /// Set the protocol, if not already set.
+    fn with_protocol(self, protocol: &'static str) -> Self;
+
+    /// Set the stage of the query, if not already set.
+    fn with_stage(self, stage: &'static str) -> Self;
+
+    /// Set the attempt number, if not already set.
+    fn with_attempt(self, attempt: usize) -> Self;
+
+    /// Set the data that failed to be parsed, if not already set.
+    fn with_data(self, data: &[u8]) -> Self;
+}
+
+impl<T> ErrorContextExt for GDResult<T> {
+    fn with_address(self, address: SocketAddr) -> Self { self.map_err(|e| e.with_address(address)) }
+
+    fn with_protocol(self, protocol: &'static str) -> Self { self.map_err(|e| e.with_protocol(protocol)) }
+
+    fn with_stage(self, stage: &'static str) -> Self { self.map_err(|e| e.with_stage(stage)) }
+
+    fn with_attempt(self, attempt: usize) -> Self { self.map_err(|e| e.with_attempt(attempt)) }
+
+    fn with_data(self, data: &[u8]) -> Self { self.map_err(|e| e.with_data(data)) }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
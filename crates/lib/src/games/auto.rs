@@ -0,0 +1,447 @@
+//! Querying a server without knowing which game it runs.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::IpAddr;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+
+use crate::games::minecraft::{self, Server};
+use crate::games::query::query_send;
+use crate::games::types::{Game, QueryPort};
+use crate::games::GAMES;
+use crate::protocols::gamespy::{self, GameSpyVersion};
+use crate::protocols::quake::QuakeVersion;
+use crate::protocols::types::{
+    CommonResponse,
+    ExtraRequestSettings,
+    GenericResponse,
+    ProprietaryProtocol,
+    Protocol,
+    QueryScope,
+    SocketSettings,
+    TimeoutSettings,
+};
+use crate::protocols::valve::Engine;
+use crate::GDErrorKind::AutoQuery;
+use crate::GDResult;
+
+/// The protocols tried by [query_auto], in order of preference if more than
+/// one answers.
+const PROBES: &[(&str, Protocol)] = &[
+    ("Valve", Protocol::Valve(Engine::Source(None))),
+    ("GameSpy 1", Protocol::Gamespy(GameSpyVersion::One)),
+    ("GameSpy 2", Protocol::Gamespy(GameSpyVersion::Two)),
+    ("GameSpy 3", Protocol::Gamespy(GameSpyVersion::Three)),
+    ("Quake 1", Protocol::Quake(QuakeVersion::One)),
+    ("Quake 2", Protocol::Quake(QuakeVersion::Two)),
+    ("Quake 3", Protocol::Quake(QuakeVersion::Three)),
+    ("Unreal 2", Protocol::Unreal2),
+    (
+        "Minecraft (java)",
+        Protocol::PROPRIETARY(ProprietaryProtocol::Minecraft(Some(Server::Java))),
+    ),
+    (
+        "Minecraft (bedrock)",
+        Protocol::PROPRIETARY(ProprietaryProtocol::Minecraft(Some(Server::Bedrock))),
+    ),
+    (
+        "Mindustry",
+        Protocol::PROPRIETARY(ProprietaryProtocol::Mindustry),
+    ),
+];
+
+/// GameSpy `gamename` values of the games in [GAMES].
+const GAMESPY_GAME_NAMES: &[(&str, &str)] = &[
+    ("bfield1942", "battlefield1942"),
+    ("crysiswars", "crysiswars"),
+    ("halom", "hce"),
+    ("halor", "hce"),
+    ("serioussam", "serioussam"),
+    ("ut", "unrealtournament"),
+];
+
+/// The response of [query_auto].
+pub struct AutoQueryResponse {
+    /// The protocol that answered.
+    pub protocol: Protocol,
+    /// The id of the best matching [GAMES] entry, if one could be told apart.
+    pub game_id: Option<&'static str>,
+    /// The query response.
+    pub response: Box<dyn CommonResponse>,
+}
+
+impl AutoQueryResponse {
+    /// The best matching game definition.
+    pub fn game(&self) -> Option<&'static Game> { self.game_id.and_then(|id| GAMES.get(id)) }
+}
+
+/// Query a server of an unknown game by probing every known protocol family
+/// on the port.
+#[inline]
+pub fn query_auto(address: &IpAddr, port: u16) -> GDResult<AutoQueryResponse> {
+    query_auto_with_timeout(address, port, None)
+}
+
+/// Query a server of an unknown game by probing every known protocol family
+/// on the port, with timeout settings applied to each probe.
+///
+/// Probes are run concurrently, the answer of the first protocol of the list
+/// that answered is returned as soon as the protocols before it failed (the
+/// remaining probes finish on their own). Fails with [AutoQuery] only if no
+/// probe got an answer.
+#[inline]
+pub fn query_auto_with_timeout(
+    address: &IpAddr,
+    port: u16,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<AutoQueryResponse> {
+    query_auto_with_socket_settings(address, port, timeout_settings, &SocketSettings::default())
+}
+
+/// [query_auto_with_timeout] with the settings of the sockets the probes use.
+pub fn query_auto_with_socket_settings(
+    address: &IpAddr,
+    port: u16,
+    timeout_settings: Option<TimeoutSettings>,
+    socket_settings: &SocketSettings,
+) -> GDResult<AutoQueryResponse> {
+    let (sender, receiver) = mpsc::channel();
+    for (index, (name, protocol)) in PROBES.iter().enumerate() {
+        let (sender, address, socket_settings) = (sender.clone(), *address, socket_settings.clone());
+
+        std::thread::spawn(move || {
+            let game = Game {
+                name: Cow::Borrowed(name),
+                default_port: port,
+                query_port: QueryPort::Same,
+                protocol: protocol.clone(),
+                request_settings: ExtraRequestSettings::default(),
+            };
+
+            let query_scope = QueryScope::start(&timeout_settings).set_socket_settings(socket_settings);
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                query_send(
+                    &game,
+                    &address,
+                    Some(port),
+                    timeout_settings,
+                    None,
+                    &query_scope,
+                )
+            }))
+            .unwrap_or_else(|_| Err(AutoQuery.context("The probe panicked")));
+
+            // The receiver is gone if a preferred probe already answered.
+            let _ = sender.send((index, result));
+        });
+    }
+    drop(sender);
+
+    // Results in the order of the probes, waiting on the first one that's
+    // still pending as long as no preferred probe answered.
+    let mut results: Vec<Option<GDResult<_>>> = PROBES.iter().map(|_| None).collect();
+    for (index, result) in receiver {
+        results[index] = Some(result);
+
+        let answered = results
+            .iter()
+            .take_while(|result| result.is_some())
+            .position(|result| matches!(result, Some(Ok(_))));
+
+        if let Some((index, Some(Ok(response)))) = answered.map(|index| (index, results[index].take())) {
+            let protocol = &PROBES[index].1;
+            return Ok(AutoQueryResponse {
+                protocol: protocol.clone(),
+                game_id: best_matching_game(protocol, port, response.as_ref()),
+                response,
+            });
+        }
+    }
+
+    let mut errors = String::new();
+    for ((name, _), result) in PROBES.iter().zip(results) {
+        if let Some(Err(error)) = result {
+            let _ = write!(errors, "{}: {:?}; ", name, error.kind);
+        }
+    }
+
+    Err(AutoQuery.context(errors.trim_end_matches("; ").to_string()))
+}
+
+/// Find the [GAMES] entry the response came from, by the ids the protocols
+/// report or, failing that, by the only game of the protocol using the port.
+fn best_matching_game(protocol: &Protocol, port: u16, response: &dyn CommonResponse) -> Option<&'static str> {
+    match response.as_original() {
+        GenericResponse::Valve(response) => {
+            let appid = response.info.appid;
+            let by_appid = matching_games(|game| {
+                matches!(game.protocol, Protocol::Valve(Engine::Source(Some((main, secondary))))
+                        if appid != 0 && (main == appid || secondary == Some(appid)))
+            });
+
+            if let Some(id) = pick(&by_appid, port) {
+                return Some(id);
+            }
+
+            if response.info.appid == 0 {
+                return unique_by_port(
+                    |game| matches!(game.protocol, Protocol::Valve(Engine::GoldSrc(_))),
+                    port,
+                );
+            }
+
+            None
+        }
+        GenericResponse::GameSpy(versioned) => {
+            let unused_entries = match versioned {
+                gamespy::VersionedResponse::One(response) => &response.unused_entries,
+                gamespy::VersionedResponse::Two(response) => &response.unused_entries,
+                gamespy::VersionedResponse::Three(response) => &response.unused_entries,
+            };
+
+            gamespy_game_name(unused_entries).or_else(|| unique_by_port(|game| &game.protocol == protocol, port))
+        }
+        GenericResponse::Minecraft(versioned) => {
+            match versioned {
+                minecraft::VersionedResponse::Bedrock(_) => Some("minecraftbedrock"),
+                minecraft::VersionedResponse::Java(response) => {
+                    match response.server_type {
+                        Server::Java => Some("minecraftjava"),
+                        Server::Bedrock => Some("minecraftbedrock"),
+                        Server::Legacy(_) => Some("minecraft"),
+                    }
+                }
+            }
+        }
+        _ => {
+            unique_by_port(|game| &game.protocol == protocol, port)
+                .or_else(|| unique(|game| &game.protocol == protocol))
+        }
+    }
+}
+
+fn gamespy_game_name(unused_entries: &HashMap<String, String>) -> Option<&'static str> {
+    let game_name = unused_entries.get("gamename")?;
+
+    GAMESPY_GAME_NAMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(game_name))
+        .map(|(_, id)| *id)
+}
+
+/// Ids of the [GAMES] entries that satisfy the predicate, sorted.
+fn matching_games(predicate: impl Fn(&Game) -> bool) -> Vec<&'static str> {
+    let mut ids: Vec<_> = GAMES
+        .entries()
+        .filter(|(_, game)| predicate(game))
+        .map(|(id, _)| *id)
+        .collect();
+
+    ids.sort_unstable();
+    ids
+}
+
+/// Of the candidates, prefer the one using the port by default, otherwise the
+/// first one.
+fn pick(candidates: &[&'static str], port: u16) -> Option<&'static str> {
+    candidates
+        .iter()
+        .find(|id| GAMES.get(id).is_some_and(|game| game.default_port == port))
+        .or_else(|| candidates.first())
+        .copied()
+}
+
+/// The only matching game using the port by default.
+fn unique_by_port(predicate: impl Fn(&Game) -> bool, port: u16) -> Option<&'static str> {
+    unique(|game| game.default_port == port && predicate(game))
+}
+
+/// The only matching game.
+fn unique(predicate: impl Fn(&Game) -> bool) -> Option<&'static str> {
+    match matching_games(predicate).as_slice() {
+        [id] => Some(id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{Connector, Scripted, Transport, TransportKind};
+    use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    /// Answers every datagram probe with a Quake 1 status, while the stream
+    /// probes (Minecraft java) take `delay` to fail.
+    struct QuakeWithSlowStreams {
+        delay: Duration,
+    }
+
+    struct Stalled(Duration);
+
+    impl Transport for Stalled {
+        fn send(&mut self, _data: &[u8]) -> GDResult<()> { Ok(()) }
+
+        fn receive(&mut self, _size: Option<usize>) -> GDResult<Vec<u8>> {
+            std::thread::sleep(self.0);
+            Err(crate::GDErrorKind::PacketReceive.context("Stalled"))
+        }
+    }
+
+    impl Connector for QuakeWithSlowStreams {
+        fn connect(
+            &self,
+            kind: TransportKind,
+            address: &SocketAddr,
+            timeout_settings: &Option<TimeoutSettings>,
+        ) -> GDResult<Box<dyn Transport>> {
+            match kind {
+                TransportKind::Udp => {
+                    Scripted::new()
+                        .add_packet(*b"\xFF\xFF\xFF\xFFn\\hostname\\Test\\map\\e1m1\\maxclients\\8\n")
+                        .connect(kind, address, timeout_settings)
+                }
+                TransportKind::Tcp => Ok(Box::new(Stalled(self.delay))),
+            }
+        }
+    }
+
+    struct Panicking;
+
+    impl Connector for Panicking {
+        fn connect(
+            &self,
+            _kind: TransportKind,
+            _address: &SocketAddr,
+            _timeout_settings: &Option<TimeoutSettings>,
+        ) -> GDResult<Box<dyn Transport>> {
+            panic!("connector failure")
+        }
+    }
+
+    #[test]
+    fn valve_appid_maps_to_game() {
+        let ids = matching_games(|game| {
+            matches!(
+                game.protocol,
+                Protocol::Valve(Engine::Source(Some((440, _))))
+            )
+        });
+        assert_eq!(pick(&ids, 27015), Some("teamfortress2"));
+    }
+
+    #[test]
+    fn gamespy_game_name_maps_to_game() {
+        let unused_entries = HashMap::from([("gamename".to_string(), "bfield1942".to_string())]);
+        assert_eq!(gamespy_game_name(&unused_entries), Some("battlefield1942"));
+    }
+
+    #[test]
+    fn answering_protocol_is_detected() {
+        // A Savage 2 like server isn't probed, but mindustry is.
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = socket.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let mut buf = [0; 64];
+            loop {
+                let (size, source) = socket.recv_from(&mut buf).unwrap();
+                if buf[.. size] == [0xFE, 0x01] {
+                    let mut response = Vec::new();
+                    for text in ["host", "map"] {
+                        response.push(text.len() as u8);
+                        response.extend_from_slice(text.as_bytes());
+                    }
+                    response.extend_from_slice(&[0, 0, 0, 3, 0, 0, 0, 1, 0, 0, 0, 146]);
+                    response.extend_from_slice(b"\x08official\x00\x00\x00\x00\x10\x04desc\x00");
+                    socket.send_to(&response, source).unwrap();
+                    return;
+                }
+            }
+        });
+
+        let timeout_settings = TimeoutSettings::new(
+            Some(Duration::from_millis(500)),
+            Some(Duration::from_millis(500)),
+            Some(Duration::from_millis(500)),
+            0,
+        )
+        .unwrap();
+
+        let response = query_auto_with_timeout(
+            &IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port,
+            Some(timeout_settings),
+        )
+        .unwrap();
+        server.join().unwrap();
+
+        assert_eq!(
+            response.protocol,
+            Protocol::PROPRIETARY(ProprietaryProtocol::Mindustry)
+        );
+        assert_eq!(response.game_id, Some("mindustry"));
+        assert_eq!(response.response.map(), Some("map"));
+    }
+
+    #[test]
+    fn nothing_answering_is_an_auto_query_error() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = socket.local_addr().unwrap().port();
+        let timeout_settings = TimeoutSettings::new(
+            Some(Duration::from_millis(100)),
+            Some(Duration::from_millis(100)),
+            Some(Duration::from_millis(100)),
+            0,
+        )
+        .unwrap();
+
+        let error = query_auto_with_timeout(
+            &IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port,
+            Some(timeout_settings),
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(error.kind, AutoQuery);
+    }
+
+    #[test]
+    fn preferred_answer_does_not_wait_on_the_other_probes() {
+        let delay = Duration::from_secs(5);
+        let socket_settings = SocketSettings::new().set_connector(Arc::new(QuakeWithSlowStreams { delay }));
+
+        let start = Instant::now();
+        let response = query_auto_with_socket_settings(
+            &IpAddr::V4(Ipv4Addr::LOCALHOST),
+            27500,
+            None,
+            &socket_settings,
+        )
+        .unwrap();
+
+        assert!(start.elapsed() < delay);
+        assert_eq!(response.protocol, Protocol::Quake(QuakeVersion::One));
+        assert_eq!(response.response.map(), Some("e1m1"));
+    }
+
+    #[test]
+    fn panicking_probe_is_an_error() {
+        let socket_settings = SocketSettings::new().set_connector(Arc::new(Panicking));
+
+        let error = query_auto_with_socket_settings(
+            &IpAddr::V4(Ipv4Addr::LOCALHOST),
+            27015,
+            None,
+            &socket_settings,
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(error.kind, AutoQuery);
+        assert!(format!("{:?}", error).contains("Valve: AutoQuery"));
+    }
+}
@@ -7,13 +7,31 @@ use crate::{
 use std::net::{IpAddr, SocketAddr};
 
 pub fn query(address: &IpAddr, port: Option<u16>) -> GDResult<game::Response> {
-    let mut valve_response = valve::query(
+    let valve_response = valve::query(
         &SocketAddr::new(*address, port.unwrap_or(7780)),
         Engine::new(489_940),
         None,
         None,
     )?;
 
+    parse_response(valve_response)
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &IpAddr, port: Option<u16>) -> GDResult<game::Response> {
+    let valve_response = valve::query_async(
+        &SocketAddr::new(*address, port.unwrap_or(7780)),
+        Engine::new(489_940),
+        None,
+        None,
+    )
+    .await?;
+
+    parse_response(valve_response)
+}
+
+fn parse_response(mut valve_response: valve::Response) -> GDResult<game::Response> {
     if let Some(rules) = &mut valve_response.rules {
         if let Some(bat_max_players) = rules.get("bat_max_players_i") {
             valve_response.info.players_maximum = bat_max_players.parse().map_err(|e| TypeParse.context(e))?;
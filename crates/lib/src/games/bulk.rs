@@ -0,0 +1,315 @@
+//! Concurrent querying of many servers at once.
+//!
+//! UDP queries are multiplexed over a small pool of shared sockets, their
+//! responses are demultiplexed by source address. TCP and HTTP based games
+//! still use their own connections, but count towards the same concurrency
+//! limit.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, Semaphore};
+
+use crate::games::query::query_with_socket_settings_async;
+use crate::games::types::Game;
+use crate::protocols::types::{CommonResponse, ExtraRequestSettings, SocketSettings, TimeoutSettings};
+use crate::socket::UdpSocketPool;
+use crate::GDResult;
+
+/// A server to query in bulk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkTarget {
+    /// The game definition to query with.
+    pub game: Game,
+    /// The server's query address.
+    pub address: SocketAddr,
+    /// Timeout settings for this target, [BulkQuery]'s are used if [None].
+    pub timeout_settings: Option<TimeoutSettings>,
+    /// Extra request settings for this target.
+    pub extra_settings: Option<ExtraRequestSettings>,
+}
+
+impl BulkTarget {
+    /// Create a target with default settings.
+    pub fn new(game: &Game, address: SocketAddr) -> Self {
+        Self {
+            game: game.clone(),
+            address,
+            timeout_settings: None,
+            extra_settings: None,
+        }
+    }
+
+    /// [Sets timeout settings](BulkTarget::timeout_settings)
+    pub fn set_timeout_settings(mut self, timeout_settings: TimeoutSettings) -> Self {
+        self.timeout_settings = Some(timeout_settings);
+        self
+    }
+
+    /// [Sets extra request settings](BulkTarget::extra_settings)
+    pub fn set_extra_settings(mut self, extra_settings: ExtraRequestSettings) -> Self {
+        self.extra_settings = Some(extra_settings);
+        self
+    }
+}
+
+impl From<(Game, SocketAddr)> for BulkTarget {
+    fn from((game, address): (Game, SocketAddr)) -> Self { Self::new(&game, address) }
+}
+
+impl From<(&Game, SocketAddr)> for BulkTarget {
+    fn from((game, address): (&Game, SocketAddr)) -> Self { Self::new(game, address) }
+}
+
+/// The outcome of querying a single [BulkTarget].
+pub struct BulkResponse {
+    /// Position of the target in the list given to [BulkQuery::run].
+    pub index: usize,
+    /// The target's address.
+    pub address: SocketAddr,
+    /// The query result.
+    pub result: GDResult<Box<dyn CommonResponse + Send + Sync>>,
+}
+
+/// Queries many servers concurrently, streaming back results as they
+/// complete.
+///
+/// ```no_run
+/// # async fn run() -> gamedig::GDResult<()> {
+/// use gamedig::{BulkQuery, GAMES};
+///
+/// let game = GAMES.get("teamfortress2").unwrap();
+/// let targets = vec![(game, "127.0.0.1:27015".parse().unwrap())];
+///
+/// let mut responses = BulkQuery::new().set_concurrency(512).run(targets).await?;
+/// while let Some(response) = responses.recv().await {
+///     match response.result {
+///         Ok(r) => println!("{}: {:#?}", response.address, r.as_json()),
+///         Err(e) => println!("{}: {}", response.address, e),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BulkQuery {
+    concurrency: usize,
+    sockets: usize,
+    timeout_settings: Option<TimeoutSettings>,
+    socket_settings: SocketSettings,
+}
+
+impl Default for BulkQuery {
+    fn default() -> Self {
+        Self {
+            concurrency: 256,
+            sockets: 4,
+            timeout_settings: None,
+            socket_settings: SocketSettings::default(),
+        }
+    }
+}
+
+impl BulkQuery {
+    /// Create a bulk query with the default settings (256 concurrent queries
+    /// over 4 sockets per address family).
+    pub fn new() -> Self { Self::default() }
+
+    /// Set the maximum amount of queries in flight at once (minimum 1,
+    /// maximum [u32::MAX]).
+    pub fn set_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.clamp(1, Semaphore::MAX_PERMITS.min(u32::MAX as usize));
+        self
+    }
+
+    /// Set the amount of shared UDP sockets per address family (minimum 1).
+    pub fn set_sockets(mut self, sockets: usize) -> Self {
+        self.sockets = sockets.max(1);
+        self
+    }
+
+    /// Set the timeout settings used by targets that don't have their own.
+    pub fn set_timeout_settings(mut self, timeout_settings: TimeoutSettings) -> Self {
+        self.timeout_settings = Some(timeout_settings);
+        self
+    }
+
+    /// Set the settings of the sockets, the shared UDP ones are bound as per
+    /// these too.
+    pub fn set_socket_settings(mut self, socket_settings: SocketSettings) -> Self {
+        self.socket_settings = socket_settings;
+        self
+    }
+
+    /// Start querying the targets, must be called from within a tokio
+    /// runtime.
+    ///
+    /// Responses are sent in completion order, the channel is closed once
+    /// every target has been answered. Dropping the receiver stops any
+    /// further targets from being queried.
+    pub async fn run<T: Into<BulkTarget>>(
+        &self,
+        targets: impl IntoIterator<Item = T>,
+    ) -> GDResult<mpsc::Receiver<BulkResponse>> {
+        let targets: Vec<BulkTarget> = targets.into_iter().map(Into::into).collect();
+
+        let pool = UdpSocketPool::bind(
+            self.sockets,
+            targets.iter().any(|target| target.address.is_ipv4()),
+            targets.iter().any(|target| target.address.is_ipv6()),
+            &self.socket_settings,
+        )
+        .await?;
+
+        let concurrency = self.concurrency;
+        let (timeout_settings, socket_settings) = (self.timeout_settings, self.socket_settings.clone());
+        let (sender, receiver) = mpsc::channel(concurrency);
+
+        tokio::spawn(async move {
+            let receivers = pool.spawn_receivers();
+            let semaphore = Arc::new(Semaphore::new(concurrency));
+
+            for (index, target) in targets.into_iter().enumerate() {
+                let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                    break;
+                };
+
+                if sender.is_closed() {
+                    break;
+                }
+
+                let pool = pool.clone();
+                let sender = sender.clone();
+                let socket_settings = socket_settings.clone();
+                tokio::spawn(async move {
+                    let result = pool
+                        .scope(query_with_socket_settings_async(
+                            &target.game,
+                            &target.address.ip(),
+                            Some(target.address.port()),
+                            target.timeout_settings.or(timeout_settings),
+                            target.extra_settings,
+                            &socket_settings,
+                        ))
+                        .await;
+
+                    let _ = sender
+                        .send(BulkResponse {
+                            index,
+                            address: target.address,
+                            result,
+                        })
+                        .await;
+
+                    drop(permit);
+                });
+            }
+
+            // Every permit being available again means all queries are done.
+            let _ = semaphore
+                .acquire_many(u32::try_from(concurrency).unwrap_or(u32::MAX))
+                .await;
+            for receiver in receivers {
+                receiver.abort();
+            }
+        });
+
+        Ok(receiver)
+    }
+}
+
+#[cfg(all(test, feature = "game_defs"))]
+mod tests {
+    use super::*;
+    use crate::games::GAMES;
+    use std::collections::HashSet;
+
+    /// A Savage 2 server that answers every request with its name, reporting
+    /// the address each request came from.
+    async fn savage2_server(name: &'static str) -> (SocketAddr, mpsc::UnboundedReceiver<SocketAddr>) {
+        let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        let (sources, receiver) = mpsc::unbounded_channel();
+
+        let mut response = vec![0; 12];
+        response.extend_from_slice(name.as_bytes());
+        response.extend_from_slice(b"\0\x02\x10time\0map\0next\0loc\0\x01mode\0proto\0\x00");
+
+        tokio::spawn(async move {
+            let mut buf = [0; 16];
+            while let Ok((_, source)) = socket.recv_from(&mut buf).await {
+                let _ = sources.send(source);
+                socket.send_to(&response, source).await.unwrap();
+            }
+        });
+
+        (address, receiver)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn queries_are_multiplexed_and_demultiplexed() {
+        let game = GAMES.get("savage2").unwrap();
+        let names = ["first", "second", "third", "fourth"];
+
+        let mut servers = Vec::new();
+        for name in names {
+            servers.push(savage2_server(name).await);
+        }
+
+        let targets: Vec<_> = servers
+            .iter()
+            .map(|(address, _)| (game, *address))
+            .collect();
+
+        let mut responses = BulkQuery::new()
+            .set_concurrency(2)
+            .set_sockets(1)
+            .set_timeout_settings(TimeoutSettings::new(Some(std::time::Duration::from_secs(2)), None, None, 0).unwrap())
+            .run(targets)
+            .await
+            .unwrap();
+
+        let mut answered = HashSet::new();
+        while let Some(response) = responses.recv().await {
+            let result = response.result.unwrap();
+            assert_eq!(result.name(), Some(names[response.index]));
+            assert_eq!(response.address, servers[response.index].0);
+            answered.insert(response.index);
+        }
+
+        assert_eq!(answered.len(), names.len());
+
+        // With a single pooled socket, every server was queried from the same
+        // local address.
+        let mut sources = HashSet::new();
+        for (_, receiver) in &mut servers {
+            sources.insert(receiver.recv().await.unwrap());
+        }
+
+        assert_eq!(sources.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unanswered_target_times_out() {
+        let game = GAMES.get("savage2").unwrap();
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        let mut responses = BulkQuery::new()
+            .set_timeout_settings(
+                TimeoutSettings::new(Some(std::time::Duration::from_millis(50)), None, None, 0).unwrap(),
+            )
+            .run([(game, silent.local_addr().unwrap())])
+            .await
+            .unwrap();
+
+        let response = responses.recv().await.unwrap();
+        assert!(response.result.is_err());
+        assert!(responses.recv().await.is_none());
+    }
+
+    #[test]
+    fn concurrency_is_bounded() {
+        assert_eq!(BulkQuery::new().set_concurrency(0).concurrency, 1);
+        assert!(u32::try_from(BulkQuery::new().set_concurrency(usize::MAX).concurrency).is_ok());
+    }
+}
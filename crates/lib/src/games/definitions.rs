@@ -1,12 +1,14 @@
 //! Static definitions of currently supported games
 
 use crate::games::minecraft::types::{LegacyGroup, Server};
+use crate::games::types::QueryPort;
 use crate::protocols::{gamespy::GameSpyVersion, quake::QuakeVersion, valve::Engine, Protocol};
 use crate::Game;
 
 use crate::protocols::types::{GatherToggle, ProprietaryProtocol};
 use crate::protocols::valve::GatheringSettings;
 use phf::{phf_map, Map};
+use std::borrow::Cow;
 
 macro_rules! game {
     ($name: literal, $default_port: expr, $protocol: expr) => {
@@ -20,8 +22,9 @@ macro_rules! game {
 
     ($name: literal, $default_port: expr, $protocol: expr, $extra_request_settings: expr) => {
         Game {
-            name: $name,
+            name: Cow::Borrowed($name),
             default_port: $default_port,
+            query_port: QueryPort::Same,
             protocol: $protocol,
             request_settings: $extra_request_settings,
         }
@@ -43,19 +46,22 @@ pub static GAMES: Map<&'static str, Game> = phf_map! {
         players: GatherToggle::Enforce,
         rules: GatherToggle::Skip,
         check_app_id: true,
+        filter_players: false,
     }.into_extra()),
     "abioticfactor" => game!("Abiotic Factor", 27015, Protocol::Valve(Engine::new(427_410))),
     "alienswarm" => game!("Alien Swarm", 27015, Protocol::Valve(Engine::new(630))),
     "aoc" => game!("Age of Chivalry", 27015, Protocol::Valve(Engine::new(17510))),
-    "a2oa" => game!("ARMA 2: Operation Arrowhead", 2304, Protocol::Valve(Engine::new(33930))),
-    "ase" => game!("ARK: Survival Evolved", 27015, Protocol::Valve(Engine::new(346_110))),
+    "a2oa" => game!("ARMA 2: Operation Arrowhead", 2304, Protocol::Valve(Engine::new(33930))).set_query_port(QueryPort::Offset(2)),
+    "arma3" => game!("Arma 3", 2303, Protocol::Valve(Engine::new(107_410))).set_query_port(QueryPort::Offset(1)),
+    "ase" => game!("ARK: Survival Evolved", 27015, Protocol::Valve(Engine::new(346_110))).set_query_port(QueryPort::Fixed),
     "asrd" => game!("Alien Swarm: Reactive Drop", 2304, Protocol::Valve(Engine::new(563_560))),
     "armareforger" => game!("Arma Reforger", 17777, Protocol::Valve(Engine::new(1_874_880)), GatheringSettings {
         players: GatherToggle::Enforce,
         rules: GatherToggle::Enforce,
         check_app_id: false,
-    }.into_extra()),
-    "atlas" => game!("ATLAS", 57561, Protocol::Valve(Engine::new(834_910))),
+        filter_players: false,
+    }.into_extra()).set_query_port(QueryPort::Fixed),
+    "atlas" => game!("ATLAS", 57561, Protocol::Valve(Engine::new(834_910))).set_query_port(QueryPort::Fixed),
     "avorion" => game!("Avorion", 27020, Protocol::Valve(Engine::new(445_220))),
     "avp2010" => game!("Aliens vs. Predator 2010", 27015, Protocol::Valve(Engine::new(10_680))),
     "barotrauma" => game!("Barotrauma", 27016, Protocol::Valve(Engine::new(602_960))),
@@ -63,10 +69,11 @@ pub static GAMES: Map<&'static str, Game> = phf_map! {
         players: GatherToggle::Enforce,
         rules: GatherToggle::Skip,
         check_app_id: true,
+        filter_players: false,
     }.into_extra()),
     "battalion1944" => game!("Battalion 1944", 7780, Protocol::Valve(Engine::new(489_940))),
     "brainbread2" => game!("BrainBread 2", 27015, Protocol::Valve(Engine::new(346_330))),
-    "battlefield1942" => game!("Battlefield 1942", 23000, Protocol::Gamespy(GameSpyVersion::One)),
+    "battlefield1942" => game!("Battlefield 1942", 23000, Protocol::Gamespy(GameSpyVersion::One)).set_query_port(QueryPort::Fixed),
     "blackmesa" => game!("Black Mesa", 27015, Protocol::Valve(Engine::new(362_890))),
     "ballisticoverkill" => game!("Ballistic Overkill", 27016, Protocol::Valve(Engine::new(296_300))),
     "codbo3" => game!("Call Of Duty: Black Ops 3", 27017, Protocol::Valve(Engine::new(311_210))),
@@ -76,7 +83,8 @@ pub static GAMES: Map<&'static str, Game> = phf_map! {
         players: GatherToggle::Skip,
         rules: GatherToggle::Enforce,
         check_app_id: true,
-    }.into_extra()),
+        filter_players: false,
+    }.into_extra()).set_query_port(QueryPort::Fixed),
     "counterstrike" => game!("Counter-Strike", 27015, Protocol::Valve(Engine::new_gold_src(false))),
     "counterstrike2" => game!("Counter-Strike 2", 27015, Protocol::Valve(Engine::new(730))),
     "cscz" => game!("Counter Strike: Condition Zero", 27015, Protocol::Valve(Engine::new_gold_src(false))),
@@ -84,12 +92,13 @@ pub static GAMES: Map<&'static str, Game> = phf_map! {
     "css" => game!("Counter-Strike: Source", 27015, Protocol::Valve(Engine::new(240))),
     "creativerse" => game!("Creativerse", 26901, Protocol::Valve(Engine::new(280_790))),
     "crysiswars" => game!("Crysis Wars", 64100, Protocol::Gamespy(GameSpyVersion::Three)),
+    "dayz" => game!("DayZ", 27016, Protocol::Valve(Engine::new(221_100))).set_query_port(QueryPort::Fixed),
     "dab" => game!("Double Action: Boogaloo", 27015, Protocol::Valve(Engine::new(317_360))),
     "dod" => game!("Day of Defeat", 27015, Protocol::Valve(Engine::new_gold_src(false))),
     "dods" => game!("Day of Defeat: Source", 27015, Protocol::Valve(Engine::new(300))),
     "doi" => game!("Day of Infamy", 27015, Protocol::Valve(Engine::new(447_820))),
     "dst" => game!("Don't Starve Together", 27016, Protocol::Valve(Engine::new(322_320))),
-    "enshrouded" => game!("Enshrouded", 15637, Protocol::Valve(Engine::new(1_203_620))),
+    "enshrouded" => game!("Enshrouded", 15637, Protocol::Valve(Engine::new(1_203_620))).set_query_port(QueryPort::Offset(1)),
     "ffow" => game!("Frontlines: Fuel of War", 5478, Protocol::PROPRIETARY(ProprietaryProtocol::FFOW)),
     "garrysmod" => game!("Garry's Mod", 27016, Protocol::Valve(Engine::new(4000))),
     "hl2d" => game!("Half-Life 2 Deathmatch", 27015, Protocol::Valve(Engine::new(320))),
@@ -98,13 +107,13 @@ pub static GAMES: Map<&'static str, Game> = phf_map! {
     "hll" => game!("Hell Let Loose", 26420, Protocol::Valve(Engine::new(686_810))),
     "insurgency" => game!("Insurgency", 27015, Protocol::Valve(Engine::new(222_880))),
     "imic" => game!("Insurgency: Modern Infantry Combat", 27015, Protocol::Valve(Engine::new(17700))),
-    "insurgencysandstorm" => game!("Insurgency: Sandstorm", 27131, Protocol::Valve(Engine::new(581_320))),
+    "insurgencysandstorm" => game!("Insurgency: Sandstorm", 27131, Protocol::Valve(Engine::new(581_320))).set_query_port(QueryPort::Fixed),
     "l4d" => game!("Left 4 Dead", 27015, Protocol::Valve(Engine::new(500))),
     "l4d2" => game!("Left 4 Dead 2", 27015, Protocol::Valve(Engine::new(550))),
     "ohd" => game!("Operation: Harsh Doorstop", 27005, Protocol::Valve(Engine::new_with_dedicated(736_590, 950_900))),
     "onset" => game!("Onset", 7776, Protocol::Valve(Engine::new(1_105_810))),
-    "pixark" => game!("PixARK", 27015, Protocol::Valve(Engine::new(593_600))),
-    "postscriptum" => game!("Post Scriptum", 10037, Protocol::Valve(Engine::new(736_220))),
+    "pixark" => game!("PixARK", 27015, Protocol::Valve(Engine::new(593_600))).set_query_port(QueryPort::Fixed),
+    "postscriptum" => game!("Post Scriptum", 10037, Protocol::Valve(Engine::new(736_220))).set_query_port(QueryPort::Offset(10)),
     "projectzomboid" => game!("Project Zomboid", 16261, Protocol::Valve(Engine::new(108_600))),
     "pvak2" => game!("Pirates, Vikings, and Knights II", 27015, Protocol::Valve(Engine::new(17_570))),
     "quake1" => game!("Quake 1", 27500, Protocol::Quake(QuakeVersion::One)),
@@ -114,46 +123,81 @@ pub static GAMES: Map<&'static str, Game> = phf_map! {
         players: GatherToggle::Enforce,
         rules: GatherToggle::Skip,
         check_app_id: true,
+        filter_players: false,
     }.into_extra()),
-    "ror2" => game!("Risk of Rain 2", 27016, Protocol::Valve(Engine::new(632_360))),
+    "ror2" => game!("Risk of Rain 2", 27016, Protocol::Valve(Engine::new(632_360))).set_query_port(QueryPort::Offset(1)),
     "rust" => game!("Rust", 27015, Protocol::Valve(Engine::new(252_490))),
     "savage2" => game!("Savage 2", 11235, Protocol::PROPRIETARY(ProprietaryProtocol::Savage2)),
     "sco" => game!("Sven Co-op", 27015, Protocol::Valve(Engine::new_gold_src(false))),
     "sdtd" => game!("7 Days to Die", 26900, Protocol::Valve(Engine::new(251_570))),
     "sof2" => game!("Soldier of Fortune 2", 20100, Protocol::Quake(QuakeVersion::Three)),
-    "soulmask" => game!("Soulmask", 27015, Protocol::Valve(Engine::new(2_646_460))),
-    "serioussam" => game!("Serious Sam", 25601, Protocol::Gamespy(GameSpyVersion::One)),
-    "squad" => game!("Squad", 27165, Protocol::Valve(Engine::new(393_380))),
+    "soulmask" => game!("Soulmask", 27015, Protocol::Valve(Engine::new(2_646_460))).set_query_port(QueryPort::Fixed),
+    "serioussam" => game!("Serious Sam", 25601, Protocol::Gamespy(GameSpyVersion::One)).set_query_port(QueryPort::Offset(1)),
+    "squad" => game!("Squad", 27165, Protocol::Valve(Engine::new(393_380))).set_query_port(QueryPort::Fixed),
     "starbound" => game!("Starbound", 21025, Protocol::Valve(Engine::new(211_820)), GatheringSettings {
         players: GatherToggle::Enforce,
         rules: GatherToggle::Enforce,
         check_app_id: false,
+        filter_players: false,
     }.into_extra()),
-    "theforest" => game!("The Forest", 27016, Protocol::Valve(Engine::new_with_dedicated(242_760, 556_450))),
+    "theforest" => game!("The Forest", 27016, Protocol::Valve(Engine::new_with_dedicated(242_760, 556_450))).set_query_port(QueryPort::Offset(1)),
     "thefront" => game!("The Front", 27015, Protocol::Valve(Engine::new(2_285_150))),
     "teamfortress2" => game!("Team Fortress 2", 27015, Protocol::Valve(Engine::new(440))),
     "tfc" => game!("Team Fortress Classic", 27015, Protocol::Valve(Engine::new_gold_src(false))),
     "theship" => game!("The Ship", 27015, Protocol::PROPRIETARY(ProprietaryProtocol::TheShip)),
     "unturned" => game!("Unturned", 27015, Protocol::Valve(Engine::new(304_930))),
-    "unrealtournament" => game!("Unreal Tournament", 7778, Protocol::Gamespy(GameSpyVersion::One)),
+    "unrealtournament" => game!("Unreal Tournament", 7778, Protocol::Gamespy(GameSpyVersion::One)).set_query_port(QueryPort::Offset(1)),
     "valheim" => game!("Valheim", 2457, Protocol::Valve(Engine::new(892_970)), GatheringSettings {
         players: GatherToggle::Enforce,
         rules: GatherToggle::Skip,
         check_app_id: true,
-    }.into_extra()),
+        filter_players: false,
+    }.into_extra()).set_query_port(QueryPort::Offset(1)),
     "vrising" => game!("V Rising", 27016, Protocol::Valve(Engine::new(1_604_030))),
     "jc2m" => game!("Just Cause 2: Multiplayer", 7777, Protocol::PROPRIETARY(ProprietaryProtocol::JC2M)),
     "warsow" => game!("Warsow", 44400, Protocol::Quake(QuakeVersion::Three)),
-    "dhe4445" => game!("Darkest Hour: Europe '44-'45 (2008)", 7758, Protocol::Unreal2),
-    "devastation" => game!("Devastation (2003)", 7778, Protocol::Unreal2),
-    "killingfloor" => game!("Killing Floor", 7708, Protocol::Unreal2),
-    "redorchestra" => game!("Red Orchestra", 7759, Protocol::Unreal2),
-    "unrealtournament2003" => game!("Unreal Tournament 2003", 7758, Protocol::Unreal2),
-    "unrealtournament2004" => game!("Unreal Tournament 2004", 7778, Protocol::Unreal2),
+    "dhe4445" => game!("Darkest Hour: Europe '44-'45 (2008)", 7758, Protocol::Unreal2).set_query_port(QueryPort::Offset(1)),
+    "devastation" => game!("Devastation (2003)", 7778, Protocol::Unreal2).set_query_port(QueryPort::Offset(1)),
+    "killingfloor" => game!("Killing Floor", 7708, Protocol::Unreal2).set_query_port(QueryPort::Offset(1)),
+    "redorchestra" => game!("Red Orchestra", 7759, Protocol::Unreal2).set_query_port(QueryPort::Offset(1)),
+    "unrealtournament2003" => game!("Unreal Tournament 2003", 7758, Protocol::Unreal2).set_query_port(QueryPort::Offset(1)),
+    "unrealtournament2004" => game!("Unreal Tournament 2004", 7778, Protocol::Unreal2).set_query_port(QueryPort::Offset(1)),
     "eco" => game!("Eco", 3000, Protocol::PROPRIETARY(ProprietaryProtocol::Eco)),
     "zps" => game!("Zombie Panic: Source", 27015, Protocol::Valve(Engine::new(17_500))),
     "moe" => game!("Myth Of Empires", 12888, Protocol::Valve(Engine::new(1_371_580))),
-    "mordhau" => game!("Mordhau", 27015, Protocol::Valve(Engine::new(629_760))),
+    "mordhau" => game!("Mordhau", 27015, Protocol::Valve(Engine::new(629_760))).set_query_port(QueryPort::Fixed),
     "mindustry" => game!("Mindustry", crate::games::mindustry::DEFAULT_PORT, Protocol::PROPRIETARY(ProprietaryProtocol::Mindustry)),
     "nla" => game!("Nova-Life: Amboise", 27015, Protocol::Valve(Engine::new(885_570))),
 };
+
+/// Alternate names of the games in [GAMES], mapped to their identifier.
+pub static ALIASES: Map<&'static str, &'static str> = phf_map! {
+    "7dtd" => "sdtd",
+    "7daystodie" => "sdtd",
+    "a3" => "arma3",
+    "ark" => "ase",
+    "arma2oa" => "a2oa",
+    "bf1942" => "battlefield1942",
+    "bo3" => "codbo3",
+    "conan" => "conanexiles",
+    "cs" => "counterstrike",
+    "cs2" => "counterstrike2",
+    "gmod" => "garrysmod",
+    "halo" => "hce",
+    "kf" => "killingfloor",
+    "left4dead" => "l4d",
+    "left4dead2" => "l4d2",
+    "mc" => "minecraft",
+    "pz" => "projectzomboid",
+    "q1" => "quake1",
+    "q2" => "quake2",
+    "q3" => "q3a",
+    "quake3" => "q3a",
+    "riskofrain2" => "ror2",
+    "sandstorm" => "insurgencysandstorm",
+    "svencoop" => "sco",
+    "tf2" => "teamfortress2",
+    "ut99" => "unrealtournament",
+    "ut2003" => "unrealtournament2003",
+    "ut2004" => "unrealtournament2004",
+};
@@ -1,8 +1,12 @@
 use crate::eco::{EcoRequestSettings, Response, Root};
 use crate::http::HttpClient;
-use crate::{GDResult, TimeoutSettings};
+use crate::protocols::types::QueryScope;
+use crate::{ErrorContextExt, GDResult, TimeoutSettings};
 use std::net::{IpAddr, SocketAddr};
 
+#[cfg(feature = "async")]
+use crate::http::AsyncHttpClient;
+
 /// Query an eco server.
 #[inline]
 pub fn query(address: &IpAddr, port: Option<u16>) -> GDResult<Response> { query_with_timeout(address, port, &None) }
@@ -24,14 +28,104 @@ pub fn query_with_timeout_and_extra_settings(
     timeout_settings: &Option<TimeoutSettings>,
     extra_settings: Option<EcoRequestSettings>,
 ) -> GDResult<Response> {
-    let address = &SocketAddr::new(*address, port.unwrap_or(3001));
+    let scope = QueryScope::start(timeout_settings);
+    query_with_scope(address, port, timeout_settings, extra_settings, &scope)
+}
+
+/// [query_with_timeout_and_extra_settings] as part of a larger query, sharing
+/// its deadline.
+pub(crate) fn query_with_scope(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: &Option<TimeoutSettings>,
+    extra_settings: Option<EcoRequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(3001));
+    get_response(&address, timeout_settings, extra_settings, scope)
+        .with_address(address)
+        .with_protocol("eco")
+}
+
+fn get_response(
+    address: &SocketAddr,
+    timeout_settings: &Option<TimeoutSettings>,
+    extra_settings: Option<EcoRequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
     let mut client = HttpClient::new(
         address,
         timeout_settings,
         extra_settings.unwrap_or_default().into(),
+        scope,
+    )?;
+
+    let mut response: Response = client.get_json::<Root>("/frontpage", None)?.into();
+    response.latency = client.latency();
+
+    Ok(response)
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &IpAddr, port: Option<u16>) -> GDResult<Response> {
+    query_with_timeout_async(address, port, &None).await
+}
+
+/// Async version of [query_with_timeout].
+#[cfg(feature = "async")]
+pub async fn query_with_timeout_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: &Option<TimeoutSettings>,
+) -> GDResult<Response> {
+    query_with_timeout_and_extra_settings_async(address, port, timeout_settings, None).await
+}
+
+/// Async version of [query_with_timeout_and_extra_settings].
+#[cfg(feature = "async")]
+pub async fn query_with_timeout_and_extra_settings_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: &Option<TimeoutSettings>,
+    extra_settings: Option<EcoRequestSettings>,
+) -> GDResult<Response> {
+    let scope = QueryScope::start(timeout_settings);
+    query_with_scope_async(address, port, timeout_settings, extra_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: &Option<TimeoutSettings>,
+    extra_settings: Option<EcoRequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(3001));
+    get_response_async(&address, timeout_settings, extra_settings, scope)
+        .await
+        .with_address(address)
+        .with_protocol("eco")
+}
+
+#[cfg(feature = "async")]
+async fn get_response_async(
+    address: &SocketAddr,
+    timeout_settings: &Option<TimeoutSettings>,
+    extra_settings: Option<EcoRequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut client = AsyncHttpClient::new(
+        address,
+        timeout_settings,
+        extra_settings.unwrap_or_default().into(),
+        scope,
     )?;
 
-    let response = client.get_json::<Root>("/frontpage", None)?;
+    let mut response: Response = client.get_json::<Root>("/frontpage", None).await?.into();
+    response.latency = client.latency();
 
-    Ok(response.into())
+    Ok(response)
 }
@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 
 use crate::http::{HttpProtocol, HttpSettings};
 use crate::protocols::types::{CommonPlayer, CommonResponse};
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 use crate::ExtraRequestSettings;
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -145,6 +147,8 @@ pub struct Response {
     pub relay_address: String,
     pub access: String,
     pub connect: String,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
 impl From<Root> for Response {
@@ -196,12 +200,14 @@ impl From<Root> for Response {
             relay_address: value.relay_address,
             access: value.access,
             connect: value.join_url,
+            latency: None,
         }
     }
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> crate::protocols::GenericResponse { crate::protocols::GenericResponse::Eco(self) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn players_online(&self) -> u32 { self.players_online }
 
@@ -213,9 +219,20 @@ impl CommonResponse for Response {
 
     fn has_password(&self) -> Option<bool> { Some(self.has_password) }
 
+    fn game_port(&self) -> Option<u16> { self.port.try_into().ok() }
+
     fn players(&self) -> Option<Vec<&dyn CommonPlayer>> { Some(self.players.iter().map(|p| p as _).collect()) }
 }
 
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.description, Dialect::None, format);
+        for player in &mut self.players {
+            normalize_in_place(&mut player.name, Dialect::None, format);
+        }
+    }
+}
+
 /// Extra request settings for eco queries.
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 pub struct EcoRequestSettings {
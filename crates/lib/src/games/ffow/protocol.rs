@@ -1,22 +1,50 @@
 use crate::buffer::{Buffer, Utf8Decoder};
 use crate::games::ffow::types::Response;
-use crate::protocols::types::TimeoutSettings;
+use crate::protocols::types::{QueryScope, TimeoutSettings};
 use crate::protocols::valve::{Engine, Environment, Server, ValveProtocol};
-use crate::GDResult;
+use crate::{ErrorContextExt, GDResult};
 use byteorder::LittleEndian;
 use std::net::{IpAddr, SocketAddr};
 
+#[cfg(feature = "async")]
+use crate::protocols::valve::AsyncValveProtocol;
+
 pub fn query(address: &IpAddr, port: Option<u16>) -> GDResult<Response> { query_with_timeout(address, port, None) }
 
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &IpAddr, port: Option<u16>) -> GDResult<Response> {
+    query_with_timeout_async(address, port, None).await
+}
+
 pub fn query_with_timeout(
     address: &IpAddr,
     port: Option<u16>,
     timeout_settings: Option<TimeoutSettings>,
 ) -> GDResult<Response> {
-    let mut client = ValveProtocol::new(
-        &SocketAddr::new(*address, port.unwrap_or(5478)),
-        timeout_settings,
-    )?;
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, port, timeout_settings, &scope)
+}
+
+/// [query_with_timeout] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(5478));
+    get_response(&address, timeout_settings, scope)
+        .with_address(address)
+        .with_protocol("ffow")
+}
+
+fn get_response(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut client = ValveProtocol::new(address, timeout_settings, scope)?;
     let data = client.get_request_data(
         &Engine::GoldSrc(true),
         0,
@@ -24,7 +52,62 @@ pub fn query_with_timeout(
         String::from("LSQ").into_bytes(),
     )?;
 
-    let mut buffer = Buffer::<LittleEndian>::new(&data);
+    let mut response = parse_response(&data).with_data(&data)?;
+    response.latency = client.latency();
+
+    Ok(response)
+}
+
+/// Async version of [query_with_timeout].
+#[cfg(feature = "async")]
+pub async fn query_with_timeout_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, port, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(5478));
+    get_response_async(&address, timeout_settings, scope)
+        .await
+        .with_address(address)
+        .with_protocol("ffow")
+}
+
+#[cfg(feature = "async")]
+async fn get_response_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut client = AsyncValveProtocol::new(address, timeout_settings, scope).await?;
+    let data = client
+        .get_request_data(
+            &Engine::GoldSrc(true),
+            0,
+            0x46,
+            String::from("LSQ").into_bytes(),
+        )
+        .await?;
+
+    let mut response = parse_response(&data).with_data(&data)?;
+    response.latency = client.latency();
+
+    Ok(response)
+}
+
+fn parse_response(data: &[u8]) -> GDResult<Response> {
+    let mut buffer = Buffer::<LittleEndian>::new(data);
 
     let protocol_version = buffer.read::<u8>()?;
     let name = buffer.read_string::<Utf8Decoder>(None)?;
@@ -62,5 +145,6 @@ pub fn query_with_timeout(
         round,
         rounds_maximum,
         time_left,
+        latency: None,
     })
 }
@@ -1,8 +1,10 @@
 use crate::protocols::types::CommonResponse;
 use crate::protocols::valve::{Environment, Server};
 use crate::protocols::GenericResponse;
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// The query response.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -40,10 +42,13 @@ pub struct Response {
     pub rounds_maximum: u8,
     /// Time left for the current round in seconds.
     pub time_left: u16,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> GenericResponse { GenericResponse::FFOW(self) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn name(&self) -> Option<&str> { Some(&self.name) }
     fn game_mode(&self) -> Option<&str> { Some(&self.game_mode) }
@@ -51,6 +56,15 @@ impl CommonResponse for Response {
     fn game_version(&self) -> Option<&str> { Some(&self.game_version) }
     fn map(&self) -> Option<&str> { Some(&self.map) }
     fn has_password(&self) -> Option<bool> { Some(self.has_password) }
+    fn is_secured(&self) -> Option<bool> { Some(self.vac_secured) }
+    fn server_os(&self) -> Option<&str> { Some(self.environment_type.as_str()) }
     fn players_maximum(&self) -> u32 { self.players_maximum.into() }
     fn players_online(&self) -> u32 { self.players_online.into() }
 }
+
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.name, Dialect::None, format);
+        normalize_in_place(&mut self.description, Dialect::None, format);
+    }
+}
@@ -2,12 +2,15 @@ use crate::buffer::{Buffer, Utf8Decoder};
 use crate::jc2m::{Player, Response};
 use crate::protocols::gamespy::common::has_password;
 use crate::protocols::gamespy::three::{data_to_map, GameSpy3};
-use crate::protocols::types::TimeoutSettings;
+use crate::protocols::types::{QueryScope, TimeoutSettings};
 use crate::GDErrorKind::{PacketBad, TypeParse};
-use crate::GDResult;
+use crate::{ErrorContextExt, GDResult};
 use byteorder::BigEndian;
 use std::net::{IpAddr, SocketAddr};
 
+#[cfg(feature = "async")]
+use crate::protocols::gamespy::three::AsyncGameSpy3;
+
 fn parse_players_and_teams(packet: &[u8]) -> GDResult<Vec<Player>> {
     let mut buf = Buffer::<BigEndian>::new(packet);
 
@@ -27,19 +30,105 @@ fn parse_players_and_teams(packet: &[u8]) -> GDResult<Vec<Player>> {
 
 pub fn query(address: &IpAddr, port: Option<u16>) -> GDResult<Response> { query_with_timeout(address, port, None) }
 
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &IpAddr, port: Option<u16>) -> GDResult<Response> {
+    query_with_timeout_async(address, port, None).await
+}
+
 pub fn query_with_timeout(
     address: &IpAddr,
     port: Option<u16>,
     timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, port, timeout_settings, &scope)
+}
+
+/// [query_with_timeout] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(7777));
+    get_response(&address, timeout_settings, scope)
+        .with_address(address)
+        .with_protocol("jc2m")
+}
+
+fn get_response(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
 ) -> GDResult<Response> {
     let mut client = GameSpy3::new_custom(
-        &SocketAddr::new(*address, port.unwrap_or(7777)),
+        address,
         timeout_settings,
+        scope,
         [0xFF, 0xFF, 0xFF, 0x02],
         true,
     )?;
 
-    let packets = client.get_server_packets()?;
+    let received = client.get_server_packets()?;
+
+    let mut response = parse_response(received.packets).with_stage("data")?;
+    response.latency = client.latency();
+
+    Ok(response)
+}
+
+/// Async version of [query_with_timeout].
+#[cfg(feature = "async")]
+pub async fn query_with_timeout_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, port, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(7777));
+    get_response_async(&address, timeout_settings, scope)
+        .await
+        .with_address(address)
+        .with_protocol("jc2m")
+}
+
+#[cfg(feature = "async")]
+async fn get_response_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut client = AsyncGameSpy3::new_custom(
+        address,
+        timeout_settings,
+        scope,
+        [0xFF, 0xFF, 0xFF, 0x02],
+        true,
+    )
+    .await?;
+
+    let received = client.get_server_packets().await?;
+
+    let mut response = parse_response(received.packets).with_stage("data")?;
+    response.latency = client.latency();
+
+    Ok(response)
+}
+
+fn parse_response(packets: Vec<Vec<u8>>) -> GDResult<Response> {
     let data = packets
         .first()
         .ok_or_else(|| PacketBad.context("First packet missing"))?;
@@ -71,5 +160,6 @@ pub fn query_with_timeout(
         players,
         players_maximum,
         players_online,
+        latency: None,
     })
 }
@@ -1,7 +1,9 @@
 use crate::protocols::types::{CommonPlayer, CommonResponse, GenericPlayer};
 use crate::protocols::GenericResponse;
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -15,6 +17,7 @@ impl CommonPlayer for Player {
     fn as_original(&self) -> GenericPlayer { GenericPlayer::JCMP2(self) }
 
     fn name(&self) -> &str { &self.name }
+    fn ping(&self) -> Option<u32> { Some(self.ping.into()) }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -27,10 +30,13 @@ pub struct Response {
     pub players: Vec<Player>,
     pub players_maximum: u32,
     pub players_online: u32,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> GenericResponse { GenericResponse::JC2M(self) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn game_version(&self) -> Option<&str> { Some(&self.game_version) }
     fn description(&self) -> Option<&str> { Some(&self.description) }
@@ -48,3 +54,13 @@ impl CommonResponse for Response {
         )
     }
 }
+
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.name, Dialect::None, format);
+        normalize_in_place(&mut self.description, Dialect::None, format);
+        for player in &mut self.players {
+            normalize_in_place(&mut player.name, Dialect::None, format);
+        }
+    }
+}
@@ -4,7 +4,7 @@
 
 use std::{net::IpAddr, net::SocketAddr};
 
-use crate::{GDResult, TimeoutSettings};
+use crate::{protocols::types::QueryScope, ErrorContextExt, GDResult, TimeoutSettings};
 
 use self::types::ServerData;
 
@@ -19,7 +19,56 @@ pub const DEFAULT_PORT: u16 = 6567;
 
 /// Query a mindustry server.
 pub fn query(ip: &IpAddr, port: Option<u16>, timeout_settings: &Option<TimeoutSettings>) -> GDResult<ServerData> {
+    query_with_scope(
+        ip,
+        port,
+        timeout_settings,
+        &QueryScope::start(timeout_settings),
+    )
+}
+
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    ip: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: &Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<ServerData> {
+    let address = SocketAddr::new(*ip, port.unwrap_or(DEFAULT_PORT));
+
+    protocol::query_with_retries_and_scope(&address, timeout_settings, scope)
+        .with_address(address)
+        .with_protocol("mindustry")
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(
+    ip: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: &Option<TimeoutSettings>,
+) -> GDResult<ServerData> {
+    query_with_scope_async(
+        ip,
+        port,
+        timeout_settings,
+        &QueryScope::start(timeout_settings),
+    )
+    .await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    ip: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: &Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<ServerData> {
     let address = SocketAddr::new(*ip, port.unwrap_or(DEFAULT_PORT));
 
-    protocol::query_with_retries(&address, timeout_settings)
+    protocol::query_with_retries_and_scope_async(&address, timeout_settings, scope)
+        .await
+        .with_address(address)
+        .with_protocol("mindustry")
 }
@@ -2,6 +2,7 @@ use std::net::SocketAddr;
 
 use crate::{
     buffer::{self, Buffer},
+    protocols::types::QueryScope,
     socket::{Socket, UdpSocket},
     utils,
     GDResult,
@@ -10,13 +11,18 @@ use crate::{
 
 use super::types::ServerData;
 
+#[cfg(feature = "async")]
+use crate::socket::{AsyncSocket, AsyncUdpSocket};
+
 /// Mindustry max datagram packet size.
 pub const MAX_BUFFER_SIZE: usize = 500;
 
 /// Send a ping packet.
 ///
 /// [Reference](https://github.com/Anuken/Mindustry/blob/a2e5fbdedb2fc1c8d3c157bf344d10ad6d321442/core/src/mindustry/net/ArcNetProvider.java#L248)
-pub(crate) fn send_ping(socket: &mut UdpSocket) -> GDResult<()> { socket.send(&[-2i8 as u8, 1i8 as u8]) }
+pub(crate) fn send_ping(socket: &mut UdpSocket) -> GDResult<()> { socket.send(&PING_REQUEST) }
+
+const PING_REQUEST: [u8; 2] = [-2i8 as u8, 1i8 as u8];
 
 /// Parse server data.
 ///
@@ -35,24 +41,120 @@ pub fn parse_server_data<B: byteorder::ByteOrder, D: buffer::StringDecoder>(
         player_limit: buffer.read()?,
         description: buffer.read_string::<D>(None)?,
         mode_name: buffer.read_string::<D>(None).ok(),
+        latency: None,
     })
 }
 
 /// Query a Mindustry server (without retries).
 pub fn query(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>) -> GDResult<ServerData> {
-    let mut socket = UdpSocket::new(address, timeout_settings)?;
+    query_with_scope(
+        address,
+        timeout_settings,
+        &QueryScope::start(timeout_settings),
+    )
+}
+
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &SocketAddr,
+    timeout_settings: &Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<ServerData> {
+    let mut socket = UdpSocket::new(address, timeout_settings, scope)?;
 
     send_ping(&mut socket)?;
 
     let socket_data = socket.receive(Some(MAX_BUFFER_SIZE))?;
-    let mut buffer = Buffer::new(&socket_data);
+
+    let mut response = parse_response(&socket_data)?;
+    response.latency = socket.latency();
+
+    Ok(response)
+}
+
+fn parse_response(data: &[u8]) -> GDResult<ServerData> {
+    let mut buffer = Buffer::new(data);
 
     parse_server_data::<byteorder::BigEndian, buffer::Utf8LengthPrefixedDecoder>(&mut buffer)
 }
 
 /// Query a Mindustry server.
 pub fn query_with_retries(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>) -> GDResult<ServerData> {
-    let retries = TimeoutSettings::get_retries_or_default(timeout_settings);
+    query_with_retries_and_scope(
+        address,
+        timeout_settings,
+        &QueryScope::start(timeout_settings),
+    )
+}
+
+/// [query_with_retries] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_retries_and_scope(
+    address: &SocketAddr,
+    timeout_settings: &Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<ServerData> {
+    let retry_policy = TimeoutSettings::get_retry_policy_or_default(timeout_settings);
+
+    utils::retry_on_timeout(retry_policy, || {
+        query_with_scope(address, timeout_settings, scope)
+    })
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>) -> GDResult<ServerData> {
+    query_with_scope_async(
+        address,
+        timeout_settings,
+        &QueryScope::start(timeout_settings),
+    )
+    .await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &SocketAddr,
+    timeout_settings: &Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<ServerData> {
+    let mut socket = AsyncUdpSocket::new(address, timeout_settings, scope).await?;
+
+    socket.send(&PING_REQUEST).await?;
+
+    let socket_data = socket.receive(Some(MAX_BUFFER_SIZE)).await?;
+
+    let mut response = parse_response(&socket_data)?;
+    response.latency = socket.latency();
+
+    Ok(response)
+}
+
+/// Async version of [query_with_retries].
+#[cfg(feature = "async")]
+pub async fn query_with_retries_async(
+    address: &SocketAddr,
+    timeout_settings: &Option<TimeoutSettings>,
+) -> GDResult<ServerData> {
+    query_with_retries_and_scope_async(
+        address,
+        timeout_settings,
+        &QueryScope::start(timeout_settings),
+    )
+    .await
+}
+
+/// Async version of [query_with_retries_and_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_retries_and_scope_async(
+    address: &SocketAddr,
+    timeout_settings: &Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<ServerData> {
+    let retry_policy = TimeoutSettings::get_retry_policy_or_default(timeout_settings);
 
-    utils::retry_on_timeout(retries, || query(address, timeout_settings))
+    utils::retry_on_timeout_async!(
+        retry_policy,
+        query_with_scope_async(address, timeout_settings, scope)
+    )
 }
@@ -1,9 +1,11 @@
 use crate::{
     protocols::types::{CommonResponse, GenericResponse},
+    text::{normalize_in_place, Dialect, NormalizeText, TextFormat},
     GDErrorKind,
 };
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Mindustry sever data
 ///
@@ -21,6 +23,8 @@ pub struct ServerData {
     pub player_limit: i32,
     pub description: String,
     pub mode_name: Option<String>,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
 /// Mindustry game mode
@@ -65,6 +69,7 @@ impl GameMode {
 
 impl CommonResponse for ServerData {
     fn as_original(&self) -> GenericResponse { GenericResponse::Mindustry(self) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn players_online(&self) -> u32 { self.players.try_into().unwrap_or(0) }
     fn players_maximum(&self) -> u32 { self.player_limit.try_into().unwrap_or(0) }
@@ -75,6 +80,13 @@ impl CommonResponse for ServerData {
     fn description(&self) -> Option<&str> { Some(&self.description) }
 }
 
+impl NormalizeText for ServerData {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.host, Dialect::Mindustry, format);
+        normalize_in_place(&mut self.description, Dialect::Mindustry, format);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::protocols::types::CommonResponse;
@@ -94,6 +106,7 @@ mod test {
             player_limit: 20,
             description: String::from("description"),
             mode_name: Some(String::from("campaign")),
+            latency: Some(std::time::Duration::from_millis(42)),
         };
 
         let common: &dyn CommonResponse = &data;
@@ -103,5 +116,6 @@ mod test {
         assert_eq!(common.game_mode(), Some("pvp"));
         assert_eq!(common.map(), Some("map"));
         assert_eq!(common.description(), Some("description"));
+        assert_eq!(common.latency(), Some(std::time::Duration::from_millis(42)));
     }
 }
@@ -64,6 +64,70 @@ pub fn query_bedrock(address: &IpAddr, port: Option<u16>) -> GDResult<BedrockRes
     )
 }
 
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &IpAddr, port: Option<u16>) -> GDResult<JavaResponse> {
+    if let Ok(response) = query_java_async(address, port, None).await {
+        return Ok(response);
+    }
+
+    if let Ok(response) = query_bedrock_async(address, port).await {
+        return Ok(JavaResponse::from_bedrock_response(response));
+    }
+
+    if let Ok(response) = query_legacy_async(address, port).await {
+        return Ok(response);
+    }
+
+    Err(GDErrorKind::AutoQuery.into())
+}
+
+/// Async version of [query_java].
+#[cfg(feature = "async")]
+pub async fn query_java_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    request_settings: Option<RequestSettings>,
+) -> GDResult<JavaResponse> {
+    protocol::query_java_async(
+        &SocketAddr::new(*address, port_or_java_default(port)),
+        None,
+        request_settings,
+    )
+    .await
+}
+
+/// Async version of [query_legacy].
+#[cfg(feature = "async")]
+pub async fn query_legacy_async(address: &IpAddr, port: Option<u16>) -> GDResult<JavaResponse> {
+    protocol::query_legacy_async(&SocketAddr::new(*address, port_or_java_default(port)), None).await
+}
+
+/// Async version of [query_legacy_specific].
+#[cfg(feature = "async")]
+pub async fn query_legacy_specific_async(
+    group: LegacyGroup,
+    address: &IpAddr,
+    port: Option<u16>,
+) -> GDResult<JavaResponse> {
+    protocol::query_legacy_specific_async(
+        group,
+        &SocketAddr::new(*address, port_or_java_default(port)),
+        None,
+    )
+    .await
+}
+
+/// Async version of [query_bedrock].
+#[cfg(feature = "async")]
+pub async fn query_bedrock_async(address: &IpAddr, port: Option<u16>) -> GDResult<BedrockResponse> {
+    protocol::query_bedrock_async(
+        &SocketAddr::new(*address, port_or_bedrock_default(port)),
+        None,
+    )
+    .await
+}
+
 fn port_or_java_default(port: Option<u16>) -> u16 { port.unwrap_or(25565) }
 
 fn port_or_bedrock_default(port: Option<u16>) -> u16 { port.unwrap_or(19132) }
@@ -3,7 +3,7 @@
 use crate::{
     buffer::{Buffer, Utf8Decoder},
     games::minecraft::{BedrockResponse, GameMode, Server},
-    protocols::types::TimeoutSettings,
+    protocols::types::{QueryScope, RetryPolicy, TimeoutSettings},
     socket::{Socket, UdpSocket},
     utils::{error_by_expected_size, retry_on_timeout},
     GDErrorKind::{PacketBad, TypeParse},
@@ -14,29 +14,30 @@ use std::net::SocketAddr;
 
 use byteorder::LittleEndian;
 
+#[cfg(feature = "async")]
+use crate::{
+    socket::{AsyncSocket, AsyncUdpSocket},
+    utils::retry_on_timeout_async,
+};
+
 pub struct Bedrock {
     socket: UdpSocket,
-    retry_count: usize,
+    retry_policy: RetryPolicy,
 }
 
 impl Bedrock {
-    fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Self> {
-        let socket = UdpSocket::new(address, &timeout_settings)?;
+    fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self> {
+        let socket = UdpSocket::new(address, &timeout_settings, scope)?;
 
-        let retry_count = TimeoutSettings::get_retries_or_default(&timeout_settings);
+        let retry_policy = TimeoutSettings::get_retry_policy_or_default(&timeout_settings);
         Ok(Self {
             socket,
-            retry_count,
+            retry_policy,
         })
     }
 
     fn send_status_request(&mut self) -> GDResult<()> {
-        self.socket.send(&[
-            0x01, // Message ID: ID_UNCONNECTED_PING
-            0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, // Nonce / timestamp
-            0x00, 0xff, 0xff, 0x00, 0xfe, 0xfe, 0xfe, 0xfe, 0xfd, 0xfd, 0xfd, 0xfd, 0x12, 0x34, // Magic
-            0x56, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Client GUID
-        ])?;
+        self.socket.send(&STATUS_REQUEST)?;
 
         Ok(())
     }
@@ -44,7 +45,7 @@ impl Bedrock {
     /// Send a status request, and parse the response.
     /// This function will retry fetch on timeouts.
     fn get_info(&mut self) -> GDResult<BedrockResponse> {
-        retry_on_timeout(self.retry_count, move || self.get_info_impl())
+        retry_on_timeout(self.retry_policy, move || self.get_info_impl())
     }
 
     /// Send a status request, and parse the response (without retry logic).
@@ -52,60 +53,102 @@ impl Bedrock {
         self.send_status_request()?;
 
         let received = self.socket.receive(None)?;
-        let mut buffer = Buffer::<LittleEndian>::new(&received);
-
-        if buffer.read::<u8>()? != 0x1c {
-            return Err(PacketBad.context("Expected 0x1c"));
-        }
-
-        // Checking for our nonce directly from a u64 (as the nonce is 8 bytes).
-        if buffer.read::<u64>()? != 9_833_440_827_789_222_417 {
-            return Err(PacketBad.context("Invalid nonce"));
-        }
-
-        // These 8 bytes are identical to the serverId string we receive in decimal
-        // below
-        buffer.move_cursor(8)?;
-
-        // Verifying the magic value (as we need 16 bytes, cast to two u64 values)
-        if buffer.read::<u64>()? != 18_374_403_896_610_127_616 {
-            return Err(PacketBad.context("Invalid magic"));
-        }
-
-        if buffer.read::<u64>()? != 8_671_175_388_723_805_693 {
-            return Err(PacketBad.context("Invalid magic"));
-        }
-
-        let remaining_length = buffer.switch_endian_chunk(2)?.read::<u16>()? as usize;
-
-        error_by_expected_size(remaining_length, buffer.remaining_length())?;
-
-        let binding = buffer.read_string::<Utf8Decoder>(None)?;
-        let status: Vec<&str> = binding.split(';').collect();
-
-        // We must have at least 6 values
-        if status.len() < 6 {
-            return Err(PacketBad.context("Not enough values"));
-        }
-
-        Ok(BedrockResponse {
-            edition: status[0].to_string(),
-            name: status[1].to_string(),
-            version_name: status[3].to_string(),
-            protocol_version: status[2].to_string(),
-            players_maximum: status[5].parse().map_err(|e| TypeParse.context(e))?,
-            players_online: status[4].parse().map_err(|e| TypeParse.context(e))?,
-            id: status.get(6).map(std::string::ToString::to_string),
-            map: status.get(7).map(std::string::ToString::to_string),
-            game_mode: match status.get(8) {
-                None => None,
-                Some(v) => Some(GameMode::from_bedrock(v)?),
-            },
-            server_type: Server::Bedrock,
-        })
+
+        let mut response = parse_status_response(&received)?;
+        response.latency = self.socket.latency();
+
+        Ok(response)
+    }
+
+    pub fn query(
+        address: &SocketAddr,
+        timeout_settings: Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<BedrockResponse> {
+        Self::new(address, timeout_settings, scope)?.get_info()
+    }
+
+    /// Async version of [Bedrock::query].
+    #[cfg(feature = "async")]
+    pub async fn query_async(
+        address: &SocketAddr,
+        timeout_settings: Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<BedrockResponse> {
+        let mut socket = AsyncUdpSocket::new(address, &timeout_settings, scope).await?;
+
+        let mut response = retry_on_timeout_async!(
+            TimeoutSettings::get_retry_policy_or_default(&timeout_settings),
+            async {
+                socket.send(&STATUS_REQUEST).await?;
+                parse_status_response(&socket.receive(None).await?)
+            }
+        )?;
+        response.latency = socket.latency();
+
+        Ok(response)
+    }
+}
+
+const STATUS_REQUEST: [u8; 33] = [
+    0x01, // Message ID: ID_UNCONNECTED_PING
+    0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, // Nonce / timestamp
+    0x00, 0xff, 0xff, 0x00, 0xfe, 0xfe, 0xfe, 0xfe, 0xfd, 0xfd, 0xfd, 0xfd, 0x12, 0x34, // Magic
+    0x56, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Client GUID
+];
+
+/// Parse the status response.
+fn parse_status_response(received: &[u8]) -> GDResult<BedrockResponse> {
+    let mut buffer = Buffer::<LittleEndian>::new(received);
+
+    if buffer.read::<u8>()? != 0x1c {
+        return Err(PacketBad.context("Expected 0x1c"));
+    }
+
+    // Checking for our nonce directly from a u64 (as the nonce is 8 bytes).
+    if buffer.read::<u64>()? != 9_833_440_827_789_222_417 {
+        return Err(PacketBad.context("Invalid nonce"));
+    }
+
+    // These 8 bytes are identical to the serverId string we receive in decimal
+    // below
+    buffer.move_cursor(8)?;
+
+    // Verifying the magic value (as we need 16 bytes, cast to two u64 values)
+    if buffer.read::<u64>()? != 18_374_403_896_610_127_616 {
+        return Err(PacketBad.context("Invalid magic"));
     }
 
-    pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<BedrockResponse> {
-        Self::new(address, timeout_settings)?.get_info()
+    if buffer.read::<u64>()? != 8_671_175_388_723_805_693 {
+        return Err(PacketBad.context("Invalid magic"));
     }
+
+    let remaining_length = buffer.switch_endian_chunk(2)?.read::<u16>()? as usize;
+
+    error_by_expected_size(remaining_length, buffer.remaining_length())?;
+
+    let binding = buffer.read_string::<Utf8Decoder>(None)?;
+    let status: Vec<&str> = binding.split(';').collect();
+
+    // We must have at least 6 values
+    if status.len() < 6 {
+        return Err(PacketBad.context("Not enough values"));
+    }
+
+    Ok(BedrockResponse {
+        edition: status[0].to_string(),
+        name: status[1].to_string(),
+        version_name: status[3].to_string(),
+        protocol_version: status[2].to_string(),
+        players_maximum: status[5].parse().map_err(|e| TypeParse.context(e))?,
+        players_online: status[4].parse().map_err(|e| TypeParse.context(e))?,
+        id: status.get(6).map(std::string::ToString::to_string),
+        map: status.get(7).map(std::string::ToString::to_string),
+        game_mode: match status.get(8) {
+            None => None,
+            Some(v) => Some(GameMode::from_bedrock(v)?),
+        },
+        server_type: Server::Bedrock,
+        latency: None,
+    })
 }
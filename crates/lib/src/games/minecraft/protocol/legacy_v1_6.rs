@@ -3,7 +3,7 @@ use byteorder::BigEndian;
 use crate::{
     buffer::{Buffer, Utf16Decoder},
     games::minecraft::{JavaResponse, LegacyGroup, Server},
-    protocols::types::TimeoutSettings,
+    protocols::types::{QueryScope, RetryPolicy, TimeoutSettings},
     socket::{Socket, TcpSocket},
     utils::{error_by_expected_size, retry_on_timeout},
     GDErrorKind::{PacketBad, ProtocolFormat},
@@ -11,34 +11,29 @@ use crate::{
 };
 use std::net::SocketAddr;
 
+#[cfg(feature = "async")]
+use crate::{
+    socket::{AsyncSocket, AsyncTcpSocket},
+    utils::retry_on_timeout_async,
+};
+
 pub struct LegacyV1_6 {
     socket: TcpSocket,
-    retry_count: usize,
+    retry_policy: RetryPolicy,
 }
 
 impl LegacyV1_6 {
-    fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Self> {
-        let socket = TcpSocket::new(address, &timeout_settings)?;
+    fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self> {
+        let socket = TcpSocket::new(address, &timeout_settings, scope)?;
 
-        let retry_count = TimeoutSettings::get_retries_or_default(&timeout_settings);
+        let retry_policy = TimeoutSettings::get_retry_policy_or_default(&timeout_settings);
         Ok(Self {
             socket,
-            retry_count,
+            retry_policy,
         })
     }
 
-    fn send_initial_request(&mut self) -> GDResult<()> {
-        self.socket.send(&[
-            0xfe, // Packet ID (FE)
-            0x01, // Ping payload (01)
-            0xfa, // Packet identifier for plugin message
-            0x00, 0x07, // Length of 'GameDig' string (7) as unsigned short
-            0x00, 0x47, 0x00, 0x61, 0x00, 0x6D, 0x00, 0x65, 0x00, 0x44, 0x00, 0x69, 0x00,
-            0x67, // 'GameDig' string as UTF-16BE
-        ])?;
-
-        Ok(())
-    }
+    fn send_initial_request(&mut self) -> GDResult<()> { self.socket.send(&INITIAL_REQUEST) }
 
     pub(crate) fn is_protocol(buffer: &mut Buffer<BigEndian>) -> GDResult<bool> {
         let state = buffer
@@ -80,13 +75,14 @@ impl LegacyV1_6 {
             previews_chat: None,
             enforces_secure_chat: None,
             server_type: Server::Legacy(LegacyGroup::V1_6),
+            latency: None,
         })
     }
 
     /// Send info request and parse response.
     /// This function will retry fetch on timeouts.
     fn get_info(&mut self) -> GDResult<JavaResponse> {
-        retry_on_timeout(self.retry_count, move || self.get_info_impl())
+        retry_on_timeout(self.retry_policy, move || self.get_info_impl())
     }
 
     /// Send info request and parse response (without retry logic).
@@ -94,23 +90,66 @@ impl LegacyV1_6 {
         self.send_initial_request()?;
 
         let data = self.socket.receive(None)?;
-        let mut buffer = Buffer::<BigEndian>::new(&data);
 
-        if buffer.read::<u8>()? != 0xFF {
-            return Err(ProtocolFormat.context("Expected 0xFF"));
-        }
+        let mut response = parse_response(&data)?;
+        response.latency = self.socket.latency();
 
-        let length = buffer.read::<u16>()? * 2;
-        error_by_expected_size((length + 3) as usize, data.len())?;
+        Ok(response)
+    }
 
-        if !Self::is_protocol(&mut buffer)? {
-            return Err(ProtocolFormat.context("Not legacy 1.6 protocol"));
-        }
+    pub fn query(
+        address: &SocketAddr,
+        timeout_settings: Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<JavaResponse> {
+        Self::new(address, timeout_settings, scope)?.get_info()
+    }
+
+    /// Async version of [LegacyV1_6::query].
+    #[cfg(feature = "async")]
+    pub async fn query_async(
+        address: &SocketAddr,
+        timeout_settings: Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<JavaResponse> {
+        let mut socket = AsyncTcpSocket::new(address, &timeout_settings, scope).await?;
+
+        let mut response = retry_on_timeout_async!(
+            TimeoutSettings::get_retry_policy_or_default(&timeout_settings),
+            async {
+                socket.send(&INITIAL_REQUEST).await?;
+                parse_response(&socket.receive(None).await?)
+            }
+        )?;
+        response.latency = socket.latency();
+
+        Ok(response)
+    }
+}
 
-        Self::get_response(&mut buffer)
+const INITIAL_REQUEST: [u8; 19] = [
+    0xfe, // Packet ID (FE)
+    0x01, // Ping payload (01)
+    0xfa, // Packet identifier for plugin message
+    0x00, 0x07, // Length of 'GameDig' string (7) as unsigned short
+    0x00, 0x47, 0x00, 0x61, 0x00, 0x6D, 0x00, 0x65, 0x00, 0x44, 0x00, 0x69, 0x00,
+    0x67, // 'GameDig' string as UTF-16BE
+];
+
+/// Parse the info response.
+fn parse_response(data: &[u8]) -> GDResult<JavaResponse> {
+    let mut buffer = Buffer::<BigEndian>::new(data);
+
+    if buffer.read::<u8>()? != 0xFF {
+        return Err(ProtocolFormat.context("Expected 0xFF"));
     }
 
-    pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<JavaResponse> {
-        Self::new(address, timeout_settings)?.get_info()
+    let length = buffer.read::<u16>()? * 2;
+    error_by_expected_size((length + 3) as usize, data.len())?;
+
+    if !LegacyV1_6::is_protocol(&mut buffer)? {
+        return Err(ProtocolFormat.context("Not legacy 1.6 protocol"));
     }
+
+    LegacyV1_6::get_response(&mut buffer)
 }
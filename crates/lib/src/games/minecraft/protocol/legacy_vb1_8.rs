@@ -1,7 +1,7 @@
 use crate::{
     buffer::{Buffer, Utf16Decoder},
     games::minecraft::{JavaResponse, LegacyGroup, Server},
-    protocols::types::TimeoutSettings,
+    protocols::types::{QueryScope, RetryPolicy, TimeoutSettings},
     socket::{Socket, TcpSocket},
     utils::{error_by_expected_size, retry_on_timeout},
     GDErrorKind::{PacketBad, ProtocolFormat},
@@ -10,30 +10,36 @@ use crate::{
 
 use std::net::SocketAddr;
 
+#[cfg(feature = "async")]
+use crate::{
+    socket::{AsyncSocket, AsyncTcpSocket},
+    utils::retry_on_timeout_async,
+};
+
 use byteorder::BigEndian;
 
 pub struct LegacyVB1_8 {
     socket: TcpSocket,
-    retry_count: usize,
+    retry_policy: RetryPolicy,
 }
 
 impl LegacyVB1_8 {
-    fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Self> {
-        let socket = TcpSocket::new(address, &timeout_settings)?;
+    fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self> {
+        let socket = TcpSocket::new(address, &timeout_settings, scope)?;
 
-        let retry_count = TimeoutSettings::get_retries_or_default(&timeout_settings);
+        let retry_policy = TimeoutSettings::get_retry_policy_or_default(&timeout_settings);
         Ok(Self {
             socket,
-            retry_count,
+            retry_policy,
         })
     }
 
-    fn send_initial_request(&mut self) -> GDResult<()> { self.socket.send(&[0xFE]) }
+    fn send_initial_request(&mut self) -> GDResult<()> { self.socket.send(&INITIAL_REQUEST) }
 
     /// Send request for info and parse response.
     /// This function will retry fetch on timeouts.
     fn get_info(&mut self) -> GDResult<JavaResponse> {
-        retry_on_timeout(self.retry_count, move || self.get_info_impl())
+        retry_on_timeout(self.retry_policy, move || self.get_info_impl())
     }
 
     /// Send request for info and parse response (without retry logic).
@@ -41,39 +47,76 @@ impl LegacyVB1_8 {
         self.send_initial_request()?;
 
         let data = self.socket.receive(None)?;
-        let mut buffer = Buffer::<BigEndian>::new(&data);
-
-        if buffer.read::<u8>()? != 0xFF {
-            return Err(ProtocolFormat.context("Expected 0xFF"));
-        }
-
-        let length = buffer.read::<u16>()? * 2;
-        error_by_expected_size((length + 3) as usize, data.len())?;
-
-        let packet_string = buffer.read_string::<Utf16Decoder<BigEndian>>(None)?;
-
-        let split: Vec<&str> = packet_string.split('§').collect();
-        error_by_expected_size(3, split.len())?;
-
-        let description = split[0].to_string();
-        let online_players = split[1].parse().map_err(|e| PacketBad.context(e))?;
-        let max_players = split[2].parse().map_err(|e| PacketBad.context(e))?;
-
-        Ok(JavaResponse {
-            game_version: "Beta 1.8+".to_string(),
-            protocol_version: -1,
-            players_maximum: max_players,
-            players_online: online_players,
-            players: None,
-            description,
-            favicon: None,
-            previews_chat: None,
-            enforces_secure_chat: None,
-            server_type: Server::Legacy(LegacyGroup::VB1_8),
-        })
+
+        let mut response = parse_response(&data)?;
+        response.latency = self.socket.latency();
+
+        Ok(response)
+    }
+
+    pub fn query(
+        address: &SocketAddr,
+        timeout_settings: Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<JavaResponse> {
+        Self::new(address, timeout_settings, scope)?.get_info()
     }
 
-    pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<JavaResponse> {
-        Self::new(address, timeout_settings)?.get_info()
+    /// Async version of [LegacyVB1_8::query].
+    #[cfg(feature = "async")]
+    pub async fn query_async(
+        address: &SocketAddr,
+        timeout_settings: Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<JavaResponse> {
+        let mut socket = AsyncTcpSocket::new(address, &timeout_settings, scope).await?;
+
+        let mut response = retry_on_timeout_async!(
+            TimeoutSettings::get_retry_policy_or_default(&timeout_settings),
+            async {
+                socket.send(&INITIAL_REQUEST).await?;
+                parse_response(&socket.receive(None).await?)
+            }
+        )?;
+        response.latency = socket.latency();
+
+        Ok(response)
     }
 }
+
+const INITIAL_REQUEST: [u8; 1] = [0xFE];
+
+/// Parse the info response.
+fn parse_response(data: &[u8]) -> GDResult<JavaResponse> {
+    let mut buffer = Buffer::<BigEndian>::new(data);
+
+    if buffer.read::<u8>()? != 0xFF {
+        return Err(ProtocolFormat.context("Expected 0xFF"));
+    }
+
+    let length = buffer.read::<u16>()? * 2;
+    error_by_expected_size((length + 3) as usize, data.len())?;
+
+    let packet_string = buffer.read_string::<Utf16Decoder<BigEndian>>(None)?;
+
+    let split: Vec<&str> = packet_string.split('§').collect();
+    error_by_expected_size(3, split.len())?;
+
+    let description = split[0].to_string();
+    let online_players = split[1].parse().map_err(|e| PacketBad.context(e))?;
+    let max_players = split[2].parse().map_err(|e| PacketBad.context(e))?;
+
+    Ok(JavaResponse {
+        game_version: "Beta 1.8+".to_string(),
+        protocol_version: -1,
+        players_maximum: max_players,
+        players_online: online_players,
+        players: None,
+        description,
+        favicon: None,
+        previews_chat: None,
+        enforces_secure_chat: None,
+        server_type: Server::Legacy(LegacyGroup::VB1_8),
+        latency: None,
+    })
+}
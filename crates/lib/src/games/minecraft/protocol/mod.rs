@@ -12,8 +12,10 @@ use crate::{
         JavaResponse,
         LegacyGroup,
     },
-    protocols::types::TimeoutSettings,
-    GDErrorKind::AutoQuery,
+    protocols::types::{QueryScope, TimeoutSettings},
+    ErrorContextExt,
+    GDError,
+    GDErrorKind::{AutoQuery, DeadlineExceeded},
     GDResult,
 };
 use std::net::SocketAddr;
@@ -24,26 +26,46 @@ mod legacy_v1_4;
 mod legacy_v1_6;
 mod legacy_vb1_8;
 
+/// Try the next protocol variant unless the query deadline expired.
+macro_rules! try_variant {
+    ($result: expr) => {
+        match $result {
+            Ok(response) => return Ok(response),
+            Err(e) if e.kind == DeadlineExceeded => return Err(e),
+            Err(_) => {}
+        }
+    };
+}
+
 /// Queries a Minecraft server with all the protocol variants one by one (Java
-/// -> Bedrock -> Legacy (1.6 -> 1.4 -> Beta 1.8)).
+/// -> Bedrock -> Legacy (1.6 -> 1.4 -> Beta 1.8)), until the query deadline
+/// (if any) expires.
 pub fn query(
     address: &SocketAddr,
     timeout_settings: Option<TimeoutSettings>,
     request_settings: Option<RequestSettings>,
 ) -> GDResult<JavaResponse> {
-    if let Ok(response) = query_java(address, timeout_settings, request_settings) {
-        return Ok(response);
-    }
-
-    if let Ok(response) = query_bedrock(address, timeout_settings) {
-        return Ok(JavaResponse::from_bedrock_response(response));
-    }
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, timeout_settings, request_settings, &scope)
+}
 
-    if let Ok(response) = query_legacy(address, timeout_settings) {
-        return Ok(response);
-    }
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    request_settings: Option<RequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<JavaResponse> {
+    try_variant!(query_java_with_scope(
+        address,
+        timeout_settings,
+        request_settings,
+        scope
+    ));
+    try_variant!(query_bedrock_with_scope(address, timeout_settings, scope).map(JavaResponse::from_bedrock_response));
+    try_variant!(query_legacy_with_scope(address, timeout_settings, scope));
 
-    Err(AutoQuery.into())
+    Err(auto_query_error(address))
 }
 
 /// Query a Java Server.
@@ -52,24 +74,54 @@ pub fn query_java(
     timeout_settings: Option<TimeoutSettings>,
     request_settings: Option<RequestSettings>,
 ) -> GDResult<JavaResponse> {
-    Java::query(address, timeout_settings, request_settings)
+    let scope = QueryScope::start(&timeout_settings);
+    query_java_with_scope(address, timeout_settings, request_settings, &scope)
+}
+
+/// [query_java] as part of a larger query, sharing its deadline.
+pub(crate) fn query_java_with_scope(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    request_settings: Option<RequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<JavaResponse> {
+    Java::query(address, timeout_settings, request_settings, scope)
+        .with_stage("java")
+        .with_address(*address)
+        .with_protocol("minecraft")
 }
 
 /// Query a (Java) Legacy Server (1.6 -> 1.4 -> Beta 1.8).
 pub fn query_legacy(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<JavaResponse> {
-    if let Ok(response) = query_legacy_specific(LegacyGroup::V1_6, address, timeout_settings) {
-        return Ok(response);
-    }
-
-    if let Ok(response) = query_legacy_specific(LegacyGroup::V1_4, address, timeout_settings) {
-        return Ok(response);
-    }
+    let scope = QueryScope::start(&timeout_settings);
+    query_legacy_with_scope(address, timeout_settings, &scope)
+}
 
-    if let Ok(response) = query_legacy_specific(LegacyGroup::VB1_8, address, timeout_settings) {
-        return Ok(response);
-    }
+fn query_legacy_with_scope(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<JavaResponse> {
+    try_variant!(query_legacy_specific_with_scope(
+        LegacyGroup::V1_6,
+        address,
+        timeout_settings,
+        scope
+    ));
+    try_variant!(query_legacy_specific_with_scope(
+        LegacyGroup::V1_4,
+        address,
+        timeout_settings,
+        scope
+    ));
+    try_variant!(query_legacy_specific_with_scope(
+        LegacyGroup::VB1_8,
+        address,
+        timeout_settings,
+        scope
+    ));
 
-    Err(AutoQuery.into())
+    Err(auto_query_error(address))
 }
 
 /// Query a specific (Java) Legacy Server.
@@ -77,15 +129,190 @@ pub fn query_legacy_specific(
     group: LegacyGroup,
     address: &SocketAddr,
     timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<JavaResponse> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_legacy_specific_with_scope(group, address, timeout_settings, &scope)
+}
+
+/// [query_legacy_specific] as part of a larger query, sharing its deadline.
+pub(crate) fn query_legacy_specific_with_scope(
+    group: LegacyGroup,
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
 ) -> GDResult<JavaResponse> {
     match group {
-        LegacyGroup::V1_6 => LegacyV1_6::query(address, timeout_settings),
-        LegacyGroup::V1_4 => LegacyV1_4::query(address, timeout_settings),
-        LegacyGroup::VB1_8 => LegacyVB1_8::query(address, timeout_settings),
+        LegacyGroup::V1_6 => LegacyV1_6::query(address, timeout_settings, scope),
+        LegacyGroup::V1_4 => LegacyV1_4::query(address, timeout_settings, scope),
+        LegacyGroup::VB1_8 => LegacyVB1_8::query(address, timeout_settings, scope),
     }
+    .with_stage(legacy_stage(group))
+    .with_address(*address)
+    .with_protocol("minecraft")
 }
 
 /// Query a Bedrock Server.
 pub fn query_bedrock(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<BedrockResponse> {
-    Bedrock::query(address, timeout_settings)
+    let scope = QueryScope::start(&timeout_settings);
+    query_bedrock_with_scope(address, timeout_settings, &scope)
+}
+
+/// [query_bedrock] as part of a larger query, sharing its deadline.
+pub(crate) fn query_bedrock_with_scope(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<BedrockResponse> {
+    Bedrock::query(address, timeout_settings, scope)
+        .with_stage("bedrock")
+        .with_address(*address)
+        .with_protocol("minecraft")
+}
+
+/// The error of a query for which no protocol variant answered.
+fn auto_query_error(address: &SocketAddr) -> GDError {
+    GDError::from(AutoQuery)
+        .with_address(*address)
+        .with_protocol("minecraft")
+}
+
+/// The stage of a failed query of a Legacy Server.
+const fn legacy_stage(group: LegacyGroup) -> &'static str {
+    match group {
+        LegacyGroup::V1_6 => "legacy_v1_6",
+        LegacyGroup::V1_4 => "legacy_v1_4",
+        LegacyGroup::VB1_8 => "legacy_vb1_8",
+    }
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    request_settings: Option<RequestSettings>,
+) -> GDResult<JavaResponse> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, timeout_settings, request_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    request_settings: Option<RequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<JavaResponse> {
+    try_variant!(query_java_with_scope_async(address, timeout_settings, request_settings, scope).await);
+    try_variant!(
+        query_bedrock_with_scope_async(address, timeout_settings, scope)
+            .await
+            .map(JavaResponse::from_bedrock_response)
+    );
+    try_variant!(query_legacy_with_scope_async(address, timeout_settings, scope).await);
+
+    Err(auto_query_error(address))
+}
+
+/// Async version of [query_java].
+#[cfg(feature = "async")]
+pub async fn query_java_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    request_settings: Option<RequestSettings>,
+) -> GDResult<JavaResponse> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_java_with_scope_async(address, timeout_settings, request_settings, &scope).await
+}
+
+/// Async version of [query_java_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_java_with_scope_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    request_settings: Option<RequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<JavaResponse> {
+    Java::query_async(address, timeout_settings, request_settings, scope)
+        .await
+        .with_stage("java")
+        .with_address(*address)
+        .with_protocol("minecraft")
+}
+
+/// Async version of [query_legacy].
+#[cfg(feature = "async")]
+pub async fn query_legacy_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<JavaResponse> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_legacy_with_scope_async(address, timeout_settings, &scope).await
+}
+
+#[cfg(feature = "async")]
+async fn query_legacy_with_scope_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<JavaResponse> {
+    try_variant!(query_legacy_specific_with_scope_async(LegacyGroup::V1_6, address, timeout_settings, scope).await);
+    try_variant!(query_legacy_specific_with_scope_async(LegacyGroup::V1_4, address, timeout_settings, scope).await);
+    try_variant!(query_legacy_specific_with_scope_async(LegacyGroup::VB1_8, address, timeout_settings, scope).await);
+
+    Err(auto_query_error(address))
+}
+
+/// Async version of [query_legacy_specific].
+#[cfg(feature = "async")]
+pub async fn query_legacy_specific_async(
+    group: LegacyGroup,
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<JavaResponse> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_legacy_specific_with_scope_async(group, address, timeout_settings, &scope).await
+}
+
+/// Async version of [query_legacy_specific_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_legacy_specific_with_scope_async(
+    group: LegacyGroup,
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<JavaResponse> {
+    match group {
+        LegacyGroup::V1_6 => LegacyV1_6::query_async(address, timeout_settings, scope).await,
+        LegacyGroup::V1_4 => LegacyV1_4::query_async(address, timeout_settings, scope).await,
+        LegacyGroup::VB1_8 => LegacyVB1_8::query_async(address, timeout_settings, scope).await,
+    }
+    .with_stage(legacy_stage(group))
+    .with_address(*address)
+    .with_protocol("minecraft")
+}
+
+/// Async version of [query_bedrock].
+#[cfg(feature = "async")]
+pub async fn query_bedrock_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<BedrockResponse> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_bedrock_with_scope_async(address, timeout_settings, &scope).await
+}
+
+/// Async version of [query_bedrock_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_bedrock_with_scope_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<BedrockResponse> {
+    Bedrock::query_async(address, timeout_settings, scope)
+        .await
+        .with_stage("bedrock")
+        .with_address(*address)
+        .with_protocol("minecraft")
 }
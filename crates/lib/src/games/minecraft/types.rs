@@ -8,6 +8,7 @@ use crate::{
         types::{CommonPlayer, CommonResponse, ExtraRequestSettings, GenericPlayer},
         GenericResponse,
     },
+    text::{normalize_in_place, Dialect, NormalizeText, TextFormat},
     GDErrorKind::{InvalidInput, PacketBad, UnknownEnumCast},
     GDResult,
 };
@@ -15,6 +16,7 @@ use crate::{
 use byteorder::ByteOrder;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// The type of Minecraft Server you want to query.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -87,6 +89,8 @@ pub struct JavaResponse {
     pub enforces_secure_chat: Option<bool>,
     /// Tell's the server type.
     pub server_type: Server,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
 /// Java-only additional request settings.
@@ -135,6 +139,7 @@ impl From<ExtraRequestSettings> for RequestSettings {
 
 impl CommonResponse for JavaResponse {
     fn as_original(&self) -> GenericResponse { GenericResponse::Minecraft(VersionedResponse::Java(self)) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn description(&self) -> Option<&str> { Some(&self.description) }
     fn players_maximum(&self) -> u32 { self.players_maximum }
@@ -148,6 +153,15 @@ impl CommonResponse for JavaResponse {
     }
 }
 
+impl NormalizeText for JavaResponse {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.description, Dialect::Minecraft, format);
+        for player in self.players.iter_mut().flatten() {
+            normalize_in_place(&mut player.name, Dialect::Minecraft, format);
+        }
+    }
+}
+
 /// A Bedrock Edition query response.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -172,10 +186,13 @@ pub struct BedrockResponse {
     pub game_mode: Option<GameMode>,
     /// Tells the server type.
     pub server_type: Server,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
 impl CommonResponse for BedrockResponse {
     fn as_original(&self) -> GenericResponse { GenericResponse::Minecraft(VersionedResponse::Bedrock(self)) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn name(&self) -> Option<&str> { Some(&self.name) }
     fn map(&self) -> Option<&str> { self.map.as_deref() }
@@ -184,6 +201,10 @@ impl CommonResponse for BedrockResponse {
     fn players_online(&self) -> u32 { self.players_online }
 }
 
+impl NormalizeText for BedrockResponse {
+    fn normalize_text(&mut self, format: TextFormat) { normalize_in_place(&mut self.name, Dialect::Minecraft, format); }
+}
+
 impl JavaResponse {
     pub fn from_bedrock_response(response: BedrockResponse) -> Self {
         Self {
@@ -197,6 +218,7 @@ impl JavaResponse {
             previews_chat: None,
             enforces_secure_chat: None,
             server_type: Server::Bedrock,
+            latency: response.latency,
         }
     }
 }
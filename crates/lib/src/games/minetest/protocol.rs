@@ -1,6 +1,7 @@
 use crate::minetest::Response;
-use crate::{minetest_master_server, GDErrorKind, GDResult, TimeoutSettings};
-use std::net::IpAddr;
+use crate::protocols::types::QueryScope;
+use crate::{minetest_master_server, ErrorContextExt, GDErrorKind, GDResult, TimeoutSettings};
+use std::net::{IpAddr, SocketAddr};
 
 pub fn query(address: &IpAddr, port: Option<u16>) -> GDResult<Response> { query_with_timeout(address, port, &None) }
 
@@ -9,12 +10,75 @@ pub fn query_with_timeout(
     port: Option<u16>,
     timeout_settings: &Option<TimeoutSettings>,
 ) -> GDResult<Response> {
-    let address = address.to_string();
-    let port = port.unwrap_or(30000);
+    query_with_scope(
+        address,
+        port,
+        timeout_settings,
+        &QueryScope::start(timeout_settings),
+    )
+}
+
+/// [query_with_timeout] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: &Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(30000));
+    let timeout_settings = Some(timeout_settings.unwrap_or_default());
+    minetest_master_server::query_with_scope(&timeout_settings, scope)
+        .with_stage("master_server")
+        .and_then(|servers| find_server(servers, &address))
+        .with_address(address)
+        .with_protocol("minetest")
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &IpAddr, port: Option<u16>) -> GDResult<Response> {
+    query_with_timeout_async(address, port, &None).await
+}
+
+/// Async version of [query_with_timeout].
+#[cfg(feature = "async")]
+pub async fn query_with_timeout_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: &Option<TimeoutSettings>,
+) -> GDResult<Response> {
+    query_with_scope_async(
+        address,
+        port,
+        timeout_settings,
+        &QueryScope::start(timeout_settings),
+    )
+    .await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: &Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(30000));
+    let timeout_settings = Some(timeout_settings.unwrap_or_default());
+    minetest_master_server::query_with_scope_async(&timeout_settings, scope)
+        .await
+        .with_stage("master_server")
+        .and_then(|servers| find_server(servers, &address))
+        .with_address(address)
+        .with_protocol("minetest")
+}
+
+fn find_server(servers: minetest_master_server::Response, address: &SocketAddr) -> GDResult<Response> {
+    let ip = address.ip().to_string();
 
-    let servers = minetest_master_server::query(timeout_settings.unwrap_or_default())?;
     for server in servers.list {
-        if server.ip == address && server.port == port {
+        if server.ip == ip && server.port == address.port() {
             return Ok(server.into());
         }
     }
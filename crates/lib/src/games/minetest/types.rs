@@ -1,7 +1,9 @@
 use crate::minetest_master_server::Server;
 use crate::protocols::types::{CommonPlayer, CommonResponse, GenericPlayer};
 use crate::protocols::GenericResponse;
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Player {
@@ -42,6 +44,9 @@ pub struct Response {
     pub pop_v: f32,
     pub geo_continent: Option<String>,
     pub ping: f32,
+    /// Always [None], as the data comes from the master server rather than
+    /// the server itself (see [Response::ping] instead).
+    pub latency: Option<Duration>,
 }
 
 impl From<Server> for Response {
@@ -78,12 +83,14 @@ impl From<Server> for Response {
             pop_v: server.pop_v,
             geo_continent: server.geo_continent,
             ping: server.ping,
+            latency: None,
         }
     }
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> GenericResponse { GenericResponse::Minetest(self) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn name(&self) -> Option<&str> { Some(&self.name) }
 
@@ -91,6 +98,8 @@ impl CommonResponse for Response {
 
     fn game_version(&self) -> Option<&str> { Some(&self.game_version) }
 
+    fn game_port(&self) -> Option<u16> { Some(self.port) }
+
     fn players_maximum(&self) -> u32 { self.players_maximum }
 
     fn players_online(&self) -> u32 { self.players_online }
@@ -106,3 +115,13 @@ impl CommonResponse for Response {
         )
     }
 }
+
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.name, Dialect::None, format);
+        normalize_in_place(&mut self.description, Dialect::None, format);
+        for player in &mut self.players {
+            normalize_in_place(&mut player.name, Dialect::None, format);
+        }
+    }
+}
@@ -43,8 +43,26 @@ pub use types::*;
 pub mod query;
 pub use query::*;
 
+pub mod probe;
+pub use probe::*;
+
+#[cfg(feature = "async")]
+pub mod bulk;
+#[cfg(feature = "async")]
+pub use bulk::*;
+
 #[cfg(feature = "game_defs")]
 mod definitions;
 
 #[cfg(feature = "game_defs")]
-pub use definitions::GAMES;
+pub use definitions::{ALIASES, GAMES};
+
+#[cfg(feature = "game_defs")]
+pub mod registry;
+#[cfg(feature = "game_defs")]
+pub use registry::{GameMatch, GameRegistry, MatchKind};
+
+#[cfg(feature = "game_defs")]
+pub mod auto;
+#[cfg(feature = "game_defs")]
+pub use auto::*;
@@ -0,0 +1,309 @@
+//! Querying a server of a known game without knowing its query port.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+use crate::games::query::query_send;
+use crate::games::types::Game;
+use crate::protocols::types::{CommonResponse, ExtraRequestSettings, QueryScope, SocketSettings, TimeoutSettings};
+use crate::GDErrorKind::{AutoQuery, InvalidInput, PacketReceive};
+use crate::GDResult;
+
+/// Offsets from a game's port that query ports commonly are at.
+pub const COMMON_QUERY_PORT_OFFSETS: &[i32] = &[1, 2, 10, -1];
+
+/// Read, write and connect timeout of the probes when no timeout settings are
+/// given.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How many ports are probed at the same time.
+const MAX_CONCURRENT_PROBES: usize = 16;
+
+/// The ports [query_probe] tries, in the order they were added (duplicates are
+/// skipped).
+///
+/// ```
+/// use gamedig::games::{ProbePorts, GAMES};
+///
+/// let valheim = GAMES.get("valheim").unwrap();
+/// let ports = ProbePorts::for_game(valheim)
+///     .add_game_port(valheim, 2500)
+///     .add_range(2600 ..= 2602);
+///
+/// assert_eq!(
+///     ports.ports(),
+///     [2457, 2458, 2459, 2467, 2456, 2501, 2500, 2502, 2510, 2499, 2600, 2601, 2602]
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProbePorts {
+    ports: Vec<u16>,
+    added: HashSet<u16>,
+}
+
+impl ProbePorts {
+    /// No ports.
+    pub fn new() -> Self { Self::default() }
+
+    /// The default port of the game and the [common
+    /// offsets](COMMON_QUERY_PORT_OFFSETS) from it.
+    pub fn for_game(game: &Game) -> Self { Self::new().add_offsets(game.default_port) }
+
+    /// The query port of the game for `game_port` (see [Game::query_port])
+    /// and the [common offsets](COMMON_QUERY_PORT_OFFSETS) from `game_port`.
+    pub fn add_game_port(self, game: &Game, game_port: u16) -> Self {
+        match game.query_port(game_port) {
+            Ok(port) => self.add_port(port),
+            Err(_) => self,
+        }
+        .add_offsets(game_port)
+    }
+
+    /// `port` and the [common offsets](COMMON_QUERY_PORT_OFFSETS) from it.
+    pub fn add_offsets(self, port: u16) -> Self {
+        let offsets = COMMON_QUERY_PORT_OFFSETS
+            .iter()
+            .filter_map(|offset| u16::try_from(i32::from(port) + offset).ok());
+
+        self.add_port(port).add_ports(offsets)
+    }
+
+    /// Add `port`.
+    pub fn add_port(mut self, port: u16) -> Self {
+        if self.added.insert(port) {
+            self.ports.push(port);
+        }
+
+        self
+    }
+
+    /// Add `ports`.
+    pub fn add_ports<I: IntoIterator<Item = u16>>(self, ports: I) -> Self {
+        ports.into_iter().fold(self, Self::add_port)
+    }
+
+    /// Add the ports of `range`.
+    pub fn add_range(self, range: RangeInclusive<u16>) -> Self { self.add_ports(range) }
+
+    /// The ports, in order.
+    pub fn ports(&self) -> &[u16] { &self.ports }
+}
+
+/// The response of [query_probe].
+pub struct ProbeResponse {
+    /// The port that answered.
+    pub port: u16,
+    /// The query response.
+    pub response: Box<dyn CommonResponse>,
+}
+
+/// Query a server of `game` on the first of `ports` that answers, see
+/// [query_probe_with_timeout_and_extra_settings].
+#[inline]
+pub fn query_probe(game: &Game, address: &IpAddr, ports: &ProbePorts) -> GDResult<ProbeResponse> {
+    query_probe_with_timeout_and_extra_settings(game, address, ports, None, None)
+}
+
+/// Query a server of `game` on the first of `ports` that answers, with timeout
+/// settings applied to each probe (without them, probes time out after a
+/// second and aren't retried).
+///
+/// Ports are probed concurrently, a few at a time, the response is the first
+/// one received (which may not be from the first port). Fails with
+/// [AutoQuery] if no port answered.
+#[inline]
+pub fn query_probe_with_timeout_and_extra_settings(
+    game: &Game,
+    address: &IpAddr,
+    ports: &ProbePorts,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+) -> GDResult<ProbeResponse> {
+    query_probe_with_socket_settings(
+        game,
+        address,
+        ports,
+        timeout_settings,
+        extra_settings,
+        &SocketSettings::default(),
+    )
+}
+
+/// [query_probe_with_timeout_and_extra_settings] with the settings of the
+/// sockets the probes use.
+pub fn query_probe_with_socket_settings(
+    game: &Game,
+    address: &IpAddr,
+    ports: &ProbePorts,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+    socket_settings: &SocketSettings,
+) -> GDResult<ProbeResponse> {
+    if ports.ports().is_empty() {
+        return Err(InvalidInput.context("No ports to probe"));
+    }
+
+    let timeout_settings = match timeout_settings {
+        Some(timeout_settings) => timeout_settings,
+        None => {
+            TimeoutSettings::new(
+                Some(PROBE_TIMEOUT),
+                Some(PROBE_TIMEOUT),
+                Some(PROBE_TIMEOUT),
+                0,
+            )?
+        }
+    };
+
+    // Workers take the ports in order until one answers, the remaining probes
+    // finish on their own (shortly, given their timeouts).
+    let queue = Arc::new(Mutex::new(ports.ports().to_vec().into_iter()));
+    let answered = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+
+    for _ in 0 .. MAX_CONCURRENT_PROBES.min(ports.ports().len()) {
+        let (queue, answered, sender) = (queue.clone(), answered.clone(), sender.clone());
+        let (game, address) = (game.clone(), *address);
+        let (extra_settings, timeout_settings, socket_settings) = (
+            extra_settings.clone(),
+            Some(timeout_settings),
+            socket_settings.clone(),
+        );
+
+        std::thread::spawn(move || {
+            while !answered.load(Ordering::Relaxed) {
+                let Some(port) = queue.lock().ok().and_then(|mut queue| queue.next()) else {
+                    break;
+                };
+
+                let result = query_send(
+                    &game,
+                    &address,
+                    Some(port),
+                    timeout_settings,
+                    extra_settings.clone(),
+                    &QueryScope::start(&timeout_settings).set_socket_settings(socket_settings.clone()),
+                );
+                if sender.send((port, result)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    drop(sender);
+
+    let mut errors = String::new();
+    for (port, result) in receiver {
+        match result {
+            Ok(response) => {
+                answered.store(true, Ordering::Relaxed);
+                return Ok(ProbeResponse { port, response });
+            }
+            // Ports that didn't answer aren't worth mentioning.
+            Err(error) if error.kind == PacketReceive => {}
+            Err(error) => {
+                let _ = write!(errors, "{}: {:?}; ", port, error.kind);
+            }
+        }
+    }
+
+    let mut message = format!("None of the {} probed ports answered", ports.ports().len());
+    if !errors.is_empty() {
+        let _ = write!(message, " ({})", errors.trim_end_matches("; "));
+    }
+
+    Err(AutoQuery.context(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::quake::QuakeVersion;
+    use crate::protocols::types::Protocol;
+    use crate::transport::Scripted;
+    use crate::GDErrorKind::PacketSend;
+    use std::net::Ipv4Addr;
+
+    /// A Quake 1 server answering on a single port.
+    fn one_port(port: u16) -> Scripted {
+        Scripted::new()
+            .add_packet(*b"\xFF\xFF\xFF\xFFn\\hostname\\Test\\map\\e1m1\\maxclients\\8\n")
+            .set_port(port)
+    }
+
+    fn quake() -> Game {
+        Game {
+            name: "Quake".into(),
+            default_port: 27500,
+            query_port: Default::default(),
+            protocol: Protocol::Quake(QuakeVersion::One),
+            request_settings: ExtraRequestSettings::default(),
+        }
+    }
+
+    #[test]
+    fn candidate_ports() {
+        let ports = ProbePorts::new()
+            .add_offsets(0)
+            .add_port(2)
+            .add_range(65534 ..= 65535)
+            .add_offsets(65535);
+
+        assert_eq!(ports.ports(), [0, 1, 2, 10, 65534, 65535]);
+    }
+
+    #[test]
+    fn first_answering_port() {
+        let socket_settings = SocketSettings::new().set_connector(Arc::new(one_port(27502)));
+        let ports = ProbePorts::new().add_range(27500 ..= 27510);
+        let probed = query_probe_with_socket_settings(
+            &quake(),
+            &IpAddr::V4(Ipv4Addr::LOCALHOST),
+            &ports,
+            None,
+            None,
+            &socket_settings,
+        )
+        .unwrap();
+
+        assert_eq!(probed.port, 27502);
+        assert_eq!(probed.response.name(), Some("Test"));
+        assert_eq!(probed.response.query_port(), Some(27502));
+    }
+
+    #[test]
+    fn no_answering_port() {
+        let socket_settings = SocketSettings::new().set_connector(Arc::new(
+            Scripted::new().set_port(1).set_final_error(PacketSend),
+        ));
+        let error = query_probe_with_socket_settings(
+            &quake(),
+            &IpAddr::V4(Ipv4Addr::LOCALHOST),
+            &ProbePorts::new().add_offsets(2),
+            None,
+            None,
+            &socket_settings,
+        )
+        .map(|probed| probed.port)
+        .unwrap_err();
+
+        assert_eq!(error.kind, AutoQuery);
+        assert!(error
+            .to_string()
+            .contains("None of the 5 probed ports answered (1: PacketSend)"));
+
+        let error = query_probe(
+            &quake(),
+            &IpAddr::V4(Ipv4Addr::LOCALHOST),
+            &ProbePorts::new(),
+        )
+        .map(|probed| probed.port)
+        .unwrap_err();
+        assert_eq!(error.kind, InvalidInput);
+    }
+}
@@ -1,7 +1,9 @@
 //! Generic query functions
 
 use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
 
+use crate::dns::{self, Resolver, SystemResolver};
 #[cfg(all(feature = "services", feature = "tls", feature = "serde"))]
 use crate::games::minetest;
 use crate::games::types::Game;
@@ -9,8 +11,20 @@ use crate::games::{eco, ffow, jc2m, mindustry, minecraft, savage2, theship};
 use crate::protocols;
 use crate::protocols::gamespy::GameSpyVersion;
 use crate::protocols::quake::QuakeVersion;
-use crate::protocols::types::{CommonResponse, ExtraRequestSettings, ProprietaryProtocol, Protocol, TimeoutSettings};
-use crate::GDResult;
+use crate::protocols::types::{
+    CommonPlayer,
+    CommonResponse,
+    CommonTeamJson,
+    ExtraRequestSettings,
+    GenericResponse,
+    ProprietaryProtocol,
+    Protocol,
+    QueryScope,
+    SocketSettings,
+    TimeoutSettings,
+};
+use crate::text::{NormalizeText, TextFormat};
+use crate::{ErrorContextExt, GDResult};
 
 /// Make a query given a game definition
 #[inline]
@@ -30,6 +44,7 @@ pub fn query_with_timeout(
 }
 
 /// Make a query given a game definition, timeout settings, and extra settings
+#[inline]
 pub fn query_with_timeout_and_extra_settings(
     game: &Game,
     address: &IpAddr,
@@ -37,101 +52,952 @@ pub fn query_with_timeout_and_extra_settings(
     timeout_settings: Option<TimeoutSettings>,
     extra_settings: Option<ExtraRequestSettings>,
 ) -> GDResult<Box<dyn CommonResponse>> {
+    query_with_socket_settings(
+        game,
+        address,
+        port,
+        timeout_settings,
+        extra_settings,
+        &SocketSettings::default(),
+    )
+}
+
+/// Make a query given a game definition, timeout settings, extra settings,
+/// and the settings of the sockets it uses.
+pub fn query_with_socket_settings(
+    game: &Game,
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+    socket_settings: &SocketSettings,
+) -> GDResult<Box<dyn CommonResponse>> {
+    let scope = QueryScope::start(&timeout_settings).set_socket_settings(socket_settings.clone());
+    query_send(
+        game,
+        address,
+        port,
+        timeout_settings,
+        extra_settings,
+        &scope,
+    )
+    .map(|response| response as Box<dyn CommonResponse>)
+}
+
+/// Make a query given a game definition and the port players connect to,
+/// the query port being derived from it (see [Game::query_port]).
+#[inline]
+pub fn query_game_port(game: &Game, address: &IpAddr, game_port: u16) -> GDResult<Box<dyn CommonResponse>> {
+    query_game_port_with_timeout_and_extra_settings(game, address, game_port, None, None)
+}
+
+/// Make a query given a game definition, the port players connect to, timeout
+/// settings, and extra settings, see [query_game_port].
+pub fn query_game_port_with_timeout_and_extra_settings(
+    game: &Game,
+    address: &IpAddr,
+    game_port: u16,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+) -> GDResult<Box<dyn CommonResponse>> {
+    let port = game.query_port(game_port)?;
+    query_with_timeout_and_extra_settings(game, address, Some(port), timeout_settings, extra_settings)
+}
+
+/// [query_with_timeout_and_extra_settings] as part of a larger query (sharing
+/// its deadline), keeping the response `Send` so it can be handed between
+/// threads.
+pub(crate) fn query_send(
+    game: &Game,
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
     let socket_addr = SocketAddr::new(*address, port.unwrap_or(game.default_port));
-    Ok(match &game.protocol {
-        Protocol::Valve(engine) => {
-            protocols::valve::query(
-                &socket_addr,
-                *engine,
-                extra_settings
-                    .or_else(|| Option::from(game.request_settings.clone()))
-                    .map(ExtraRequestSettings::into),
-                timeout_settings,
-            )
-            .map(Box::new)?
-        }
-        #[cfg(feature = "tls")]
-        Protocol::Epic(credentials) => {
-            protocols::epic::query_with_timeout(credentials.clone(), &socket_addr, timeout_settings).map(Box::new)?
-        }
-        Protocol::Gamespy(version) => {
-            match version {
-                GameSpyVersion::One => protocols::gamespy::one::query(&socket_addr, timeout_settings).map(Box::new)?,
-                GameSpyVersion::Two => protocols::gamespy::two::query(&socket_addr, timeout_settings).map(Box::new)?,
-                GameSpyVersion::Three => {
-                    protocols::gamespy::three::query(&socket_addr, timeout_settings).map(Box::new)?
-                }
+    query_protocol(game, address, port, timeout_settings, extra_settings, scope)
+        .map(|response| queried(response, socket_addr.port()))
+        .with_address(socket_addr)
+        .with_protocol(game.protocol.name())
+}
+
+/// Call the query function of `game`'s protocol, `$mode` being `sync` or
+/// `async`: [query_protocol] and its async version share this so their
+/// protocol arguments can't drift apart.
+macro_rules! dispatch_protocol {
+    ($mode:tt, $game:expr, $address:expr, $port:expr, $timeout_settings:expr, $extra_settings:expr, $scope:expr) => {{
+        let (game, address, port, timeout_settings, extra_settings, scope) =
+            ($game, $address, $port, $timeout_settings, $extra_settings, $scope);
+        let socket_addr = SocketAddr::new(*address, port.unwrap_or(game.default_port));
+        let name_format = extra_settings
+            .as_ref()
+            .and_then(|settings: &ExtraRequestSettings| settings.name_format);
+        Ok(match &game.protocol {
+            Protocol::Valve(engine) => {
+                call_query!(
+                    $mode,
+                    protocols::valve::{query_with_scope, query_with_scope_async}(
+                        &socket_addr,
+                        *engine,
+                        extra_settings
+                            .or_else(|| Option::from(game.request_settings.clone()))
+                            .map(ExtraRequestSettings::into),
+                        timeout_settings,
+                        scope,
+                    )
+                )
+                .map(|r| normalized(r, name_format))?
             }
-        }
-        Protocol::Quake(version) => {
-            match version {
-                QuakeVersion::One => protocols::quake::one::query(&socket_addr, timeout_settings).map(Box::new)?,
-                QuakeVersion::Two => protocols::quake::two::query(&socket_addr, timeout_settings).map(Box::new)?,
-                QuakeVersion::Three => protocols::quake::three::query(&socket_addr, timeout_settings).map(Box::new)?,
+            #[cfg(feature = "tls")]
+            Protocol::Epic(credentials) => {
+                call_query!(
+                    $mode,
+                    protocols::epic::{query_with_scope, query_with_scope_async}(
+                        credentials.clone(),
+                        &socket_addr,
+                        timeout_settings,
+                        scope,
+                    )
+                )
+                .map(|r| normalized(r, name_format))?
             }
-        }
-        Protocol::Unreal2 => {
-            protocols::unreal2::query(
-                &socket_addr,
-                &extra_settings
-                    .map(ExtraRequestSettings::into)
-                    .unwrap_or_default(),
-                timeout_settings,
-            )
-            .map(Box::new)?
-        }
-        Protocol::PROPRIETARY(protocol) => {
-            match protocol {
-                ProprietaryProtocol::Savage2 => {
-                    savage2::query_with_timeout(address, port, timeout_settings).map(Box::new)?
-                }
-                ProprietaryProtocol::TheShip => {
-                    theship::query_with_timeout(address, port, timeout_settings).map(Box::new)?
+            Protocol::Gamespy(version) => {
+                match version {
+                    GameSpyVersion::One => {
+                        call_query!(
+                            $mode,
+                            protocols::gamespy::one::{query_with_scope, query_with_scope_async}(
+                                &socket_addr,
+                                timeout_settings,
+                                scope,
+                            )
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
+                    GameSpyVersion::Two => {
+                        call_query!(
+                            $mode,
+                            protocols::gamespy::two::{query_with_scope, query_with_scope_async}(
+                                &socket_addr,
+                                timeout_settings,
+                                scope,
+                            )
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
+                    GameSpyVersion::Three => {
+                        call_query!(
+                            $mode,
+                            protocols::gamespy::three::{query_with_scope, query_with_scope_async}(
+                                &socket_addr,
+                                timeout_settings,
+                                scope,
+                            )
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
                 }
-                ProprietaryProtocol::FFOW => ffow::query_with_timeout(address, port, timeout_settings).map(Box::new)?,
-                ProprietaryProtocol::JC2M => jc2m::query_with_timeout(address, port, timeout_settings).map(Box::new)?,
-                ProprietaryProtocol::Mindustry => mindustry::query(address, port, &timeout_settings).map(Box::new)?,
-                ProprietaryProtocol::Minecraft(version) => {
-                    match version {
-                        Some(minecraft::Server::Java) => {
-                            minecraft::protocol::query_java(
+            }
+            Protocol::Quake(version) => {
+                match version {
+                    QuakeVersion::One => {
+                        call_query!(
+                            $mode,
+                            protocols::quake::one::{query_with_scope, query_with_scope_async}(
                                 &socket_addr,
                                 timeout_settings,
-                                extra_settings.map(ExtraRequestSettings::into),
+                                scope,
                             )
-                            .map(Box::new)?
-                        }
-                        Some(minecraft::Server::Bedrock) => {
-                            minecraft::protocol::query_bedrock(&socket_addr, timeout_settings).map(Box::new)?
-                        }
-                        Some(minecraft::Server::Legacy(group)) => {
-                            minecraft::protocol::query_legacy_specific(*group, &socket_addr, timeout_settings)
-                                .map(Box::new)?
-                        }
-                        None => {
-                            minecraft::protocol::query(
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
+                    QuakeVersion::Two => {
+                        call_query!(
+                            $mode,
+                            protocols::quake::two::{query_with_scope, query_with_scope_async}(
                                 &socket_addr,
                                 timeout_settings,
-                                extra_settings.map(ExtraRequestSettings::into),
+                                scope,
                             )
-                            .map(Box::new)?
-                        }
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
+                    QuakeVersion::Three => {
+                        call_query!(
+                            $mode,
+                            protocols::quake::three::{query_with_scope, query_with_scope_async}(
+                                &socket_addr,
+                                timeout_settings,
+                                scope,
+                            )
+                        )
+                        .map(|r| normalized(r, name_format))?
                     }
                 }
-                ProprietaryProtocol::Eco => {
-                    eco::query_with_timeout_and_extra_settings(
-                        address,
-                        port,
-                        &timeout_settings,
-                        extra_settings.map(ExtraRequestSettings::into),
+            }
+            Protocol::Unreal2 => {
+                call_query!(
+                    $mode,
+                    protocols::unreal2::{query_with_scope, query_with_scope_async}(
+                        &socket_addr,
+                        &extra_settings
+                            .map(ExtraRequestSettings::into)
+                            .unwrap_or_default(),
+                        timeout_settings,
+                        scope,
                     )
-                    .map(Box::new)?
-                }
-                #[cfg(all(feature = "services", feature = "tls", feature = "serde"))]
-                ProprietaryProtocol::Minetest => {
-                    minetest::query_with_timeout(address, port, &timeout_settings).map(Box::new)?
+                )
+                .map(Box::new)?
+            }
+            Protocol::PROPRIETARY(protocol) => {
+                match protocol {
+                    ProprietaryProtocol::Savage2 => {
+                        call_query!(
+                            $mode,
+                            savage2::{query_with_scope, query_with_scope_async}(address, port, timeout_settings, scope)
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
+                    ProprietaryProtocol::TheShip => {
+                        call_query!(
+                            $mode,
+                            theship::{query_with_scope, query_with_scope_async}(address, port, timeout_settings, scope)
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
+                    ProprietaryProtocol::FFOW => {
+                        call_query!(
+                            $mode,
+                            ffow::{query_with_scope, query_with_scope_async}(address, port, timeout_settings, scope)
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
+                    ProprietaryProtocol::JC2M => {
+                        call_query!(
+                            $mode,
+                            jc2m::{query_with_scope, query_with_scope_async}(address, port, timeout_settings, scope)
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
+                    ProprietaryProtocol::Mindustry => {
+                        call_query!(
+                            $mode,
+                            mindustry::{query_with_scope, query_with_scope_async}(
+                                address,
+                                port,
+                                &timeout_settings,
+                                scope,
+                            )
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
+                    ProprietaryProtocol::Minecraft(version) => {
+                        match version {
+                            Some(minecraft::Server::Java) => {
+                                call_query!(
+                                    $mode,
+                                    minecraft::protocol::{query_java_with_scope, query_java_with_scope_async}(
+                                        &socket_addr,
+                                        timeout_settings,
+                                        extra_settings.map(ExtraRequestSettings::into),
+                                        scope,
+                                    )
+                                )
+                                .map(|r| normalized(r, name_format))?
+                            }
+                            Some(minecraft::Server::Bedrock) => {
+                                call_query!(
+                                    $mode,
+                                    minecraft::protocol::{query_bedrock_with_scope, query_bedrock_with_scope_async}(
+                                        &socket_addr,
+                                        timeout_settings,
+                                        scope,
+                                    )
+                                )
+                                .map(|r| normalized(r, name_format))?
+                            }
+                            Some(minecraft::Server::Legacy(group)) => {
+                                call_query!(
+                                    $mode,
+                                    minecraft::protocol::{
+                                        query_legacy_specific_with_scope,
+                                        query_legacy_specific_with_scope_async
+                                    }(*group, &socket_addr, timeout_settings, scope)
+                                )
+                                .map(|r| normalized(r, name_format))?
+                            }
+                            None => {
+                                call_query!(
+                                    $mode,
+                                    minecraft::protocol::{query_with_scope, query_with_scope_async}(
+                                        &socket_addr,
+                                        timeout_settings,
+                                        extra_settings.map(ExtraRequestSettings::into),
+                                        scope,
+                                    )
+                                )
+                                .map(|r| normalized(r, name_format))?
+                            }
+                        }
+                    }
+                    ProprietaryProtocol::Eco => {
+                        call_query!(
+                            $mode,
+                            eco::{query_with_scope, query_with_scope_async}(
+                                address,
+                                port,
+                                &timeout_settings,
+                                extra_settings.map(ExtraRequestSettings::into),
+                                scope,
+                            )
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
+                    #[cfg(all(feature = "services", feature = "tls", feature = "serde"))]
+                    ProprietaryProtocol::Minetest => {
+                        call_query!(
+                            $mode,
+                            minetest::{query_with_scope, query_with_scope_async}(
+                                address,
+                                port,
+                                &timeout_settings,
+                                scope,
+                            )
+                        )
+                        .map(|r| normalized(r, name_format))?
+                    }
                 }
             }
+        })
+    }};
+}
+
+/// Call the sync or the async (awaiting it) one of a query function pair.
+macro_rules! call_query {
+    (sync, $($module:ident)::+::{$sync:ident, $async:ident $(,)?}($($arg:expr),* $(,)?)) => {
+        $($module)::+::$sync($($arg),*)
+    };
+    (async, $($module:ident)::+::{$sync:ident, $async:ident $(,)?}($($arg:expr),* $(,)?)) => {
+        $($module)::+::$async($($arg),*).await
+    };
+}
+
+fn query_protocol(
+    game: &Game,
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    dispatch_protocol!(
+        sync,
+        game,
+        address,
+        port,
+        timeout_settings,
+        extra_settings,
+        scope
+    )
+}
+
+/// Make a query given a game definition and the server's hostname (or IP
+/// address), see [resolve_host].
+#[inline]
+pub fn query_host(game: &Game, host: &str, port: Option<u16>) -> GDResult<Box<dyn CommonResponse>> {
+    query_host_with_timeout_and_extra_settings(game, host, port, None, None)
+}
+
+/// Make a query given a game definition, the server's hostname (or IP
+/// address), timeout settings, and extra settings, see [resolve_host].
+pub fn query_host_with_timeout_and_extra_settings(
+    game: &Game,
+    host: &str,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+) -> GDResult<Box<dyn CommonResponse>> {
+    query_host_with_resolver(
+        &SystemResolver::default(),
+        game,
+        host,
+        port,
+        timeout_settings,
+        extra_settings,
+        &SocketSettings::default(),
+    )
+}
+
+/// Make a query given a game definition, the server's hostname (or IP
+/// address) resolved by `resolver`, timeout settings, extra settings, and
+/// socket settings, see [resolve_host].
+///
+/// The query deadline (if any) covers both the resolution and the query.
+pub fn query_host_with_resolver(
+    resolver: &dyn Resolver,
+    game: &Game,
+    host: &str,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    mut extra_settings: Option<ExtraRequestSettings>,
+    socket_settings: &SocketSettings,
+) -> GDResult<Box<dyn CommonResponse>> {
+    let scope = QueryScope::start(&timeout_settings).set_socket_settings(socket_settings.clone());
+    let (address, port) = resolve(
+        resolver,
+        host,
+        port,
+        srv_service(game),
+        &timeout_settings,
+        &mut extra_settings,
+        &scope,
+    )?;
+    query_send(
+        game,
+        &address,
+        port,
+        timeout_settings,
+        extra_settings,
+        &scope,
+    )
+    .map(|response| response as Box<dyn CommonResponse>)
+}
+
+/// Resolve the hostname (or IP address) of a server of `game` with
+/// `resolver`, returning the address and port to query it at.
+///
+/// Hostnames are also set as the [hostname](ExtraRequestSettings::hostname)
+/// of the extra settings (unless there already is one), and for Minecraft Java
+/// servers without a port the `_minecraft._tcp` SRV record of the hostname is
+/// followed (if there is one).
+pub fn resolve_host(
+    resolver: &dyn Resolver,
+    game: &Game,
+    host: &str,
+    port: Option<u16>,
+    timeout_settings: &Option<TimeoutSettings>,
+    extra_settings: &mut Option<ExtraRequestSettings>,
+) -> GDResult<(IpAddr, Option<u16>)> {
+    resolve(
+        resolver,
+        host,
+        port,
+        srv_service(game),
+        timeout_settings,
+        extra_settings,
+        &QueryScope::start(timeout_settings),
+    )
+}
+
+/// The SRV service the hostnames of the game's servers may point to.
+fn srv_service(game: &Game) -> Option<&'static str> {
+    match game.protocol {
+        Protocol::PROPRIETARY(ProprietaryProtocol::Minecraft(None | Some(minecraft::types::Server::Java))) => {
+            Some("_minecraft._tcp")
         }
+        _ => None,
+    }
+}
+
+fn resolve(
+    resolver: &dyn Resolver,
+    host: &str,
+    port: Option<u16>,
+    srv_service: Option<&str>,
+    timeout_settings: &Option<TimeoutSettings>,
+    extra_settings: &mut Option<ExtraRequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<(IpAddr, Option<u16>)> {
+    if let Ok(address) = host.parse() {
+        return Ok((address, port));
+    }
+
+    set_hostname_if_missing(host, extra_settings);
+
+    // Each lookup may take at most what's left of the query deadline.
+    if let (None, Some(service)) = (port, srv_service) {
+        // SRV records are optional, failing to look them up isn't fatal.
+        let records = resolver
+            .lookup_srv(
+                &format!("{service}.{host}"),
+                &scope.operation_timeout(timeout_settings, 1)?,
+            )
+            .unwrap_or_default();
+        if let Some(record) = dns::pick_srv(&records) {
+            return Ok((
+                dns::lookup_host(
+                    resolver,
+                    &record.target,
+                    &scope.operation_timeout(timeout_settings, 1)?,
+                )?,
+                Some(record.port),
+            ));
+        }
+    }
+
+    Ok((
+        dns::lookup_host(
+            resolver,
+            host,
+            &scope.operation_timeout(timeout_settings, 1)?,
+        )?,
+        port,
+    ))
+}
+
+/// Sets the hostname on extra request settings if it is not already set.
+fn set_hostname_if_missing(host: &str, extra_settings: &mut Option<ExtraRequestSettings>) {
+    match extra_settings {
+        Some(extra_settings) => {
+            if extra_settings.hostname.is_none() {
+                extra_settings.hostname = Some(host.to_string());
+            }
+        }
+        None => *extra_settings = Some(ExtraRequestSettings::default().set_hostname(host.to_string())),
+    }
+}
+
+/// A response of the generic query functions, which know the port it was
+/// queried on.
+struct Queried {
+    response: Box<dyn CommonResponse + Send + Sync>,
+    port: u16,
+}
+
+fn queried(response: Box<dyn CommonResponse + Send + Sync>, port: u16) -> Box<dyn CommonResponse + Send + Sync> {
+    Box::new(Queried { response, port })
+}
+
+impl CommonResponse for Queried {
+    fn as_original(&self) -> GenericResponse<'_> { self.response.as_original() }
+
+    fn name(&self) -> Option<&str> { self.response.name() }
+    fn description(&self) -> Option<&str> { self.response.description() }
+    fn game_mode(&self) -> Option<&str> { self.response.game_mode() }
+    fn game_version(&self) -> Option<&str> { self.response.game_version() }
+    fn map(&self) -> Option<&str> { self.response.map() }
+    fn players_maximum(&self) -> u32 { self.response.players_maximum() }
+    fn players_online(&self) -> u32 { self.response.players_online() }
+    fn players_bots(&self) -> Option<u32> { self.response.players_bots() }
+    fn has_password(&self) -> Option<bool> { self.response.has_password() }
+    fn players(&self) -> Option<Vec<&dyn CommonPlayer>> { self.response.players() }
+    fn latency(&self) -> Option<Duration> { self.response.latency() }
+    fn rules(&self) -> Option<Vec<(&str, &str)>> { self.response.rules() }
+    fn teams(&self) -> Option<Vec<CommonTeamJson<'_>>> { self.response.teams() }
+    fn tags(&self) -> Option<Vec<&str>> { self.response.tags() }
+    fn is_secured(&self) -> Option<bool> { self.response.is_secured() }
+    fn server_os(&self) -> Option<&str> { self.response.server_os() }
+    fn game_port(&self) -> Option<u16> { self.response.game_port() }
+    fn query_port(&self) -> Option<u16> { Some(self.port) }
+}
+
+/// Box a response, normalizing its names first if a format was requested.
+/// Unreal2 isn't passed through here as its gathering settings handle it.
+fn normalized<R: CommonResponse + NormalizeText + Send + Sync + 'static>(
+    mut response: R,
+    name_format: Option<TextFormat>,
+) -> Box<dyn CommonResponse + Send + Sync> {
+    if let Some(format) = name_format {
+        response.normalize_text(format);
+    }
+
+    Box::new(response)
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+#[inline]
+pub async fn query_async(
+    game: &Game,
+    address: &IpAddr,
+    port: Option<u16>,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    query_with_timeout_and_extra_settings_async(game, address, port, None, None).await
+}
+
+/// Async version of [query_with_timeout].
+#[cfg(feature = "async")]
+#[inline]
+pub async fn query_with_timeout_async(
+    game: &Game,
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    query_with_timeout_and_extra_settings_async(game, address, port, timeout_settings, None).await
+}
+
+/// Async version of [query_with_timeout_and_extra_settings], the returned
+/// future is `Send` so it can be spawned on a multi-threaded runtime.
+#[cfg(feature = "async")]
+#[inline]
+pub async fn query_with_timeout_and_extra_settings_async(
+    game: &Game,
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    query_with_socket_settings_async(
+        game,
+        address,
+        port,
+        timeout_settings,
+        extra_settings,
+        &SocketSettings::default(),
+    )
+    .await
+}
+
+/// Async version of [query_with_socket_settings].
+#[cfg(feature = "async")]
+pub async fn query_with_socket_settings_async(
+    game: &Game,
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+    socket_settings: &SocketSettings,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    let scope = QueryScope::start(&timeout_settings).set_socket_settings(socket_settings.clone());
+    query_send_async(
+        game,
+        address,
+        port,
+        timeout_settings,
+        extra_settings,
+        &scope,
+    )
+    .await
+}
+
+/// Async version of [query_send].
+#[cfg(feature = "async")]
+async fn query_send_async(
+    game: &Game,
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    let socket_addr = SocketAddr::new(*address, port.unwrap_or(game.default_port));
+    query_protocol_async(game, address, port, timeout_settings, extra_settings, scope)
+        .await
+        .map(|response| queried(response, socket_addr.port()))
+        .with_address(socket_addr)
+        .with_protocol(game.protocol.name())
+}
+
+/// Async version of [query_game_port].
+#[cfg(feature = "async")]
+#[inline]
+pub async fn query_game_port_async(
+    game: &Game,
+    address: &IpAddr,
+    game_port: u16,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    query_game_port_with_timeout_and_extra_settings_async(game, address, game_port, None, None).await
+}
+
+/// Async version of [query_game_port_with_timeout_and_extra_settings].
+#[cfg(feature = "async")]
+pub async fn query_game_port_with_timeout_and_extra_settings_async(
+    game: &Game,
+    address: &IpAddr,
+    game_port: u16,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    let port = game.query_port(game_port)?;
+    query_with_timeout_and_extra_settings_async(game, address, Some(port), timeout_settings, extra_settings).await
+}
+
+/// Async version of [query_host].
+#[cfg(feature = "async")]
+#[inline]
+pub async fn query_host_async(
+    game: &Game,
+    host: &str,
+    port: Option<u16>,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    query_host_with_timeout_and_extra_settings_async(game, host, port, None, None).await
+}
+
+/// Async version of [query_host_with_timeout_and_extra_settings], the
+/// resolution runs on tokio's blocking threads.
+#[cfg(feature = "async")]
+pub async fn query_host_with_timeout_and_extra_settings_async(
+    game: &Game,
+    host: &str,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    query_host_with_resolver_async(
+        std::sync::Arc::new(SystemResolver::default()),
+        game,
+        host,
+        port,
+        timeout_settings,
+        extra_settings,
+        &SocketSettings::default(),
+    )
+    .await
+}
+
+/// Async version of [query_host_with_resolver], the resolution runs on
+/// tokio's blocking threads.
+#[cfg(feature = "async")]
+pub async fn query_host_with_resolver_async(
+    resolver: std::sync::Arc<dyn Resolver>,
+    game: &Game,
+    host: &str,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    mut extra_settings: Option<ExtraRequestSettings>,
+    socket_settings: &SocketSettings,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    let scope = QueryScope::start(&timeout_settings).set_socket_settings(socket_settings.clone());
+    let (host, srv_service) = (host.to_string(), srv_service(game));
+    let (resolve_timeout_settings, resolve_scope) = (timeout_settings, scope.clone());
+    let (resolved, extra_settings) = tokio::task::spawn_blocking(move || {
+        let resolved = resolve(
+            resolver.as_ref(),
+            &host,
+            port,
+            srv_service,
+            &resolve_timeout_settings,
+            &mut extra_settings,
+            &resolve_scope,
+        );
+        (resolved, extra_settings)
     })
+    .await
+    .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+    let (address, port) = resolved?;
+
+    query_send_async(
+        game,
+        &address,
+        port,
+        timeout_settings,
+        extra_settings,
+        &scope,
+    )
+    .await
+}
+
+#[cfg(feature = "async")]
+async fn query_protocol_async(
+    game: &Game,
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    extra_settings: Option<ExtraRequestSettings>,
+    scope: &QueryScope,
+) -> GDResult<Box<dyn CommonResponse + Send + Sync>> {
+    dispatch_protocol!(
+        async,
+        game,
+        address,
+        port,
+        timeout_settings,
+        extra_settings,
+        scope
+    )
+}
+
+#[cfg(all(test, feature = "game_defs"))]
+mod tests {
+    use super::*;
+    use crate::dns::SrvRecord;
+    use crate::games::GAMES;
+    use crate::transport::{Scripted, TransportKind};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    /// Answers like a Quake 1 server.
+    fn quake_server() -> Arc<Scripted> {
+        Arc::new(Scripted::new().add_packet(*b"\xFF\xFF\xFF\xFFn\\hostname\\Test\\map\\e1m1\\maxclients\\8\n"))
+    }
+
+    /// Knows of `example.com` and of the Minecraft server of `mc.example.com`.
+    struct StubResolver;
+
+    impl Resolver for StubResolver {
+        fn lookup_ip(&self, host: &str, _timeout_settings: &Option<TimeoutSettings>) -> GDResult<Vec<IpAddr>> {
+            Ok(match host {
+                "example.com" => vec![IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))],
+                "java.example.com" => vec![IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2))],
+                _ => Vec::new(),
+            })
+        }
+
+        fn lookup_srv(&self, name: &str, _timeout_settings: &Option<TimeoutSettings>) -> GDResult<Vec<SrvRecord>> {
+            Ok(match name {
+                "_minecraft._tcp.mc.example.com" => {
+                    vec![SrvRecord {
+                        priority: 0,
+                        weight: 0,
+                        port: 25600,
+                        target: "java.example.com".to_string(),
+                    }]
+                }
+                _ => Vec::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn resolve_hosts() {
+        let tf2 = GAMES.get("teamfortress2").unwrap();
+        let minecraft = GAMES.get("minecraft").unwrap();
+
+        let mut extra_settings = None;
+        let resolved = resolve_host(
+            &StubResolver,
+            tf2,
+            "example.com",
+            None,
+            &None,
+            &mut extra_settings,
+        )
+        .unwrap();
+        assert_eq!(resolved, ("192.0.2.1".parse().unwrap(), None));
+        assert_eq!(
+            extra_settings.unwrap().hostname.as_deref(),
+            Some("example.com")
+        );
+
+        // IP addresses aren't hostnames.
+        let mut extra_settings = None;
+        let resolved = resolve_host(
+            &StubResolver,
+            tf2,
+            "127.0.0.1",
+            Some(27016),
+            &None,
+            &mut extra_settings,
+        )
+        .unwrap();
+        assert_eq!(resolved, ("127.0.0.1".parse().unwrap(), Some(27016)));
+        assert!(extra_settings.is_none());
+
+        // The SRV record is followed, the hostname is still the original one.
+        let mut extra_settings = Some(ExtraRequestSettings::default());
+        let resolved = resolve_host(
+            &StubResolver,
+            minecraft,
+            "mc.example.com",
+            None,
+            &None,
+            &mut extra_settings,
+        )
+        .unwrap();
+        assert_eq!(resolved, ("192.0.2.2".parse().unwrap(), Some(25600)));
+        assert_eq!(
+            extra_settings.unwrap().hostname.as_deref(),
+            Some("mc.example.com")
+        );
+
+        // Unless there is a port.
+        let error = resolve_host(
+            &StubResolver,
+            minecraft,
+            "mc.example.com",
+            Some(25565),
+            &None,
+            &mut None,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind, crate::GDErrorKind::HostLookup);
+    }
+
+    #[test]
+    fn query_hosts_with_resolver() {
+        let connector = quake_server();
+        let quake = GAMES.get("quake1").unwrap();
+
+        let response = query_host_with_resolver(
+            &StubResolver,
+            quake,
+            "example.com",
+            Some(27500),
+            None,
+            None,
+            &SocketSettings::new().set_connector(connector.clone()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            connector.connections(),
+            [(TransportKind::Udp, "192.0.2.1:27500".parse().unwrap())]
+        );
+        assert_eq!(response.name(), Some("Test"));
+    }
+
+    #[test]
+    fn game_ports() {
+        let valheim = GAMES.get("valheim").unwrap();
+        assert_eq!(valheim.query_port(2456), Ok(2457));
+        assert_eq!(valheim.default_game_port(), Some(2456));
+        assert!(valheim.query_port(u16::MAX).is_err());
+
+        let ark = GAMES.get("ase").unwrap();
+        assert_eq!(ark.query_port(7777), Ok(27015));
+        assert_eq!(ark.default_game_port(), None);
+
+        let tf2 = GAMES.get("teamfortress2").unwrap();
+        assert_eq!(tf2.query_port(27016), Ok(27016));
+    }
+
+    #[test]
+    fn query_port_echoed() {
+        use crate::games::types::QueryPort;
+
+        let connector = quake_server();
+
+        let game = Game {
+            name: "Quake with an offset".into(),
+            default_port: 27501,
+            query_port: QueryPort::Offset(1),
+            protocol: Protocol::Quake(QuakeVersion::One),
+            request_settings: ExtraRequestSettings::default(),
+        };
+        let response = query_with_socket_settings(
+            &game,
+            &IpAddr::V4(Ipv4Addr::LOCALHOST),
+            Some(game.query_port(27600).unwrap()),
+            None,
+            None,
+            &SocketSettings::new().set_connector(connector.clone()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            connector.connections(),
+            [(TransportKind::Udp, "127.0.0.1:27601".parse().unwrap())]
+        );
+        assert_eq!(response.name(), Some("Test"));
+        assert_eq!(response.query_port(), Some(27601));
+        assert_eq!(response.as_json().query_port, Some(27601));
+    }
+
+    #[cfg(feature = "async")]
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_query_future_is_send() {
+        let game = GAMES.get("teamfortress2").unwrap();
+        let address = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let future = query_with_timeout_and_extra_settings_async(game, &address, None, None, None);
+
+        assert_send(&future);
+
+        let future = query_host_with_timeout_and_extra_settings_async(game, "localhost", None, None, None);
+        assert_send(&future);
+    }
 }
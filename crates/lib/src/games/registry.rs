@@ -0,0 +1,607 @@
+//! Game definitions that can be extended at runtime.
+//!
+//! A [GameRegistry] starts with the [built-in definitions](GAMES), others can
+//! be added, replaced or removed programmatically or loaded from a file, and
+//! the games it returns are used with the usual [query](crate::query())
+//! functions. Games can also be looked up by [alias](ALIASES) or
+//! [searched](GameRegistry::search) by Steam app ID or name.
+//!
+//! ```
+//! use gamedig::games::{GameRegistry, GAMES};
+//! use gamedig::protocols::{types::Protocol, valve::Engine};
+//! use gamedig::Game;
+//!
+//! let mut registry = GameRegistry::new();
+//! registry.insert(
+//!     "mygame",
+//!     Game {
+//!         name: "My Game".into(),
+//!         default_port: 27015,
+//!         query_port: Default::default(),
+//!         protocol: Protocol::Valve(Engine::new(123_456)),
+//!         request_settings: Default::default(),
+//!     },
+//! );
+//! registry.remove("teamfortress2");
+//!
+//! assert_eq!(registry.get("mygame").unwrap().name, "My Game");
+//! assert!(registry.get("teamfortress2").is_none());
+//! assert_eq!(registry.len(), GAMES.len());
+//! ```
+
+use crate::games::types::Game;
+#[cfg(feature = "serde")]
+use crate::games::types::QueryPort;
+use crate::games::{ALIASES, GAMES};
+#[cfg(feature = "serde")]
+use crate::protocols::types::ExtraRequestSettings;
+use crate::protocols::types::Protocol;
+use crate::protocols::valve::Engine;
+#[cfg(feature = "serde")]
+use crate::GDErrorKind::{InvalidInput, JsonParse};
+#[cfg(feature = "serde")]
+use crate::GDResult;
+
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+
+/// Game definitions by ID (ignoring case): the [built-in ones](GAMES) along
+/// with the ones added at runtime, which take precedence.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameRegistry {
+    /// Added games (by lowercase ID), including the ones overriding a built-in
+    /// definition.
+    added: HashMap<String, Game>,
+    /// IDs of the removed built-in games.
+    removed: HashSet<String>,
+    /// Added aliases (lowercase), to the ID of their game.
+    aliases: HashMap<String, String>,
+}
+
+/// How a game matched a [search](GameRegistry::search), from the best kind of
+/// match to the worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MatchKind {
+    /// The ID of the game.
+    Id,
+    /// An [alias](GameRegistry::add_alias) of the game.
+    Alias,
+    /// One of the Steam app IDs of the game.
+    AppId,
+    /// The name of the game, ignoring case, spaces and punctuation.
+    Name,
+    /// The initials of the name of the game (`l4d2` for `Left 4 Dead 2`).
+    Acronym,
+    /// The start of the name or ID of the game.
+    Prefix,
+    /// A part of the name of the game.
+    Contains,
+    /// Close to the name or ID of the game, by this many edits.
+    Similar(usize),
+}
+
+/// A game found by [GameRegistry::search].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameMatch<'a> {
+    /// The ID of the game.
+    pub id: &'a str,
+    /// The definition of the game.
+    pub game: &'a Game,
+    /// How the game matched.
+    pub kind: MatchKind,
+}
+
+/// A game as written in a definitions file.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct GameDefinition {
+    name: String,
+    default_port: u16,
+    #[serde(default)]
+    query_port: QueryPort,
+    protocol: Protocol,
+    #[serde(default)]
+    request_settings: ExtraRequestSettings,
+    #[serde(default)]
+    aliases: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<GameDefinition> for Game {
+    fn from(definition: GameDefinition) -> Self {
+        Self {
+            name: definition.name.into(),
+            default_port: definition.default_port,
+            query_port: definition.query_port,
+            protocol: definition.protocol,
+            request_settings: definition.request_settings,
+        }
+    }
+}
+
+impl GameRegistry {
+    /// A registry with the built-in definitions.
+    pub fn new() -> Self { Self::default() }
+
+    /// The definition of `id`.
+    pub fn get(&self, id: &str) -> Option<&Game> { self.entry(&id.to_lowercase()).map(|(_, game)| game) }
+
+    /// The ID and definition of `id`, which must be lowercase.
+    fn entry(&self, id: &str) -> Option<(&str, &Game)> {
+        match self.added.get_key_value(id) {
+            Some((id, game)) => Some((id.as_str(), game)),
+            None if self.removed.contains(id) => None,
+            None => GAMES.get_entry(id).map(|(id, game)| (*id, game)),
+        }
+    }
+
+    /// The ID and definition of `query`, an ID or an alias (ignoring case).
+    pub fn lookup(&self, query: &str) -> Option<(&str, &Game)> {
+        let query = query.trim().to_lowercase();
+
+        self.entry(&query)
+            .or_else(|| self.entry(self.alias_target(&query)?))
+    }
+
+    /// Add `alias` (case-insensitive) as another name of the game `id`,
+    /// replacing the [built-in](ALIASES) one.
+    pub fn add_alias(&mut self, alias: &str, id: impl Into<String>) {
+        self.aliases
+            .insert(alias.to_lowercase(), id.into().to_lowercase());
+    }
+
+    fn alias_target(&self, alias: &str) -> Option<&str> {
+        match self.aliases.get(alias) {
+            Some(id) => Some(id),
+            None => ALIASES.get(alias).copied(),
+        }
+    }
+
+    /// The aliases and the IDs of their games, built-in and added ones.
+    pub fn aliases(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        let builtin = ALIASES
+            .entries()
+            .filter(|(alias, _)| !self.aliases.contains_key(**alias))
+            .map(|(alias, id)| (*alias, *id));
+
+        builtin.chain(
+            self.aliases
+                .iter()
+                .map(|(alias, id)| (alias.as_str(), id.as_str())),
+        )
+    }
+
+    /// Whether `id` is defined.
+    pub fn contains(&self, id: &str) -> bool { self.get(id).is_some() }
+
+    /// Add the definition of `id` (stored lowercase), returning the one it
+    /// replaces.
+    pub fn insert(&mut self, id: impl Into<String>, game: Game) -> Option<Game> {
+        let id = id.into().to_lowercase();
+        let replaced = self.get(&id).cloned();
+
+        self.removed.remove(&id);
+        self.added.insert(id, game);
+
+        replaced
+    }
+
+    /// Remove the definition of `id`, returning it.
+    pub fn remove(&mut self, id: &str) -> Option<Game> {
+        let id = id.to_lowercase();
+        let removed = self.get(&id).cloned();
+
+        self.added.remove(&id);
+        if GAMES.contains_key(&id) {
+            self.removed.insert(id);
+        }
+
+        removed
+    }
+
+    /// How many games are defined.
+    pub fn len(&self) -> usize { self.iter().count() }
+
+    /// Whether no games are defined.
+    pub fn is_empty(&self) -> bool { self.iter().next().is_none() }
+
+    /// The IDs and definitions of all the games, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Game)> + '_ {
+        let builtin = GAMES
+            .entries()
+            .filter(|(id, _)| !self.removed.contains(**id) && !self.added.contains_key(**id))
+            .map(|(id, game)| (*id, game));
+
+        builtin.chain(self.added.iter().map(|(id, game)| (id.as_str(), game)))
+    }
+
+    /// The games with `appid` as one of their Steam app IDs, sorted by ID.
+    pub fn find_by_appid(&self, appid: u32) -> Vec<(&str, &Game)> {
+        let mut games: Vec<_> = self
+            .iter()
+            .filter(|(_, game)| has_appid(game, appid))
+            .collect();
+        games.sort_unstable_by_key(|(id, _)| *id);
+
+        games
+    }
+
+    /// The games matching `query` (an ID, alias, Steam app ID or name), best
+    /// matches first (see [MatchKind]), to suggest games for unknown IDs.
+    ///
+    /// ```
+    /// # use gamedig::games::{GameRegistry, MatchKind};
+    /// let registry = GameRegistry::new();
+    ///
+    /// let matches = registry.search("Team Fortress");
+    /// assert_eq!(matches[0].id, "teamfortress2");
+    /// assert_eq!(matches[0].kind, MatchKind::Prefix);
+    ///
+    /// assert_eq!(registry.search("valhiem")[0].id, "valheim");
+    /// ```
+    pub fn search(&self, query: &str) -> Vec<GameMatch<'_>> {
+        let query = query.trim();
+        let normalized = normalize(query);
+        if normalized.is_empty() {
+            return Vec::new();
+        }
+
+        let lowercase = query.to_lowercase();
+        let appid = query.parse::<u32>().ok();
+        let aliased: HashSet<&str> = self
+            .aliases()
+            .filter(|(alias, _)| normalize(alias) == normalized)
+            .map(|(_, id)| id)
+            .collect();
+
+        let mut matches: Vec<_> = self
+            .iter()
+            .filter_map(|(id, game)| {
+                let name = normalize(&game.name);
+                let kind = if id == lowercase || normalize(id) == normalized {
+                    MatchKind::Id
+                } else if aliased.contains(id) {
+                    MatchKind::Alias
+                } else if appid.is_some_and(|appid| has_appid(game, appid)) {
+                    MatchKind::AppId
+                } else if name == normalized {
+                    MatchKind::Name
+                } else if acronym(&game.name) == normalized {
+                    MatchKind::Acronym
+                } else if name.starts_with(&normalized) || id.starts_with(&normalized) {
+                    MatchKind::Prefix
+                } else if normalized.len() >= 3 && name.contains(&normalized) {
+                    MatchKind::Contains
+                } else {
+                    let distance = edit_distance(&normalized, &name).min(edit_distance(&normalized, id));
+                    // Allow a typo every few characters.
+                    if distance > normalized.len() / 4 + 1 {
+                        return None;
+                    }
+
+                    MatchKind::Similar(distance)
+                };
+
+                Some(GameMatch { id, game, kind })
+            })
+            .collect();
+        matches.sort_unstable_by_key(|game_match| (game_match.kind, game_match.id));
+
+        matches
+    }
+
+    /// Add the games of `definitions`, a JSON object of games by ID (games
+    /// being in the same format as the serialized [Game]s, with optional
+    /// `request_settings` and `aliases`), replacing the existing ones.
+    ///
+    /// ```
+    /// # use gamedig::games::GameRegistry;
+    /// let mut registry = GameRegistry::new();
+    /// registry
+    ///     .load_json(r#"{ "mygame": { "name": "My Game", "default_port": 27015, "protocol": { "Valve": { "Source": [123456, null] } } } }"#)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(registry.get("mygame").unwrap().default_port, 27015);
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn load_json(&mut self, definitions: &str) -> GDResult<()> {
+        let definitions: HashMap<String, GameDefinition> =
+            serde_json::from_str(definitions).map_err(|e| JsonParse.context(e))?;
+
+        self.extend(definitions);
+        Ok(())
+    }
+
+    /// Add the games of `definitions`, a TOML document with a table per game
+    /// ID, see [GameRegistry::load_json] for the format of the games.
+    #[cfg(feature = "toml")]
+    pub fn load_toml(&mut self, definitions: &str) -> GDResult<()> {
+        let definitions: HashMap<String, GameDefinition> =
+            toml::from_str(definitions).map_err(|e| InvalidInput.context(e))?;
+
+        self.extend(definitions);
+        Ok(())
+    }
+
+    /// Add the games of the file at `path`, as [JSON](GameRegistry::load_json)
+    /// or, if the path ends with `.toml`, as [TOML](GameRegistry::load_toml).
+    #[cfg(feature = "serde")]
+    pub fn load_file<P: AsRef<std::path::Path>>(&mut self, path: P) -> GDResult<()> {
+        let path = path.as_ref();
+        let definitions = std::fs::read_to_string(path).map_err(|e| InvalidInput.context(e))?;
+
+        match path.extension() {
+            #[cfg(feature = "toml")]
+            Some(extension) if extension == "toml" => self.load_toml(&definitions),
+            #[cfg(not(feature = "toml"))]
+            Some(extension) if extension == "toml" => {
+                Err(InvalidInput.context("TOML definitions need the toml feature"))
+            }
+            _ => self.load_json(&definitions),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn extend(&mut self, definitions: HashMap<String, GameDefinition>) {
+        for (id, mut definition) in definitions {
+            for alias in std::mem::take(&mut definition.aliases) {
+                self.add_alias(&alias, id.clone());
+            }
+
+            self.insert(id, definition.into());
+        }
+    }
+}
+
+fn has_appid(game: &Game, appid: u32) -> bool {
+    matches!(
+        game.protocol,
+        Protocol::Valve(Engine::Source(Some((main, other)))) if main == appid || other == Some(appid)
+    )
+}
+
+/// Lowercase letters and digits of `text`.
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The lowercase initials of the words of `name`, numbers being kept whole.
+fn acronym(name: &str) -> String {
+    let mut acronym = String::new();
+
+    for word in name.split(|c: char| c.is_whitespace() || c == '-' || c == ':') {
+        if !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()) {
+            acronym.push_str(word);
+        } else if let Some(initial) = word.chars().find(|c| c.is_alphanumeric()) {
+            acronym.extend(initial.to_lowercase());
+        }
+    }
+
+    acronym
+}
+
+/// The Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0 ..= b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, b) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a == *b {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::types::Protocol;
+
+    fn game(name: &'static str) -> Game {
+        Game {
+            name: name.into(),
+            default_port: 1234,
+            query_port: Default::default(),
+            protocol: Protocol::Unreal2,
+            request_settings: Default::default(),
+        }
+    }
+
+    #[test]
+    fn starts_with_builtin_games() {
+        let registry = GameRegistry::new();
+
+        assert_eq!(registry.len(), GAMES.len());
+        assert_eq!(registry.get("teamfortress2"), GAMES.get("teamfortress2"));
+        assert!(registry.get("unknown").is_none());
+    }
+
+    #[test]
+    fn insert_override_and_remove() {
+        let mut registry = GameRegistry::new();
+
+        assert_eq!(registry.insert("mygame", game("Mine")), None);
+        assert_eq!(
+            registry.insert("teamfortress2", game("Override")),
+            GAMES.get("teamfortress2").cloned()
+        );
+        assert_eq!(registry.get("teamfortress2").unwrap().name, "Override");
+        assert_eq!(registry.len(), GAMES.len() + 1);
+
+        // Removing an overridden game removes the built-in one too.
+        assert_eq!(registry.remove("teamfortress2"), Some(game("Override")));
+        assert!(!registry.contains("teamfortress2"));
+        assert_eq!(registry.remove("teamfortress2"), None);
+        assert_eq!(registry.remove("mygame"), Some(game("Mine")));
+        assert_eq!(registry.len(), GAMES.len() - 1);
+
+        // And it can be added back.
+        assert_eq!(registry.insert("teamfortress2", game("Back")), None);
+        assert_eq!(
+            registry
+                .iter()
+                .filter(|(id, _)| *id == "teamfortress2")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn aliases_point_to_games() {
+        for (alias, id) in ALIASES.entries() {
+            assert!(
+                GAMES.contains_key(id),
+                "{alias} points to unknown game {id}"
+            );
+            assert!(!GAMES.contains_key(alias), "{alias} is a game ID");
+        }
+    }
+
+    #[test]
+    fn lookup_by_id_and_alias() {
+        let mut registry = GameRegistry::new();
+        assert_eq!(registry.lookup(" TF2 ").unwrap().0, "teamfortress2");
+        assert_eq!(registry.lookup("TeamFortress2").unwrap().0, "teamfortress2");
+        assert!(registry.lookup("tf3").is_none());
+
+        registry.add_alias("TF3", "tfc");
+        assert_eq!(registry.lookup("tf3").unwrap().0, "tfc");
+        registry.remove("tfc");
+        assert!(registry.lookup("tf3").is_none());
+    }
+
+    #[test]
+    fn mixed_case_ids() {
+        let mut registry = GameRegistry::new();
+        registry.insert("MyGame", game("Mine"));
+        registry.add_alias("mine", "MYGAME");
+
+        assert_eq!(registry.lookup("MyGame"), Some(("mygame", &game("Mine"))));
+        assert_eq!(registry.lookup("mygame").unwrap().0, "mygame");
+        assert_eq!(registry.lookup("MINE").unwrap().0, "mygame");
+        assert!(registry.contains("MYGAME"));
+
+        assert_eq!(registry.insert("mygame", game("Other")), Some(game("Mine")));
+        assert_eq!(registry.len(), GAMES.len() + 1);
+
+        assert_eq!(
+            registry.remove("TeamFortress2"),
+            GAMES.get("teamfortress2").cloned()
+        );
+        assert!(registry.lookup("teamfortress2").is_none());
+    }
+
+    #[test]
+    fn find_by_appid() {
+        let registry = GameRegistry::new();
+
+        let ids: Vec<_> = registry
+            .find_by_appid(440)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids, ["teamfortress2"]);
+        // The dedicated server app ID.
+        assert_eq!(registry.find_by_appid(556_450)[0].0, "theforest");
+        assert!(registry.find_by_appid(1).is_empty());
+    }
+
+    #[test]
+    fn search_ranking() {
+        let registry = GameRegistry::new();
+        let best = |query| {
+            let game_match = registry.search(query)[0];
+            (game_match.id, game_match.kind)
+        };
+
+        assert_eq!(best("csgo"), ("csgo", MatchKind::Id));
+        assert_eq!(best("TF2"), ("teamfortress2", MatchKind::Alias));
+        assert_eq!(best("440"), ("teamfortress2", MatchKind::AppId));
+        assert_eq!(best("counter strike source"), ("css", MatchKind::Name));
+        assert_eq!(best("JC2M"), ("jc2m", MatchKind::Id));
+        assert_eq!(best("l4d2"), ("l4d2", MatchKind::Id));
+        assert_eq!(best("bm"), ("blackmesa", MatchKind::Acronym));
+        assert_eq!(best("Team Fortress"), ("teamfortress2", MatchKind::Prefix));
+        assert_eq!(best("sandstorm"), ("insurgencysandstorm", MatchKind::Alias));
+        assert_eq!(best("zomboid"), ("projectzomboid", MatchKind::Contains));
+        assert_eq!(best("valhiem"), ("valheim", MatchKind::Similar(2)));
+
+        assert!(registry.search("").is_empty());
+        assert!(registry.search("nothing like it").is_empty());
+    }
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", "abc"), 0);
+        assert_eq!(acronym("Counter-Strike: Global Offensive"), "csgo");
+        assert_eq!(acronym("Left 4 Dead 2"), "l4d2");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn load_json() {
+        let mut registry = GameRegistry::new();
+        registry
+            .load_json(
+                r#"{
+                    "mygame": {
+                        "name": "My Game",
+                        "default_port": 27015,
+                        "protocol": { "Valve": { "Source": [123456, null] } },
+                        "aliases": ["MG"]
+                    },
+                    "mindustry": { "name": "Mindustry (modded)", "default_port": 6568, "protocol": "Unreal2" }
+                }"#,
+            )
+            .unwrap();
+
+        let mygame = registry.get("mygame").unwrap();
+        assert_eq!(mygame.name, "My Game");
+        assert_eq!(
+            mygame.protocol,
+            Protocol::Valve(crate::protocols::valve::Engine::new(123_456))
+        );
+        assert_eq!(registry.get("mindustry").unwrap().default_port, 6568);
+        assert_eq!(registry.lookup("mg").unwrap().0, "mygame");
+
+        assert!(registry
+            .load_json(r#"{ "bad": { "name": "Bad" } }"#)
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn load_toml() {
+        let mut registry = GameRegistry::new();
+        registry
+            .load_toml(
+                r#"
+                [mygame]
+                name = "My Game"
+                default_port = 7777
+                protocol = "Unreal2"
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(registry.get("mygame").unwrap().default_port, 7777);
+    }
+}
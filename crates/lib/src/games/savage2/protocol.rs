@@ -1,23 +1,103 @@
 use crate::buffer::{Buffer, Utf8Decoder};
 use crate::games::savage2::types::Response;
-use crate::protocols::types::TimeoutSettings;
+use crate::protocols::types::{QueryScope, TimeoutSettings};
 use crate::socket::{Socket, UdpSocket};
-use crate::GDResult;
+use crate::{ErrorContextExt, GDResult};
 use byteorder::LittleEndian;
 use std::net::{IpAddr, SocketAddr};
 
+#[cfg(feature = "async")]
+use crate::socket::{AsyncSocket, AsyncUdpSocket};
+
 pub fn query(address: &IpAddr, port: Option<u16>) -> GDResult<Response> { query_with_timeout(address, port, None) }
 
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &IpAddr, port: Option<u16>) -> GDResult<Response> {
+    query_with_timeout_async(address, port, None).await
+}
+
 pub fn query_with_timeout(
     address: &IpAddr,
     port: Option<u16>,
     timeout_settings: Option<TimeoutSettings>,
 ) -> GDResult<Response> {
-    let addr = &SocketAddr::new(*address, port.unwrap_or(11235));
-    let mut socket = UdpSocket::new(addr, &timeout_settings)?;
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, port, timeout_settings, &scope)
+}
+
+/// [query_with_timeout] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(11235));
+    get_response(&address, timeout_settings, scope)
+        .with_address(address)
+        .with_protocol("savage2")
+}
+
+fn get_response(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut socket = UdpSocket::new(address, &timeout_settings, scope)?;
     socket.send(&[0x01])?;
     let data = socket.receive(None)?;
-    let mut buffer = Buffer::<LittleEndian>::new(&data);
+
+    let mut response = parse_response(&data).with_data(&data)?;
+    response.latency = socket.latency();
+
+    Ok(response)
+}
+
+/// Async version of [query_with_timeout].
+#[cfg(feature = "async")]
+pub async fn query_with_timeout_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, port, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(11235));
+    get_response_async(&address, timeout_settings, scope)
+        .await
+        .with_address(address)
+        .with_protocol("savage2")
+}
+
+#[cfg(feature = "async")]
+async fn get_response_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut socket = AsyncUdpSocket::new(address, &timeout_settings, scope).await?;
+    socket.send(&[0x01]).await?;
+    let data = socket.receive(None).await?;
+
+    let mut response = parse_response(&data).with_data(&data)?;
+    response.latency = socket.latency();
+
+    Ok(response)
+}
+
+fn parse_response(data: &[u8]) -> GDResult<Response> {
+    let mut buffer = Buffer::<LittleEndian>::new(data);
 
     buffer.move_cursor(12)?;
 
@@ -33,5 +113,6 @@ pub fn query_with_timeout(
         game_mode: buffer.read_string::<Utf8Decoder>(None)?,
         protocol_version: buffer.read_string::<Utf8Decoder>(None)?,
         level_minimum: buffer.read::<u8>()?,
+        latency: None,
     })
 }
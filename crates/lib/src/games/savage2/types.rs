@@ -1,7 +1,9 @@
 use crate::protocols::types::CommonResponse;
 use crate::protocols::GenericResponse;
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,10 +19,13 @@ pub struct Response {
     pub game_mode: String,
     pub protocol_version: String,
     pub level_minimum: u8,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> GenericResponse { GenericResponse::Savage2(self) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn name(&self) -> Option<&str> { Some(&self.name) }
     fn game_mode(&self) -> Option<&str> { Some(&self.game_mode) }
@@ -28,3 +33,7 @@ impl CommonResponse for Response {
     fn players_maximum(&self) -> u32 { self.players_maximum.into() }
     fn players_online(&self) -> u32 { self.players_online.into() }
 }
+
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) { normalize_in_place(&mut self.name, Dialect::None, format); }
+}
@@ -1,23 +1,64 @@
 use crate::games::theship::types::Response;
-use crate::protocols::types::TimeoutSettings;
+use crate::protocols::types::{QueryScope, TimeoutSettings};
 use crate::protocols::valve;
 use crate::protocols::valve::Engine;
-use crate::GDResult;
+use crate::{ErrorContextExt, GDResult};
 use std::net::{IpAddr, SocketAddr};
 
 pub fn query(address: &IpAddr, port: Option<u16>) -> GDResult<Response> { query_with_timeout(address, port, None) }
 
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &IpAddr, port: Option<u16>) -> GDResult<Response> {
+    query_with_timeout_async(address, port, None).await
+}
+
 pub fn query_with_timeout(
     address: &IpAddr,
     port: Option<u16>,
     timeout_settings: Option<TimeoutSettings>,
 ) -> GDResult<Response> {
-    let valve_response = valve::query(
-        &SocketAddr::new(*address, port.unwrap_or(27015)),
-        Engine::new(2400),
-        None,
-        timeout_settings,
-    )?;
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, port, timeout_settings, &scope)
+}
+
+/// [query_with_timeout] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(27015));
+    valve::query_with_scope(&address, Engine::new(2400), None, timeout_settings, scope)
+        .and_then(Response::new_from_valve_response)
+        .with_address(address)
+        .with_protocol("theship")
+}
 
-    Response::new_from_valve_response(valve_response)
+/// Async version of [query_with_timeout].
+#[cfg(feature = "async")]
+pub async fn query_with_timeout_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, port, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &IpAddr,
+    port: Option<u16>,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let address = SocketAddr::new(*address, port.unwrap_or(27015));
+    valve::query_with_scope_async(&address, Engine::new(2400), None, timeout_settings, scope)
+        .await
+        .and_then(Response::new_from_valve_response)
+        .with_address(address)
+        .with_protocol("theship")
 }
@@ -1,9 +1,12 @@
-use crate::protocols::types::{CommonPlayer, CommonResponse, GenericPlayer};
+use crate::protocols::types::{sorted_rules, CommonPlayer, CommonResponse, GenericPlayer, PlayerKind};
+use crate::protocols::valve::keywords::keyword_tags;
 use crate::protocols::valve::{get_optional_extracted_data, Server, ServerPlayer};
 use crate::protocols::{valve, GenericResponse};
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 use crate::GDErrorKind::PacketBad;
 use crate::GDResult;
 use std::collections::HashMap;
+use std::time::Duration;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -16,6 +19,8 @@ pub struct TheShipPlayer {
     pub duration: f32,
     pub deaths: u32,
     pub money: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kind: PlayerKind,
 }
 
 impl TheShipPlayer {
@@ -26,6 +31,7 @@ impl TheShipPlayer {
             duration: player.duration,
             deaths: player.deaths.ok_or(PacketBad)?,
             money: player.money.ok_or(PacketBad)?,
+            kind: player.kind,
         })
     }
 }
@@ -35,6 +41,10 @@ impl CommonPlayer for TheShipPlayer {
 
     fn name(&self) -> &str { &self.name }
     fn score(&self) -> Option<i32> { Some(self.score) }
+    fn time_connected(&self) -> Option<Duration> { Duration::try_from_secs_f32(self.duration).ok() }
+    fn deaths(&self) -> Option<u32> { Some(self.deaths) }
+    fn is_bot(&self) -> Option<bool> { Some(self.kind == PlayerKind::Bot) }
+    fn kind(&self) -> Option<PlayerKind> { Some(self.kind) }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -61,10 +71,13 @@ pub struct Response {
     pub mode: u8,
     pub witnesses: u8,
     pub duration: u8,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> GenericResponse { GenericResponse::TheShip(self) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn name(&self) -> Option<&str> { Some(&self.name) }
     fn map(&self) -> Option<&str> { Some(&self.map) }
@@ -73,6 +86,10 @@ impl CommonResponse for Response {
     fn players_online(&self) -> u32 { self.players_online.into() }
     fn players_bots(&self) -> Option<u32> { Some(self.players_bots.into()) }
     fn has_password(&self) -> Option<bool> { Some(self.has_password) }
+    fn is_secured(&self) -> Option<bool> { Some(self.vac_secured) }
+    fn game_port(&self) -> Option<u16> { self.port }
+    fn tags(&self) -> Option<Vec<&str>> { self.keywords.as_deref().map(keyword_tags) }
+    fn rules(&self) -> Option<Vec<(&str, &str)>> { Some(sorted_rules(self.rules.iter())) }
 
     fn players(&self) -> Option<Vec<&dyn CommonPlayer>> {
         Some(
@@ -84,6 +101,15 @@ impl CommonResponse for Response {
     }
 }
 
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.name, Dialect::None, format);
+        for player in &mut self.players {
+            normalize_in_place(&mut player.name, Dialect::None, format);
+        }
+    }
+}
+
 impl Response {
     pub fn new_from_valve_response(response: valve::Response) -> GDResult<Self> {
         let (port, steam_id, tv_port, tv_name, keywords) = get_optional_extracted_data(response.info.extra_data);
@@ -117,6 +143,7 @@ impl Response {
             mode: the_unwrapped_ship.mode,
             witnesses: the_unwrapped_ship.witnesses,
             duration: the_unwrapped_ship.duration,
+            latency: response.latency,
         })
     }
 }
@@ -1,6 +1,10 @@
 //! Game related types
 
 use crate::protocols::types::{ExtraRequestSettings, Protocol};
+use crate::GDErrorKind::InvalidInput;
+use crate::GDResult;
+
+use std::borrow::Cow;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -9,12 +13,63 @@ use serde::{Deserialize, Serialize};
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Game {
-    /// Full name of the game
-    pub name: &'static str,
-    /// Default port used by game
+    /// Full name of the game (owned when loaded at runtime)
+    pub name: Cow<'static, str>,
+    /// Default port used by game to answer queries
     pub default_port: u16,
+    /// How the query port relates to the port players connect to
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub query_port: QueryPort,
     /// The protocol the game's query uses
     pub protocol: Protocol,
     /// Request settings.
     pub request_settings: ExtraRequestSettings,
 }
+
+/// Where a game answers queries, relative to the port players connect to (the
+/// game port).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum QueryPort {
+    /// On the game port.
+    #[default]
+    Same,
+    /// On the game port plus this offset.
+    Offset(i32),
+    /// On its own port, whatever the game port is (the default one unless
+    /// configured otherwise).
+    Fixed,
+}
+
+impl Game {
+    /// Set how the query port relates to the game port.
+    pub const fn set_query_port(mut self, query_port: QueryPort) -> Self {
+        self.query_port = query_port;
+        self
+    }
+
+    /// The port to query a server players connect to on `game_port`.
+    pub fn query_port(&self, game_port: u16) -> GDResult<u16> {
+        match self.query_port {
+            QueryPort::Same => Ok(game_port),
+            QueryPort::Offset(offset) => {
+                u16::try_from(i32::from(game_port) + offset).map_err(|_| {
+                    InvalidInput.context(format!(
+                        "Game port {game_port} with a query port offset of {offset} is out of range"
+                    ))
+                })
+            }
+            QueryPort::Fixed => Ok(self.default_port),
+        }
+    }
+
+    /// The default port players connect to, if it can be told from the default
+    /// (query) port.
+    pub fn default_game_port(&self) -> Option<u16> {
+        match self.query_port {
+            QueryPort::Same => Some(self.default_port),
+            QueryPort::Offset(offset) => u16::try_from(i32::from(self.default_port) - offset).ok(),
+            QueryPort::Fixed => None,
+        }
+    }
+}
@@ -1,13 +1,21 @@
 //! Valve game query modules
 
-use crate::protocols::valve::game_query_mod;
+use crate::protocols::valve::{arma_query_mod, game_query_mod};
 
 game_query_mod!(abioticfactor, "Abiotic Factor", Engine::new(427_410), 27015);
-game_query_mod!(
+arma_query_mod!(
     a2oa,
     "ARMA 2: Operation Arrowhead",
     Engine::new(33930),
-    2304
+    2304,
+    ArmaLayout::Arma
+);
+arma_query_mod!(
+    arma3,
+    "Arma 3",
+    Engine::new(107_410),
+    2303,
+    ArmaLayout::Arma
 );
 game_query_mod!(basedefense, "Base Defense", Engine::new(632_730), 27015);
 game_query_mod!(alienswarm, "Alien Swarm", Engine::new(630), 27015);
@@ -21,9 +29,16 @@ game_query_mod!(
         players: GatherToggle::Enforce,
         rules: GatherToggle::Skip,
         check_app_id: true,
+        filter_players: false,
     }
 );
-game_query_mod!(ase, "ARK: Survival Evolved", Engine::new(346_110), 27015);
+game_query_mod!(
+    ase,
+    "ARK: Survival Evolved",
+    Engine::new(346_110),
+    27015,
+    keywords: UnrealKeywords
+);
 game_query_mod!(
     asrd,
     "Alien Swarm: Reactive Drop",
@@ -47,6 +62,7 @@ game_query_mod!(
         players: GatherToggle::Enforce,
         rules: GatherToggle::Enforce,
         check_app_id: false,
+        filter_players: false,
     }
 );
 game_query_mod!(
@@ -80,6 +96,7 @@ game_query_mod!(
         players: GatherToggle::Skip,
         rules: GatherToggle::Enforce,
         check_app_id: true,
+        filter_players: false,
     }
 );
 game_query_mod!(
@@ -88,7 +105,13 @@ game_query_mod!(
     Engine::new_gold_src(false),
     27015
 );
-game_query_mod!(counterstrike2, "Counter-Strike 2", Engine::new(730), 27015);
+game_query_mod!(
+    counterstrike2,
+    "Counter-Strike 2",
+    Engine::new(730),
+    27015,
+    keywords: Cs2Keywords
+);
 game_query_mod!(creativerse, "Creativerse", Engine::new(280_790), 26901);
 game_query_mod!(
     cscz,
@@ -104,6 +127,7 @@ game_query_mod!(
 );
 game_query_mod!(css, "Counter-Strike: Source", Engine::new(240), 27015);
 game_query_mod!(dab, "Double Action: Boogaloo", Engine::new(317_360), 27015);
+arma_query_mod!(dayz, "DayZ", Engine::new(221_100), 27016, ArmaLayout::DayZ);
 game_query_mod!(dod, "Day of Defeat", Engine::new_gold_src(false), 27015);
 game_query_mod!(dods, "Day of Defeat: Source", Engine::new(300), 27015);
 game_query_mod!(doi, "Day of Infamy", Engine::new(447_820), 27015);
@@ -149,11 +173,23 @@ game_query_mod!(
 );
 game_query_mod!(risingworld, "Rising World", Engine::new(324_080), 4254);
 game_query_mod!(ror2, "Risk of Rain 2", Engine::new(632_360), 27016);
-game_query_mod!(rust, "Rust", Engine::new(252_490), 27015);
+game_query_mod!(
+    rust,
+    "Rust",
+    Engine::new(252_490),
+    27015,
+    keywords: RustKeywords
+);
 game_query_mod!(sco, "Sven Co-op", Engine::new_gold_src(false), 27015);
 game_query_mod!(sdtd, "7 Days to Die", Engine::new(251_570), 26900);
 game_query_mod!(soulmask, "Soulmask", Engine::new(2_646_460), 27015);
-game_query_mod!(squad, "Squad", Engine::new(393_380), 27165);
+game_query_mod!(
+    squad,
+    "Squad",
+    Engine::new(393_380),
+    27165,
+    keywords: UnrealKeywords
+);
 game_query_mod!(
     starbound,
     "Starbound",
@@ -163,6 +199,7 @@ game_query_mod!(
         players: GatherToggle::Enforce,
         rules: GatherToggle::Enforce,
         check_app_id: false,
+        filter_players: false,
     }
 );
 game_query_mod!(teamfortress2, "Team Fortress 2", Engine::new(440), 27015);
@@ -184,6 +221,7 @@ game_query_mod!(
         players: GatherToggle::Enforce,
         rules: GatherToggle::Skip,
         check_app_id: true,
+        filter_players: false,
     }
 );
 game_query_mod!(vrising, "V Rising", Engine::new(1_604_030), 27016);
@@ -7,11 +7,14 @@
 // TODO: When this is used in more places remove this and refine the interface.
 #![allow(dead_code)]
 
+use crate::protocols::types::QueryScope;
+use crate::socket::LatencyTimer;
 use crate::GDErrorKind::{HostLookup, InvalidInput, PacketReceive, PacketSend, ProtocolFormat};
 use crate::{GDResult, TimeoutSettings};
 
 use std::io::Read;
 use std::net::{SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs};
+use std::time::Duration;
 
 use ureq::{Agent, AgentBuilder, Request};
 use url::{Host, Url};
@@ -35,6 +38,7 @@ pub struct HttpClient {
     client: Agent,
     address: Url,
     headers: Vec<(String, String)>,
+    timer: LatencyTimer,
 }
 
 /// HttpHeaders for use with a single request.
@@ -121,10 +125,12 @@ impl HttpClient {
     /// - [timeout_settings](TimeoutSettings): Used to set the connect and
     ///   socket timeouts for the requests.
     /// - [http_settings](HttpSettings): Additional settings for the HTTPClient.
+    /// - [scope](QueryScope): The query the requests are part of.
     pub fn new<S: Into<String>>(
         address: &SocketAddr,
         timeout_settings: &Option<TimeoutSettings>,
         http_settings: HttpSettings<S>,
+        scope: &QueryScope,
     ) -> GDResult<Self>
     where
         Self: Sized,
@@ -146,10 +152,27 @@ impl HttpClient {
             client_builder = client_builder.timeout_connect(connect_timeout);
         }
 
+        // Each request may take at most what's left of the query deadline
+        if let Some(remaining) = scope.remaining()? {
+            client_builder = client_builder.timeout(remaining);
+        }
+
         // Every request sent from this client will connect to the address set
+        // (through the proxy, if there is one)
         {
             let address = *address;
-            client_builder = client_builder.resolver(move |_: &str| Ok(vec![address]));
+            let proxy = scope.socket_settings().get_proxy();
+            client_builder = client_builder.resolver(move |netloc: &str| {
+                Ok(match proxy {
+                    Some(proxy) if netloc == proxy.to_string() => vec![proxy],
+                    _ => vec![address],
+                })
+            });
+
+            if let Some(proxy) = proxy {
+                let proxy = ureq::Proxy::new(format!("socks5://{proxy}")).map_err(|e| InvalidInput.context(e))?;
+                client_builder = client_builder.proxy(proxy);
+            }
         }
 
         // Set a friendly user-agent string
@@ -181,6 +204,7 @@ impl HttpClient {
                 .into_iter()
                 .map(|(k, v)| (k.into(), v.into()))
                 .collect(),
+            timer: LatencyTimer::default(),
         })
     }
 
@@ -194,6 +218,7 @@ impl HttpClient {
         url: U,
         timeout_settings: &Option<TimeoutSettings>,
         headers: Option<Vec<(&str, &str)>>,
+        scope: &QueryScope,
     ) -> GDResult<Self>
     where
         U::Error: std::error::Error + Send + Sync + 'static,
@@ -229,7 +254,7 @@ impl HttpClient {
             headers: headers.unwrap_or_default(),
         };
 
-        Self::new(&address, timeout_settings, http_settings)
+        Self::new(&address, timeout_settings, http_settings, scope)
     }
 
     /// Send a HTTP GET request and return the response data as a buffer.
@@ -240,6 +265,33 @@ impl HttpClient {
         self.request_json("GET", path, headers)
     }
 
+    /// Send a HTTP GET request with `query` parameters and parse the JSON
+    /// response.
+    ///
+    /// Errors don't include the URL, as the query may hold secrets (such as an
+    /// API key).
+    pub fn get_json_with_query<T: DeserializeOwned>(
+        &mut self,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: HttpHeaders,
+    ) -> GDResult<T> {
+        self.address.set_path(path);
+        let request = self
+            .make_request("GET", headers)
+            .query_pairs(query.iter().copied());
+
+        self.timer.sent();
+        let http_response = request
+            .call()
+            .map_err(|e| PacketSend.context(describe_without_url(e)))?;
+        self.timer.received();
+
+        http_response
+            .into_json::<T>()
+            .map_err(|e| ProtocolFormat.context(e))
+    }
+
     /// Send a HTTP Post request with JSON data and parse a JSON response.
     pub fn post_json<T: DeserializeOwned, S: Serialize>(
         &mut self,
@@ -260,6 +312,10 @@ impl HttpClient {
         self.request_with_form_data("POST", path, headers, data)
     }
 
+    /// Get the time the first request took to be answered (without reading
+    /// the body), if one was.
+    pub const fn latency(&self) -> Option<Duration> { self.timer.latency() }
+
     // NOTE: More methods can be added here as required using the request_json or
     // request_with_json methods
 
@@ -288,7 +344,9 @@ impl HttpClient {
         let request = self.make_request(method, headers);
 
         // Send the request.
+        self.timer.sent();
         let http_response = request.call().map_err(|e| PacketSend.context(e))?;
+        self.timer.received();
 
         let length = if let Some(length) = http_response.header("Content-Length") {
             length
@@ -318,9 +376,11 @@ impl HttpClient {
         let request = self.make_request(method, headers);
 
         // Send the request and parse the response as JSON.
-        request
-            .call()
-            .map_err(|e| PacketSend.context(e))?
+        self.timer.sent();
+        let http_response = request.call().map_err(|e| PacketSend.context(e))?;
+        self.timer.received();
+
+        http_response
             .into_json::<T>()
             .map_err(|e| ProtocolFormat.context(e))
     }
@@ -337,9 +397,11 @@ impl HttpClient {
         self.address.set_path(path);
         let request = self.make_request(method, headers);
 
-        request
-            .send_json(data)
-            .map_err(|e| PacketSend.context(e))?
+        self.timer.sent();
+        let http_response = request.send_json(data).map_err(|e| PacketSend.context(e))?;
+        self.timer.received();
+
+        http_response
             .into_json::<T>()
             .map_err(|e| ProtocolFormat.context(e))
     }
@@ -356,14 +418,228 @@ impl HttpClient {
         self.address.set_path(path);
         let request = self.make_request(method, headers);
 
-        request
-            .send_form(data)
-            .map_err(|e| PacketSend.context(e))?
+        self.timer.sent();
+        let http_response = request.send_form(data).map_err(|e| PacketSend.context(e))?;
+        self.timer.received();
+
+        http_response
             .into_json::<T>()
             .map_err(|e| ProtocolFormat.context(e))
     }
 }
 
+/// Describe a request's `error` without the request's URL.
+fn describe_without_url(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => format!("Status code {} ({})", code, response.status_text()),
+        ureq::Error::Transport(transport) => {
+            match transport.message() {
+                Some(message) => format!("{}: {}", transport.kind(), message),
+                None => transport.kind().to_string(),
+            }
+        }
+    }
+}
+
+/// Owned copy of [HttpHeaders] that can be moved onto a blocking thread.
+#[cfg(feature = "async")]
+type OwnedHttpHeaders = Option<Vec<(String, String)>>;
+
+#[cfg(feature = "async")]
+fn to_owned_headers(headers: HttpHeaders) -> OwnedHttpHeaders {
+    headers.map(|headers| {
+        headers
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    })
+}
+
+#[cfg(feature = "async")]
+fn as_borrowed_pairs(pairs: &[(String, String)]) -> Vec<(&str, &str)> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect()
+}
+
+/// Async HTTP request client.
+///
+/// The underlying HTTP agent is blocking, so every request made through this
+/// client runs on tokio's blocking thread pool
+/// ([spawn_blocking](tokio::task::spawn_blocking)) instead of occupying an
+/// async worker thread.
+#[cfg(feature = "async")]
+pub struct AsyncHttpClient {
+    /// None only while a request is in flight (or if one panicked).
+    client: Option<HttpClient>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncHttpClient {
+    /// Async version of [HttpClient::new].
+    pub fn new<S: Into<String>>(
+        address: &SocketAddr,
+        timeout_settings: &Option<TimeoutSettings>,
+        http_settings: HttpSettings<S>,
+        scope: &QueryScope,
+    ) -> GDResult<Self> {
+        Ok(Self {
+            client: Some(HttpClient::new(
+                address,
+                timeout_settings,
+                http_settings,
+                scope,
+            )?),
+        })
+    }
+
+    /// Async version of [HttpClient::from_url], the DNS lookup is done on a
+    /// blocking thread.
+    pub async fn from_url<U: TryInto<Url>>(
+        url: U,
+        timeout_settings: &Option<TimeoutSettings>,
+        headers: Option<Vec<(&str, &str)>>,
+        scope: &QueryScope,
+    ) -> GDResult<Self>
+    where
+        U::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let url: Url = url.try_into().map_err(|e| InvalidInput.context(e))?;
+        let (timeout_settings, scope) = (*timeout_settings, scope.clone());
+        let headers = headers.map(|headers| {
+            headers
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        });
+
+        let client = tokio::task::spawn_blocking(move || {
+            HttpClient::from_url(
+                url,
+                &timeout_settings,
+                headers.as_deref().map(as_borrowed_pairs),
+                &scope,
+            )
+        })
+        .await
+        .map_err(|e| HostLookup.context(e))??;
+
+        Ok(Self {
+            client: Some(client),
+        })
+    }
+
+    /// Run a request using the blocking client on tokio's blocking thread pool.
+    async fn run<T: Send + 'static>(
+        &mut self,
+        request: impl FnOnce(&mut HttpClient) -> GDResult<T> + Send + 'static,
+    ) -> GDResult<T> {
+        let mut client = self
+            .client
+            .take()
+            .ok_or_else(|| PacketSend.context("HTTP client was lost by a previous request"))?;
+
+        let (client, result) = tokio::task::spawn_blocking(move || {
+            let result = request(&mut client);
+            (client, result)
+        })
+        .await
+        .map_err(|e| PacketReceive.context(e))?;
+
+        self.client = Some(client);
+
+        result
+    }
+
+    /// Async version of [HttpClient::latency].
+    pub fn latency(&self) -> Option<Duration> { self.client.as_ref().and_then(HttpClient::latency) }
+
+    /// Send a HTTP GET request and return the response data as a buffer.
+    pub async fn get(&mut self, path: &str, headers: HttpHeaders<'_>) -> GDResult<Vec<u8>> {
+        let path = path.to_string();
+        let headers = to_owned_headers(headers);
+
+        self.run(move |client| client.get(&path, headers.as_deref().map(as_borrowed_pairs).as_deref()))
+            .await
+    }
+
+    /// Send a HTTP GET request and parse the JSON resonse.
+    pub async fn get_json<T: DeserializeOwned + Send + 'static>(
+        &mut self,
+        path: &str,
+        headers: HttpHeaders<'_>,
+    ) -> GDResult<T> {
+        let path = path.to_string();
+        let headers = to_owned_headers(headers);
+
+        self.run(move |client| client.get_json(&path, headers.as_deref().map(as_borrowed_pairs).as_deref()))
+            .await
+    }
+
+    /// Async version of [HttpClient::get_json_with_query].
+    pub async fn get_json_with_query<T: DeserializeOwned + Send + 'static>(
+        &mut self,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: HttpHeaders<'_>,
+    ) -> GDResult<T> {
+        let path = path.to_string();
+        let query = to_owned_headers(Some(query)).unwrap_or_default();
+        let headers = to_owned_headers(headers);
+
+        self.run(move |client| {
+            client.get_json_with_query(
+                &path,
+                &as_borrowed_pairs(&query),
+                headers.as_deref().map(as_borrowed_pairs).as_deref(),
+            )
+        })
+        .await
+    }
+
+    /// Send a HTTP Post request with JSON data and parse a JSON response.
+    pub async fn post_json<T: DeserializeOwned + Send + 'static, S: Serialize + Send + 'static>(
+        &mut self,
+        path: &str,
+        headers: HttpHeaders<'_>,
+        data: S,
+    ) -> GDResult<T> {
+        let path = path.to_string();
+        let headers = to_owned_headers(headers);
+
+        self.run(move |client| {
+            client.post_json(
+                &path,
+                headers.as_deref().map(as_borrowed_pairs).as_deref(),
+                data,
+            )
+        })
+        .await
+    }
+
+    /// Send a HTTP Post request with FORM data and parse a JSON response.
+    pub async fn post_json_with_form<T: DeserializeOwned + Send + 'static>(
+        &mut self,
+        path: &str,
+        headers: HttpHeaders<'_>,
+        data: &[(&str, &str)],
+    ) -> GDResult<T> {
+        let path = path.to_string();
+        let headers = to_owned_headers(headers);
+        let data = to_owned_headers(Some(data)).unwrap_or_default();
+
+        self.run(move |client| {
+            client.post_json_with_form(
+                &path,
+                headers.as_deref().map(as_borrowed_pairs).as_deref(),
+                &as_borrowed_pairs(&data),
+            )
+        })
+        .await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::net::{Ipv4Addr, SocketAddrV4, ToSocketAddrs};
@@ -403,7 +679,7 @@ mod tests {
             headers: vec![("Authorization", "UUDDLRLRBA")],
         };
 
-        let client = HttpClient::new(&ADDRESS, &None, settings).unwrap();
+        let client = HttpClient::new(&ADDRESS, &None, settings, &QueryScope::default()).unwrap();
 
         assert_eq!(client.address.as_str(), "http://github.com:8000/");
         assert_eq!(
@@ -426,7 +702,7 @@ mod tests {
             .protocol(HttpProtocol::Https)
             .hostname("api.github.com");
 
-        let mut client = HttpClient::new(&address, &None, settings).unwrap();
+        let mut client = HttpClient::new(&address, &None, settings, &QueryScope::default()).unwrap();
 
         let response: serde_json::Value = client.get_json("/events", None).unwrap();
 
@@ -444,7 +720,7 @@ mod tests {
 
         let settings = HttpSettings::default().hostname("postman-echo.com");
 
-        let mut client = HttpClient::new(&address, &None, settings).unwrap();
+        let mut client = HttpClient::new(&address, &None, settings, &QueryScope::default()).unwrap();
 
         let response: serde_json::Value = client.get_json("/get", None).unwrap();
 
@@ -460,7 +736,7 @@ mod tests {
             .hostname("ifconfig.me")
             .header("User-Agent", "Curl/8.6.0");
 
-        let mut client = HttpClient::new(&address, &None, settings).unwrap();
+        let mut client = HttpClient::new(&address, &None, settings, &QueryScope::default()).unwrap();
 
         let response = client.get("/", None).unwrap();
 
@@ -470,7 +746,13 @@ mod tests {
     #[test]
     #[ignore = "HTTP requests won't work without internet"]
     fn http_get_from_url() {
-        let mut client = HttpClient::from_url("http://postman-echo.com/path-is-ignored", &None, None).unwrap();
+        let mut client = HttpClient::from_url(
+            "http://postman-echo.com/path-is-ignored",
+            &None,
+            None,
+            &QueryScope::default(),
+        )
+        .unwrap();
 
         let response: serde_json::Value = client.get_json("/get", None).unwrap();
 
@@ -482,10 +764,48 @@ mod tests {
     fn http_get_from_url_parsed() {
         let url = Url::parse("http://postman-echo.com/path-is-ignored").unwrap();
 
-        let mut client = HttpClient::from_url(url, &None, None).unwrap();
+        let mut client = HttpClient::from_url(url, &None, None, &QueryScope::default()).unwrap();
 
         let response: serde_json::Value = client.get_json("/get", None).unwrap();
 
         println!("{:?}", response);
     }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_http_json_get_request() {
+        use std::io::Write;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server_thread = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+
+            let body = r#"{"hello":"world"}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: \
+                 close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+
+        let mut client = AsyncHttpClient::new(
+            &address,
+            &None,
+            HttpSettings::<String>::default(),
+            &QueryScope::default(),
+        )
+        .unwrap();
+
+        let response: serde_json::Value = client.get_json("/", None).await.unwrap();
+
+        server_thread.join().expect("server thread panicked");
+
+        assert_eq!(response["hello"], "world");
+    }
 }
@@ -35,18 +35,24 @@
 //! `game_defs` - include game definitions for programmatic access (enabled by
 //! default). <br>
 //! `clap` - enable clap derivations for gamedig settings types. <br>
-//! `tls` - enable TLS support for the HTTP client.
+//! `tls` - enable TLS support for the HTTP client. <br>
+//! `async` - enable the `*_async` query functions, built on tokio (requires
+//! Rust 1.75 or newer).
 
+pub mod dns;
 pub mod errors;
 #[cfg(feature = "games")]
 pub mod games;
 pub mod protocols;
 #[cfg(feature = "services")]
 pub mod services;
+pub mod text;
+pub mod transport;
 
 mod buffer;
 mod http;
 mod socket;
+mod socks5;
 mod utils;
 
 #[cfg(feature = "packet_capture")]
@@ -62,4 +68,4 @@ pub use query::*;
 pub use services::*;
 
 // Re-export types needed to call games::query::query in the root
-pub use protocols::types::{ExtraRequestSettings, TimeoutSettings};
+pub use protocols::types::{ExtraRequestSettings, PortRange, RetryPolicy, SocketSettings, TimeoutSettings};
@@ -51,6 +51,19 @@ macro_rules! game_query_fn {
                 &std::net::SocketAddr::new(*address, port.unwrap_or($default_port)),
             )
         }
+
+        /// Async version of [query].
+        #[cfg(feature = "async")]
+        pub async fn query_async(
+            address: &std::net::IpAddr,
+            port: Option<u16>,
+        ) -> crate::GDResult<crate::protocols::epic::Response> {
+            crate::protocols::epic::query_async(
+                $credentials,
+                &std::net::SocketAddr::new(*address, port.unwrap_or($default_port)),
+            )
+            .await
+        }
     };
 }
 
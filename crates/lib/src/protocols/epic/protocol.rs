@@ -1,5 +1,8 @@
+#[cfg(feature = "async")]
+use crate::http::AsyncHttpClient;
 use crate::http::HttpClient;
 use crate::protocols::epic::Response;
+use crate::protocols::types::QueryScope;
 use crate::GDErrorKind::{JsonParse, PacketBad};
 use crate::{GDResult, TimeoutSettings};
 use base64::prelude::BASE64_STANDARD;
@@ -58,8 +61,19 @@ macro_rules! extract_field {
 
 impl EpicProtocol {
     pub fn new(credentials: Credentials, timeout_settings: TimeoutSettings) -> GDResult<Self> {
+        let timeout_settings = Some(timeout_settings);
+        let scope = QueryScope::start(&timeout_settings);
+        Self::new_with_scope(credentials, &timeout_settings, &scope)
+    }
+
+    /// [EpicProtocol::new] as part of a larger query, sharing its deadline.
+    pub(crate) fn new_with_scope(
+        credentials: Credentials,
+        timeout_settings: &Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<Self> {
         Ok(Self {
-            client: HttpClient::from_url(EPIC_API_ENDPOINT, &Some(timeout_settings), None)?,
+            client: HttpClient::from_url(EPIC_API_ENDPOINT, timeout_settings, None, scope)?,
             credentials,
         })
     }
@@ -72,13 +86,9 @@ impl EpicProtocol {
             ("deployment_id", self.credentials.deployment),
         ];
 
-        let auth_format = format!("{}:{}", self.credentials.id, self.credentials.secret);
-        let auth_base = BASE64_STANDARD.encode(auth_format);
-        let auth = format!("Basic {}", auth_base.as_str());
-        let authorization = auth.as_str();
-
+        let auth = client_authorization(&self.credentials);
         let headers = [
-            ("Authorization", authorization),
+            ("Authorization", auth.as_str()),
             ("Content-Type", "application/x-www-form-urlencoded"),
         ];
 
@@ -89,14 +99,7 @@ impl EpicProtocol {
     }
 
     pub fn query_raw(&mut self, address: &SocketAddr) -> GDResult<Value> {
-        let port = address.port();
-        let address = address.ip().to_string();
-
-        let body = format!(
-            "{{\"criteria\":[{{\"key\":\"attributes.ADDRESS_s\",\"op\":\"EQUAL\",\"value\":\"{}\"}}]}}",
-            address
-        );
-        let body = serde_json::from_str::<Value>(body.as_str()).map_err(|e| JsonParse.context(e))?;
+        let body = filter_body(address)?;
 
         let token = if self.credentials.auth_by_external {
             self.auth_by_external()?
@@ -113,60 +116,91 @@ impl EpicProtocol {
         let url = format!("/matchmaking/v1/{}/filter", self.credentials.deployment);
         let response: QueryResponse = self.client.post_json(url.as_str(), Some(&headers), body)?;
 
-        if let Value::Array(sessions) = response.sessions {
-            if sessions.is_empty() {
-                return Err(PacketBad.context("No servers provided."));
-            }
+        find_session(response, address)
+    }
 
-            for session in sessions.into_iter() {
-                let attributes = session
-                    .get("attributes")
-                    .ok_or(PacketBad.context("Expected attributes field missing in sessions."))?;
-
-                let address_match = attributes
-                    .get("ADDRESSBOUND_s")
-                    .and_then(Value::as_str)
-                    .map_or(false, |v| v == address || v == format!("0.0.0.0:{}", port))
-                    || attributes
-                        .get("GAMESERVER_PORT_1")
-                        .and_then(Value::as_u64)
-                        .map_or(false, |v| v == port as u64);
-
-                if address_match {
-                    return Ok(session);
-                }
-            }
+    pub fn query(&mut self, address: &SocketAddr) -> GDResult<Response> {
+        let value = self.query_raw(address)?;
 
-            return Err(
-                PacketBad.context("Servers were provided but the specified one couldn't be found amongst them.")
-            );
-        }
+        let mut response = parse_response(value)?;
+        response.latency = self.client.latency();
 
-        Err(PacketBad.context("Expected session field to be an array."))
+        Ok(response)
     }
+}
 
-    pub fn query(&mut self, address: &SocketAddr) -> GDResult<Response> {
-        let value = self.query_raw(address)?;
+/// The basic authorization header value for the client's credentials.
+fn client_authorization(credentials: &Credentials) -> String {
+    let auth_format = format!("{}:{}", credentials.id, credentials.secret);
+    let auth_base = BASE64_STANDARD.encode(auth_format);
+    format!("Basic {}", auth_base.as_str())
+}
 
-        let build_version = extract_optional_field!(value, ["attributes", "BUILDID_s"], Value::as_str);
-        let minor_version = extract_optional_field!(value, ["attributes", "MINORBUILDID_s"], Value::as_str);
+/// The matchmaking filter body that looks for sessions of the address.
+fn filter_body(address: &SocketAddr) -> GDResult<Value> {
+    let body = format!(
+        "{{\"criteria\":[{{\"key\":\"attributes.ADDRESS_s\",\"op\":\"EQUAL\",\"value\":\"{}\"}}]}}",
+        address.ip()
+    );
+    serde_json::from_str::<Value>(body.as_str()).map_err(|e| JsonParse.context(e))
+}
 
-        let game_version = match (build_version, minor_version) {
-            (Some(b), Some(m)) => Some(format!("{b}.{m}")),
-            _ => None,
-        };
+/// Find the session of the address amongst the matchmaking response.
+fn find_session(response: QueryResponse, address: &SocketAddr) -> GDResult<Value> {
+    let port = address.port();
+    let address = address.ip().to_string();
 
-        Ok(Response {
-            name: extract_field!(value, ["attributes", "CUSTOMSERVERNAME_s"], Value::as_str).to_string(),
-            map: extract_field!(value, ["attributes", "MAPNAME_s"], Value::as_str).to_string(),
-            has_password: extract_field!(value, ["attributes", "SERVERPASSWORD_b"], Value::as_bool),
-            players_online: extract_field!(value, ["totalPlayers"], Value::as_u64) as u32,
-            players_maxmimum: extract_field!(value, ["settings", "maxPublicPlayers"], Value::as_u64) as u32,
-            players: vec![],
-            game_version,
-            raw: value,
-        })
+    if let Value::Array(sessions) = response.sessions {
+        if sessions.is_empty() {
+            return Err(PacketBad.context("No servers provided."));
+        }
+
+        for session in sessions.into_iter() {
+            let attributes = session
+                .get("attributes")
+                .ok_or(PacketBad.context("Expected attributes field missing in sessions."))?;
+
+            let address_match = attributes
+                .get("ADDRESSBOUND_s")
+                .and_then(Value::as_str)
+                .map_or(false, |v| v == address || v == format!("0.0.0.0:{}", port))
+                || attributes
+                    .get("GAMESERVER_PORT_1")
+                    .and_then(Value::as_u64)
+                    .map_or(false, |v| v == port as u64);
+
+            if address_match {
+                return Ok(session);
+            }
+        }
+
+        return Err(PacketBad.context("Servers were provided but the specified one couldn't be found amongst them."));
     }
+
+    Err(PacketBad.context("Expected session field to be an array."))
+}
+
+/// Build a [Response] from the raw session value.
+fn parse_response(value: Value) -> GDResult<Response> {
+    let build_version = extract_optional_field!(value, ["attributes", "BUILDID_s"], Value::as_str);
+    let minor_version = extract_optional_field!(value, ["attributes", "MINORBUILDID_s"], Value::as_str);
+
+    let game_version = match (build_version, minor_version) {
+        (Some(b), Some(m)) => Some(format!("{b}.{m}")),
+        _ => None,
+    };
+
+    Ok(Response {
+        name: extract_field!(value, ["attributes", "CUSTOMSERVERNAME_s"], Value::as_str).to_string(),
+        map: extract_field!(value, ["attributes", "MAPNAME_s"], Value::as_str).to_string(),
+        has_password: extract_field!(value, ["attributes", "SERVERPASSWORD_b"], Value::as_bool),
+        players_online: extract_field!(value, ["totalPlayers"], Value::as_u64) as u32,
+        players_maxmimum: extract_field!(value, ["settings", "maxPublicPlayers"], Value::as_u64) as u32,
+        players: vec![],
+        game_version,
+        raw: value,
+        latency: None,
+    })
 }
 
 pub fn query(credentials: Credentials, address: &SocketAddr) -> GDResult<Response> {
@@ -181,3 +215,127 @@ pub fn query_with_timeout(
     let mut client = EpicProtocol::new(credentials, timeout_settings.unwrap_or_default())?;
     client.query(address)
 }
+
+/// [query_with_timeout] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    credentials: Credentials,
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let timeout_settings = Some(timeout_settings.unwrap_or_default());
+    let mut client = EpicProtocol::new_with_scope(credentials, &timeout_settings, scope)?;
+    client.query(address)
+}
+
+/// Async version of [EpicProtocol].
+#[cfg(feature = "async")]
+pub struct AsyncEpicProtocol {
+    client: AsyncHttpClient,
+    credentials: Credentials,
+}
+
+#[cfg(feature = "async")]
+impl AsyncEpicProtocol {
+    pub async fn new(credentials: Credentials, timeout_settings: TimeoutSettings) -> GDResult<Self> {
+        let timeout_settings = Some(timeout_settings);
+        let scope = QueryScope::start(&timeout_settings);
+        Self::new_with_scope(credentials, &timeout_settings, &scope).await
+    }
+
+    /// Async version of [EpicProtocol::new_with_scope].
+    pub(crate) async fn new_with_scope(
+        credentials: Credentials,
+        timeout_settings: &Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<Self> {
+        Ok(Self {
+            client: AsyncHttpClient::from_url(EPIC_API_ENDPOINT, timeout_settings, None, scope).await?,
+            credentials,
+        })
+    }
+
+    pub async fn auth_by_external(&self) -> GDResult<String> { Ok(String::new()) }
+
+    pub async fn auth_by_client(&mut self) -> GDResult<String> {
+        let body = [
+            ("grant_type", "client_credentials"),
+            ("deployment_id", self.credentials.deployment),
+        ];
+
+        let auth = client_authorization(&self.credentials);
+        let headers = [
+            ("Authorization", auth.as_str()),
+            ("Content-Type", "application/x-www-form-urlencoded"),
+        ];
+
+        let response = self
+            .client
+            .post_json_with_form::<ClientTokenResponse>("/auth/v1/oauth/token", Some(&headers), &body)
+            .await?;
+        Ok(response.access_token)
+    }
+
+    pub async fn query_raw(&mut self, address: &SocketAddr) -> GDResult<Value> {
+        let body = filter_body(address)?;
+
+        let token = if self.credentials.auth_by_external {
+            self.auth_by_external().await?
+        } else {
+            self.auth_by_client().await?
+        };
+        let authorization = format!("Bearer {}", token);
+        let headers = [
+            ("Content-Type", "application/json"),
+            ("Accept", "application/json"),
+            ("Authorization", authorization.as_str()),
+        ];
+
+        let url = format!("/matchmaking/v1/{}/filter", self.credentials.deployment);
+        let response: QueryResponse = self
+            .client
+            .post_json(url.as_str(), Some(&headers), body)
+            .await?;
+
+        find_session(response, address)
+    }
+
+    pub async fn query(&mut self, address: &SocketAddr) -> GDResult<Response> {
+        let value = self.query_raw(address).await?;
+
+        let mut response = parse_response(value)?;
+        response.latency = self.client.latency();
+
+        Ok(response)
+    }
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(credentials: Credentials, address: &SocketAddr) -> GDResult<Response> {
+    query_with_timeout_async(credentials, address, None).await
+}
+
+/// Async version of [query_with_timeout].
+#[cfg(feature = "async")]
+pub async fn query_with_timeout_async(
+    credentials: Credentials,
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response> {
+    let mut client = AsyncEpicProtocol::new(credentials, timeout_settings.unwrap_or_default()).await?;
+    client.query(address).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    credentials: Credentials,
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let timeout_settings = Some(timeout_settings.unwrap_or_default());
+    let mut client = AsyncEpicProtocol::new_with_scope(credentials, &timeout_settings, scope).await?;
+    client.query(address).await
+}
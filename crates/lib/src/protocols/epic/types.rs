@@ -1,8 +1,10 @@
 use crate::protocols::types::{CommonPlayer, CommonResponse, GenericPlayer};
 use crate::protocols::GenericResponse;
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::time::Duration;
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq)]
@@ -15,10 +17,13 @@ pub struct Response {
     pub players: Vec<Player>,
     pub game_version: Option<String>,
     pub raw: Value,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> GenericResponse { GenericResponse::Epic(self) }
+    fn latency(&self) -> Option<Duration> { self.latency }
     fn name(&self) -> Option<&str> { Some(&self.name) }
     fn map(&self) -> Option<&str> { Some(&self.map) }
     fn players_maximum(&self) -> u32 { self.players_maxmimum }
@@ -39,6 +44,15 @@ impl CommonResponse for Response {
     fn game_version(&self) -> Option<&str> { self.game_version.as_deref() }
 }
 
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.name, Dialect::None, format);
+        for player in &mut self.players {
+            normalize_in_place(&mut player.name, Dialect::None, format);
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq)]
 pub struct Player {
@@ -1,6 +1,9 @@
 use crate::{GDErrorKind, GDResult};
 use std::collections::HashMap;
 
+/// The game port reported in the server's variables.
+pub fn host_port(server_vars: &HashMap<String, String>) -> Option<u16> { server_vars.get("hostport")?.parse().ok() }
+
 pub fn has_password(server_vars: &mut HashMap<String, String>) -> GDResult<bool> {
     let password_value = server_vars
         .remove("password")
@@ -9,41 +9,54 @@ use crate::{
     buffer::Buffer,
     protocols::{
         gamespy::one::{Player, Response},
-        types::TimeoutSettings,
+        types::{QueryScope, TimeoutSettings},
     },
     socket::{Socket, UdpSocket},
+    ErrorContextExt,
     GDErrorKind,
     GDResult,
 };
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::time::Duration;
 
-/// Send status request, and parse response into HashMap.
-/// This function will retry fetch on timeouts.
+#[cfg(feature = "async")]
+use crate::{
+    socket::{AsyncSocket, AsyncUdpSocket},
+    utils::retry_on_timeout_async,
+};
+
+/// Send status request, and parse response into HashMap, alongside the
+/// latency of the exchange. This function will retry fetch on timeouts.
 fn get_server_values(
     address: &SocketAddr,
     timeout_settings: &Option<TimeoutSettings>,
-) -> GDResult<HashMap<String, String>> {
-    let mut socket = UdpSocket::new(address, timeout_settings)?;
-    retry_on_timeout(
-        TimeoutSettings::get_retries_or_default(timeout_settings),
-        move || get_server_values_impl(&mut socket),
-    )
+    scope: &QueryScope,
+) -> GDResult<(HashMap<String, String>, Option<Duration>)> {
+    let mut socket = UdpSocket::new(address, timeout_settings, scope)?;
+    let server_vars = retry_on_timeout(
+        TimeoutSettings::get_retry_policy_or_default(timeout_settings),
+        || get_server_values_impl(&mut socket),
+    )?;
+
+    Ok((server_vars, socket.latency()))
 }
 
-/// Send status request, and parse response into HashMap (without retry logic).
-fn get_server_values_impl(socket: &mut UdpSocket) -> GDResult<HashMap<String, String>> {
-    socket.send(b"\\status\\xserverquery")?;
+/// Status request packet.
+const STATUS_REQUEST: &[u8] = b"\\status\\xserverquery";
 
-    let mut received_query_id: Option<usize> = None;
-    let mut parts: Vec<usize> = Vec::new();
-    let mut is_finished = false;
-
-    let mut server_values = HashMap::new();
+/// Collects the (possibly multiple) packets of a status response.
+#[derive(Default)]
+struct ServerValues {
+    received_query_id: Option<usize>,
+    parts: Vec<usize>,
+    values: HashMap<String, String>,
+}
 
-    while !is_finished {
-        let data = socket.receive(None)?;
-        let mut bufferer = Buffer::<LittleEndian>::new(&data);
+impl ServerValues {
+    /// Parse a received packet, returns whether it was the final one.
+    fn parse_packet(&mut self, data: &[u8]) -> GDResult<bool> {
+        let mut bufferer = Buffer::<LittleEndian>::new(data);
 
         let mut as_string = bufferer.read_string::<Utf8Decoder>(None)?;
         as_string.remove(0);
@@ -57,14 +70,14 @@ fn get_server_values_impl(socket: &mut UdpSocket) -> GDResult<HashMap<String, St
                 .get(position + 1)
                 .map_or_else(String::new, Clone::clone);
 
-            server_values.insert(key, value);
+            self.values.insert(key, value);
         }
 
-        is_finished = server_values.remove("final").is_some();
+        let is_finished = self.values.remove("final").is_some();
 
-        let query_data = server_values.get("queryid");
+        let query_data = self.values.get("queryid");
 
-        let mut part = parts.len(); // if the part number isn't provided, it's value is the parts length
+        let mut part = self.parts.len(); // if the part number isn't provided, it's value is the parts length
         let mut query_id = None;
         if let Some(qid) = query_data {
             let split: Vec<&str> = qid.split('.').collect();
@@ -78,21 +91,31 @@ fn get_server_values_impl(socket: &mut UdpSocket) -> GDResult<HashMap<String, St
             };
         }
 
-        server_values.remove("queryid");
+        self.values.remove("queryid");
 
-        if received_query_id.is_some() && received_query_id != query_id {
+        if self.received_query_id.is_some() && self.received_query_id != query_id {
             return Err(GDErrorKind::PacketBad.into()); // wrong query id!
         }
 
-        received_query_id = query_id;
+        self.received_query_id = query_id;
 
-        match parts.contains(&part) {
+        match self.parts.contains(&part) {
             true => Err(GDErrorKind::PacketBad)?,
-            false => parts.push(part),
+            false => self.parts.push(part),
         }
+
+        Ok(is_finished)
     }
+}
 
-    Ok(server_values)
+/// Send status request, and parse response into HashMap (without retry logic).
+fn get_server_values_impl(socket: &mut UdpSocket) -> GDResult<HashMap<String, String>> {
+    socket.send(STATUS_REQUEST)?;
+
+    let mut server_values = ServerValues::default();
+    while !server_values.parse_packet(&socket.receive(None)?)? {}
+
+    Ok(server_values.values)
 }
 
 fn extract_players(server_vars: &mut HashMap<String, String>, players_maximum: u32) -> GDResult<Vec<Player>> {
@@ -188,15 +211,44 @@ pub fn query_vars(
     address: &SocketAddr,
     timeout_settings: Option<TimeoutSettings>,
 ) -> GDResult<HashMap<String, String>> {
-    get_server_values(address, &timeout_settings)
+    let scope = QueryScope::start(&timeout_settings);
+    get_server_values(address, &timeout_settings, &scope)
+        .map(|(server_vars, _)| server_vars)
+        .with_address(*address)
+        .with_protocol("gamespy1")
 }
 
 /// Query a server by providing the address, the port and timeout settings.
 /// Providing None to the timeout settings results in using the default values.
 /// (TimeoutSettings::[default](TimeoutSettings::default)).
 pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Response> {
-    let mut server_vars = query_vars(address, timeout_settings)?;
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, timeout_settings, &scope)
+}
+
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    get_server_values(address, &timeout_settings, scope)
+        .and_then(|(server_vars, latency)| response(server_vars, latency))
+        .with_address(*address)
+        .with_protocol("gamespy1")
+}
 
+/// Build a [Response] from the server's values and the latency of their
+/// request.
+fn response(server_vars: HashMap<String, String>, latency: Option<Duration>) -> GDResult<Response> {
+    let mut response = parse_response(server_vars)?;
+    response.latency = latency;
+
+    Ok(response)
+}
+
+/// Build a [Response] from the server's values.
+fn parse_response(mut server_vars: HashMap<String, String>) -> GDResult<Response> {
     let players_maximum: u32 = server_vars
         .remove("maxplayers")
         .ok_or(GDErrorKind::PacketBad)?
@@ -239,5 +291,68 @@ pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) ->
             .parse()
             .map_err(|e| TypeParse.context(e))?,
         unused_entries: server_vars,
+        latency: None,
     })
 }
+
+/// Async version of [get_server_values_impl].
+#[cfg(feature = "async")]
+async fn get_server_values_impl_async(socket: &mut AsyncUdpSocket) -> GDResult<HashMap<String, String>> {
+    socket.send(STATUS_REQUEST).await?;
+
+    let mut server_values = ServerValues::default();
+    while !server_values.parse_packet(&socket.receive(None).await?)? {}
+
+    Ok(server_values.values)
+}
+
+/// Async version of [get_server_values].
+#[cfg(feature = "async")]
+async fn get_server_values_async(
+    address: &SocketAddr,
+    timeout_settings: &Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<(HashMap<String, String>, Option<Duration>)> {
+    let mut socket = AsyncUdpSocket::new(address, timeout_settings, scope).await?;
+    let server_vars = retry_on_timeout_async!(
+        TimeoutSettings::get_retry_policy_or_default(timeout_settings),
+        get_server_values_impl_async(&mut socket)
+    )?;
+
+    Ok((server_vars, socket.latency()))
+}
+
+/// Async version of [query_vars].
+#[cfg(feature = "async")]
+pub async fn query_vars_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<HashMap<String, String>> {
+    let scope = QueryScope::start(&timeout_settings);
+    get_server_values_async(address, &timeout_settings, &scope)
+        .await
+        .map(|(server_vars, _)| server_vars)
+        .with_address(*address)
+        .with_protocol("gamespy1")
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Response> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    get_server_values_async(address, &timeout_settings, scope)
+        .await
+        .and_then(|(server_vars, latency)| response(server_vars, latency))
+        .with_address(*address)
+        .with_protocol("gamespy1")
+}
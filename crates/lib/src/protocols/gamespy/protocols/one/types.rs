@@ -1,11 +1,14 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::protocols::gamespy::common::host_port;
 use crate::protocols::gamespy::{VersionedPlayer, VersionedResponse};
-use crate::protocols::types::{CommonPlayer, CommonResponse, GenericPlayer};
+use crate::protocols::types::{sorted_rules, CommonPlayer, CommonResponse, GenericPlayer};
 use crate::protocols::GenericResponse;
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 
 /// A player’s details.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -29,6 +32,9 @@ impl CommonPlayer for Player {
 
     fn name(&self) -> &str { &self.name }
     fn score(&self) -> Option<i32> { Some(self.score) }
+    fn team(&self) -> Option<u32> { self.team.map(u32::from) }
+    fn ping(&self) -> Option<u32> { Some(self.ping.into()) }
+    fn deaths(&self) -> Option<u32> { self.deaths }
 }
 
 /// A query response.
@@ -49,10 +55,13 @@ pub struct Response {
     pub players: Vec<Player>,
     pub tournament: bool,
     pub unused_entries: HashMap<String, String>,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> GenericResponse { GenericResponse::GameSpy(VersionedResponse::One(self)) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn name(&self) -> Option<&str> { Some(&self.name) }
     fn map(&self) -> Option<&str> { Some(&self.map) }
@@ -61,6 +70,8 @@ impl CommonResponse for Response {
     fn game_version(&self) -> Option<&str> { Some(&self.game_version) }
     fn players_maximum(&self) -> u32 { self.players_maximum }
     fn players_online(&self) -> u32 { self.players_online }
+    fn rules(&self) -> Option<Vec<(&str, &str)>> { Some(sorted_rules(self.unused_entries.iter())) }
+    fn game_port(&self) -> Option<u16> { host_port(&self.unused_entries) }
 
     fn players(&self) -> Option<Vec<&dyn CommonPlayer>> {
         Some(
@@ -71,3 +82,12 @@ impl CommonResponse for Response {
         )
     }
 }
+
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.name, Dialect::None, format);
+        for player in &mut self.players {
+            normalize_in_place(&mut player.name, Dialect::None, format);
+        }
+    }
+}
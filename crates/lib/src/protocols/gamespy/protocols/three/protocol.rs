@@ -3,13 +3,20 @@ use byteorder::{BigEndian, LittleEndian};
 use crate::buffer::{Buffer, Utf8Decoder};
 use crate::protocols::gamespy::common::has_password;
 use crate::protocols::gamespy::three::{Player, Response, Team};
-use crate::protocols::types::TimeoutSettings;
+use crate::protocols::types::{QueryScope, RetryPolicy, SectionOutcome, TimeoutSettings};
 use crate::socket::{Socket, UdpSocket};
 use crate::utils::retry_on_timeout;
-use crate::GDErrorKind::{PacketBad, TypeParse};
-use crate::{GDErrorKind, GDResult};
+use crate::GDErrorKind::{DeadlineExceeded, PacketBad, TypeParse};
+use crate::{ErrorContextExt, GDError, GDErrorKind, GDResult};
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::time::Duration;
+
+#[cfg(feature = "async")]
+use crate::{
+    socket::{AsyncSocket, AsyncUdpSocket},
+    utils::retry_on_timeout_async,
+};
 
 const THIS_SESSION_ID: u32 = 1;
 
@@ -44,145 +51,212 @@ pub(crate) struct GameSpy3 {
     socket: UdpSocket,
     payload: [u8; 4],
     single_packets: bool,
-    retry_count: usize,
+    retry_policy: RetryPolicy,
 }
 
 const PACKET_SIZE: usize = 2048;
 const DEFAULT_PAYLOAD: [u8; 4] = [0xFF, 0xFF, 0xFF, 0x01];
 
 impl GameSpy3 {
-    fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Self> {
-        let socket = UdpSocket::new(address, &timeout_settings)?;
-        let retry_count = TimeoutSettings::get_retries_or_default(&timeout_settings);
-
-        Ok(Self {
-            socket,
-            payload: DEFAULT_PAYLOAD,
-            single_packets: false,
-            retry_count,
-        })
+    fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self> {
+        Self::new_custom(address, timeout_settings, scope, DEFAULT_PAYLOAD, false)
     }
 
     pub(crate) fn new_custom(
         address: &SocketAddr,
         timeout_settings: Option<TimeoutSettings>,
+        scope: &QueryScope,
         payload: [u8; 4],
         single_packets: bool,
     ) -> GDResult<Self> {
-        let socket = UdpSocket::new(address, &timeout_settings)?;
-        let retry_count = TimeoutSettings::get_retries_or_default(&timeout_settings);
+        let socket = UdpSocket::new(address, &timeout_settings, scope)?;
+        let retry_policy = TimeoutSettings::get_retry_policy_or_default(&timeout_settings);
 
         Ok(Self {
             socket,
             payload,
             single_packets,
-            retry_count,
+            retry_policy,
         })
     }
 
     fn receive(&mut self, size: Option<usize>, kind: u8) -> GDResult<Vec<u8>> {
         let received = self.socket.receive(size.or(Some(PACKET_SIZE)))?;
-        let mut buf = Buffer::<BigEndian>::new(&received);
-
-        if buf.read::<u8>()? != kind {
-            return Err(PacketBad.context("Kind of packet did not match"));
-        }
-
-        if buf.read::<u32>()? != THIS_SESSION_ID {
-            return Err(PacketBad.context("Session ID did not match"));
-        }
-
-        Ok(buf.remaining_bytes().to_vec())
+        check_response_header(&received, kind)
     }
 
     fn make_initial_handshake(&mut self) -> GDResult<Option<i32>> {
-        self.socket.send(
-            &RequestPacket {
-                header: 65277,
-                kind: 9,
-                session_id: THIS_SESSION_ID,
-                challenge: None,
-                payload: None,
-            }
-            .to_bytes(),
-        )?;
+        self.socket.send(&handshake_request())?;
 
         let data = self.receive(Some(16), 9)?;
-        let mut buf = Buffer::<LittleEndian>::new(&data);
-
-        let challenge_as_string = buf.read_string::<Utf8Decoder>(None)?;
-        let challenge = challenge_as_string
-            .parse()
-            .map_err(|e| TypeParse.context(e))?;
-
-        Ok(match challenge == 0 {
-            true => None,
-            false => Some(challenge),
-        })
+        parse_challenge(&data).with_data(&data)
     }
 
     fn send_data_request(&mut self, challenge: Option<i32>) -> GDResult<()> {
-        self.socket.send(
-            &RequestPacket {
-                header: 65277,
-                kind: 0,
-                session_id: THIS_SESSION_ID,
-                challenge,
-                payload: Some(self.payload),
-            }
-            .to_bytes(),
-        )
+        self.socket.send(&data_request(challenge, self.payload))
     }
 
+    /// The latency of the first exchange with the server.
+    pub(crate) fn latency(&self) -> Option<Duration> { self.socket.latency() }
+
     /// Fetch packets from server and store in buffer.
     /// This function will retry fetch on timeouts.
-    pub(crate) fn get_server_packets(&mut self) -> GDResult<Vec<Vec<u8>>> {
-        retry_on_timeout(self.retry_count, move || self.get_server_packets_impl())
+    pub(crate) fn get_server_packets(&mut self) -> GDResult<ReceivedPackets> {
+        retry_on_timeout(self.retry_policy, move || self.get_server_packets_impl())
     }
 
     /// Fetch packets from server and store in buffer (without retry logic).
-    fn get_server_packets_impl(&mut self) -> GDResult<Vec<Vec<u8>>> {
-        let challenge = self.make_initial_handshake()?;
+    fn get_server_packets_impl(&mut self) -> GDResult<ReceivedPackets> {
+        let challenge = self.make_initial_handshake().with_stage("challenge")?;
+        self.get_data_packets(challenge).with_stage("data")
+    }
+
+    /// Request the data (with the handshake's challenge) and receive its
+    /// packets.
+    fn get_data_packets(&mut self, challenge: Option<i32>) -> GDResult<ReceivedPackets> {
         self.send_data_request(challenge)?;
 
-        let mut values: Vec<Vec<u8>> = Vec::new();
+        let mut packets = ServerPackets::new(self.single_packets);
+        loop {
+            match self.receive(None, 0) {
+                Ok(data) if packets.parse_packet(&data).with_data(&data)? => return packets.finish(),
+                Ok(_) => {}
+                Err(e) => return packets.cut_short(e),
+            }
+        }
+    }
+}
+
+/// Check a response's kind and session id, returning the data after them.
+fn check_response_header(received: &[u8], kind: u8) -> GDResult<Vec<u8>> {
+    let mut buf = Buffer::<BigEndian>::new(received);
 
-        let mut reached_expected_packets_size = false;
+    if buf.read::<u8>()? != kind {
+        return Err(PacketBad.context("Kind of packet did not match"));
+    }
 
-        while !reached_expected_packets_size {
-            let received_data = self.receive(None, 0)?;
-            let mut buf = Buffer::<BigEndian>::new(&received_data);
+    if buf.read::<u32>()? != THIS_SESSION_ID {
+        return Err(PacketBad.context("Session ID did not match"));
+    }
 
-            if self.single_packets {
-                buf.move_cursor(11)?;
-                return Ok(vec![buf.remaining_bytes().to_vec()]);
-            }
+    Ok(buf.remaining_bytes().to_vec())
+}
 
-            if buf.read_string::<Utf8Decoder>(None)? != "splitnum" {
-                return Err(PacketBad.context("Expected string \"splitnum\""));
-            }
+fn handshake_request() -> Vec<u8> {
+    RequestPacket {
+        header: 65277,
+        kind: 9,
+        session_id: THIS_SESSION_ID,
+        challenge: None,
+        payload: None,
+    }
+    .to_bytes()
+}
 
-            let id = buf.read::<u8>()?;
-            let is_last = (id & 0x80) > 0;
-            let packet_id = (id & 0x7f) as usize;
-            buf.move_cursor(1)?; //unknown byte regarding packet no.
+fn parse_challenge(data: &[u8]) -> GDResult<Option<i32>> {
+    let mut buf = Buffer::<LittleEndian>::new(data);
 
-            if is_last && packet_id + 1 != values.len() {
-                reached_expected_packets_size = true;
-            }
+    let challenge_as_string = buf.read_string::<Utf8Decoder>(None)?;
+    let challenge = challenge_as_string
+        .parse()
+        .map_err(|e| TypeParse.context(e))?;
 
-            while values.len() <= packet_id {
-                values.push(Vec::new());
-            }
+    Ok(match challenge == 0 {
+        true => None,
+        false => Some(challenge),
+    })
+}
+
+fn data_request(challenge: Option<i32>, payload: [u8; 4]) -> Vec<u8> {
+    RequestPacket {
+        header: 65277,
+        kind: 0,
+        session_id: THIS_SESSION_ID,
+        challenge,
+        payload: Some(payload),
+    }
+    .to_bytes()
+}
+
+/// The packets of a data response.
+pub(crate) struct ReceivedPackets {
+    pub(crate) packets: Vec<Vec<u8>>,
+    /// Why some of the packets are missing (the query deadline expired), if
+    /// they are.
+    pub(crate) cut_short: Option<GDError>,
+}
 
-            values[packet_id] = buf.remaining_bytes().to_vec();
+/// Collects the packets of a data response.
+struct ServerPackets {
+    single_packets: bool,
+    values: Vec<Vec<u8>>,
+}
+
+impl ServerPackets {
+    const fn new(single_packets: bool) -> Self {
+        Self {
+            single_packets,
+            values: Vec::new(),
         }
+    }
+
+    /// Parse a received packet, returns whether all packets were received.
+    fn parse_packet(&mut self, received_data: &[u8]) -> GDResult<bool> {
+        let mut buf = Buffer::<BigEndian>::new(received_data);
+
+        if self.single_packets {
+            buf.move_cursor(11)?;
+            self.values = vec![buf.remaining_bytes().to_vec()];
+            return Ok(true);
+        }
+
+        if buf.read_string::<Utf8Decoder>(None)? != "splitnum" {
+            return Err(PacketBad.context("Expected string \"splitnum\""));
+        }
+
+        let id = buf.read::<u8>()?;
+        let is_last = (id & 0x80) > 0;
+        let packet_id = (id & 0x7f) as usize;
+        buf.move_cursor(1)?; //unknown byte regarding packet no.
 
-        if values.iter().any(Vec::is_empty) {
+        let reached_expected_packets_size = is_last && packet_id + 1 != self.values.len();
+
+        while self.values.len() <= packet_id {
+            self.values.push(Vec::new());
+        }
+
+        self.values[packet_id] = buf.remaining_bytes().to_vec();
+
+        Ok(reached_expected_packets_size)
+    }
+
+    fn finish(self) -> GDResult<ReceivedPackets> {
+        if self.values.iter().any(Vec::is_empty) {
             return Err(PacketBad.context("One (or more) packets is empty"));
         }
 
-        Ok(values)
+        Ok(ReceivedPackets {
+            packets: self.values,
+            cut_short: None,
+        })
+    }
+
+    /// Keep the packets received so far if the query deadline expired after
+    /// the first one (which has the server's variables) arrived, else fail
+    /// with `error`.
+    fn cut_short(self, error: GDError) -> GDResult<ReceivedPackets> {
+        if error.kind != DeadlineExceeded || self.values.first().map_or(true, Vec::is_empty) {
+            return Err(error);
+        }
+
+        Ok(ReceivedPackets {
+            packets: self
+                .values
+                .into_iter()
+                .filter(|packet| !packet.is_empty())
+                .collect(),
+            cut_short: Some(error),
+        })
     }
 }
 
@@ -210,12 +284,18 @@ pub fn query_vars(
     address: &SocketAddr,
     timeout_settings: Option<TimeoutSettings>,
 ) -> GDResult<HashMap<String, String>> {
-    let mut client = GameSpy3::new(address, timeout_settings)?;
-    let packets = client.get_server_packets()?;
+    let scope = QueryScope::start(&timeout_settings);
+    GameSpy3::new(address, timeout_settings, &scope)
+        .and_then(|mut client| client.get_server_packets())
+        .and_then(|received| packets_to_vars(&received.packets).with_stage("data"))
+        .with_address(*address)
+        .with_protocol("gamespy3")
+}
 
+fn packets_to_vars(packets: &[Vec<u8>]) -> GDResult<HashMap<String, String>> {
     let mut vars = HashMap::new();
 
-    for packet in &packets {
+    for packet in packets {
         let (key_values, _remaining_data) = data_to_map(packet)?;
         vars.extend(key_values);
     }
@@ -351,14 +431,54 @@ fn parse_players_and_teams(packets: Vec<Vec<u8>>) -> GDResult<(Vec<Player>, Vec<
 /// Providing None to the timeout settings results in using the default values.
 /// (TimeoutSettings::[default](TimeoutSettings::default)).
 pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Response> {
-    let mut client = GameSpy3::new(address, timeout_settings)?;
-    let packets = client.get_server_packets()?;
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, timeout_settings, &scope)
+}
 
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    get_response(address, timeout_settings, scope)
+        .with_address(*address)
+        .with_protocol("gamespy3")
+}
+
+fn get_response(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut client = GameSpy3::new(address, timeout_settings, scope)?;
+    let received = client.get_server_packets()?;
+
+    let mut response = parse_response(received).with_stage("data")?;
+    response.latency = client.latency();
+
+    Ok(response)
+}
+
+/// Build a [Response] from the received packets.
+fn parse_response(received: ReceivedPackets) -> GDResult<Response> {
+    let packets = received.packets;
     let (mut server_vars, remaining_data) = data_to_map(packets.first().ok_or(GDErrorKind::PacketBad)?)?;
 
     let mut remaining_data_packets = vec![remaining_data];
     remaining_data_packets.extend_from_slice(&packets[1 ..]);
-    let (players, teams) = parse_players_and_teams(remaining_data_packets)?;
+    // The server info is still of use if the players or teams weren't all
+    // received in time (even if what was received can't be parsed), but a
+    // complete response that can't be parsed is an error.
+    let ((players, teams), players_and_teams_outcome) = match (
+        parse_players_and_teams(remaining_data_packets),
+        received.cut_short,
+    ) {
+        (Ok(players_and_teams), None) => (players_and_teams, SectionOutcome::Succeeded),
+        (Ok(players_and_teams), Some(e)) => (players_and_teams, SectionOutcome::from_error(&e)),
+        (Err(_), Some(e)) => ((Vec::new(), Vec::new()), SectionOutcome::from_error(&e)),
+        (Err(e), None) => return Err(e),
+    };
 
     let players_maximum = server_vars
         .remove("maxplayers")
@@ -406,5 +526,286 @@ pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) ->
             .parse()
             .map_err(|e| TypeParse.context(e))?,
         unused_entries: server_vars,
+        latency: None,
+        players_and_teams_outcome,
     })
 }
+
+/// Async version of [GameSpy3].
+#[cfg(feature = "async")]
+pub(crate) struct AsyncGameSpy3 {
+    socket: AsyncUdpSocket,
+    payload: [u8; 4],
+    single_packets: bool,
+    retry_policy: RetryPolicy,
+}
+
+#[cfg(feature = "async")]
+impl AsyncGameSpy3 {
+    async fn new(
+        address: &SocketAddr,
+        timeout_settings: Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<Self> {
+        Self::new_custom(address, timeout_settings, scope, DEFAULT_PAYLOAD, false).await
+    }
+
+    pub(crate) async fn new_custom(
+        address: &SocketAddr,
+        timeout_settings: Option<TimeoutSettings>,
+        scope: &QueryScope,
+        payload: [u8; 4],
+        single_packets: bool,
+    ) -> GDResult<Self> {
+        let socket = AsyncUdpSocket::new(address, &timeout_settings, scope).await?;
+        let retry_policy = TimeoutSettings::get_retry_policy_or_default(&timeout_settings);
+
+        Ok(Self {
+            socket,
+            payload,
+            single_packets,
+            retry_policy,
+        })
+    }
+
+    async fn receive(&mut self, size: Option<usize>, kind: u8) -> GDResult<Vec<u8>> {
+        let received = self.socket.receive(size.or(Some(PACKET_SIZE))).await?;
+        check_response_header(&received, kind)
+    }
+
+    /// Async version of [GameSpy3::latency].
+    pub(crate) fn latency(&self) -> Option<Duration> { self.socket.latency() }
+
+    /// Fetch packets from server and store in buffer.
+    /// This function will retry fetch on timeouts.
+    pub(crate) async fn get_server_packets(&mut self) -> GDResult<ReceivedPackets> {
+        retry_on_timeout_async!(self.retry_policy, self.get_server_packets_impl())
+    }
+
+    /// Fetch packets from server and store in buffer (without retry logic).
+    async fn get_server_packets_impl(&mut self) -> GDResult<ReceivedPackets> {
+        let challenge = self
+            .make_initial_handshake()
+            .await
+            .with_stage("challenge")?;
+        self.get_data_packets(challenge).await.with_stage("data")
+    }
+
+    /// Async version of [GameSpy3::make_initial_handshake].
+    async fn make_initial_handshake(&mut self) -> GDResult<Option<i32>> {
+        self.socket.send(&handshake_request()).await?;
+
+        let data = self.receive(Some(16), 9).await?;
+        parse_challenge(&data).with_data(&data)
+    }
+
+    /// Async version of [GameSpy3::get_data_packets].
+    async fn get_data_packets(&mut self, challenge: Option<i32>) -> GDResult<ReceivedPackets> {
+        self.socket
+            .send(&data_request(challenge, self.payload))
+            .await?;
+
+        let mut packets = ServerPackets::new(self.single_packets);
+        loop {
+            match self.receive(None, 0).await {
+                Ok(data) if packets.parse_packet(&data).with_data(&data)? => return packets.finish(),
+                Ok(_) => {}
+                Err(e) => return packets.cut_short(e),
+            }
+        }
+    }
+}
+
+/// Async version of [query_vars].
+#[cfg(feature = "async")]
+pub async fn query_vars_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<HashMap<String, String>> {
+    let scope = QueryScope::start(&timeout_settings);
+    get_vars_async(address, timeout_settings, &scope)
+        .await
+        .with_address(*address)
+        .with_protocol("gamespy3")
+}
+
+/// The server's variables of [query_vars_async].
+#[cfg(feature = "async")]
+async fn get_vars_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<HashMap<String, String>> {
+    let mut client = AsyncGameSpy3::new(address, timeout_settings, scope).await?;
+    let received = client.get_server_packets().await?;
+
+    packets_to_vars(&received.packets).with_stage("data")
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Response> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    get_response_async(address, timeout_settings, scope)
+        .await
+        .with_address(*address)
+        .with_protocol("gamespy3")
+}
+
+#[cfg(feature = "async")]
+async fn get_response_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut client = AsyncGameSpy3::new(address, timeout_settings, scope).await?;
+    let received = client.get_server_packets().await?;
+
+    let mut response = parse_response(received).with_stage("data")?;
+    response.latency = client.latency();
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::types::SocketSettings;
+    use crate::transport::Scripted;
+    use std::sync::Arc;
+
+    /// A field of the players (or teams, with `_t`) and its values, in the
+    /// layout read by [parse_players_and_teams].
+    fn field(name: &str, values: &[&str]) -> Vec<u8> {
+        let mut data = vec![3, 0];
+        data.extend(name.as_bytes());
+        data.extend([0, 0]);
+        for value in values {
+            data.extend(value.as_bytes());
+            data.push(0);
+        }
+        data.push(0);
+
+        data
+    }
+
+    /// The server's variables and two players.
+    fn first_packet(scores: [&str; 2]) -> Vec<u8> {
+        let mut data = vec![0, 0, 0, 0, THIS_SESSION_ID as u8];
+        data.extend(b"splitnum\0\x00\x00");
+        data.extend(b"hostname\0Test\0mapname\0Bridge\0gametype\0ctf\0gamever\x001.0\0");
+        data.extend(b"maxplayers\x0016\0numplayers\x002\0password\x000\0\0");
+        data.extend(field("player_", &["Gordon", "Alyx"]));
+        data.extend(field("score_", &scores));
+        data.extend(field("ping_", &["20", "40"]));
+        data.extend(field("team_", &["0", "1"]));
+        data.extend(field("deaths_", &["1", "2"]));
+        data.extend(field("skill_", &["3", "4"]));
+
+        data
+    }
+
+    /// The (last) packet of the teams.
+    fn last_packet() -> Vec<u8> {
+        let mut data = vec![0, 0, 0, 0, THIS_SESSION_ID as u8];
+        data.extend(b"splitnum\0\x81\x00");
+        data.extend(field("team_t", &["Red"]));
+        data.extend(field("score_t", &["5"]));
+
+        data
+    }
+
+    /// Query a server answering the challenge and then with the packets,
+    /// letting the query deadline expire after them.
+    fn query_packets(packets: Vec<Vec<u8>>) -> GDResult<Response> {
+        let connector = Scripted::new()
+            .add_packet(vec![9, 0, 0, 0, THIS_SESSION_ID as u8, b'0', 0])
+            .add_packets(packets)
+            .set_final_error(DeadlineExceeded);
+        let scope = QueryScope::default().set_socket_settings(SocketSettings::new().set_connector(Arc::new(connector)));
+        query_with_scope(&"127.0.0.1:29900".parse().unwrap(), None, &scope)
+    }
+
+    #[test]
+    fn players_and_teams() {
+        let response = query_packets(vec![first_packet(["10", "20"]), last_packet()]).unwrap();
+
+        assert_eq!(response.name, "Test");
+        assert_eq!(response.players.len(), 2);
+        assert_eq!(response.players[1].name, "Alyx");
+        assert_eq!(response.players[1].score, 20);
+        assert_eq!(
+            response.teams,
+            [Team {
+                name: "Red".to_string(),
+                score: 5,
+            }]
+        );
+        assert_eq!(
+            response.players_and_teams_outcome,
+            SectionOutcome::Succeeded
+        );
+    }
+
+    #[test]
+    fn partial_players_and_teams() {
+        // The teams' packet doesn't arrive before the deadline.
+        let response = query_packets(vec![first_packet(["10", "20"])]).unwrap();
+
+        assert_eq!(response.name, "Test");
+        assert_eq!(response.players.len(), 2);
+        assert!(response.teams.is_empty());
+        assert!(matches!(
+            response.players_and_teams_outcome,
+            SectionOutcome::Failed {
+                kind: DeadlineExceeded,
+                ..
+            }
+        ));
+
+        // What was received can't be parsed, the server's variables are kept.
+        let response = query_packets(vec![first_packet(["10", "twenty"])]).unwrap();
+
+        assert_eq!(response.name, "Test");
+        assert!(response.players.is_empty());
+        assert!(response.players_and_teams_outcome.is_failed());
+    }
+
+    #[test]
+    fn unparsable_players() {
+        let error = query_packets(vec![first_packet(["10", "twenty"]), last_packet()]).unwrap_err();
+
+        assert_eq!(error.kind, TypeParse);
+        assert_eq!(error.context.stage, Some("data"));
+        assert_eq!(error.context.protocol, Some("gamespy3"));
+        assert_eq!(
+            error.context.address,
+            Some("127.0.0.1:29900".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn unanswered_challenge() {
+        let error = query_packets(Vec::new()).unwrap_err();
+
+        assert_eq!(error.kind, DeadlineExceeded);
+        assert_eq!(error.context.stage, Some("data"));
+
+        let scope =
+            QueryScope::default().set_socket_settings(SocketSettings::new().set_connector(Arc::new(Scripted::new())));
+        let error = query_with_scope(&"127.0.0.1:29900".parse().unwrap(), None, &scope).unwrap_err();
+
+        assert_eq!(error.context.stage, Some("challenge"));
+        assert_eq!(error.context.protocol, Some("gamespy3"));
+    }
+}
@@ -1,7 +1,17 @@
+use crate::protocols::gamespy::common::host_port;
 use crate::protocols::gamespy::{VersionedPlayer, VersionedResponse};
-use crate::protocols::types::{CommonPlayer, CommonResponse, GenericPlayer};
+use crate::protocols::types::{
+    sorted_rules,
+    CommonPlayer,
+    CommonResponse,
+    CommonTeamJson,
+    GenericPlayer,
+    SectionOutcome,
+};
 use crate::protocols::GenericResponse;
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 use std::collections::HashMap;
+use std::time::Duration;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -25,6 +35,9 @@ impl CommonPlayer for Player {
 
     fn name(&self) -> &str { &self.name }
     fn score(&self) -> Option<i32> { Some(self.score) }
+    fn team(&self) -> Option<u32> { Some(self.team.into()) }
+    fn ping(&self) -> Option<u32> { Some(self.ping.into()) }
+    fn deaths(&self) -> Option<u32> { Some(self.deaths) }
 }
 
 /// A team's details
@@ -51,10 +64,18 @@ pub struct Response {
     pub teams: Vec<Team>,
     pub tournament: bool,
     pub unused_entries: HashMap<String, String>,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
+    /// Whether all of the players and teams were received, if the query
+    /// deadline expired first they are the ones received (or left empty if
+    /// those can't be parsed).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub players_and_teams_outcome: SectionOutcome,
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> GenericResponse { GenericResponse::GameSpy(VersionedResponse::Three(self)) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn name(&self) -> Option<&str> { Some(&self.name) }
     fn map(&self) -> Option<&str> { Some(&self.map) }
@@ -63,6 +84,22 @@ impl CommonResponse for Response {
     fn game_version(&self) -> Option<&str> { Some(&self.game_version) }
     fn players_maximum(&self) -> u32 { self.players_maximum }
     fn players_online(&self) -> u32 { self.players_online }
+    fn rules(&self) -> Option<Vec<(&str, &str)>> { Some(sorted_rules(self.unused_entries.iter())) }
+    fn game_port(&self) -> Option<u16> { host_port(&self.unused_entries) }
+
+    fn teams(&self) -> Option<Vec<CommonTeamJson<'_>>> {
+        Some(
+            self.teams
+                .iter()
+                .map(|team| {
+                    CommonTeamJson {
+                        name: &team.name,
+                        score: Some(team.score),
+                    }
+                })
+                .collect(),
+        )
+    }
 
     fn players(&self) -> Option<Vec<&dyn CommonPlayer>> {
         Some(
@@ -73,3 +110,12 @@ impl CommonResponse for Response {
         )
     }
 }
+
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.name, Dialect::None, format);
+        for player in &mut self.players {
+            normalize_in_place(&mut player.name, Dialect::None, format);
+        }
+    }
+}
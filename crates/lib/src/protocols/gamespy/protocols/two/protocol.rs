@@ -1,17 +1,23 @@
 use crate::buffer::{Buffer, Utf8Decoder};
 use crate::protocols::gamespy::two::{Player, Response, Team};
-use crate::protocols::types::TimeoutSettings;
+use crate::protocols::types::{QueryScope, RetryPolicy, TimeoutSettings};
 use crate::socket::{Socket, UdpSocket};
 use crate::utils::retry_on_timeout;
 use crate::GDErrorKind::{PacketBad, TypeParse};
-use crate::{GDErrorKind, GDResult};
+use crate::{ErrorContextExt, GDErrorKind, GDResult};
 use byteorder::BigEndian;
 use std::collections::HashMap;
 use std::net::SocketAddr;
 
+#[cfg(feature = "async")]
+use crate::{
+    socket::{AsyncSocket, AsyncUdpSocket},
+    utils::retry_on_timeout_async,
+};
+
 struct GameSpy2 {
     socket: UdpSocket,
-    retry_count: usize,
+    retry_policy: RetryPolicy,
 }
 
 macro_rules! table_extract {
@@ -78,40 +84,47 @@ fn data_as_table(data: &mut Buffer<BigEndian>) -> GDResult<(HashMap<String, Vec<
 }
 
 impl GameSpy2 {
-    fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Self> {
-        let socket = UdpSocket::new(address, &timeout_settings)?;
-        let retry_count = TimeoutSettings::get_retries_or_default(&timeout_settings);
+    fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self> {
+        let socket = UdpSocket::new(address, &timeout_settings, scope)?;
+        let retry_policy = TimeoutSettings::get_retry_policy_or_default(&timeout_settings);
 
         Ok(Self {
             socket,
-            retry_count,
+            retry_policy,
         })
     }
 
     /// Send fetch request to server and store result in buffer.
     /// This function will retry fetch on timeouts.
     fn request_data(&mut self) -> GDResult<(Vec<u8>, usize)> {
-        retry_on_timeout(self.retry_count, move || self.request_data_impl())
+        retry_on_timeout(self.retry_policy, move || self.request_data_impl())
     }
 
     /// Send fetch request to server and store result in buffer (without retry
     /// logic).
     fn request_data_impl(&mut self) -> GDResult<(Vec<u8>, usize)> {
-        self.socket
-            .send(&[0xFE, 0xFD, 0x00, 0x00, 0x00, 0x00, 0x01, 0xFF, 0xFF, 0xFF])?;
+        self.socket.send(&REQUEST_PACKET)?;
 
         let received = self.socket.receive(None)?;
+        let buf_index = check_response_header(&received)?;
 
-        let mut buf = Buffer::<BigEndian>::new(&received);
-        if buf.read::<u8>()? != 0 || buf.read::<u32>()? != 1 {
-            return Err(PacketBad.into());
-        }
-
-        let buf_index = buf.current_position();
         Ok((received, buf_index))
     }
 }
 
+/// Request for the server's values, players and teams.
+const REQUEST_PACKET: [u8; 10] = [0xFE, 0xFD, 0x00, 0x00, 0x00, 0x00, 0x01, 0xFF, 0xFF, 0xFF];
+
+/// Validate the response header, returning the position the data starts at.
+fn check_response_header(received: &[u8]) -> GDResult<usize> {
+    let mut buf = Buffer::<BigEndian>::new(received);
+    if buf.read::<u8>()? != 0 || buf.read::<u32>()? != 1 {
+        return Err(PacketBad.into());
+    }
+
+    Ok(buf.current_position())
+}
+
 fn get_server_vars(bufferer: &mut Buffer<BigEndian>) -> GDResult<HashMap<String, String>> {
     let mut values = HashMap::new();
 
@@ -168,10 +181,38 @@ fn get_players(bufferer: &mut Buffer<BigEndian>) -> GDResult<Vec<Player>> {
 }
 
 pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Response> {
-    let mut client = GameSpy2::new(address, timeout_settings)?;
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, timeout_settings, &scope)
+}
+
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    get_response(address, timeout_settings, scope)
+        .with_address(*address)
+        .with_protocol("gamespy2")
+}
+
+fn get_response(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut client = GameSpy2::new(address, timeout_settings, scope)?;
     let (data, buf_index) = client.request_data()?;
 
-    let mut buffer = Buffer::<BigEndian>::new(&data);
+    let mut response = parse_response(&data, buf_index)?;
+    response.latency = client.socket.latency();
+
+    Ok(response)
+}
+
+/// Parse the response data, starting at `buf_index`.
+fn parse_response(data: &[u8], buf_index: usize) -> GDResult<Response> {
+    let mut buffer = Buffer::<BigEndian>::new(data);
     buffer.move_cursor(buf_index as isize)?;
 
     let mut server_vars = get_server_vars(&mut buffer)?;
@@ -206,5 +247,50 @@ pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) ->
         players_minimum,
         players,
         unused_entries: server_vars,
+        latency: None,
     })
 }
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Response> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    get_response_async(address, timeout_settings, scope)
+        .await
+        .with_address(*address)
+        .with_protocol("gamespy2")
+}
+
+#[cfg(feature = "async")]
+async fn get_response_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut socket = AsyncUdpSocket::new(address, &timeout_settings, scope).await?;
+    let retry_policy = TimeoutSettings::get_retry_policy_or_default(&timeout_settings);
+
+    let (data, buf_index) = retry_on_timeout_async!(retry_policy, async {
+        socket.send(&REQUEST_PACKET).await?;
+
+        let received = socket.receive(None).await?;
+        let buf_index = check_response_header(&received)?;
+
+        Ok::<_, crate::GDError>((received, buf_index))
+    })?;
+
+    let mut response = parse_response(&data, buf_index)?;
+    response.latency = socket.latency();
+
+    Ok(response)
+}
@@ -1,8 +1,11 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
+use crate::protocols::gamespy::common::host_port;
 use crate::protocols::gamespy::{VersionedPlayer, VersionedResponse};
-use crate::protocols::types::{CommonPlayer, CommonResponse, GenericPlayer};
+use crate::protocols::types::{sorted_rules, CommonPlayer, CommonResponse, CommonTeamJson, GenericPlayer};
 use crate::protocols::GenericResponse;
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -28,6 +31,8 @@ impl CommonPlayer for Player {
 
     fn name(&self) -> &str { &self.name }
     fn score(&self) -> Option<i32> { Some(self.score.into()) }
+    fn team(&self) -> Option<u32> { Some(self.team_index.into()) }
+    fn ping(&self) -> Option<u32> { Some(self.ping.into()) }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -42,16 +47,35 @@ pub struct Response {
     pub players_minimum: Option<u32>,
     pub players: Vec<Player>,
     pub unused_entries: HashMap<String, String>,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> GenericResponse { GenericResponse::GameSpy(VersionedResponse::Two(self)) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn name(&self) -> Option<&str> { Some(&self.name) }
     fn map(&self) -> Option<&str> { Some(&self.map) }
     fn has_password(&self) -> Option<bool> { Some(self.has_password) }
     fn players_maximum(&self) -> u32 { self.players_maximum }
     fn players_online(&self) -> u32 { self.players_online }
+    fn rules(&self) -> Option<Vec<(&str, &str)>> { Some(sorted_rules(self.unused_entries.iter())) }
+    fn game_port(&self) -> Option<u16> { host_port(&self.unused_entries) }
+
+    fn teams(&self) -> Option<Vec<CommonTeamJson<'_>>> {
+        Some(
+            self.teams
+                .iter()
+                .map(|team| {
+                    CommonTeamJson {
+                        name: &team.name,
+                        score: Some(team.score.into()),
+                    }
+                })
+                .collect(),
+        )
+    }
 
     fn players(&self) -> Option<Vec<&dyn CommonPlayer>> {
         Some(
@@ -62,3 +86,12 @@ impl CommonResponse for Response {
         )
     }
 }
+
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.name, Dialect::None, format);
+        for player in &mut self.players {
+            normalize_in_place(&mut player.name, Dialect::None, format);
+        }
+    }
+}
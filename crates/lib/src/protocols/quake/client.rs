@@ -2,14 +2,21 @@ use byteorder::LittleEndian;
 
 use crate::buffer::{Buffer, Utf8Decoder};
 use crate::protocols::quake::types::Response;
-use crate::protocols::types::TimeoutSettings;
+use crate::protocols::types::{QueryScope, TimeoutSettings};
 use crate::socket::{Socket, UdpSocket};
 use crate::utils::retry_on_timeout;
 use crate::GDErrorKind::{PacketBad, TypeParse};
-use crate::{GDErrorKind, GDResult};
+use crate::{ErrorContextExt, GDErrorKind, GDResult};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::slice::Iter;
+use std::time::Duration;
+
+#[cfg(feature = "async")]
+use crate::{
+    socket::{AsyncSocket, AsyncUdpSocket},
+    utils::retry_on_timeout_async,
+};
 
 pub trait QuakeClient {
     type Player;
@@ -19,32 +26,42 @@ pub trait QuakeClient {
     fn parse_player_string(data: Iter<&str>) -> GDResult<Self::Player>;
 }
 
-/// Send request and return result buffer.
-/// This function will retry fetch on timeouts.
+/// Send request and return result buffer, alongside the latency of the
+/// exchange. This function will retry fetch on timeouts.
 fn get_data<Client: QuakeClient>(
     address: &SocketAddr,
     timeout_settings: &Option<TimeoutSettings>,
-) -> GDResult<Vec<u8>> {
-    let mut socket = UdpSocket::new(address, timeout_settings)?;
-    retry_on_timeout(
-        TimeoutSettings::get_retries_or_default(timeout_settings),
-        move || get_data_impl::<Client>(&mut socket),
-    )
+    scope: &QueryScope,
+) -> GDResult<(Vec<u8>, Option<Duration>)> {
+    let mut socket = UdpSocket::new(address, timeout_settings, scope)?;
+    let data = retry_on_timeout(
+        TimeoutSettings::get_retry_policy_or_default(timeout_settings),
+        || get_data_impl::<Client>(&mut socket),
+    )?;
+
+    Ok((data, socket.latency()))
 }
 
 /// Send request and return result buffer (without retry logic).
 fn get_data_impl<Client: QuakeClient>(socket: &mut UdpSocket) -> GDResult<Vec<u8>> {
-    socket.send(
-        &[
-            &[0xFF, 0xFF, 0xFF, 0xFF],
-            Client::get_send_header().as_bytes(),
-            &[0x00],
-        ]
-        .concat(),
-    )?;
+    socket.send(&request_packet::<Client>())?;
 
     let data = socket.receive(None)?;
-    let mut bufferer = Buffer::<LittleEndian>::new(&data);
+    strip_response_header::<Client>(&data)
+}
+
+fn request_packet<Client: QuakeClient>() -> Vec<u8> {
+    [
+        &[0xFF, 0xFF, 0xFF, 0xFF],
+        Client::get_send_header().as_bytes(),
+        &[0x00],
+    ]
+    .concat()
+}
+
+/// Check the response header, returning the data after it.
+fn strip_response_header<Client: QuakeClient>(data: &[u8]) -> GDResult<Vec<u8>> {
+    let mut bufferer = Buffer::<LittleEndian>::new(data);
 
     if bufferer.read::<u32>()? != u32::MAX {
         return Err(PacketBad.context("Expected 4294967295"));
@@ -106,9 +123,18 @@ fn get_players<Client: QuakeClient>(bufferer: &mut Buffer<LittleEndian>) -> GDRe
 pub fn client_query<Client: QuakeClient>(
     address: &SocketAddr,
     timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
 ) -> GDResult<Response<Client::Player>> {
-    let data = get_data::<Client>(address, &timeout_settings)?;
-    let mut bufferer = Buffer::<LittleEndian>::new(&data);
+    let (data, latency) = get_data::<Client>(address, &timeout_settings, scope)?;
+
+    let mut response = parse_response::<Client>(&data).with_data(&data)?;
+    response.latency = latency;
+
+    Ok(response)
+}
+
+fn parse_response<Client: QuakeClient>(data: &[u8]) -> GDResult<Response<Client::Player>> {
+    let mut bufferer = Buffer::<LittleEndian>::new(data);
 
     let mut server_vars = get_server_values(&mut bufferer)?;
     let players = get_players::<Client>(&mut bufferer)?;
@@ -134,9 +160,34 @@ pub fn client_query<Client: QuakeClient>(
             .remove("version")
             .or_else(|| server_vars.remove("*version")),
         unused_entries: server_vars,
+        latency: None,
     })
 }
 
+/// Async version of [client_query].
+#[cfg(feature = "async")]
+pub async fn client_query_async<Client: QuakeClient>(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response<Client::Player>> {
+    let mut socket = AsyncUdpSocket::new(address, &timeout_settings, scope).await?;
+    let data = retry_on_timeout_async!(
+        TimeoutSettings::get_retry_policy_or_default(&timeout_settings),
+        async {
+            socket.send(&request_packet::<Client>()).await?;
+
+            let data = socket.receive(None).await?;
+            strip_response_header::<Client>(&data)
+        }
+    )?;
+
+    let mut response = parse_response::<Client>(&data).with_data(&data)?;
+    response.latency = socket.latency();
+
+    Ok(response)
+}
+
 pub fn remove_wrapping_quotes<'a>(string: &&'a str) -> &'a str {
     match string.starts_with('\"') && string.ends_with('\"') {
         false => string,
@@ -1,12 +1,14 @@
 use crate::protocols::quake::client::{client_query, remove_wrapping_quotes, QuakeClient};
 use crate::protocols::quake::Response;
-use crate::protocols::types::{CommonPlayer, GenericPlayer, TimeoutSettings};
+use crate::protocols::types::{CommonPlayer, GenericPlayer, QueryScope, TimeoutSettings};
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 use crate::GDErrorKind::TypeParse;
-use crate::{GDErrorKind, GDResult};
+use crate::{ErrorContextExt, GDErrorKind, GDResult};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use std::slice::Iter;
+use std::time::Duration;
 
 use super::QuakePlayerType;
 
@@ -17,6 +19,7 @@ pub struct Player {
     /// Player's server id.
     pub id: u8,
     pub score: u16,
+    /// Time connected, in minutes.
     pub time: u16,
     pub ping: u16,
     pub name: String,
@@ -34,6 +37,12 @@ impl CommonPlayer for Player {
 
     fn name(&self) -> &str { &self.name }
     fn score(&self) -> Option<i32> { Some(self.score.into()) }
+    fn ping(&self) -> Option<u32> { Some(self.ping.into()) }
+    fn time_connected(&self) -> Option<Duration> { Some(Duration::from_secs(u64::from(self.time) * 60)) }
+}
+
+impl NormalizeText for Player {
+    fn normalize_text(&mut self, format: TextFormat) { normalize_in_place(&mut self.name, Dialect::Quake, format); }
 }
 
 pub(crate) struct QuakeOne;
@@ -83,5 +92,40 @@ impl QuakeClient for QuakeOne {
 }
 
 pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Response<Player>> {
-    client_query::<QuakeOne>(address, timeout_settings)
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, timeout_settings, &scope)
+}
+
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response<Player>> {
+    client_query::<QuakeOne>(address, timeout_settings, scope)
+        .with_address(*address)
+        .with_protocol("quake1")
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response<Player>> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response<Player>> {
+    crate::protocols::quake::client::client_query_async::<QuakeOne>(address, timeout_settings, scope)
+        .await
+        .with_address(*address)
+        .with_protocol("quake1")
 }
@@ -1,8 +1,8 @@
 use crate::protocols::quake::client::{client_query, QuakeClient};
 use crate::protocols::quake::two::QuakeTwo;
 use crate::protocols::quake::Response;
-use crate::protocols::types::TimeoutSettings;
-use crate::GDResult;
+use crate::protocols::types::{QueryScope, TimeoutSettings};
+use crate::{ErrorContextExt, GDResult};
 use std::net::SocketAddr;
 use std::slice::Iter;
 
@@ -20,5 +20,40 @@ impl QuakeClient for QuakeThree {
 }
 
 pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Response<Player>> {
-    client_query::<QuakeThree>(address, timeout_settings)
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, timeout_settings, &scope)
+}
+
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response<Player>> {
+    client_query::<QuakeThree>(address, timeout_settings, scope)
+        .with_address(*address)
+        .with_protocol("quake3")
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response<Player>> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response<Player>> {
+    crate::protocols::quake::client::client_query_async::<QuakeThree>(address, timeout_settings, scope)
+        .await
+        .with_address(*address)
+        .with_protocol("quake3")
 }
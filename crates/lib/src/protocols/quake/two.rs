@@ -1,9 +1,10 @@
 use crate::protocols::quake::client::{client_query, remove_wrapping_quotes, QuakeClient};
 use crate::protocols::quake::one::QuakeOne;
 use crate::protocols::quake::Response;
-use crate::protocols::types::{CommonPlayer, GenericPlayer, TimeoutSettings};
+use crate::protocols::types::{CommonPlayer, GenericPlayer, QueryScope, TimeoutSettings};
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 use crate::GDErrorKind::TypeParse;
-use crate::{GDErrorKind, GDResult};
+use crate::{ErrorContextExt, GDErrorKind, GDResult};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
@@ -33,6 +34,12 @@ impl CommonPlayer for Player {
     fn name(&self) -> &str { &self.name }
 
     fn score(&self) -> Option<i32> { Some(self.score) }
+
+    fn ping(&self) -> Option<u32> { Some(self.ping.into()) }
+}
+
+impl NormalizeText for Player {
+    fn normalize_text(&mut self, format: TextFormat) { normalize_in_place(&mut self.name, Dialect::Quake, format); }
 }
 
 pub(crate) struct QuakeTwo;
@@ -63,5 +70,40 @@ impl QuakeClient for QuakeTwo {
 }
 
 pub fn query(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Response<Player>> {
-    client_query::<QuakeTwo>(address, timeout_settings)
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, timeout_settings, &scope)
+}
+
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response<Player>> {
+    client_query::<QuakeTwo>(address, timeout_settings, scope)
+        .with_address(*address)
+        .with_protocol("quake2")
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response<Player>> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &SocketAddr,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response<Player>> {
+    crate::protocols::quake::client::client_query_async::<QuakeTwo>(address, timeout_settings, scope)
+        .await
+        .with_address(*address)
+        .with_protocol("quake2")
 }
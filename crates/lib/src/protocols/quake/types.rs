@@ -1,11 +1,13 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 
 use crate::protocols::{
-    types::{CommonPlayer, CommonResponse},
+    types::{sorted_rules, CommonPlayer, CommonResponse},
     GenericResponse,
 };
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 
 /// General server information's.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -25,20 +27,24 @@ pub struct Response<P> {
     pub game_version: Option<String>,
     /// Other server entries that weren't used.
     pub unused_entries: HashMap<String, String>,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
 }
 
-pub trait QuakePlayerType: Sized + CommonPlayer {
+pub trait QuakePlayerType: Sized + CommonPlayer + NormalizeText {
     fn version(response: &Response<Self>) -> VersionedResponse;
 }
 
 impl<P: QuakePlayerType> CommonResponse for Response<P> {
     fn as_original(&self) -> GenericResponse { GenericResponse::Quake(P::version(self)) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn name(&self) -> Option<&str> { Some(&self.name) }
     fn game_version(&self) -> Option<&str> { self.game_version.as_deref() }
     fn map(&self) -> Option<&str> { Some(&self.map) }
     fn players_maximum(&self) -> u32 { self.players_maximum.into() }
     fn players_online(&self) -> u32 { self.players_online.into() }
+    fn rules(&self) -> Option<Vec<(&str, &str)>> { Some(sorted_rules(self.unused_entries.iter())) }
 
     fn players(&self) -> Option<Vec<&dyn CommonPlayer>> {
         Some(
@@ -50,6 +56,15 @@ impl<P: QuakePlayerType> CommonResponse for Response<P> {
     }
 }
 
+impl<P: QuakePlayerType> NormalizeText for Response<P> {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.name, Dialect::Quake, format);
+        for player in &mut self.players {
+            player.normalize_text(format);
+        }
+    }
+}
+
 /// Versioned response type
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
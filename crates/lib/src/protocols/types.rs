@@ -3,10 +3,14 @@ use crate::games::minecraft;
 #[cfg(feature = "tls")]
 use crate::protocols::epic;
 use crate::protocols::{gamespy, quake, unreal2, valve};
-use crate::GDErrorKind::InvalidInput;
-use crate::GDResult;
+use crate::text::TextFormat;
+use crate::transport::Connector;
+use crate::GDErrorKind::{DeadlineExceeded, InvalidInput};
+use crate::{GDError, GDErrorKind, GDResult};
 
-use std::time::Duration;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -41,6 +45,39 @@ pub enum Protocol {
     PROPRIETARY(ProprietaryProtocol),
 }
 
+impl Protocol {
+    /// The name of the protocol, as used in the [context](crate::ErrorContext)
+    /// of errors.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Gamespy(gamespy::GameSpyVersion::One) => "gamespy1",
+            Self::Gamespy(gamespy::GameSpyVersion::Two) => "gamespy2",
+            Self::Gamespy(gamespy::GameSpyVersion::Three) => "gamespy3",
+            Self::Quake(quake::QuakeVersion::One) => "quake1",
+            Self::Quake(quake::QuakeVersion::Two) => "quake2",
+            Self::Quake(quake::QuakeVersion::Three) => "quake3",
+            Self::Valve(_) => "valve",
+            Self::Unreal2 => "unreal2",
+            #[cfg(feature = "tls")]
+            Self::Epic(_) => "epic",
+            #[cfg(feature = "games")]
+            Self::PROPRIETARY(protocol) => {
+                match protocol {
+                    ProprietaryProtocol::TheShip => "theship",
+                    ProprietaryProtocol::Minecraft(_) => "minecraft",
+                    ProprietaryProtocol::FFOW => "ffow",
+                    ProprietaryProtocol::JC2M => "jc2m",
+                    ProprietaryProtocol::Savage2 => "savage2",
+                    ProprietaryProtocol::Eco => "eco",
+                    ProprietaryProtocol::Mindustry => "mindustry",
+                    #[cfg(all(feature = "services", feature = "tls", feature = "serde"))]
+                    ProprietaryProtocol::Minetest => "minetest",
+                }
+            }
+        }
+    }
+}
+
 /// All response types
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Debug, Clone, PartialEq)]
@@ -121,8 +158,19 @@ pub trait CommonResponse {
             players: self
                 .players()
                 .map(|players| players.iter().map(|p| p.as_json()).collect()),
+            latency: self.latency(),
+            rules: self.rules(),
+            teams: self.teams(),
+            tags: self.tags(),
+            is_secured: self.is_secured(),
+            server_os: self.server_os(),
+            game_port: self.game_port(),
+            query_port: self.query_port(),
         }
     }
+    /// Get an owned copy of the [JSON representation](CommonResponse::as_json)
+    /// that can be stored and loaded back (you don't need to override this)
+    fn as_owned(&self) -> CommonResponseOwned { self.as_json().into() }
 
     /// The name of the server
     fn name(&self) -> Option<&str> { None }
@@ -144,6 +192,32 @@ pub trait CommonResponse {
     fn has_password(&self) -> Option<bool> { None }
     /// Currently connected players
     fn players(&self) -> Option<Vec<&dyn CommonPlayer>> { None }
+    /// Round-trip time of the first request/response exchange with the server
+    fn latency(&self) -> Option<Duration> { None }
+    /// Server rules (or variables), as key/value pairs sorted by key
+    fn rules(&self) -> Option<Vec<(&str, &str)>> { None }
+    /// Teams and their scores
+    fn teams(&self) -> Option<Vec<CommonTeamJson<'_>>> { None }
+    /// Tags (or keywords) the server describes itself with
+    fn tags(&self) -> Option<Vec<&str>> { None }
+    /// Whether the server is protected by an anti-cheat (such as VAC)
+    fn is_secured(&self) -> Option<bool> { None }
+    /// The operating system the server is running on
+    fn server_os(&self) -> Option<&str> { None }
+    /// The port players connect to, if it was reported
+    fn game_port(&self) -> Option<u16> { None }
+    /// The port the server was queried on (known to the generic query
+    /// functions)
+    fn query_port(&self) -> Option<u16> { None }
+}
+
+/// Sort rules by key (and then value), for a stable generic representation.
+pub(crate) fn sorted_rules<'a>(rules: impl Iterator<Item = (&'a String, &'a String)>) -> Vec<(&'a str, &'a str)> {
+    let mut rules: Vec<_> = rules
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+    rules.sort_unstable();
+    rules
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -159,6 +233,128 @@ pub struct CommonResponseJson<'a> {
     pub players_bots: Option<u32>,
     pub has_password: Option<bool>,
     pub players: Option<Vec<CommonPlayerJson<'a>>>,
+    pub latency: Option<Duration>,
+    pub rules: Option<Vec<(&'a str, &'a str)>>,
+    pub teams: Option<Vec<CommonTeamJson<'a>>>,
+    pub tags: Option<Vec<&'a str>>,
+    pub is_secured: Option<bool>,
+    pub server_os: Option<&'a str>,
+    pub game_port: Option<u16>,
+    pub query_port: Option<u16>,
+}
+
+/// An owned version of [CommonResponseJson], it doesn't borrow from the
+/// original response so it can be cached, sent elsewhere or stored and
+/// deserialized back later.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct CommonResponseOwned {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub game_mode: Option<String>,
+    pub game_version: Option<String>,
+    pub map: Option<String>,
+    pub players_maximum: u32,
+    pub players_online: u32,
+    pub players_bots: Option<u32>,
+    pub has_password: Option<bool>,
+    pub players: Option<Vec<CommonPlayerOwned>>,
+    pub latency: Option<Duration>,
+    pub rules: Option<Vec<(String, String)>>,
+    pub teams: Option<Vec<CommonTeamOwned>>,
+    pub tags: Option<Vec<String>>,
+    pub is_secured: Option<bool>,
+    pub server_os: Option<String>,
+    pub game_port: Option<u16>,
+    pub query_port: Option<u16>,
+}
+
+impl CommonResponseOwned {
+    /// Get the borrowed [JSON representation](CommonResponseJson) back.
+    pub fn as_json(&self) -> CommonResponseJson<'_> {
+        CommonResponseJson {
+            name: self.name.as_deref(),
+            description: self.description.as_deref(),
+            game_mode: self.game_mode.as_deref(),
+            game_version: self.game_version.as_deref(),
+            map: self.map.as_deref(),
+            players_maximum: self.players_maximum,
+            players_online: self.players_online,
+            players_bots: self.players_bots,
+            has_password: self.has_password,
+            players: self
+                .players
+                .as_ref()
+                .map(|players| players.iter().map(CommonPlayerOwned::as_json).collect()),
+            latency: self.latency,
+            rules: self.rules.as_ref().map(|rules| {
+                rules
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.as_str()))
+                    .collect()
+            }),
+            teams: self
+                .teams
+                .as_ref()
+                .map(|teams| teams.iter().map(CommonTeamOwned::as_json).collect()),
+            tags: self
+                .tags
+                .as_ref()
+                .map(|tags| tags.iter().map(String::as_str).collect()),
+            is_secured: self.is_secured,
+            server_os: self.server_os.as_deref(),
+            game_port: self.game_port,
+            query_port: self.query_port,
+        }
+    }
+}
+
+impl From<CommonResponseJson<'_>> for CommonResponseOwned {
+    fn from(json: CommonResponseJson<'_>) -> Self {
+        Self {
+            name: json.name.map(str::to_string),
+            description: json.description.map(str::to_string),
+            game_mode: json.game_mode.map(str::to_string),
+            game_version: json.game_version.map(str::to_string),
+            map: json.map.map(str::to_string),
+            players_maximum: json.players_maximum,
+            players_online: json.players_online,
+            players_bots: json.players_bots,
+            has_password: json.has_password,
+            players: json
+                .players
+                .map(|players| players.into_iter().map(CommonPlayerOwned::from).collect()),
+            latency: json.latency,
+            rules: json.rules.map(|rules| {
+                rules
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect()
+            }),
+            teams: json
+                .teams
+                .map(|teams| teams.into_iter().map(CommonTeamOwned::from).collect()),
+            tags: json
+                .tags
+                .map(|tags| tags.into_iter().map(str::to_string).collect()),
+            is_secured: json.is_secured,
+            server_os: json.server_os.map(str::to_string),
+            game_port: json.game_port,
+            query_port: json.query_port,
+        }
+    }
+}
+
+impl From<&dyn CommonResponse> for CommonResponseOwned {
+    fn from(response: &dyn CommonResponse) -> Self { response.as_owned() }
+}
+
+impl From<Box<dyn CommonResponse>> for CommonResponseOwned {
+    fn from(response: Box<dyn CommonResponse>) -> Self { response.as_owned() }
+}
+
+impl From<Box<dyn CommonResponse + Send + Sync>> for CommonResponseOwned {
+    fn from(response: Box<dyn CommonResponse + Send + Sync>) -> Self { response.as_owned() }
 }
 
 pub trait CommonPlayer {
@@ -170,13 +366,56 @@ pub trait CommonPlayer {
         CommonPlayerJson {
             name: self.name(),
             score: self.score(),
+            team: self.team(),
+            ping: self.ping(),
+            time_connected: self.time_connected(),
+            deaths: self.deaths(),
+            is_bot: self.is_bot(),
+            kind: self.kind(),
         }
     }
+    /// Get an owned copy of the [JSON representation](CommonPlayer::as_json)
+    /// (you don't need to override this)
+    fn as_owned(&self) -> CommonPlayerOwned { self.as_json().into() }
 
     /// Player name
     fn name(&self) -> &str;
     /// Player score
     fn score(&self) -> Option<i32> { None }
+    /// Index (or id) of the player's team
+    fn team(&self) -> Option<u32> { None }
+    /// Player ping (in milliseconds), from the server's perspective
+    fn ping(&self) -> Option<u32> { None }
+    /// How long the player has been connected for
+    fn time_connected(&self) -> Option<Duration> { None }
+    /// Number of deaths
+    fn deaths(&self) -> Option<u32> { None }
+    /// Whether the player is a bot
+    fn is_bot(&self) -> Option<bool> { None }
+    /// What the player entry is (derived from [is_bot](CommonPlayer::is_bot)
+    /// unless the protocol tells more)
+    fn kind(&self) -> Option<PlayerKind> {
+        self.is_bot().map(|is_bot| {
+            match is_bot {
+                true => PlayerKind::Bot,
+                false => PlayerKind::Player,
+            }
+        })
+    }
+}
+
+/// What an entry of a server's players list is.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum PlayerKind {
+    /// A (human) player.
+    #[default]
+    Player,
+    Bot,
+    /// A spectator relay listed as a player, such as SourceTV.
+    Relay,
+    /// An entry without a name (a player still connecting or a placeholder).
+    Unnamed,
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -184,12 +423,113 @@ pub trait CommonPlayer {
 pub struct CommonPlayerJson<'a> {
     pub name: &'a str,
     pub score: Option<i32>,
+    pub team: Option<u32>,
+    pub ping: Option<u32>,
+    pub time_connected: Option<Duration>,
+    pub deaths: Option<u32>,
+    pub is_bot: Option<bool>,
+    pub kind: Option<PlayerKind>,
 }
 
+/// An owned version of [CommonPlayerJson].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct CommonPlayerOwned {
+    pub name: String,
+    pub score: Option<i32>,
+    pub team: Option<u32>,
+    pub ping: Option<u32>,
+    pub time_connected: Option<Duration>,
+    pub deaths: Option<u32>,
+    pub is_bot: Option<bool>,
+    pub kind: Option<PlayerKind>,
+}
+
+impl CommonPlayerOwned {
+    /// Get the borrowed [JSON representation](CommonPlayerJson) back.
+    pub fn as_json(&self) -> CommonPlayerJson<'_> {
+        CommonPlayerJson {
+            name: &self.name,
+            score: self.score,
+            team: self.team,
+            ping: self.ping,
+            time_connected: self.time_connected,
+            deaths: self.deaths,
+            is_bot: self.is_bot,
+            kind: self.kind,
+        }
+    }
+}
+
+impl From<CommonPlayerJson<'_>> for CommonPlayerOwned {
+    fn from(json: CommonPlayerJson<'_>) -> Self {
+        Self {
+            name: json.name.to_string(),
+            score: json.score,
+            team: json.team,
+            ping: json.ping,
+            time_connected: json.time_connected,
+            deaths: json.deaths,
+            is_bot: json.is_bot,
+            kind: json.kind,
+        }
+    }
+}
+
+/// A team, as given by [CommonResponse::teams].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CommonTeamJson<'a> {
+    pub name: &'a str,
+    pub score: Option<i32>,
+}
+
+/// An owned version of [CommonTeamJson].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct CommonTeamOwned {
+    pub name: String,
+    pub score: Option<i32>,
+}
+
+impl CommonTeamOwned {
+    /// Get the borrowed [JSON representation](CommonTeamJson) back.
+    pub fn as_json(&self) -> CommonTeamJson<'_> {
+        CommonTeamJson {
+            name: &self.name,
+            score: self.score,
+        }
+    }
+}
+
+impl From<CommonTeamJson<'_>> for CommonTeamOwned {
+    fn from(json: CommonTeamJson<'_>) -> Self {
+        Self {
+            name: json.name.to_string(),
+            score: json.score,
+        }
+    }
+}
+
+/// Parse a duration given in seconds, or with a `ms`, `s` or `m` unit suffix.
 #[cfg(feature = "clap")]
-fn parse_duration_secs(value: &str) -> Result<Duration, std::num::ParseIntError> {
-    let secs = value.parse()?;
-    Ok(Duration::from_secs(secs))
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (amount, to_duration): (_, fn(u64) -> Duration) = if let Some(millis) = value.strip_suffix("ms") {
+        (millis, Duration::from_millis)
+    } else if let Some(mins) = value.strip_suffix('m') {
+        (mins, |mins| Duration::from_secs(mins.saturating_mul(60)))
+    } else {
+        (
+            value.strip_suffix('s').unwrap_or(value),
+            Duration::from_secs,
+        )
+    };
+
+    amount
+        .trim()
+        .parse()
+        .map(to_duration)
+        .map_err(|e| format!("{e} (expected a number of seconds, or one followed by ms, s or m)"))
 }
 
 /// Timeout settings for socket operations
@@ -197,15 +537,426 @@ fn parse_duration_secs(value: &str) -> Result<Duration, std::num::ParseIntError>
 #[cfg_attr(feature = "clap", derive(clap::Args))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct TimeoutSettings {
-    #[cfg_attr(feature = "clap", arg(long = "connect-timeout", value_parser = parse_duration_secs, help = "Socket connect timeout (in seconds)", default_value = "4"))]
+    #[cfg_attr(feature = "clap", arg(long = "connect-timeout", value_parser = parse_duration, help = "Socket connect timeout (in seconds, or with a ms, s or m suffix)", default_value = "4"))]
     connect: Option<Duration>,
-    #[cfg_attr(feature = "clap", arg(long = "read-timeout", value_parser = parse_duration_secs, help = "Socket read timeout (in seconds)", default_value = "4"))]
+    #[cfg_attr(feature = "clap", arg(long = "read-timeout", value_parser = parse_duration, help = "Socket read timeout (in seconds, or with a ms, s or m suffix)", default_value = "4"))]
     read: Option<Duration>,
-    #[cfg_attr(feature = "clap", arg(long = "write-timeout", value_parser = parse_duration_secs, help = "Socket write timeout (in seconds)", default_value = "4"))]
+    #[cfg_attr(feature = "clap", arg(long = "write-timeout", value_parser = parse_duration, help = "Socket write timeout (in seconds, or with a ms, s or m suffix)", default_value = "4"))]
     write: Option<Duration>,
+    /// Total time a query may take
+    #[cfg_attr(feature = "clap", arg(long = "query-deadline", value_parser = parse_duration, help = "Total time a query may take, across all its requests and retries (in seconds, or with a ms, s or m suffix)"))]
+    query_deadline: Option<Duration>,
+    /// How failed requests are retried
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    #[cfg_attr(feature = "clap", command(flatten))]
+    retry_policy: RetryPolicy,
+}
+
+/// Settings of the sockets a query is sent through: the local address and
+/// ports they are bound to, a SOCKS5 proxy or a [Connector] creating them
+/// instead of the built-in implementation.
+///
+/// Given to the generic query functions (e.g.
+/// [query_with_socket_settings](crate::games::query_with_socket_settings))
+/// alongside the [TimeoutSettings], the protocol specific functions use the
+/// default ones.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "clap", derive(clap::Args))]
+#[derive(Clone, Default)]
+pub struct SocketSettings {
+    /// Local address to send the queries from
+    #[cfg_attr(
+        feature = "clap",
+        arg(long = "bind-address", help = "Local address to send queries from")
+    )]
+    bind_address: Option<IpAddr>,
+    /// Local ports to send the queries from
+    #[cfg_attr(feature = "clap", arg(long = "bind-ports", value_parser = parse_port_range, help = "Local port (or range, e.g. 27000-27100) to send queries from"))]
+    bind_ports: Option<PortRange>,
+    /// SOCKS5 proxy to route the queries through
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long,
+            help = "SOCKS5 proxy (ip:port, without authentication) to route queries through"
+        )
+    )]
+    proxy: Option<SocketAddr>,
+    /// Creates the sockets instead of the built-in implementation
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "clap", arg(skip))]
+    connector: Option<Arc<dyn Connector>>,
+}
+
+impl SocketSettings {
+    /// Settings of the built-in sockets, bound to an ephemeral port on the
+    /// unspecified address and without a proxy.
+    pub fn new() -> Self { Self::default() }
+
+    /// Set the local address sockets are bound to, it must be of the same
+    /// family as the servers that are queried.
+    ///
+    /// By default the unspecified address of the server's family is used
+    /// (`0.0.0.0` or `[::]`).
+    pub fn set_bind_address(mut self, bind_address: IpAddr) -> Self {
+        self.bind_address = Some(bind_address);
+        self
+    }
+
+    /// Set the local ports sockets are bound to, the first free one is used.
+    ///
+    /// By default the system picks an ephemeral port.
+    pub fn set_bind_ports(mut self, bind_ports: PortRange) -> Self {
+        self.bind_ports = Some(bind_ports);
+        self
+    }
+
+    /// Set a SOCKS5 proxy (without authentication) to route the queries
+    /// through: TCP connections use CONNECT, UDP datagrams are relayed using
+    /// UDP ASSOCIATE and the HTTP client is configured to use it too.
+    ///
+    /// The bind settings then apply to the connections to the proxy.
+    pub fn set_proxy(mut self, proxy: SocketAddr) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Set a [Connector] that creates the UDP and TCP sockets of the queries,
+    /// see the [transport](crate::transport) module.
+    ///
+    /// The bind and proxy settings are then up to the connector. The HTTP
+    /// based queries don't use it.
+    pub fn set_connector(mut self, connector: Arc<dyn Connector>) -> Self {
+        self.connector = Some(connector);
+        self
+    }
+
+    /// Get the local bind address.
+    pub const fn get_bind_address(&self) -> Option<IpAddr> { self.bind_address }
+
+    /// Get the local bind ports.
+    pub const fn get_bind_ports(&self) -> Option<PortRange> { self.bind_ports }
+
+    /// Get the SOCKS5 proxy.
+    pub const fn get_proxy(&self) -> Option<SocketAddr> { self.proxy }
+
+    /// Get the connector.
+    pub const fn get_connector(&self) -> Option<&Arc<dyn Connector>> { self.connector.as_ref() }
+}
+
+impl std::fmt::Debug for SocketSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SocketSettings")
+            .field("bind_address", &self.bind_address)
+            .field("bind_ports", &self.bind_ports)
+            .field("proxy", &self.proxy)
+            .field("connector", &self.connector.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+#[cfg(feature = "clap")]
+fn parse_port_range(value: &str) -> Result<PortRange, String> {
+    let (start, end) = value.split_once('-').unwrap_or((value, value));
+    let parse = |port: &str| port.trim().parse::<u16>().map_err(|e| e.to_string());
+
+    PortRange::new(parse(start)?, parse(end)?).map_err(|_| format!("Invalid port range {value:?}"))
+}
+
+/// An inclusive range of local ports to bind sockets to.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "PortRangeFields"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PortRange {
+    start: u16,
+    end: u16,
+}
+
+/// The fields of a deserialized [PortRange], checked by [PortRange::new].
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct PortRangeFields {
+    start: u16,
+    end: u16,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<PortRangeFields> for PortRange {
+    type Error = crate::GDError;
+
+    fn try_from(fields: PortRangeFields) -> GDResult<Self> { Self::new(fields.start, fields.end) }
+}
+
+impl PortRange {
+    /// Construct a new range, the ports must not be 0 and `start` must not be
+    /// greater than `end` or GDErrorKind::[InvalidInput] is thrown.
+    pub fn new(start: u16, end: u16) -> GDResult<Self> {
+        if start == 0 || start > end {
+            return Err(InvalidInput.context(format!("Invalid port range {start}-{end}")));
+        }
+
+        Ok(Self { start, end })
+    }
+
+    /// Get the first port of the range.
+    pub const fn get_start(&self) -> u16 { self.start }
+
+    /// Get the last port of the range.
+    pub const fn get_end(&self) -> u16 { self.end }
+
+    /// Iterate over the ports of the range.
+    pub fn iter(&self) -> impl Iterator<Item = u16> { self.start ..= self.end }
+}
+
+/// The error kinds retried by default.
+const DEFAULT_RETRYABLE: RetryableKinds = RetryableKinds::new(&[GDErrorKind::PacketReceive, GDErrorKind::PacketSend]);
+
+// Each kind takes a bit of the set.
+const _: () = assert!(GDErrorKind::ALL.len() <= u32::BITS as usize);
+
+/// A set of error kinds, (de)serialized as a list of them.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "Vec<GDErrorKind>", into = "Vec<GDErrorKind>")
+)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct RetryableKinds(u32);
+
+impl RetryableKinds {
+    const fn new(kinds: &[GDErrorKind]) -> Self {
+        let mut set = Self(0);
+        let mut i = 0;
+        while i < kinds.len() {
+            set.0 |= Self::bit(&kinds[i]);
+            i += 1;
+        }
+
+        set
+    }
+
+    /// The bit of a kind is the one of its discriminant.
+    const fn bit(kind: &GDErrorKind) -> u32 { 1 << *kind as u32 }
+
+    const fn contains(&self, kind: &GDErrorKind) -> bool { self.0 & Self::bit(kind) != 0 }
+
+    fn iter(&self) -> impl Iterator<Item = GDErrorKind> + '_ {
+        GDErrorKind::ALL
+            .into_iter()
+            .filter(|kind| self.contains(kind))
+    }
+}
+
+impl std::fmt::Debug for RetryableKinds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { f.debug_set().entries(self.iter()).finish() }
+}
+
+impl From<Vec<GDErrorKind>> for RetryableKinds {
+    fn from(kinds: Vec<GDErrorKind>) -> Self { Self::new(&kinds) }
+}
+
+impl From<RetryableKinds> for Vec<GDErrorKind> {
+    fn from(kinds: RetryableKinds) -> Self { kinds.iter().collect() }
+}
+
+/// How failed requests are retried, part of the [TimeoutSettings].
+///
+/// By default there are no retries, once set a failed request (with a
+/// [PacketReceive](GDErrorKind::PacketReceive) or
+/// [PacketSend](GDErrorKind::PacketSend) error by default) is sent again right
+/// away with the same timeouts, this can be changed to wait increasingly
+/// longer between tries (backoff, optionally randomized with jitter so that
+/// many clients don't retry in lockstep), to give each try longer timeouts
+/// than the previous one and to stop retrying after a deadline.
+///
+/// ```
+/// use gamedig::protocols::types::RetryPolicy;
+/// use std::time::Duration;
+///
+/// // Wait 100ms, then 200ms, 400ms... (at most 2s) between 5 retries, giving
+/// // up after 10 seconds.
+/// let policy = RetryPolicy::new(5)
+///     .set_backoff(Duration::from_millis(100))
+///     .set_max_backoff(Duration::from_secs(2))
+///     .set_jitter(true)
+///     .set_deadline(Duration::from_secs(10));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "clap", derive(clap::Args))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RetryPolicy {
     /// Number of retries per request
     #[cfg_attr(feature = "clap", arg(long, default_value = "0"))]
     retries: usize,
+    /// Delay before the first retry
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "clap", arg(long = "retry-backoff", value_parser = parse_duration, help = "Delay before the first retry (in seconds, or with a ms, s or m suffix)"))]
+    backoff: Option<Duration>,
+    /// Factor the delay grows by with each retry
+    #[cfg_attr(feature = "serde", serde(default = "default_backoff_factor"))]
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long = "retry-backoff-factor",
+            default_value = "2",
+            help = "Factor the delay between retries grows by"
+        )
+    )]
+    backoff_factor: u32,
+    /// Longest delay between retries
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "clap", arg(long = "retry-max-backoff", value_parser = parse_duration, help = "Longest delay between retries (in seconds, or with a ms, s or m suffix)"))]
+    max_backoff: Option<Duration>,
+    /// Whether the delays are randomized
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long = "retry-jitter",
+            help = "Randomize the delays between retries (between half and all of them)"
+        )
+    )]
+    jitter: bool,
+    /// Factor the read and write timeouts grow by with each retry
+    #[cfg_attr(feature = "serde", serde(default = "default_timeout_factor"))]
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long = "retry-timeout-factor",
+            default_value = "1",
+            help = "Factor the read and write timeouts grow by with each retry"
+        )
+    )]
+    timeout_factor: u32,
+    /// Time after which no more retries are started
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "clap", arg(long = "retry-deadline", value_parser = parse_duration, help = "Time after which no more retries are made (in seconds, or with a ms, s or m suffix)"))]
+    deadline: Option<Duration>,
+    /// The error kinds that are retried
+    #[cfg_attr(feature = "serde", serde(default = "default_retryable"))]
+    #[cfg_attr(feature = "clap", arg(skip = DEFAULT_RETRYABLE))]
+    retryable: RetryableKinds,
+}
+
+#[cfg(feature = "serde")]
+const fn default_backoff_factor() -> u32 { 2 }
+#[cfg(feature = "serde")]
+const fn default_timeout_factor() -> u32 { 1 }
+#[cfg(feature = "serde")]
+const fn default_retryable() -> RetryableKinds { DEFAULT_RETRYABLE }
+
+impl RetryPolicy {
+    /// Retry failed requests `retries` times, right away and with the same
+    /// timeouts.
+    pub const fn new(retries: usize) -> Self {
+        Self {
+            retries,
+            backoff: None,
+            backoff_factor: 2,
+            max_backoff: None,
+            jitter: false,
+            timeout_factor: 1,
+            deadline: None,
+            retryable: DEFAULT_RETRYABLE,
+        }
+    }
+
+    /// Set the number of retries.
+    pub const fn set_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Wait `backoff` before the first retry, the delay then grows by the
+    /// [backoff factor](Self::set_backoff_factor) with each retry.
+    pub const fn set_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = Some(backoff);
+        self
+    }
+
+    /// Set the factor the delay grows by with each retry (2 by default).
+    pub const fn set_backoff_factor(mut self, backoff_factor: u32) -> Self {
+        self.backoff_factor = backoff_factor;
+        self
+    }
+
+    /// Set the longest delay between retries.
+    pub const fn set_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = Some(max_backoff);
+        self
+    }
+
+    /// Randomize each delay between half and all of it.
+    pub const fn set_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Multiply the read and write timeouts by `timeout_factor` with each
+    /// retry (1 by default, so they don't change), it isn't applied to custom
+    /// [transports](crate::transport).
+    pub const fn set_timeout_factor(mut self, timeout_factor: u32) -> Self {
+        self.timeout_factor = timeout_factor;
+        self
+    }
+
+    /// Don't start retries once `deadline` has passed since the first try
+    /// (tries that already started aren't interrupted).
+    pub const fn set_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set the kinds of errors that are retried.
+    pub const fn set_retryable(mut self, kinds: &[GDErrorKind]) -> Self {
+        self.retryable = RetryableKinds::new(kinds);
+        self
+    }
+
+    /// Get the number of retries.
+    pub const fn get_retries(&self) -> usize { self.retries }
+
+    /// Get the delay before the first retry.
+    pub const fn get_backoff(&self) -> Option<Duration> { self.backoff }
+
+    /// Get the factor the delay grows by.
+    pub const fn get_backoff_factor(&self) -> u32 { self.backoff_factor }
+
+    /// Get the longest delay between retries.
+    pub const fn get_max_backoff(&self) -> Option<Duration> { self.max_backoff }
+
+    /// Get whether the delays are randomized.
+    pub const fn get_jitter(&self) -> bool { self.jitter }
+
+    /// Get the factor the timeouts grow by.
+    pub const fn get_timeout_factor(&self) -> u32 { self.timeout_factor }
+
+    /// Get the deadline of the retries.
+    pub const fn get_deadline(&self) -> Option<Duration> { self.deadline }
+
+    /// Whether errors of `kind` are retried.
+    pub const fn is_retryable(&self, kind: &GDErrorKind) -> bool { self.retryable.contains(kind) }
+
+    /// The delay before the `retry`th retry (starting at 1), without jitter.
+    pub fn get_delay(&self, retry: usize) -> Option<Duration> {
+        let backoff = self.backoff?;
+        let exponent = u32::try_from(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+        let delay = backoff.saturating_mul(self.backoff_factor.saturating_pow(exponent));
+
+        Some(self.max_backoff.map_or(delay, |max| delay.min(max)))
+    }
+
+    /// The factor the timeouts of the `attempt`th try (starting at 1) are
+    /// multiplied by.
+    pub fn get_attempt_timeout_factor(&self, attempt: usize) -> u32 {
+        let exponent = u32::try_from(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.timeout_factor.saturating_pow(exponent)
+    }
+}
+
+impl Default for RetryPolicy {
+    /// No retries.
+    fn default() -> Self { Self::new(0) }
 }
 
 impl TimeoutSettings {
@@ -245,10 +996,30 @@ impl TimeoutSettings {
             read,
             write,
             connect,
-            retries,
+            retry_policy: RetryPolicy::new(retries),
+            query_deadline: None,
         })
     }
 
+    /// Set how failed requests are retried, replacing the retry count given
+    /// to [new](Self::new).
+    pub const fn set_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Set the total time a query may take, across all of its requests,
+    /// retries and split packets (the read, write and connect timeouts are
+    /// shortened to fit it).
+    ///
+    /// Once it expires the query returns what it gathered so far (sections
+    /// whose request didn't complete are marked as failed) or fails with
+    /// GDErrorKind::[DeadlineExceeded] if it has nothing to return.
+    pub const fn set_query_deadline(mut self, query_deadline: Duration) -> Self {
+        self.query_deadline = Some(query_deadline);
+        self
+    }
+
     /// Get the read timeout.
     pub const fn get_read(&self) -> Option<Duration> { self.read }
 
@@ -258,8 +1029,30 @@ impl TimeoutSettings {
     /// Get the connect timeout.
     pub const fn get_connect(&self) -> Option<Duration> { self.connect }
 
+    /// Get the query deadline.
+    pub const fn get_query_deadline(&self) -> Option<Duration> { self.query_deadline }
+
     /// Get number of retries
-    pub const fn get_retries(&self) -> usize { self.retries }
+    pub const fn get_retries(&self) -> usize { self.retry_policy.get_retries() }
+
+    /// Get the retry policy.
+    pub const fn get_retry_policy(&self) -> RetryPolicy { self.retry_policy }
+
+    /// The default read, write and connect timeouts.
+    const DEFAULT_TIMEOUT: Option<Duration> = Some(Duration::from_secs(4));
+
+    /// The default retry policy (no retries).
+    const DEFAULT_RETRY_POLICY: RetryPolicy = RetryPolicy::new(0);
+
+    /// Get the retry policy if there are timeout settings else fall back to
+    /// the default
+    pub const fn get_retry_policy_or_default(timeout_settings: &Option<Self>) -> RetryPolicy {
+        if let Some(timeout_settings) = timeout_settings {
+            timeout_settings.get_retry_policy()
+        } else {
+            Self::DEFAULT_RETRY_POLICY
+        }
+    }
 
     /// Get the number of retries if there are timeout settings else fall back
     /// to the default
@@ -267,7 +1060,7 @@ impl TimeoutSettings {
         if let Some(timeout_settings) = timeout_settings {
             timeout_settings.get_retries()
         } else {
-            Self::const_default().get_retries()
+            Self::DEFAULT_RETRY_POLICY.get_retries()
         }
     }
 
@@ -279,8 +1072,7 @@ impl TimeoutSettings {
         if let Some(timeout_settings) = timeout_settings {
             (timeout_settings.get_read(), timeout_settings.get_write())
         } else {
-            let default = Self::const_default();
-            (default.get_read(), default.get_write())
+            (Self::DEFAULT_TIMEOUT, Self::DEFAULT_TIMEOUT)
         }
     }
 
@@ -289,17 +1081,18 @@ impl TimeoutSettings {
         if let Some(timeout_settings) = timeout_settings {
             timeout_settings.get_connect()
         } else {
-            Self::const_default().get_connect()
+            Self::DEFAULT_TIMEOUT
         }
     }
 
     /// Default values are 4 seconds for both read and write, no retries.
     pub const fn const_default() -> Self {
         Self {
-            read: Some(Duration::from_secs(4)),
-            write: Some(Duration::from_secs(4)),
-            connect: Some(Duration::from_secs(4)),
-            retries: 0,
+            read: Self::DEFAULT_TIMEOUT,
+            write: Self::DEFAULT_TIMEOUT,
+            connect: Self::DEFAULT_TIMEOUT,
+            retry_policy: Self::DEFAULT_RETRY_POLICY,
+            query_deadline: None,
         }
     }
 }
@@ -309,6 +1102,79 @@ impl Default for TimeoutSettings {
     fn default() -> Self { Self::const_default() }
 }
 
+/// The state of a query shared by all of its requests (and their retries),
+/// passed along with its [TimeoutSettings], which stay plain configuration.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct QueryScope {
+    /// When the query deadline expires, if there is one.
+    expires_at: Option<Instant>,
+    /// The settings of the sockets the query is sent through.
+    socket_settings: SocketSettings,
+}
+
+impl QueryScope {
+    /// Start a query, along with its deadline (if the settings have one).
+    pub(crate) fn start(timeout_settings: &Option<TimeoutSettings>) -> Self {
+        Self {
+            expires_at: timeout_settings
+                .as_ref()
+                .and_then(TimeoutSettings::get_query_deadline)
+                .map(|deadline| Instant::now() + deadline),
+            socket_settings: SocketSettings::default(),
+        }
+    }
+
+    /// Send the query through sockets with these settings.
+    #[cfg_attr(not(feature = "games"), allow(dead_code))]
+    pub(crate) fn set_socket_settings(mut self, socket_settings: SocketSettings) -> Self {
+        self.socket_settings = socket_settings;
+        self
+    }
+
+    /// The settings of the sockets the query is sent through.
+    pub(crate) const fn socket_settings(&self) -> &SocketSettings { &self.socket_settings }
+
+    /// The time left before the query deadline, failing with
+    /// GDErrorKind::[DeadlineExceeded] once it expired.
+    pub(crate) fn remaining(&self) -> GDResult<Option<Duration>> {
+        match self.expires_at {
+            None => Ok(None),
+            Some(expires_at) => {
+                match expires_at.checked_duration_since(Instant::now()) {
+                    Some(remaining) if !remaining.is_zero() => Ok(Some(remaining)),
+                    _ => Err(DeadlineExceeded.context("The query deadline expired")),
+                }
+            }
+        }
+    }
+
+    /// The settings (or the defaults) to use for the next socket operation:
+    /// the read and write durations multiplied by `factor` (as used by the
+    /// retries of a [RetryPolicy]) and every duration shortened to the time
+    /// left before the query deadline.
+    pub(crate) fn operation_timeout(
+        &self,
+        timeout_settings: &Option<TimeoutSettings>,
+        factor: u32,
+    ) -> GDResult<Option<TimeoutSettings>> {
+        let remaining = self.remaining()?;
+
+        let mut settings = timeout_settings.unwrap_or_else(TimeoutSettings::const_default);
+        settings.read = settings.read.map(|read| read.saturating_mul(factor));
+        settings.write = settings.write.map(|write| write.saturating_mul(factor));
+
+        if let Some(remaining) = remaining {
+            let shorten =
+                |duration: Option<Duration>| Some(duration.map_or(remaining, |duration| duration.min(remaining)));
+            settings.read = shorten(settings.read);
+            settings.write = shorten(settings.write);
+            settings.connect = shorten(settings.connect);
+        }
+
+        Ok(Some(settings))
+    }
+}
+
 /// Generic extra request settings
 ///
 /// Fields of this struct may not be used depending on which protocol
@@ -364,6 +1230,22 @@ pub struct ExtraRequestSettings {
     /// - [valve::GatheringSettings#structfield.check_app_id]
     #[cfg_attr(feature = "clap", arg(long))]
     pub check_app_id: Option<bool>,
+    /// How to return server and player names that contain formatting (color)
+    /// codes, they are left raw if unset.
+    ///
+    /// Used by:
+    /// - [unreal2::GatheringSettings#structfield.name_format] (which strips
+    ///   them if unset)
+    /// - The generic query functions, for every other protocol.
+    #[cfg_attr(feature = "clap", arg(long, value_enum))]
+    pub name_format: Option<TextFormat>,
+    /// Whether to leave the unnamed entries and the spectator relays out of
+    /// the players (see [PlayerKind]).
+    ///
+    /// Used by:
+    /// - [valve::GatheringSettings#structfield.filter_players]
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub filter_players: Option<bool>,
 }
 
 /// Select how to go about gathering extra information via additional requests.
@@ -383,14 +1265,53 @@ pub enum GatherToggle {
 
     /// A request will be sent, but errors are not treated as criticial.
     /// In the case of an error, the operation will return a default value or
-    /// `None`.
+    /// `None` and the response's [SectionOutcome] tells the error.
     Try,
 
     /// A request will be sent, and any resulting errors will be propagated.
-    /// This option treats successful data gathering as mandatory.
+    /// This option treats successful data gathering as mandatory: the whole
+    /// query fails, its error carrying what was already gathered (such as the
+    /// server's info) as its
+    /// [partial response](crate::GDError#structfield.partial_response).
     Enforce,
 }
 
+/// What happened to a section of a response gathered by a secondary request
+/// (such as Valve's players or rules), to tell a section that wasn't requested
+/// from one that failed.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SectionOutcome {
+    /// No request was sent for the section.
+    #[default]
+    NotRequested,
+    /// The section was gathered.
+    Succeeded,
+    /// The request failed, the section is left empty.
+    Failed {
+        /// The kind of the error.
+        kind: GDErrorKind,
+        /// A description of the error.
+        message: String,
+    },
+}
+
+impl SectionOutcome {
+    /// The failed outcome of an error.
+    pub fn from_error(error: &GDError) -> Self {
+        Self::Failed {
+            kind: error.kind,
+            message: error
+                .source
+                .as_ref()
+                .map_or_else(|| format!("{:?}", error.kind), ToString::to_string),
+        }
+    }
+
+    /// Whether the section's request failed.
+    pub const fn is_failed(&self) -> bool { matches!(self, Self::Failed { .. }) }
+}
+
 impl ExtraRequestSettings {
     /// [Sets hostname](ExtraRequestSettings#structfield.hostname)
     pub fn set_hostname(mut self, hostname: String) -> Self {
@@ -418,13 +1339,106 @@ impl ExtraRequestSettings {
         self.check_app_id = Some(check_app_id);
         self
     }
+    /// [Sets name format](ExtraRequestSettings#structfield.name_format)
+    pub const fn set_name_format(mut self, name_format: TextFormat) -> Self {
+        self.name_format = Some(name_format);
+        self
+    }
+    /// [Sets filter players](ExtraRequestSettings#structfield.filter_players)
+    pub const fn set_filter_players(mut self, filter_players: bool) -> Self {
+        self.filter_players = Some(filter_players);
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
     use std::time::Duration;
 
+    // Test the query deadline shortening the timeouts of operations, then
+    // failing them once expired
+    #[test]
+    fn test_query_deadline() {
+        let settings = TimeoutSettings::default().set_query_deadline(Duration::from_millis(500));
+        // No deadline, nothing is shortened.
+        assert_eq!(QueryScope::default().remaining(), Ok(None));
+
+        let scope = QueryScope::start(&Some(settings));
+        let operation = scope
+            .operation_timeout(&Some(settings), 1)
+            .unwrap()
+            .unwrap();
+        assert!(operation.get_read().unwrap() <= Duration::from_millis(500));
+        assert!(operation.get_connect().unwrap() <= Duration::from_millis(500));
+
+        let expired = Some(settings.set_query_deadline(Duration::from_nanos(1)));
+        let scope = QueryScope::start(&expired);
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(
+            scope.operation_timeout(&expired, 1).unwrap_err().kind,
+            GDErrorKind::DeadlineExceeded
+        );
+    }
+
+    // Test the delays, timeout factors and retried kinds of a retry policy
+    #[test]
+    fn test_retry_policy() {
+        let policy = RetryPolicy::new(3)
+            .set_backoff(Duration::from_millis(100))
+            .set_max_backoff(Duration::from_millis(300))
+            .set_timeout_factor(2);
+
+        assert_eq!(policy.get_delay(1), Some(Duration::from_millis(100)));
+        assert_eq!(policy.get_delay(2), Some(Duration::from_millis(200)));
+        assert_eq!(policy.get_delay(3), Some(Duration::from_millis(300)));
+        assert_eq!(RetryPolicy::new(3).get_delay(1), None);
+
+        assert_eq!(policy.get_attempt_timeout_factor(1), 1);
+        assert_eq!(policy.get_attempt_timeout_factor(3), 4);
+
+        assert!(policy.is_retryable(&GDErrorKind::PacketReceive));
+        assert!(policy.is_retryable(&GDErrorKind::PacketSend));
+        assert!(!policy.is_retryable(&GDErrorKind::PacketBad));
+
+        let settings = QueryScope::default()
+            .operation_timeout(&Some(TimeoutSettings::default()), 3)
+            .unwrap()
+            .unwrap();
+        assert_eq!(settings.get_read(), Some(Duration::from_secs(12)));
+    }
+
+    // Test that every error kind can be retried on its own
+    #[test]
+    fn test_retryable_kinds() {
+        for kind in GDErrorKind::ALL {
+            let policy = RetryPolicy::new(1).set_retryable(&[kind]);
+            for other in GDErrorKind::ALL {
+                assert_eq!(policy.is_retryable(&other), other == kind);
+            }
+        }
+
+        let policy = RetryPolicy::new(1).set_retryable(&GDErrorKind::ALL);
+        assert!(GDErrorKind::ALL
+            .iter()
+            .all(|kind| policy.is_retryable(kind)));
+    }
+
+    // Test that the retried kinds are serialized as a list of them
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_retryable_kinds_serialization() {
+        let policy = RetryPolicy::new(1).set_retryable(&[GDErrorKind::DeadlineExceeded, GDErrorKind::PacketBad]);
+
+        let json = serde_json::to_value(policy).unwrap();
+        assert_eq!(
+            json["retryable"],
+            serde_json::json!(["PacketBad", "DeadlineExceeded"])
+        );
+        assert_eq!(serde_json::from_value::<RetryPolicy>(json).unwrap(), policy);
+    }
+
     // Test creating new TimeoutSettings with valid durations
     #[test]
     fn test_new_with_valid_durations() -> GDResult<()> {
@@ -490,4 +1504,102 @@ mod tests {
 
         let _: valve::GatheringSettings = settings.into();
     }
+
+    fn quake_response() -> Box<dyn CommonResponse> {
+        Box::new(quake::Response {
+            name: "server".to_string(),
+            map: "e1m1".to_string(),
+            players: vec![quake::one::Player {
+                id: 1,
+                score: 12,
+                time: 30,
+                ping: 40,
+                name: "player".to_string(),
+                skin: "base".to_string(),
+                color_primary: 0,
+                color_secondary: 0,
+            }],
+            players_online: 1,
+            players_maximum: 16,
+            game_version: None,
+            unused_entries: HashMap::from([
+                ("timelimit".to_string(), "20".to_string()),
+                ("fraglimit".to_string(), "50".to_string()),
+            ]),
+            latency: Some(Duration::from_millis(25)),
+        })
+    }
+
+    // Test that rules are carried into the JSON representation, sorted
+    #[test]
+    fn test_rules_in_json() {
+        let response = quake_response();
+
+        assert_eq!(
+            response.as_json().rules,
+            Some(vec![("fraglimit", "50"), ("timelimit", "20")])
+        );
+        assert_eq!(response.as_json().teams, None);
+    }
+
+    // Test that an owned response keeps everything the JSON one has
+    #[test]
+    fn test_owned_response_from_boxed() {
+        let response = quake_response();
+        let owned = CommonResponseOwned::from(response.as_ref());
+
+        assert_eq!(owned.as_json(), response.as_json());
+        assert_eq!(owned, response.into());
+    }
+
+    // Test that port ranges are checked when deserialized
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_port_range_deserialization() {
+        let range: PortRange = serde_json::from_str(r#"{"start":27000,"end":27100}"#).unwrap();
+        assert_eq!(range, PortRange::new(27000, 27100).unwrap());
+        assert_eq!(
+            serde_json::to_string(&range).unwrap(),
+            r#"{"start":27000,"end":27100}"#
+        );
+
+        let error = serde_json::from_str::<PortRange>(r#"{"start":10,"end":5}"#).unwrap_err();
+        assert!(error.to_string().contains("Invalid port range 10-5"));
+        assert!(serde_json::from_str::<PortRange>(r#"{"start":0,"end":5}"#).is_err());
+    }
+
+    // Test that an owned response round-trips through serde
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_owned_response_round_trip() {
+        let owned = quake_response().as_owned();
+
+        let json = serde_json::to_string(&owned).unwrap();
+        let loaded: CommonResponseOwned = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded, owned);
+        assert_eq!(
+            loaded.players,
+            Some(vec![CommonPlayerOwned {
+                name: "player".to_string(),
+                score: Some(12),
+                team: None,
+                ping: Some(40),
+                time_connected: Some(Duration::from_secs(30 * 60)),
+                deaths: None,
+                is_bot: None,
+                kind: None,
+            }])
+        );
+    }
+    #[test]
+    #[cfg(feature = "clap")]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("4"), Ok(Duration::from_secs(4)));
+        assert_eq!(parse_duration("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("100ms"), Ok(Duration::from_millis(100)));
+        assert_eq!(parse_duration("3m"), Ok(Duration::from_secs(180)));
+        assert!(parse_duration("1h").is_err());
+        assert!(parse_duration("ms").is_err());
+    }
 }
@@ -1,15 +1,23 @@
 use crate::buffer::{Buffer, StringDecoder};
 use crate::errors::GDErrorKind::PacketBad;
-use crate::protocols::types::TimeoutSettings;
+use crate::protocols::types::{QueryScope, RetryPolicy, SectionOutcome, TimeoutSettings};
 use crate::socket::{Socket, UdpSocket};
-use crate::utils::{maybe_gather, retry_on_timeout};
-use crate::GDResult;
+use crate::text::NormalizeText;
+use crate::utils::{enforce_sections, gather_section, retry_on_timeout};
+use crate::{ErrorContextExt, GDResult};
 
 use super::{GatheringSettings, MutatorsAndRules, PacketKind, Players, Response, ServerInfo};
 
 use std::net::SocketAddr;
+use std::time::Duration;
 
-use byteorder::{ByteOrder, LittleEndian};
+#[cfg(feature = "async")]
+use crate::{
+    socket::{AsyncSocket, AsyncUdpSocket},
+    utils::retry_on_timeout_async,
+};
+
+use byteorder::LittleEndian;
 use encoding_rs::{UTF_16LE, WINDOWS_1252};
 
 /// Response packets don't seem to exceed 500 bytes, set to 1024 just to be
@@ -27,70 +35,40 @@ const MAXIMUM_PLAYER_PREALLOCATION: usize = 50;
 /// The Unreal2 protocol implementation.
 pub(crate) struct Unreal2Protocol {
     socket: UdpSocket,
-    retry_count: usize,
+    retry_policy: RetryPolicy,
 }
 
 impl Unreal2Protocol {
-    pub fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Self> {
-        let socket = UdpSocket::new(address, &timeout_settings)?;
-        let retry_count = timeout_settings.as_ref().map_or_else(
-            || TimeoutSettings::default().get_retries(),
-            TimeoutSettings::get_retries,
-        );
+    pub fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self> {
+        let socket = UdpSocket::new(address, &timeout_settings, scope)?;
+        let retry_policy = TimeoutSettings::get_retry_policy_or_default(&timeout_settings);
 
         Ok(Self {
             socket,
-            retry_count,
+            retry_policy,
         })
     }
 
     /// Send a request packet and recieve the first response (with retries).
     fn get_request_data(&mut self, packet_type: PacketKind) -> GDResult<Vec<u8>> {
-        retry_on_timeout(self.retry_count, move || {
+        retry_on_timeout(self.retry_policy, move || {
             self.get_request_data_impl(packet_type)
         })
     }
 
     /// Send a request packet
     fn get_request_data_impl(&mut self, packet_type: PacketKind) -> GDResult<Vec<u8>> {
-        let request = [0x79, 0, 0, 0, packet_type as u8];
-        self.socket.send(&request)?;
-
-        let data = self.socket.receive(Some(PACKET_SIZE))?;
+        self.socket.send(&request_packet(packet_type))?;
 
-        Ok(data)
-    }
-
-    /// Consume the header part of a response packet, validate that the packet
-    /// type matches what is expected.
-    fn consume_response_headers<B: ByteOrder>(
-        buffer: &mut Buffer<B>,
-        expected_packet_type: PacketKind,
-    ) -> GDResult<()> {
-        // Skip header
-        buffer.move_cursor(4)?;
-
-        let packet_type: u8 = buffer.read()?;
-
-        let packet_type: PacketKind = packet_type.try_into()?;
-
-        if packet_type != expected_packet_type {
-            Err(PacketBad.context(format!(
-                "Packet response ({:?}) didn't match request ({:?}) packet type",
-                packet_type, expected_packet_type
-            )))
-        } else {
-            Ok(())
-        }
+        self.socket.receive(Some(PACKET_SIZE))
     }
 
     /// Send server info query.
     pub fn query_server_info(&mut self) -> GDResult<ServerInfo> {
-        let data = self.get_request_data(PacketKind::ServerInfo)?;
-        let mut buffer = Buffer::<LittleEndian>::new(&data);
-        // TODO: Maybe put consume headers in individual packet parse methods
-        Self::consume_response_headers(&mut buffer, PacketKind::ServerInfo)?;
-        ServerInfo::parse(&mut buffer)
+        let data = self
+            .get_request_data(PacketKind::ServerInfo)
+            .with_stage("info")?;
+        parse_server_info(&data)
     }
 
     /// Send mutators and rules query.
@@ -101,25 +79,21 @@ impl Unreal2Protocol {
         // error).
 
         let mut mutators_and_rules = MutatorsAndRules::default();
-        {
-            let data = self.get_request_data(PacketKind::MutatorsAndRules)?;
-            let mut buffer = Buffer::<LittleEndian>::new(&data);
-            // TODO: Maybe put consume headers in individual packet parse methods
-            Self::consume_response_headers(&mut buffer, PacketKind::MutatorsAndRules)?;
-            mutators_and_rules.parse(&mut buffer)?
-        };
+        let data = self
+            .get_request_data(PacketKind::MutatorsAndRules)
+            .with_stage("rules")?;
+        parse_mutators_and_rules(&mut mutators_and_rules, &data)?;
 
         // We could receive multiple packets in response
         while let Ok(data) = self.socket.receive(Some(PACKET_SIZE)) {
-            let mut buffer = Buffer::<LittleEndian>::new(&data);
-
-            let r = Self::consume_response_headers(&mut buffer, PacketKind::MutatorsAndRules);
-            if r.is_err() {
-                println!("{:?}", r);
+            let Ok(mut buffer) = response_payload(&data, PacketKind::MutatorsAndRules) else {
                 break;
-            }
+            };
 
-            mutators_and_rules.parse(&mut buffer)?;
+            mutators_and_rules
+                .parse(&mut buffer)
+                .with_data(&data)
+                .with_stage("rules")?;
         }
 
         Ok(mutators_and_rules)
@@ -127,33 +101,17 @@ impl Unreal2Protocol {
 
     /// Send players query.
     pub fn query_players(&mut self, server_info: Option<&ServerInfo>) -> GDResult<Players> {
-        // Pre-allocate the player arrays, but don't over allocate memory if the server
-        // specifies an insane number of players.
-        let num_players: Option<usize> = server_info.and_then(|i| i.num_players.try_into().ok());
-
-        let mut players = Players::with_capacity(
-            num_players
-                .unwrap_or(DEFAULT_PLAYER_PREALLOCATION)
-                .min(MAXIMUM_PLAYER_PREALLOCATION),
-        );
+        let (mut players, num_players) = players_of(server_info);
 
         // Fetch first players packet (with retries)
         let mut players_data = self.get_request_data(PacketKind::Players);
         // Players are non required so if we don't get any responses we continue to
         // return
         while let Ok(data) = players_data {
-            let mut buffer = Buffer::<LittleEndian>::new(&data);
-
-            Self::consume_response_headers(&mut buffer, PacketKind::Players)?;
+            parse_players(&mut players, &data)?;
 
-            players.parse(&mut buffer)?;
-
-            if let Some(num_players) = num_players {
-                if players.total_len() >= num_players {
-                    // If we have already received the amount of players specified in server info
-                    // then we don't need to wait for more player packets to time out.
-                    break;
-                }
+            if has_all_players(&players, num_players) {
+                break;
             }
 
             // Receive next packet
@@ -163,37 +121,260 @@ impl Unreal2Protocol {
         Ok(players)
     }
 
-    /// Make a full server query.
+    /// Make a full server query, failing with the response gathered so far as
+    /// the error's [partial
+    /// response](crate::GDError#structfield.partial_response)
+    /// if an enforced section failed.
     pub fn query(&mut self, gather_settings: &GatheringSettings) -> GDResult<Response> {
         // Fetch the server info, this can only handle one response packet
         let mut server_info = self.query_server_info()?;
 
-        let mutators_and_rules = maybe_gather!(
+        let mut enforced_failure = None;
+        let mutators_and_rules = gather_section!(
             gather_settings.mutators_and_rules,
-            self.query_mutators_and_rules()
-        )
-        .unwrap_or_default();
-
-        if let Some(password) = mutators_and_rules.rules.get("GamePassword") {
-            let string = password.concat().to_lowercase();
-            server_info.password = string == "true";
-        }
+            self.query_mutators_and_rules(),
+            enforced_failure
+        );
+        set_password(&mut server_info, mutators_and_rules.0.as_ref());
 
-        let players = maybe_gather!(
+        let players = gather_section!(
             gather_settings.players,
-            self.query_players(Some(&server_info))
+            self.query_players(Some(&server_info)),
+            enforced_failure
+        );
+
+        enforce_sections(
+            response(
+                server_info,
+                mutators_and_rules,
+                players,
+                gather_settings,
+                self.socket.latency(),
+            ),
+            enforced_failure,
         )
-        .unwrap_or_else(|| Players::with_capacity(0));
+    }
+}
 
-        // TODO: Handle extra info parsing when we detect certain game types (or maybe
-        // include that in gather settings).
+/// Create a request packet for the given packet type.
+const fn request_packet(packet_type: PacketKind) -> [u8; 5] { [0x79, 0, 0, 0, packet_type as u8] }
 
-        Ok(Response {
-            server_info,
-            mutators_and_rules,
-            players,
+/// The payload of the response packet `data`, once its header is consumed and
+/// its packet type validated to match the `expected` one.
+fn response_payload(data: &[u8], expected: PacketKind) -> GDResult<Buffer<'_, LittleEndian>> {
+    let mut buffer = Buffer::<LittleEndian>::new(data);
+
+    // Skip header
+    buffer.move_cursor(4)?;
+
+    let packet_type: u8 = buffer.read()?;
+    let packet_type: PacketKind = packet_type.try_into()?;
+
+    if packet_type != expected {
+        return Err(PacketBad.context(format!(
+            "Packet response ({:?}) didn't match request ({:?}) packet type",
+            packet_type, expected
+        )));
+    }
+
+    Ok(buffer)
+}
+
+/// Parse a server info response.
+fn parse_server_info(data: &[u8]) -> GDResult<ServerInfo> {
+    response_payload(data, PacketKind::ServerInfo)
+        .and_then(|mut buffer| ServerInfo::parse(&mut buffer))
+        .with_data(data)
+        .with_stage("info")
+}
+
+/// Parse a mutators and rules response into `mutators_and_rules`.
+fn parse_mutators_and_rules(mutators_and_rules: &mut MutatorsAndRules, data: &[u8]) -> GDResult<()> {
+    response_payload(data, PacketKind::MutatorsAndRules)
+        .and_then(|mut buffer| mutators_and_rules.parse(&mut buffer))
+        .with_data(data)
+        .with_stage("rules")
+}
+
+/// Parse a players response into `players`.
+fn parse_players(players: &mut Players, data: &[u8]) -> GDResult<()> {
+    response_payload(data, PacketKind::Players)
+        .and_then(|mut buffer| players.parse(&mut buffer))
+        .with_data(data)
+        .with_stage("players")
+}
+
+/// The players to fill with the answer to the players query, along with their
+/// number if the server info has it.
+fn players_of(server_info: Option<&ServerInfo>) -> (Players, Option<usize>) {
+    let num_players: Option<usize> = server_info.and_then(|i| i.num_players.try_into().ok());
+
+    // Pre-allocate the player arrays, but don't over allocate memory if the server
+    // specifies an insane number of players.
+    let players = Players::with_capacity(
+        num_players
+            .unwrap_or(DEFAULT_PLAYER_PREALLOCATION)
+            .min(MAXIMUM_PLAYER_PREALLOCATION),
+    );
+
+    (players, num_players)
+}
+
+/// Whether the amount of players specified in server info was received, in
+/// which case there's no need to wait for more player packets to time out.
+fn has_all_players(players: &Players, num_players: Option<usize>) -> bool {
+    num_players.is_some_and(|num_players| players.total_len() >= num_players)
+}
+
+/// Set whether the server has a password from its rules (if they were
+/// gathered).
+fn set_password(server_info: &mut ServerInfo, mutators_and_rules: Option<&MutatorsAndRules>) {
+    if let Some(password) =
+        mutators_and_rules.and_then(|mutators_and_rules| mutators_and_rules.rules.get("GamePassword"))
+    {
+        let string = password.concat().to_lowercase();
+        server_info.password = string == "true";
+    }
+}
+
+/// Put the sections gathered as per `gather_settings` together.
+fn response(
+    server_info: ServerInfo,
+    (mutators_and_rules, mutators_and_rules_outcome): (Option<MutatorsAndRules>, SectionOutcome),
+    (players, players_outcome): (Option<Players>, SectionOutcome),
+    gather_settings: &GatheringSettings,
+    latency: Option<Duration>,
+) -> Response {
+    // TODO: Handle extra info parsing when we detect certain game types (or maybe
+    // include that in gather settings).
+
+    let mut response = Response {
+        server_info,
+        mutators_and_rules: mutators_and_rules.unwrap_or_default(),
+        players: players.unwrap_or_else(|| Players::with_capacity(0)),
+        latency,
+        mutators_and_rules_outcome,
+        players_outcome,
+    };
+    response.normalize_text(gather_settings.name_format);
+
+    response
+}
+
+/// Async version of [Unreal2Protocol].
+#[cfg(feature = "async")]
+pub(crate) struct AsyncUnreal2Protocol {
+    socket: AsyncUdpSocket,
+    retry_policy: RetryPolicy,
+}
+
+#[cfg(feature = "async")]
+impl AsyncUnreal2Protocol {
+    pub async fn new(
+        address: &SocketAddr,
+        timeout_settings: Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<Self> {
+        let socket = AsyncUdpSocket::new(address, &timeout_settings, scope).await?;
+        let retry_policy = TimeoutSettings::get_retry_policy_or_default(&timeout_settings);
+
+        Ok(Self {
+            socket,
+            retry_policy,
         })
     }
+
+    /// Send a request packet and recieve the first response (with retries).
+    async fn get_request_data(&mut self, packet_type: PacketKind) -> GDResult<Vec<u8>> {
+        retry_on_timeout_async!(self.retry_policy, self.get_request_data_impl(packet_type))
+    }
+
+    /// Send a request packet
+    async fn get_request_data_impl(&mut self, packet_type: PacketKind) -> GDResult<Vec<u8>> {
+        self.socket.send(&request_packet(packet_type)).await?;
+
+        self.socket.receive(Some(PACKET_SIZE)).await
+    }
+
+    /// Async version of [Unreal2Protocol::query_server_info].
+    pub async fn query_server_info(&mut self) -> GDResult<ServerInfo> {
+        let data = self
+            .get_request_data(PacketKind::ServerInfo)
+            .await
+            .with_stage("info")?;
+        parse_server_info(&data)
+    }
+
+    /// Async version of [Unreal2Protocol::query_mutators_and_rules].
+    pub async fn query_mutators_and_rules(&mut self) -> GDResult<MutatorsAndRules> {
+        let mut mutators_and_rules = MutatorsAndRules::default();
+        let data = self
+            .get_request_data(PacketKind::MutatorsAndRules)
+            .await
+            .with_stage("rules")?;
+        parse_mutators_and_rules(&mut mutators_and_rules, &data)?;
+
+        while let Ok(data) = self.socket.receive(Some(PACKET_SIZE)).await {
+            let Ok(mut buffer) = response_payload(&data, PacketKind::MutatorsAndRules) else {
+                break;
+            };
+
+            mutators_and_rules
+                .parse(&mut buffer)
+                .with_data(&data)
+                .with_stage("rules")?;
+        }
+
+        Ok(mutators_and_rules)
+    }
+
+    /// Async version of [Unreal2Protocol::query_players].
+    pub async fn query_players(&mut self, server_info: Option<&ServerInfo>) -> GDResult<Players> {
+        let (mut players, num_players) = players_of(server_info);
+
+        let mut players_data = self.get_request_data(PacketKind::Players).await;
+        while let Ok(data) = players_data {
+            parse_players(&mut players, &data)?;
+
+            if has_all_players(&players, num_players) {
+                break;
+            }
+
+            players_data = self.socket.receive(Some(PACKET_SIZE)).await;
+        }
+
+        Ok(players)
+    }
+
+    /// Async version of [Unreal2Protocol::query].
+    pub async fn query(&mut self, gather_settings: &GatheringSettings) -> GDResult<Response> {
+        let mut server_info = self.query_server_info().await?;
+
+        let mut enforced_failure = None;
+        let mutators_and_rules = gather_section!(
+            gather_settings.mutators_and_rules,
+            self.query_mutators_and_rules().await,
+            enforced_failure
+        );
+        set_password(&mut server_info, mutators_and_rules.0.as_ref());
+
+        let players = gather_section!(
+            gather_settings.players,
+            self.query_players(Some(&server_info)).await,
+            enforced_failure
+        );
+
+        enforce_sections(
+            response(
+                server_info,
+                mutators_and_rules,
+                players,
+                gather_settings,
+                self.socket.latency(),
+            ),
+            enforced_failure,
+        )
+    }
 }
 
 /// Unreal 2 string decoder
@@ -268,28 +449,8 @@ impl StringDecoder for Unreal2StringDecoder {
             result
         };
 
-        // Strip color encodings
-        // TODO: Improve efficiency
-        // TODO: There might be a nicer way to do this once string patterns are stable
-        //       https://github.com/rust-lang/rust/issues/27721
-
-        // After '0x1b' skip 3 characters (including the '0x1b')
-        let mut char_skip = 0usize;
-        let result: String = result
-            .chars()
-            .filter(|c: &char| {
-                if '\x1b'.eq(c) {
-                    char_skip = 4;
-                    return false;
-                }
-                char_skip = char_skip.saturating_sub(1);
-
-                char_skip == 0
-            })
-            .collect();
-
-        // Remove all characters between 0x00 and 0x1a
-        let result = result.replace(|c: char| c > '\x00' && c <= '\x1a', "");
+        // Color sequences are kept, they are handled by the text module (see
+        // GatheringSettings::name_format).
 
         *cursor += start + length;
 
@@ -304,9 +465,126 @@ pub fn query(
     gather_settings: &GatheringSettings,
     timeout_settings: Option<TimeoutSettings>,
 ) -> GDResult<Response> {
-    let mut client = Unreal2Protocol::new(address, timeout_settings)?;
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, gather_settings, timeout_settings, &scope)
+}
+
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &SocketAddr,
+    gather_settings: &GatheringSettings,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    Unreal2Protocol::new(address, timeout_settings, scope)
+        .and_then(|mut client| client.query(gather_settings))
+        .with_address(*address)
+        .with_protocol("unreal2")
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(
+    address: &SocketAddr,
+    gather_settings: &GatheringSettings,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, gather_settings, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &SocketAddr,
+    gather_settings: &GatheringSettings,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    get_response_async(address, gather_settings, timeout_settings, scope)
+        .await
+        .with_address(*address)
+        .with_protocol("unreal2")
+}
+
+#[cfg(feature = "async")]
+async fn get_response_async(
+    address: &SocketAddr,
+    gather_settings: &GatheringSettings,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut client = AsyncUnreal2Protocol::new(address, timeout_settings, scope).await?;
 
-    client.query(gather_settings)
+    client.query(gather_settings).await
 }
 
-// TODO: Add tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::types::{GatherToggle, GenericResponse, SocketSettings};
+    use crate::transport::Scripted;
+    use crate::GDErrorKind::PacketReceive;
+    use std::sync::Arc;
+
+    fn string(value: &str) -> Vec<u8> {
+        let mut data = vec![value.len() as u8 + 1];
+        data.extend(value.as_bytes());
+        data.push(0);
+
+        data
+    }
+
+    /// A server answering the server info request only.
+    fn info_only() -> Scripted {
+        let mut info = vec![0x79, 0, 0, 0, PacketKind::ServerInfo as u8];
+        info.extend(1u32.to_le_bytes());
+        info.extend(string("127.0.0.1"));
+        info.extend(7777u32.to_le_bytes());
+        info.extend(7778u32.to_le_bytes());
+        info.extend(string("Test"));
+        info.extend(string("CTF-Face"));
+        info.extend(string("xCTFGame"));
+        info.extend(3u32.to_le_bytes());
+        info.extend(16u32.to_le_bytes());
+
+        Scripted::new().add_packet(info)
+    }
+
+    #[test]
+    fn failed_section_keeps_info() {
+        let address = "127.0.0.1:7778".parse().unwrap();
+        let scope =
+            QueryScope::default().set_socket_settings(SocketSettings::new().set_connector(Arc::new(info_only())));
+
+        let gather_settings = GatheringSettings {
+            mutators_and_rules: GatherToggle::Try,
+            ..GatheringSettings::default()
+        };
+        let response = query_with_scope(&address, &gather_settings, None, &scope).unwrap();
+        assert_eq!(response.server_info.num_players, 3);
+        assert_eq!(response.server_info.max_players, 16);
+        assert!(response.mutators_and_rules.rules.is_empty());
+        assert!(matches!(
+            response.mutators_and_rules_outcome,
+            SectionOutcome::Failed {
+                kind: PacketReceive,
+                ..
+            }
+        ));
+
+        // The mutators and rules are enforced by default, the error carries the
+        // server info.
+        let error = query_with_scope(&address, &GatheringSettings::default(), None, &scope).unwrap_err();
+        assert_eq!(error.kind, PacketReceive);
+        assert_eq!(error.context.stage, Some("rules"));
+        assert_eq!(error.context.protocol, Some("unreal2"));
+        assert_eq!(error.context.address, Some(address));
+        let Some(GenericResponse::Unreal2(partial)) = error.partial_response.as_ref().map(|r| r.as_original()) else {
+            panic!("expected a partial unreal2 response");
+        };
+        assert_eq!(partial.server_info.num_players, 3);
+        assert!(partial.mutators_and_rules_outcome.is_failed());
+        assert_eq!(partial.players_outcome, SectionOutcome::NotRequested);
+    }
+}
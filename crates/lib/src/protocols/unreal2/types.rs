@@ -1,12 +1,22 @@
 use crate::buffer::Buffer;
 use crate::errors::GDErrorKind::PacketBad;
-use crate::protocols::types::{CommonPlayer, CommonResponse, ExtraRequestSettings, GatherToggle, GenericPlayer};
+use crate::protocols::types::{
+    sorted_rules,
+    CommonPlayer,
+    CommonResponse,
+    ExtraRequestSettings,
+    GatherToggle,
+    GenericPlayer,
+    SectionOutcome,
+};
 use crate::protocols::GenericResponse;
+use crate::text::{normalize, normalize_in_place, Dialect, NormalizeText, TextFormat};
 use crate::{GDError, GDResult};
 
 use super::Unreal2StringDecoder;
 
 use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
 use byteorder::ByteOrder;
 
@@ -52,12 +62,12 @@ impl ServerInfo {
     pub fn parse<B: ByteOrder>(buffer: &mut Buffer<B>) -> GDResult<Self> {
         Ok(Self {
             server_id: buffer.read()?,
-            ip: buffer.read_string::<Unreal2StringDecoder>(None)?,
+            ip: read_plain_string(buffer)?,
             game_port: buffer.read()?,
             query_port: buffer.read()?,
             name: buffer.read_string::<Unreal2StringDecoder>(None)?,
-            map: buffer.read_string::<Unreal2StringDecoder>(None)?,
-            game_type: buffer.read_string::<Unreal2StringDecoder>(None)?,
+            map: read_plain_string(buffer)?,
+            game_type: read_plain_string(buffer)?,
             num_players: buffer.read()?,
             max_players: buffer.read()?,
             password: false,
@@ -65,6 +75,14 @@ impl ServerInfo {
     }
 }
 
+/// Read a string with its color sequences stripped, only names are kept raw
+/// (see [GatheringSettings::name_format]).
+fn read_plain_string<B: ByteOrder>(buffer: &mut Buffer<B>) -> GDResult<String> {
+    let string = buffer.read_string::<Unreal2StringDecoder>(None)?;
+
+    Ok(normalize(&string, Dialect::Unreal2, TextFormat::Plain))
+}
+
 /// Unreal 2 mutators and rules.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -76,8 +94,8 @@ pub struct MutatorsAndRules {
 impl MutatorsAndRules {
     pub fn parse<B: ByteOrder>(&mut self, buffer: &mut Buffer<B>) -> GDResult<()> {
         while buffer.remaining_length() > 0 {
-            let key = buffer.read_string::<Unreal2StringDecoder>(None)?;
-            let value = buffer.read_string::<Unreal2StringDecoder>(None).ok();
+            let key = read_plain_string(buffer)?;
+            let value = read_plain_string(buffer).ok();
 
             if key.eq_ignore_ascii_case("mutator") {
                 if let Some(value) = value {
@@ -167,6 +185,10 @@ impl CommonPlayer for Player {
 
     fn score(&self) -> Option<i32> { Some(self.score) }
 
+    fn ping(&self) -> Option<u32> { Some(self.ping) }
+
+    fn is_bot(&self) -> Option<bool> { Some(self.ping == 0) }
+
     fn as_original(&self) -> GenericPlayer { GenericPlayer::Unreal2(self) }
 }
 
@@ -177,10 +199,19 @@ pub struct Response {
     pub server_info: ServerInfo,
     pub mutators_and_rules: MutatorsAndRules,
     pub players: Players,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
+    /// What happened to the mutators and rules request.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mutators_and_rules_outcome: SectionOutcome,
+    /// What happened to the players request.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub players_outcome: SectionOutcome,
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> GenericResponse { GenericResponse::Unreal2(self) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn name(&self) -> Option<&str> { Some(&self.server_info.name) }
 
@@ -194,31 +225,62 @@ impl CommonResponse for Response {
 
     fn has_password(&self) -> Option<bool> { Some(self.server_info.password) }
 
+    fn players_bots(&self) -> Option<u32> { self.players.bots.len().try_into().ok() }
+
+    fn game_port(&self) -> Option<u16> { self.server_info.game_port.try_into().ok() }
+
+    fn rules(&self) -> Option<Vec<(&str, &str)>> {
+        let rules = self.mutators_and_rules.rules.iter();
+
+        Some(sorted_rules(rules.flat_map(|(key, values)| {
+            values.iter().map(move |value| (key, value))
+        })))
+    }
+
     fn players(&self) -> Option<Vec<&dyn crate::protocols::types::CommonPlayer>> {
         Some(
             self.players
                 .players
                 .iter()
+                .chain(&self.players.bots)
                 .map(|player| player as _)
                 .collect(),
         )
     }
 }
 
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.server_info.name, Dialect::Unreal2, format);
+        for player in self
+            .players
+            .players
+            .iter_mut()
+            .chain(&mut self.players.bots)
+        {
+            normalize_in_place(&mut player.name, Dialect::Unreal2, format);
+        }
+    }
+}
+
 /// What data to gather, purely used only with the query function.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct GatheringSettings {
     pub players: GatherToggle,
     pub mutators_and_rules: GatherToggle,
+    /// How the server and player names are returned, other strings always
+    /// have their color sequences stripped.
+    pub name_format: TextFormat,
 }
 
 impl GatheringSettings {
-    /// Default values is attempt both players and rules.
+    /// Default values is attempt both players and rules, with plain names.
     pub const fn default() -> Self {
         Self {
             players: GatherToggle::Try,
             mutators_and_rules: GatherToggle::Enforce,
+            name_format: TextFormat::Plain,
         }
     }
 
@@ -229,6 +291,8 @@ impl GatheringSettings {
             gather_players: Some(self.players),
             gather_rules: Some(self.mutators_and_rules),
             check_app_id: None,
+            name_format: Some(self.name_format),
+            filter_players: None,
         }
     }
 }
@@ -243,6 +307,7 @@ impl From<ExtraRequestSettings> for GatheringSettings {
         Self {
             players: value.gather_players.unwrap_or(default.players),
             mutators_and_rules: value.gather_rules.unwrap_or(default.mutators_and_rules),
+            name_format: value.name_format.unwrap_or(default.name_format),
         }
     }
 }
@@ -0,0 +1,480 @@
+//! The binary rules of Arma and DayZ servers.
+//!
+//! Instead of text rules, these servers answer the rules request with a binary
+//! payload (the mods, DLCs and difficulty settings of the server), escaped and
+//! split into chunks named by their index and count, see [ArmaRules].
+//!
+//! Reference: [Arma 3 server browser protocol](https://community.bistudio.com/wiki/Arma_3:_ServerBrowserProtocol3)
+
+use crate::buffer::Buffer;
+use crate::protocols::types::{QueryScope, TimeoutSettings};
+use crate::protocols::valve::{Engine, RawRule, ValveProtocol};
+use crate::ErrorContextExt;
+use crate::GDErrorKind::{PacketBad, PacketUnderflow};
+use crate::GDResult;
+
+#[cfg(feature = "async")]
+use crate::protocols::valve::AsyncValveProtocol;
+
+use byteorder::LittleEndian;
+use std::net::SocketAddr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The Arma 3 DLCs, in the order of their [flag](ArmaRules::dlc_flags) bits.
+pub const ARMA3_DLCS: &[&str] = &[
+    "Karts",
+    "Marksmen",
+    "Helicopters",
+    "Zeus",
+    "Apex",
+    "Jets",
+    "Laws of War",
+    "Malden",
+    "Tac-Ops",
+    "Tanks",
+    "Contact",
+    "Livonia",
+];
+
+/// How a game lays out the payload.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArmaLayout {
+    /// Arma 2: Operation Arrowhead and Arma 3.
+    Arma,
+    /// DayZ, without the difficulty settings.
+    DayZ,
+}
+
+/// The difficulty settings of a server.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArmaDifficulty {
+    /// The difficulty preset (0 being the easiest).
+    pub difficulty: u8,
+    /// The skill level of the AI (0 being the easiest).
+    pub ai_level: u8,
+    /// Whether the advanced flight model is forced.
+    pub advanced_flight_model: bool,
+    /// Whether the third person view is allowed.
+    pub third_person: bool,
+    /// Whether the crosshair is shown.
+    pub crosshair: bool,
+}
+
+impl ArmaDifficulty {
+    const fn new(flags: u8, crosshair: u8) -> Self {
+        Self {
+            difficulty: flags & 0b111,
+            ai_level: (flags >> 3) & 0b111,
+            advanced_flight_model: flags & 0b0100_0000 != 0,
+            third_person: flags & 0b1000_0000 != 0,
+            crosshair: crosshair != 0,
+        }
+    }
+}
+
+/// A mod loaded by a server.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArmaMod {
+    /// The name of the mod.
+    pub name: String,
+    /// The Steam workshop ID of the mod (or the app ID of a DLC), 0 if none.
+    pub workshop_id: u64,
+    /// The hash of the mod's files.
+    pub hash: u32,
+    /// Whether the mod is a DLC.
+    pub is_dlc: bool,
+}
+
+/// The decoded binary rules of a server.
+///
+/// ```
+/// use gamedig::protocols::valve::arma::{ArmaLayout, ArmaRules};
+///
+/// // Version 3, no DLC, regular difficulty, no mods nor signatures, as a
+/// // single chunk (the zeroes being escaped).
+/// let rules = [(vec![1, 1], b"\x03\x01\x02\x01\x02\x01\x02\x09\x01\x01\x01\x02\x01\x02".to_vec())];
+/// let decoded = ArmaRules::decode(&rules, ArmaLayout::Arma).unwrap();
+///
+/// assert_eq!(decoded.version, 3);
+/// assert_eq!(decoded.difficulty.unwrap().ai_level, 1);
+/// assert!(decoded.mods.is_empty());
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArmaRules {
+    /// The version of the payload's format.
+    pub version: u8,
+    /// Set when lists didn't fit in the payload and were truncated.
+    pub overflow_flags: u8,
+    /// One bit per owned DLC (see [ARMA3_DLCS] for Arma 3's).
+    pub dlc_flags: u16,
+    /// The hashes of the DLCs, in the order of their bits.
+    pub dlc_hashes: Vec<u32>,
+    /// `None` for DayZ, which doesn't send it.
+    pub difficulty: Option<ArmaDifficulty>,
+    /// The mods (and DLCs) the server runs.
+    pub mods: Vec<ArmaMod>,
+    /// The names of the keys the server accepts signed addons of.
+    pub signatures: Vec<String>,
+}
+
+impl ArmaRules {
+    /// Decode the binary chunks of `rules` (as returned by the server, other
+    /// rules are ignored).
+    pub fn decode(rules: &[RawRule], layout: ArmaLayout) -> GDResult<Self> {
+        let payload = unescape(&reassemble(rules)?)?;
+        Self::parse(&payload, layout).with_data(&payload)
+    }
+
+    fn parse(payload: &[u8], layout: ArmaLayout) -> GDResult<Self> {
+        let mut buffer = Buffer::<LittleEndian>::new(payload);
+
+        let version = buffer.read()?;
+        let overflow_flags = buffer.read()?;
+        let dlc_flags = buffer.read::<u16>()?;
+        let dlc_hashes = (0 .. dlc_flags.count_ones())
+            .map(|_| buffer.read())
+            .collect::<GDResult<_>>()?;
+
+        let difficulty = match layout {
+            ArmaLayout::Arma => Some(ArmaDifficulty::new(buffer.read()?, buffer.read()?)),
+            ArmaLayout::DayZ => None,
+        };
+
+        let mod_count = buffer.read::<u8>()?;
+        let mods = (0 .. mod_count)
+            .map(|_| read_mod(&mut buffer))
+            .collect::<GDResult<_>>()?;
+
+        // Left out when the mods overflowed.
+        let signature_count = match buffer.remaining_length() {
+            0 => 0,
+            _ => buffer.read::<u8>()?,
+        };
+        let signatures = (0 .. signature_count)
+            .map(|_| read_short_string(&mut buffer))
+            .collect::<GDResult<_>>()?;
+
+        Ok(Self {
+            version,
+            overflow_flags,
+            dlc_flags,
+            dlc_hashes,
+            difficulty,
+            mods,
+            signatures,
+        })
+    }
+
+    /// The names of the Arma 3 DLCs owned by the server.
+    pub fn arma3_dlcs(&self) -> Vec<&'static str> {
+        ARMA3_DLCS
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.dlc_flags & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+/// Join the chunks, whose names are their (1-based) index and the number of
+/// chunks.
+fn reassemble(rules: &[RawRule]) -> GDResult<Vec<u8>> {
+    let mut chunks: Vec<Option<&[u8]>> = Vec::new();
+
+    for (name, value) in rules {
+        let &[index, count] = name.as_slice() else {
+            continue;
+        };
+        if index == 0 || index > count {
+            continue;
+        }
+
+        if chunks.is_empty() {
+            chunks = vec![None; count.into()];
+        } else if chunks.len() != usize::from(count) {
+            return Err(PacketBad.context("Rules chunks disagree on their count"));
+        }
+
+        chunks[usize::from(index - 1)] = Some(value);
+    }
+
+    if chunks.is_empty() {
+        return Err(PacketBad.context("No binary rules"));
+    }
+
+    let count = chunks.len();
+    let mut payload = Vec::new();
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        let chunk =
+            chunk.ok_or_else(|| PacketBad.context(format!("Missing rules chunk {} of {}", index + 1, count)))?;
+        payload.extend_from_slice(chunk);
+    }
+
+    Ok(payload)
+}
+
+/// Undo the escaping of the bytes the rules can't contain (`0x00` and
+/// `0xFF`) and of the escape byte itself.
+fn unescape(data: &[u8]) -> GDResult<Vec<u8>> {
+    let mut unescaped = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+
+    while let Some(&byte) = bytes.next() {
+        unescaped.push(match byte {
+            0x01 => {
+                match bytes.next() {
+                    Some(0x01) => 0x01,
+                    Some(0x02) => 0x00,
+                    Some(0x03) => 0xFF,
+                    other => return Err(PacketBad.context(format!("Invalid escape sequence: 0x01 {:02x?}", other))),
+                }
+            }
+            byte => byte,
+        });
+    }
+
+    Ok(unescaped)
+}
+
+fn read_mod(buffer: &mut Buffer<LittleEndian>) -> GDResult<ArmaMod> {
+    let hash = buffer.read()?;
+    let flags = buffer.read::<u8>()?;
+
+    let id_length = flags & 0x0F;
+    if id_length > 8 {
+        return Err(PacketBad.context(format!("Workshop ID of {} bytes", id_length)));
+    }
+
+    let mut workshop_id = 0;
+    for shift in 0 .. id_length {
+        workshop_id |= u64::from(buffer.read::<u8>()?) << (8 * shift);
+    }
+
+    Ok(ArmaMod {
+        name: read_short_string(buffer)?,
+        workshop_id,
+        hash,
+        is_dlc: flags & 0x10 != 0,
+    })
+}
+
+/// Read a string prefixed by its length.
+fn read_short_string(buffer: &mut Buffer<LittleEndian>) -> GDResult<String> {
+    let length = buffer.read::<u8>()?;
+    let bytes = buffer
+        .remaining_bytes()
+        .get(.. length.into())
+        .ok_or_else(|| PacketUnderflow.context("String longer than the payload"))?;
+    let string = String::from_utf8_lossy(bytes).into_owned();
+
+    buffer.move_cursor(length.into())?;
+
+    Ok(string)
+}
+
+/// Query the rules of a server and decode them.
+pub fn query_rules(
+    address: &SocketAddr,
+    engine: Engine,
+    layout: ArmaLayout,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<ArmaRules> {
+    let scope = QueryScope::start(&timeout_settings);
+    let mut client = ValveProtocol::new(address, timeout_settings, &scope)?;
+    let rules = client.get_server_rules_raw(&engine, 0)?;
+
+    ArmaRules::decode(&rules, layout)
+        .with_stage("rules")
+        .with_address(*address)
+        .with_protocol("valve")
+}
+
+/// Async version of [query_rules].
+#[cfg(feature = "async")]
+pub async fn query_rules_async(
+    address: &SocketAddr,
+    engine: Engine,
+    layout: ArmaLayout,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<ArmaRules> {
+    let scope = QueryScope::start(&timeout_settings);
+    let mut client = AsyncValveProtocol::new(address, timeout_settings, &scope).await?;
+    let rules = client.get_server_rules_raw(&engine, 0).await?;
+
+    ArmaRules::decode(&rules, layout)
+        .with_stage("rules")
+        .with_address(*address)
+        .with_protocol("valve")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::types::SocketSettings;
+    use crate::transport::Scripted;
+    use std::sync::Arc;
+
+    /// Escape `payload` and split it into rules of `size` bytes.
+    fn chunked(payload: &[u8], size: usize) -> Vec<RawRule> {
+        let escaped: Vec<u8> = payload
+            .iter()
+            .flat_map(|&byte| {
+                match byte {
+                    0x00 => vec![0x01, 0x02],
+                    0x01 => vec![0x01, 0x01],
+                    0xFF => vec![0x01, 0x03],
+                    byte => vec![byte],
+                }
+            })
+            .collect();
+
+        let count = escaped.chunks(size).count() as u8;
+        escaped
+            .chunks(size)
+            .zip(1 ..)
+            .map(|(chunk, index)| (vec![index, count], chunk.to_vec()))
+            .collect()
+    }
+
+    fn arma3_payload() -> Vec<u8> {
+        let mut payload = vec![3, 0];
+        // Marksmen and Apex, with their hashes.
+        payload.extend([0b0001_0010, 0]);
+        payload.extend(0x0102_03FF_u32.to_le_bytes());
+        payload.extend(0xDEAD_BEEF_u32.to_le_bytes());
+        // Veteran, AI level 2, third person and crosshair.
+        payload.extend([0b1001_0010, 1]);
+        // Two mods: CBA from the workshop and a DLC.
+        payload.push(2);
+        payload.extend(0x1234_5678_u32.to_le_bytes());
+        payload.push(0x04);
+        payload.extend(450_814_997_u32.to_le_bytes());
+        payload.push(8);
+        payload.extend(b"CBA_A3 \xFF");
+        payload.extend(0u32.to_le_bytes());
+        payload.push(0x13);
+        payload.extend([0x58, 0x45, 0x0F]);
+        payload.push(4);
+        payload.extend(b"Apex");
+        // One signature.
+        payload.push(1);
+        payload.push(3);
+        payload.extend(b"cba");
+
+        payload
+    }
+
+    #[test]
+    fn decode_chunks() {
+        let mut rules = chunked(&arma3_payload(), 16);
+        assert!(rules.len() > 2);
+        // Chunks in any order, among other rules.
+        rules.reverse();
+        rules.insert(1, (b"other".to_vec(), b"rule".to_vec()));
+
+        let decoded = ArmaRules::decode(&rules, ArmaLayout::Arma).unwrap();
+        assert_eq!(
+            decoded,
+            ArmaRules {
+                version: 3,
+                overflow_flags: 0,
+                dlc_flags: 0b0001_0010,
+                dlc_hashes: vec![0x0102_03FF, 0xDEAD_BEEF],
+                difficulty: Some(ArmaDifficulty {
+                    difficulty: 2,
+                    ai_level: 2,
+                    advanced_flight_model: false,
+                    third_person: true,
+                    crosshair: true,
+                }),
+                mods: vec![
+                    ArmaMod {
+                        name: "CBA_A3 \u{FFFD}".to_string(),
+                        workshop_id: 450_814_997,
+                        hash: 0x1234_5678,
+                        is_dlc: false,
+                    },
+                    ArmaMod {
+                        name: "Apex".to_string(),
+                        workshop_id: 0x0F_4558,
+                        hash: 0,
+                        is_dlc: true,
+                    },
+                ],
+                signatures: vec!["cba".to_string()],
+            }
+        );
+        assert_eq!(decoded.arma3_dlcs(), ["Marksmen", "Apex"]);
+    }
+
+    #[test]
+    fn decode_dayz() {
+        let mut payload = vec![2, 0, 0, 0, 1];
+        payload.extend(7u32.to_le_bytes());
+        payload.push(0x04);
+        payload.extend(1_559_212_036_u32.to_le_bytes());
+        payload.push(2);
+        payload.extend(b"CF");
+
+        let decoded = ArmaRules::decode(&chunked(&payload, 127), ArmaLayout::DayZ).unwrap();
+        assert_eq!(decoded.difficulty, None);
+        assert_eq!(decoded.mods[0].workshop_id, 1_559_212_036);
+        assert_eq!(decoded.mods[0].name, "CF");
+        assert!(decoded.signatures.is_empty());
+    }
+
+    #[test]
+    fn bad_chunks() {
+        let mut rules = chunked(&arma3_payload(), 16);
+        rules.remove(1);
+        let error = ArmaRules::decode(&rules, ArmaLayout::Arma).unwrap_err();
+        assert!(error.to_string().contains("Missing rules chunk 2"));
+
+        let rules = [(b"version".to_vec(), b"1".to_vec())];
+        assert_eq!(
+            ArmaRules::decode(&rules, ArmaLayout::Arma)
+                .unwrap_err()
+                .kind,
+            PacketBad
+        );
+
+        assert!(unescape(b"\x01\x04").is_err());
+        assert!(unescape(b"\x01").is_err());
+    }
+
+    /// The answer to the rules request.
+    fn rules_packet() -> Vec<u8> {
+        let mut packet = b"\xFF\xFF\xFF\xFFE".to_vec();
+        let rules = chunked(&arma3_payload(), 127);
+        packet.extend((rules.len() as u16 + 1).to_le_bytes());
+        packet.extend(b"version\x001.0\x00");
+        for (name, value) in rules {
+            packet.extend(name);
+            packet.push(0);
+            packet.extend(value);
+            packet.push(0);
+        }
+
+        packet
+    }
+
+    #[test]
+    fn query_server_rules() {
+        let connector = Scripted::new().add_packet(rules_packet());
+        let scope = QueryScope::default().set_socket_settings(SocketSettings::new().set_connector(Arc::new(connector)));
+        let mut client = ValveProtocol::new(&"127.0.0.1:2303".parse().unwrap(), None, &scope).unwrap();
+        let rules = client
+            .get_server_rules_raw(&Engine::new(107_410), 0)
+            .unwrap();
+        let rules = ArmaRules::decode(&rules, ArmaLayout::Arma).unwrap();
+
+        assert_eq!(rules.mods.len(), 2);
+        assert_eq!(rules.signatures, ["cba"]);
+    }
+}
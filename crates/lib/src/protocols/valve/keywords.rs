@@ -0,0 +1,240 @@
+//! Typed decoding of the keywords (tags) of servers.
+//!
+//! The keywords of [ExtraData](super::ExtraData) are a comma separated list
+//! of tags, which some games use to encode their state (Rust's `mp100,cp42`
+//! being its maximum and current players, for example). [keyword_tags] splits
+//! them and the [GameKeywords] implementations decode the tags of a game.
+//!
+//! ```
+//! use gamedig::protocols::valve::keywords::{GameKeywords, RustKeywords};
+//!
+//! let keywords = RustKeywords::parse("mp100,cp42,qp5,v2500,born1700000000,gmrust,oxide");
+//!
+//! assert_eq!(keywords.max_players, Some(100));
+//! assert_eq!(keywords.queued_players, Some(5));
+//! assert_eq!(keywords.born, Some(1_700_000_000));
+//! assert!(keywords.modded);
+//! ```
+
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Split comma separated keywords into tags.
+pub fn keyword_tags(keywords: &str) -> Vec<&str> {
+    keywords
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// The keywords of a game, decoded from its tags.
+pub trait GameKeywords: Sized {
+    /// Decode `tags`, the ones that aren't known are ignored.
+    fn from_tags(tags: &[&str]) -> Self;
+
+    /// Decode comma separated keywords.
+    fn parse(keywords: &str) -> Self { Self::from_tags(&keyword_tags(keywords)) }
+}
+
+/// The value of the first tag made of `prefix` followed by a valid value
+/// (such as `mp100`).
+fn prefixed<T: FromStr>(tags: &[&str], prefix: &str) -> Option<T> {
+    tags.iter()
+        .find_map(|tag| tag.strip_prefix(prefix)?.parse().ok())
+}
+
+/// The value of the first `key:value` tag.
+fn value<'a>(tags: &[&'a str], key: &str) -> Option<&'a str> {
+    tags.iter().find_map(|tag| {
+        match tag.split_once(':') {
+            Some((tag_key, value)) if tag_key == key => Some(value),
+            _ => None,
+        }
+    })
+}
+
+/// The value of the first `key:value` tag, parsed.
+fn parsed_value<T: FromStr>(tags: &[&str], key: &str) -> Option<T> { value(tags, key)?.parse().ok() }
+
+fn has_tag(tags: &[&str], tag: &str) -> bool { tags.contains(&tag) }
+
+/// Rust's keywords.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RustKeywords {
+    /// Players on the server (`cp`), not capped to 255 unlike the info's.
+    pub players: Option<u32>,
+    /// Maximum population of the server (`mp`).
+    pub max_players: Option<u32>,
+    /// Players waiting in the queue (`qp`).
+    pub queued_players: Option<u32>,
+    /// Network protocol version (`v`).
+    pub protocol: Option<u32>,
+    /// When the server was last wiped, as a unix timestamp (`born`).
+    pub born: Option<u64>,
+    /// The game mode (`gm`), e.g. `rust` for vanilla servers.
+    pub game_mode: Option<String>,
+    /// Whether the server runs a modding framework (Oxide or Carbon).
+    pub modded: bool,
+    /// Whether the server is a PvE (player versus environment) one.
+    pub pve: bool,
+}
+
+impl RustKeywords {
+    /// When the server was last wiped.
+    pub fn wipe_time(&self) -> Option<SystemTime> { UNIX_EPOCH.checked_add(Duration::from_secs(self.born?)) }
+}
+
+impl GameKeywords for RustKeywords {
+    fn from_tags(tags: &[&str]) -> Self {
+        Self {
+            players: prefixed(tags, "cp"),
+            max_players: prefixed(tags, "mp"),
+            queued_players: prefixed(tags, "qp"),
+            protocol: prefixed(tags, "v"),
+            born: prefixed(tags, "born"),
+            game_mode: tags
+                .iter()
+                .find_map(|tag| tag.strip_prefix("gm").filter(|mode| !mode.is_empty()))
+                .map(str::to_string),
+            modded: ["oxide", "carbon", "modded"]
+                .iter()
+                .any(|tag| has_tag(tags, tag)),
+            pve: has_tag(tags, "pve"),
+        }
+    }
+}
+
+/// The keywords of Unreal Engine servers using Steam sessions (ARK, Squad),
+/// made of `KEY:value` tags.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct UnrealKeywords {
+    /// The ID of the session's owner (`OWNINGID`).
+    pub owning_id: Option<u64>,
+    /// The name of the session's owner (`OWNINGNAME`).
+    pub owning_name: Option<String>,
+    /// The session's settings, as flags (`SESSIONFLAGS`).
+    pub session_flags: Option<u32>,
+    /// The build of the server (`BUILDID`).
+    pub build_id: Option<u32>,
+    /// Public slots left (`NUMOPENPUBCONN`).
+    pub open_public_connections: Option<u32>,
+    /// The Steam peer to peer address of the server (`P2PADDR`).
+    pub p2p_address: Option<String>,
+    /// The Steam peer to peer port of the server (`P2PPORT`).
+    pub p2p_port: Option<u16>,
+}
+
+impl GameKeywords for UnrealKeywords {
+    fn from_tags(tags: &[&str]) -> Self {
+        Self {
+            owning_id: parsed_value(tags, "OWNINGID"),
+            owning_name: value(tags, "OWNINGNAME").map(str::to_string),
+            session_flags: parsed_value(tags, "SESSIONFLAGS"),
+            build_id: parsed_value(tags, "BUILDID"),
+            open_public_connections: parsed_value(tags, "NUMOPENPUBCONN"),
+            p2p_address: value(tags, "P2PADDR").map(str::to_string),
+            p2p_port: parsed_value(tags, "P2PPORT"),
+        }
+    }
+}
+
+/// The game modes Counter-Strike 2 servers tag themselves with.
+const CS2_GAME_MODES: &[&str] = &[
+    "casual",
+    "competitive",
+    "wingman",
+    "deathmatch",
+    "armsrace",
+    "demolition",
+    "retakes",
+    "custom",
+];
+
+/// Counter-Strike 2's keywords.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Cs2Keywords {
+    /// The game mode, if the server tags it.
+    pub game_mode: Option<String>,
+    /// Whether the server is an official (Valve) one.
+    pub official: bool,
+    /// Whether the server is VAC secured.
+    pub secure: bool,
+    /// Whether the server reports itself as empty.
+    pub empty: bool,
+}
+
+impl GameKeywords for Cs2Keywords {
+    fn from_tags(tags: &[&str]) -> Self {
+        Self {
+            game_mode: tags
+                .iter()
+                .find(|tag| CS2_GAME_MODES.contains(tag))
+                .map(|tag| tag.to_string()),
+            official: has_tag(tags, "valve_ds"),
+            secure: has_tag(tags, "secure"),
+            empty: has_tag(tags, "empty"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rust() {
+        let keywords = RustKeywords::parse(
+            "mp250,cp200,qp0,v2511,h9b5a2f01,stok,born1718899200,gmrust,cs85b2e,carbon,pve,monthly,vanilla",
+        );
+
+        assert_eq!(
+            keywords,
+            RustKeywords {
+                players: Some(200),
+                max_players: Some(250),
+                queued_players: Some(0),
+                protocol: Some(2511),
+                born: Some(1_718_899_200),
+                game_mode: Some("rust".to_string()),
+                modded: true,
+                pve: true,
+            }
+        );
+        assert_eq!(
+            keywords.wipe_time(),
+            Some(UNIX_EPOCH + Duration::from_secs(1_718_899_200))
+        );
+
+        assert_eq!(RustKeywords::parse(""), RustKeywords::default());
+    }
+
+    #[test]
+    fn unreal() {
+        let keywords = UnrealKeywords::parse(
+            ",OWNINGID:90185938946281483,OWNINGNAME:90185938946281483,NUMOPENPUBCONN:50,P2PADDR:90185938946281483,\
+             P2PPORT:7777,LEGACY_i:0,SESSIONFLAGS:683",
+        );
+
+        assert_eq!(keywords.owning_id, Some(90_185_938_946_281_483));
+        assert_eq!(keywords.owning_name.as_deref(), Some("90185938946281483"));
+        assert_eq!(keywords.open_public_connections, Some(50));
+        assert_eq!(keywords.p2p_port, Some(7777));
+        assert_eq!(keywords.session_flags, Some(683));
+        assert_eq!(keywords.build_id, None);
+    }
+
+    #[test]
+    fn counter_strike_2() {
+        let keywords = Cs2Keywords::parse("valve_ds, competitive ,secure");
+
+        assert_eq!(keywords.game_mode.as_deref(), Some("competitive"));
+        assert!(keywords.official && keywords.secure && !keywords.empty);
+    }
+}
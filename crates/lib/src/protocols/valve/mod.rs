@@ -1,3 +1,5 @@
+pub mod arma;
+pub mod keywords;
 /// The implementation.
 pub mod protocol;
 /// All types used by the implementation.
@@ -13,6 +15,8 @@ pub use types::*;
 /// * `pretty_name` - The full name of the game, will be used as the
 ///   documentation for the created module.
 /// * `steam_app`, `default_port` - Passed through to [game_query_fn].
+/// * `keywords` - The [keywords::GameKeywords] of the game, decoded by the
+///   module's `keywords` function.
 #[cfg(feature = "games")]
 macro_rules! game_query_mod {
     ($mod_name: ident, $pretty_name: expr, $engine: expr, $default_port: literal) => {
@@ -25,6 +29,27 @@ macro_rules! game_query_mod {
         );
     };
 
+    ($mod_name: ident, $pretty_name: expr, $engine: expr, $default_port: literal, keywords: $keywords: ident) => {
+        #[doc = $pretty_name]
+        pub mod $mod_name {
+            use crate::protocols::valve::{Engine, GatheringSettings};
+
+            pub use crate::protocols::valve::keywords::{$keywords, GameKeywords};
+
+            crate::protocols::valve::game_query_fn!(
+                $pretty_name,
+                $engine,
+                $default_port,
+                GatheringSettings::default()
+            );
+
+            #[doc = concat!("Decode the keywords of a ", $pretty_name, " server's response (if it sent any).")]
+            pub fn keywords(response: &crate::protocols::valve::game::Response) -> Option<$keywords> {
+                response.keywords.as_deref().map(<$keywords>::parse)
+            }
+        }
+    };
+
     ($mod_name: ident, $pretty_name: expr, $engine: expr, $default_port: literal, $gathering_settings: expr) => {
         #[doc = $pretty_name]
         pub mod $mod_name {
@@ -42,6 +67,60 @@ macro_rules! game_query_mod {
 #[cfg(feature = "games")]
 pub(crate) use game_query_mod;
 
+/// Generate a module like [game_query_mod] for a game sending
+/// [binary rules](arma), with a `query_rules` function decoding them.
+///
+/// * `layout` - The [arma::ArmaLayout] of the game's rules.
+#[cfg(feature = "games")]
+macro_rules! arma_query_mod {
+    ($mod_name: ident, $pretty_name: expr, $engine: expr, $default_port: literal, $layout: expr) => {
+        #[doc = $pretty_name]
+        pub mod $mod_name {
+            use crate::protocols::valve::{arma::ArmaLayout, Engine, GatheringSettings};
+
+            pub use crate::protocols::valve::arma::{ArmaDifficulty, ArmaMod, ArmaRules};
+
+            crate::protocols::valve::game_query_fn!(
+                $pretty_name,
+                $engine,
+                $default_port,
+                GatheringSettings::default()
+            );
+
+            #[doc = concat!(
+                        "Query the rules of a ", $pretty_name, " server and decode them.\n\n",
+                        "If port is `None`, then the default port (", stringify!($default_port), ") will be used."
+                    )]
+            pub fn query_rules(address: &std::net::IpAddr, port: Option<u16>) -> crate::GDResult<ArmaRules> {
+                crate::protocols::valve::arma::query_rules(
+                    &std::net::SocketAddr::new(*address, port.unwrap_or($default_port)),
+                    $engine,
+                    $layout,
+                    None,
+                )
+            }
+
+            /// Async version of [query_rules].
+            #[cfg(feature = "async")]
+            pub async fn query_rules_async(
+                address: &std::net::IpAddr,
+                port: Option<u16>,
+            ) -> crate::GDResult<ArmaRules> {
+                crate::protocols::valve::arma::query_rules_async(
+                    &std::net::SocketAddr::new(*address, port.unwrap_or($default_port)),
+                    $engine,
+                    $layout,
+                    None,
+                )
+                .await
+            }
+        }
+    };
+}
+
+#[cfg(feature = "games")]
+pub(crate) use arma_query_mod;
+
 // Allow generating doc comments:
 // https://users.rust-lang.org/t/macros-filling-text-in-comments/20473
 /// Generate a query function for a valve game.
@@ -74,6 +153,23 @@ macro_rules! game_query_fn {
 
             Ok(crate::protocols::valve::game::Response::new_from_valve_response(valve_response))
         }
+
+        /// Async version of [query].
+        #[cfg(feature = "async")]
+        pub async fn query_async(
+            address: &std::net::IpAddr,
+            port: Option<u16>,
+        ) -> crate::GDResult<crate::protocols::valve::game::Response> {
+            let valve_response = crate::protocols::valve::query_async(
+                &std::net::SocketAddr::new(*address, port.unwrap_or($default_port)),
+                $engine,
+                Some($gathering_settings),
+                None,
+            )
+            .await?;
+
+            Ok(crate::protocols::valve::game::Response::new_from_valve_response(valve_response))
+        }
     };
 }
 
@@ -1,7 +1,7 @@
 use crate::{
     buffer::Buffer,
     protocols::{
-        types::TimeoutSettings,
+        types::{PlayerKind, QueryScope, RetryPolicy, SectionOutcome, TimeoutSettings},
         valve::{
             types::{
                 Environment,
@@ -19,154 +19,409 @@ use crate::{
         },
     },
     socket::{Socket, UdpSocket},
-    utils::{maybe_gather, retry_on_timeout, u8_lower_upper},
-    GDErrorKind::{BadGame, Decompress, UnknownEnumCast},
+    utils::{enforce_sections, gather_section, retry_on_timeout, u8_lower_upper},
+    ErrorContextExt,
+    GDErrorKind::{BadGame, Decompress, PacketBad, PacketOverflow, PacketUnderflow, UnknownEnumCast},
     GDResult,
 };
 
-use bzip2_rs::decoder::Decoder;
+#[cfg(feature = "async")]
+use crate::{
+    socket::{AsyncSocket, AsyncUdpSocket},
+    utils::retry_on_timeout_async,
+};
+
+use bzip2_rs::decoder::{Decoder, ReadState, WriteState};
 
 use crate::buffer::Utf8Decoder;
 use crate::protocols::valve::Packet;
 use byteorder::LittleEndian;
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::time::Duration;
+
+/// The biggest payload (joined from fragments or decompressed) accepted from a
+/// server, far above what servers send, so that one can't make us allocate
+/// arbitrary amounts of memory.
+const MAX_PAYLOAD_SIZE: usize = 1 << 20;
+
+/// The apps whose servers don't send the fragments' size in the header of split
+/// packets, when on protocol 7.
+const NO_SPLIT_SIZE_APPS: &[u32] = &[215, 240, 17550, 17700];
+
+/// How the header of a split packet's fragments is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SplitHeader {
+    /// A byte, holding the fragment's number (upper half) and the fragments
+    /// count (lower half).
+    GoldSrc,
+    /// A byte each for the fragments count and the fragment's number, followed
+    /// by the maximum size of fragments, which older engines don't send.
+    Source { has_size: bool },
+}
+
+impl SplitHeader {
+    /// The layout used by servers of `engine`.
+    fn of_engine(engine: &Engine, protocol: u8) -> Self {
+        match engine {
+            Engine::GoldSrc(_) => Self::GoldSrc,
+            Engine::Source(_) => {
+                Self::Source {
+                    has_size: !(protocol == 7
+                        && NO_SPLIT_SIZE_APPS
+                            .iter()
+                            .any(|appid| *engine == Engine::new(*appid))),
+                }
+            }
+        }
+    }
 
+    /// The layout of the (first) fragment `data`, told apart by where its
+    /// payload's header is, as servers don't always use their engine's one
+    /// (newer GoldSrc servers use Source's).
+    fn detect(data: &[u8]) -> Option<Self> {
+        let is_compressed = data.get(7).is_some_and(|id| id >> 7 == 1);
+        let header_at = |offset: usize| data.get(offset .. offset + 4) == Some(&[0xFF; 4]);
+        let is_first = data.get(9) == Some(&0);
+
+        if is_compressed {
+            // Only engines sending the size compress.
+            Some(Self::Source { has_size: true })
+        } else if header_at(9) {
+            Some(Self::GoldSrc)
+        } else if is_first && header_at(10) {
+            Some(Self::Source { has_size: false })
+        } else if is_first && header_at(12) {
+            Some(Self::Source { has_size: true })
+        } else {
+            None
+        }
+    }
+}
+
+/// A fragment of a split packet.
 #[derive(Debug)]
-#[allow(dead_code)] //remove this later on
 struct SplitPacket {
-    pub header: u32,
-    pub id: u32,
-    pub total: u8,
-    pub number: u8,
-    pub size: u16,
-    /// None means its not compressed, Some means it is
-    /// and it contains (size and crc32)
-    pub decompressed: Option<(u32, u32)>,
+    id: u32,
+    total: u8,
+    number: u8,
+    /// The size and CRC32 of the decompressed payload, only sent in the first
+    /// fragment of compressed packets.
+    decompressed: Option<(u32, u32)>,
     payload: Vec<u8>,
 }
 
 impl SplitPacket {
-    fn new(engine: &Engine, protocol: u8, buffer: &mut Buffer<LittleEndian>) -> GDResult<Self> {
-        let header = buffer.read()?; //buffer.get_u32()?;
-        let id = buffer.read()?;
-        let (total, number, size, decompressed) = match engine {
-            Engine::GoldSrc(_) => {
-                let (lower, upper) = u8_lower_upper(buffer.read()?);
-                (lower, upper, 0, None)
-            }
-            Engine::Source(_) => {
-                let total = buffer.read()?;
-                let number = buffer.read()?;
-                let size = match protocol == 7 && (*engine == Engine::new(240)) {
-                    // certain apps with protocol = 7 dont have this field, such as CSS
-                    false => buffer.read()?,
-                    true => 1248,
-                };
-
-                let is_compressed = ((id >> 31) & 1u32) == 1u32;
-                let decompressed = match is_compressed {
-                    false => None,
-                    true => Some((buffer.read()?, buffer.read()?)),
-                };
+    fn parse(layout: SplitHeader, data: &[u8]) -> GDResult<Self> {
+        let mut buffer = Buffer::<LittleEndian>::new(data);
 
-                (total, number, size, decompressed)
+        let _header: u32 = buffer.read()?;
+        let id: u32 = buffer.read()?;
+        let (total, number) = match layout {
+            SplitHeader::GoldSrc => u8_lower_upper(buffer.read()?),
+            SplitHeader::Source { has_size } => {
+                let total_and_number = (buffer.read()?, buffer.read()?);
+                if has_size {
+                    buffer.move_cursor(2)?;
+                }
+
+                total_and_number
             }
         };
 
+        if number >= total {
+            return Err(PacketBad.context(format!("Fragment {number} of a packet split in {total}")));
+        }
+
+        let is_compressed = matches!(layout, SplitHeader::Source { .. }) && id >> 31 == 1;
+        let decompressed = match is_compressed && number == 0 {
+            false => None,
+            true => Some((buffer.read()?, buffer.read()?)),
+        };
+
         Ok(Self {
-            header,
             id,
             total,
             number,
-            size,
             decompressed,
             payload: buffer.remaining_bytes().to_vec(),
         })
     }
+}
+
+/// The fragments of a split packet, which may be received in any order.
+#[derive(Debug)]
+struct SplitPackets {
+    layout: SplitHeader,
+    id: u32,
+    fragments: Vec<Option<SplitPacket>>,
+    size: usize,
+}
 
-    fn get_payload(&self) -> GDResult<Vec<u8>> {
-        if let Some(decompressed) = self.decompressed {
-            let mut decoder = Decoder::new();
-            decoder
-                .write(&self.payload)
-                .map_err(|e| Decompress.context(e))?;
+impl SplitPackets {
+    /// Start collecting the fragments of the packet `data` is a fragment of.
+    fn new(engine: &Engine, protocol: u8, data: &[u8]) -> GDResult<Self> {
+        let layout = SplitHeader::detect(data).unwrap_or_else(|| SplitHeader::of_engine(engine, protocol));
+        let fragment = SplitPacket::parse(layout, data)?;
 
-            let decompressed_size = decompressed.0 as usize;
+        let mut packets = Self {
+            layout,
+            id: fragment.id,
+            fragments: (0 .. fragment.total).map(|_| None).collect(),
+            size: 0,
+        };
+        packets.insert(fragment)?;
 
-            let mut decompressed_payload = vec![0; decompressed_size];
+        Ok(packets)
+    }
 
-            decoder
-                .read(&mut decompressed_payload)
-                .map_err(|e| Decompress.context(e))?;
+    /// Add the received datagram `data`, which is ignored if it isn't a new
+    /// fragment of this packet (such as a late answer to a retried request).
+    fn add(&mut self, data: &[u8]) -> GDResult<()> {
+        if !is_split(data) {
+            return Ok(());
+        }
 
-            if decompressed_payload.len() != decompressed_size
-                || crc32fast::hash(&decompressed_payload) != decompressed.1
-            {
-                Err(Decompress.context(format!(
-                    "Decompressed size {} was not expected {}",
-                    decompressed_payload.len(),
-                    decompressed_size
-                )))
-            } else {
-                Ok(decompressed_payload)
+        let fragment = SplitPacket::parse(self.layout, data)?;
+        match fragment.id == self.id {
+            true => self.insert(fragment),
+            false => Ok(()),
+        }
+    }
+
+    fn insert(&mut self, fragment: SplitPacket) -> GDResult<()> {
+        if usize::from(fragment.total) != self.fragments.len() {
+            return Err(PacketBad.context(format!(
+                "Fragment of a packet split in {} instead of {}",
+                fragment.total,
+                self.fragments.len()
+            )));
+        }
+
+        let slot = &mut self.fragments[usize::from(fragment.number)];
+        if slot.is_none() {
+            self.size += fragment.payload.len();
+            if self.size > MAX_PAYLOAD_SIZE {
+                return Err(PacketOverflow.context(format!(
+                    "Split packet is bigger than {MAX_PAYLOAD_SIZE} bytes"
+                )));
             }
-        } else {
-            Ok(self.payload.clone())
+
+            *slot = Some(fragment);
         }
+
+        Ok(())
+    }
+
+    fn is_complete(&self) -> bool { self.fragments.iter().all(Option::is_some) }
+
+    /// How many more datagrams to receive at most to get the missing
+    /// fragments, leaving room for duplicated and stray ones.
+    fn max_datagrams(&self) -> usize { self.fragments.len() * 2 }
+
+    /// Join the fragments and parse the (possibly decompressed) result.
+    fn reassemble(self) -> GDResult<Packet> {
+        let missing = self.fragments.iter().filter(|f| f.is_none()).count();
+        if missing > 0 {
+            return Err(PacketBad.context(format!(
+                "Missing {missing} of the {} fragments of a split packet",
+                self.fragments.len()
+            )));
+        }
+
+        let mut payload = Vec::with_capacity(self.size);
+        let mut decompressed = None;
+        for fragment in self.fragments.into_iter().flatten() {
+            decompressed = decompressed.or(fragment.decompressed);
+            payload.extend(fragment.payload);
+        }
+
+        let payload = match decompressed {
+            None => payload,
+            Some((size, crc)) => decompress(&payload, size, crc)?,
+        };
+
+        Packet::new_from_bufferer(&mut Buffer::<LittleEndian>::new(&payload))
     }
 }
 
+/// Decompress the bzip2 `data` of a split packet, which should be `size` bytes
+/// with a CRC32 of `crc` once decompressed.
+fn decompress(data: &[u8], size: u32, crc: u32) -> GDResult<Vec<u8>> {
+    let size = size as usize;
+    if size > MAX_PAYLOAD_SIZE {
+        return Err(PacketOverflow.context(format!(
+            "Decompressed size {size} is bigger than {MAX_PAYLOAD_SIZE} bytes"
+        )));
+    }
+
+    let mut decoder = Decoder::new();
+    let mut input = data;
+    let mut decompressed = Vec::with_capacity(size);
+    let mut chunk = [0; 4096];
+    // How many times in a row the decoder asked for data when there was no more
+    // of it, which it does endlessly when the data is truncated.
+    let mut starved = 0;
+
+    loop {
+        match decoder
+            .read(&mut chunk)
+            .map_err(|e| Decompress.context(e))?
+        {
+            ReadState::NeedsWrite(_) => {
+                if input.is_empty() {
+                    starved += 1;
+                    if starved > 2 {
+                        return Err(Decompress.context("Compressed data is truncated"));
+                    }
+                }
+
+                // Writing nothing tells the decoder that the data ended.
+                if let WriteState::Written(written) = decoder.write(input).map_err(|e| Decompress.context(e))? {
+                    input = &input[written ..];
+                }
+            }
+            ReadState::Read(read) => {
+                if decompressed.len() + read > size {
+                    return Err(Decompress.context(format!(
+                        "Decompressed data is bigger than the expected {size} bytes"
+                    )));
+                }
+
+                decompressed.extend_from_slice(&chunk[.. read]);
+                starved = 0;
+            }
+            ReadState::Eof => break,
+        }
+    }
+
+    if decompressed.len() != size {
+        return Err(Decompress.context(format!(
+            "Decompressed size {} was not expected {size}",
+            decompressed.len()
+        )));
+    }
+
+    if crc32fast::hash(&decompressed) != crc {
+        return Err(Decompress.context("Decompressed data's CRC32 did not match"));
+    }
+
+    Ok(decompressed)
+}
+
+/// Whether the datagram `data` is a fragment of a split packet.
+fn is_split(data: &[u8]) -> bool { data.first() == Some(&0xFE) }
+
+/// Reads the packet answering a request out of the datagrams received,
+/// reassembling split packets.
+struct PacketReader<'a> {
+    engine: &'a Engine,
+    protocol: u8,
+    split: Option<SplitPackets>,
+    /// Datagrams received after the first fragment of the split packet.
+    received: usize,
+}
+
+impl<'a> PacketReader<'a> {
+    const fn new(engine: &'a Engine, protocol: u8) -> Self {
+        Self {
+            engine,
+            protocol,
+            split: None,
+            received: 0,
+        }
+    }
+
+    /// Add the received datagram `data`, returning the packet once it is
+    /// whole (or once the missing fragments aren't worth waiting for, which
+    /// fails).
+    fn add(&mut self, data: &[u8]) -> GDResult<Option<Packet>> {
+        match &mut self.split {
+            Some(packets) => {
+                packets.add(data).with_data(data)?;
+                self.received += 1;
+            }
+            None if is_split(data) => {
+                self.split = Some(SplitPackets::new(self.engine, self.protocol, data).with_data(data)?);
+            }
+            None => {
+                return Packet::new_from_bufferer(&mut Buffer::<LittleEndian>::new(data))
+                    .with_data(data)
+                    .map(Some);
+            }
+        }
+
+        match self.split.take() {
+            Some(packets) if packets.is_complete() || self.received >= packets.max_datagrams() => {
+                packets.reassemble().map(Some)
+            }
+            split => {
+                self.split = split;
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// A packet received for a request: either the answer's data or a challenge,
+/// to reply to with the packet it holds.
+enum Answer {
+    Data(Vec<u8>),
+    Challenge(Vec<u8>),
+}
+
+impl Answer {
+    /// Tell what `packet`, received for a `kind` request, is.
+    fn of(kind: u8, packet: Packet) -> Self {
+        match packet.kind {
+            // 'A'
+            0x41 => Self::Challenge(challenge_response(kind, packet.payload)),
+            _ => Self::Data(packet.payload),
+        }
+    }
+}
+
+/// Create the packet that answers a challenge received for a `kind` request.
+fn challenge_response(kind: u8, challenge: Vec<u8>) -> Vec<u8> {
+    const INFO: u8 = Request::Info as u8;
+    Packet::new(
+        kind,
+        match kind {
+            INFO => [Request::Info.get_default_payload(), challenge].concat(),
+            _ => challenge,
+        },
+    )
+    .to_bytes()
+}
+
 pub(crate) struct ValveProtocol {
     socket: UdpSocket,
-    retry_count: usize,
+    retry_policy: RetryPolicy,
 }
 
 static PACKET_SIZE: usize = 6144;
 
 impl ValveProtocol {
-    pub fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>) -> GDResult<Self> {
-        let socket = UdpSocket::new(address, &timeout_settings)?;
-        let retry_count = timeout_settings.as_ref().map_or_else(
-            || TimeoutSettings::default().get_retries(),
-            TimeoutSettings::get_retries,
-        );
+    pub fn new(address: &SocketAddr, timeout_settings: Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self> {
+        let socket = UdpSocket::new(address, &timeout_settings, scope)?;
+        let retry_policy = TimeoutSettings::get_retry_policy_or_default(&timeout_settings);
 
         Ok(Self {
             socket,
-            retry_count,
+            retry_policy,
         })
     }
 
-    fn receive(&mut self, engine: &Engine, protocol: u8, buffer_size: usize) -> GDResult<Packet> {
-        let data = self.socket.receive(Some(buffer_size))?;
-        let mut buffer = Buffer::<LittleEndian>::new(&data);
-
-        let header: u8 = buffer.read()?;
-        buffer.move_cursor(-1)?;
-        if header == 0xFE {
-            // the packet is split
-            let mut main_packet = SplitPacket::new(engine, protocol, &mut buffer)?;
-            let mut chunk_packets = Vec::with_capacity((main_packet.total - 1) as usize);
-
-            for _ in 1 .. main_packet.total {
-                let new_data = self.socket.receive(Some(buffer_size))?;
-                buffer = Buffer::<LittleEndian>::new(&new_data);
-                let chunk_packet = SplitPacket::new(engine, protocol, &mut buffer)?;
-                chunk_packets.push(chunk_packet);
-            }
-
-            chunk_packets.sort_by(|a, b| a.number.cmp(&b.number));
+    /// The latency of the first exchange with the server.
+    pub fn latency(&self) -> Option<Duration> { self.socket.latency() }
 
-            for chunk_packet in chunk_packets {
-                main_packet.payload.extend(chunk_packet.payload);
+    fn receive(&mut self, engine: &Engine, protocol: u8, buffer_size: usize) -> GDResult<Packet> {
+        let mut reader = PacketReader::new(engine, protocol);
+        loop {
+            let data = self.socket.receive(Some(buffer_size))?;
+            if let Some(packet) = reader.add(&data)? {
+                return Ok(packet);
             }
-
-            let payload = main_packet.get_payload()?; // Creating a non-temporary value here
-            let mut new_packet_buffer = Buffer::<LittleEndian>::new(&payload); // Using the non-temporary value here
-            Ok(Packet::new_from_bufferer(&mut new_packet_buffer)?)
-        } else {
-            Packet::new_from_bufferer(&mut buffer)
         }
     }
 
@@ -178,7 +433,7 @@ impl ValveProtocol {
     /// Ask for a specific request only.
     /// This function will retry fetch on timeouts.
     pub fn get_request_data(&mut self, engine: &Engine, protocol: u8, kind: u8, payload: Vec<u8>) -> GDResult<Vec<u8>> {
-        retry_on_timeout(self.retry_count, || {
+        retry_on_timeout(self.retry_policy, || {
             self.get_request_data_impl(engine, protocol, kind, payload.clone())
         })
     }
@@ -191,238 +446,407 @@ impl ValveProtocol {
         kind: u8,
         payload: Vec<u8>,
     ) -> GDResult<Vec<u8>> {
-        let request_initial_packet = Packet::new(kind, payload).to_bytes();
-        self.socket.send(&request_initial_packet)?;
+        self.socket.send(&Packet::new(kind, payload).to_bytes())?;
 
         let mut packet = self.receive(engine, protocol, PACKET_SIZE)?;
-        while packet.kind == 0x41 {
-            // 'A'
-            let challenge = packet.payload;
-
-            const INFO: u8 = Request::Info as u8;
-            let challenge_packet = Packet::new(
-                kind,
-                match kind {
-                    INFO => [Request::Info.get_default_payload(), challenge].concat(),
-                    _ => challenge,
-                },
-            )
-            .to_bytes();
-
-            self.socket.send(&challenge_packet)?;
-
-            packet = self.receive(engine, protocol, PACKET_SIZE)?;
-        }
-
-        Ok(packet.payload)
-    }
-
-    fn get_goldsrc_server_info(buffer: &mut Buffer<LittleEndian>) -> GDResult<ServerInfo> {
-        let _header: u8 = buffer.read()?; //get the header (useless info)
-        let _address: String = buffer.read_string::<Utf8Decoder>(None)?; //get the server address (useless info)
-        let name = buffer.read_string::<Utf8Decoder>(None)?;
-        let map = buffer.read_string::<Utf8Decoder>(None)?;
-        let folder = buffer.read_string::<Utf8Decoder>(None)?;
-        let game_mode = buffer.read_string::<Utf8Decoder>(None)?;
-        let players = buffer.read()?;
-        let max_players = buffer.read()?;
-        let protocol = buffer.read()?;
-        let server_type = match buffer.read::<u8>()? {
-            68 => Server::Dedicated,    //'D'
-            76 => Server::NonDedicated, //'L'
-            80 => Server::TV,           //'P'
-            _ => Err(UnknownEnumCast)?,
-        };
-        let environment_type = match buffer.read::<u8>()? {
-            76 => Environment::Linux,   //'L'
-            87 => Environment::Windows, //'W'
-            _ => Err(UnknownEnumCast)?,
-        };
-        let has_password = buffer.read::<u8>()? == 1;
-        let is_mod = buffer.read::<u8>()? == 1;
-        let mod_data = match is_mod {
-            false => None,
-            true => {
-                Some(ModData {
-                    link: buffer.read_string::<Utf8Decoder>(None)?,
-                    download_link: buffer.read_string::<Utf8Decoder>(None)?,
-                    version: buffer.read()?,
-                    size: buffer.read()?,
-                    multiplayer_only: buffer.read::<u8>()? == 1,
-                    has_own_dll: buffer.read::<u8>()? == 1,
-                })
+        loop {
+            match Answer::of(kind, packet) {
+                Answer::Data(data) => return Ok(data),
+                Answer::Challenge(reply) => {
+                    self.socket.send(&reply).with_stage("challenge")?;
+                    packet = self
+                        .receive(engine, protocol, PACKET_SIZE)
+                        .with_stage("challenge")?;
+                }
             }
-        };
-        let vac_secured = buffer.read::<u8>()? == 1;
-        let bots = buffer.read::<u8>()?;
-
-        Ok(ServerInfo {
-            protocol_version: protocol,
-            name,
-            map,
-            folder,
-            game_mode,
-            appid: 0, // not present in the obsolete response
-            players_online: players,
-            players_maximum: max_players,
-            players_bots: bots,
-            server_type,
-            environment_type,
-            has_password,
-            vac_secured,
-            the_ship: None,
-            game_version: String::new(), // a version field only for the mod
-            extra_data: None,
-            is_mod,
-            mod_data,
-        })
+        }
     }
 
     /// Get the server information's.
     fn get_server_info(&mut self, engine: &Engine) -> GDResult<ServerInfo> {
-        let data = self.get_kind_request_data(engine, 0, Request::Info)?;
-        let mut buffer = Buffer::<LittleEndian>::new(&data);
+        let data = self
+            .get_kind_request_data(engine, 0, Request::Info)
+            .with_stage("info")?;
+        info_section(engine, &data)
+    }
 
-        if let Engine::GoldSrc(force) = engine {
-            if *force {
-                return Self::get_goldsrc_server_info(&mut buffer);
-            }
-        }
+    /// Get the server player's.
+    fn get_server_players(&mut self, engine: &Engine, info: &ServerInfo) -> GDResult<Vec<ServerPlayer>> {
+        let data = self
+            .get_kind_request_data(engine, info.protocol_version, Request::Players)
+            .with_stage("players")?;
+        players_section(engine, info, &data)
+    }
 
-        let protocol = buffer.read()?;
-        let name = buffer.read_string::<Utf8Decoder>(None)?;
-        let map = buffer.read_string::<Utf8Decoder>(None)?;
-        let folder = buffer.read_string::<Utf8Decoder>(None)?;
-        let game_mode = buffer.read_string::<Utf8Decoder>(None)?;
-        let mut appid = buffer.read::<u16>()? as u32;
-        let players = buffer.read()?;
-        let max_players = buffer.read()?;
-        let bots = buffer.read()?;
-        let server_type = Server::from_gldsrc(buffer.read()?)?;
-        let environment_type = Environment::from_gldsrc(buffer.read()?)?;
-        let has_password = buffer.read::<u8>()? == 1;
-        let vac_secured = buffer.read::<u8>()? == 1;
-        let the_ship = match *engine == Engine::new(2400) {
-            false => None,
-            true => {
-                Some(TheShip {
-                    mode: buffer.read()?,
-                    witnesses: buffer.read()?,
-                    duration: buffer.read()?,
-                })
-            }
-        };
-        let game_version = buffer.read_string::<Utf8Decoder>(None)?;
-        let extra_data = match buffer.read::<u8>() {
-            Err(_) => None,
-            Ok(value) => {
-                Some(ExtraData {
-                    port: match (value & 0x80) > 0 {
-                        false => None,
-                        true => Some(buffer.read()?),
-                    },
-                    steam_id: match (value & 0x10) > 0 {
-                        false => None,
-                        true => Some(buffer.read()?),
-                    },
-                    tv_port: match (value & 0x40) > 0 {
-                        false => None,
-                        true => Some(buffer.read()?),
-                    },
-                    tv_name: match (value & 0x40) > 0 {
-                        false => None,
-                        true => Some(buffer.read_string::<Utf8Decoder>(None)?),
-                    },
-                    keywords: match (value & 0x20) > 0 {
-                        false => None,
-                        true => Some(buffer.read_string::<Utf8Decoder>(None)?),
-                    },
-                    game_id: match (value & 0x01) > 0 {
-                        false => None,
-                        true => {
-                            let gid = buffer.read()?;
-                            appid = (gid & ((1 << 24) - 1)) as u32;
-
-                            Some(gid)
-                        }
-                    },
-                })
-            }
-        };
+    /// Get the server's rules.
+    fn get_server_rules(&mut self, engine: &Engine, protocol: u8) -> GDResult<HashMap<String, String>> {
+        let data = self
+            .get_kind_request_data(engine, protocol, Request::Rules)
+            .with_stage("rules")?;
+        rules_section(engine, &data)
+    }
 
-        Ok(ServerInfo {
-            protocol_version: protocol,
-            name,
-            map,
-            folder,
-            game_mode,
-            appid,
-            players_online: players,
-            players_maximum: max_players,
-            players_bots: bots,
-            server_type,
-            environment_type,
-            has_password,
-            vac_secured,
-            the_ship,
-            game_version,
-            extra_data,
-            is_mod: false,
-            mod_data: None,
-        })
+    /// Get the server's rules, without decoding their names and values (which
+    /// some games use to send binary data, see [arma](super::arma)).
+    pub fn get_server_rules_raw(&mut self, engine: &Engine, protocol: u8) -> GDResult<Vec<RawRule>> {
+        let data = self
+            .get_kind_request_data(engine, protocol, Request::Rules)
+            .with_stage("rules")?;
+        raw_rules_section(&data)
     }
+}
 
-    /// Get the server player's.
-    fn get_server_players(&mut self, engine: &Engine, protocol: u8) -> GDResult<Vec<ServerPlayer>> {
-        let data = self.get_kind_request_data(engine, protocol, Request::Players)?;
-        let mut buffer = Buffer::<LittleEndian>::new(&data);
+/// Parse the obsolete GoldSrc server information's response.
+fn parse_goldsrc_server_info(buffer: &mut Buffer<LittleEndian>) -> GDResult<ServerInfo> {
+    let _header: u8 = buffer.read()?; //get the header (useless info)
+    let _address: String = buffer.read_string::<Utf8Decoder>(None)?; //get the server address (useless info)
+    let name = buffer.read_string::<Utf8Decoder>(None)?;
+    let map = buffer.read_string::<Utf8Decoder>(None)?;
+    let folder = buffer.read_string::<Utf8Decoder>(None)?;
+    let game_mode = buffer.read_string::<Utf8Decoder>(None)?;
+    let players = buffer.read()?;
+    let max_players = buffer.read()?;
+    let protocol = buffer.read()?;
+    let server_type = match buffer.read::<u8>()? {
+        68 => Server::Dedicated,    //'D'
+        76 => Server::NonDedicated, //'L'
+        80 => Server::TV,           //'P'
+        _ => Err(UnknownEnumCast)?,
+    };
+    let environment_type = match buffer.read::<u8>()? {
+        76 => Environment::Linux,   //'L'
+        87 => Environment::Windows, //'W'
+        _ => Err(UnknownEnumCast)?,
+    };
+    let has_password = buffer.read::<u8>()? == 1;
+    let is_mod = buffer.read::<u8>()? == 1;
+    let mod_data = match is_mod {
+        false => None,
+        true => {
+            Some(ModData {
+                link: buffer.read_string::<Utf8Decoder>(None)?,
+                download_link: buffer.read_string::<Utf8Decoder>(None)?,
+                version: buffer.read()?,
+                size: buffer.read()?,
+                multiplayer_only: buffer.read::<u8>()? == 1,
+                has_own_dll: buffer.read::<u8>()? == 1,
+            })
+        }
+    };
+    let vac_secured = buffer.read::<u8>()? == 1;
+    let bots = buffer.read::<u8>()?;
+
+    Ok(ServerInfo {
+        protocol_version: protocol,
+        name,
+        map,
+        folder,
+        game_mode,
+        appid: 0, // not present in the obsolete response
+        players_online: players,
+        players_maximum: max_players,
+        players_bots: bots,
+        server_type,
+        environment_type,
+        has_password,
+        vac_secured,
+        the_ship: None,
+        game_version: String::new(), // a version field only for the mod
+        extra_data: None,
+        is_mod,
+        mod_data,
+    })
+}
 
-        let count = buffer.read::<u8>()? as usize;
-        let mut players: Vec<ServerPlayer> = Vec::with_capacity(count);
+/// Parse the server information's response.
+fn parse_server_info(engine: &Engine, data: &[u8]) -> GDResult<ServerInfo> {
+    let mut buffer = Buffer::<LittleEndian>::new(data);
 
-        for _ in 0 .. count {
-            buffer.move_cursor(1)?; //skip the index byte
+    if let Engine::GoldSrc(force) = engine {
+        if *force {
+            return parse_goldsrc_server_info(&mut buffer);
+        }
+    }
 
-            players.push(ServerPlayer {
-                name: buffer.read_string::<Utf8Decoder>(None)?,
-                score: buffer.read()?,
+    let protocol = buffer.read()?;
+    let name = buffer.read_string::<Utf8Decoder>(None)?;
+    let map = buffer.read_string::<Utf8Decoder>(None)?;
+    let folder = buffer.read_string::<Utf8Decoder>(None)?;
+    let game_mode = buffer.read_string::<Utf8Decoder>(None)?;
+    let mut appid = buffer.read::<u16>()? as u32;
+    let players = buffer.read()?;
+    let max_players = buffer.read()?;
+    let bots = buffer.read()?;
+    let server_type = Server::from_gldsrc(buffer.read()?)?;
+    let environment_type = Environment::from_gldsrc(buffer.read()?)?;
+    let has_password = buffer.read::<u8>()? == 1;
+    let vac_secured = buffer.read::<u8>()? == 1;
+    let the_ship = match *engine == Engine::new(2400) {
+        false => None,
+        true => {
+            Some(TheShip {
+                mode: buffer.read()?,
+                witnesses: buffer.read()?,
                 duration: buffer.read()?,
-                deaths: match *engine == Engine::new(2400) {
+            })
+        }
+    };
+    let game_version = buffer.read_string::<Utf8Decoder>(None)?;
+    let extra_data = match buffer.read::<u8>() {
+        Err(_) => None,
+        Ok(value) => {
+            Some(ExtraData {
+                port: match (value & 0x80) > 0 {
+                    false => None,
+                    true => Some(buffer.read()?),
+                },
+                steam_id: match (value & 0x10) > 0 {
                     false => None,
                     true => Some(buffer.read()?),
                 },
-                money: match *engine == Engine::new(2400) {
+                tv_port: match (value & 0x40) > 0 {
                     false => None,
                     true => Some(buffer.read()?),
                 },
-            });
+                tv_name: match (value & 0x40) > 0 {
+                    false => None,
+                    true => Some(buffer.read_string::<Utf8Decoder>(None)?),
+                },
+                keywords: match (value & 0x20) > 0 {
+                    false => None,
+                    true => Some(buffer.read_string::<Utf8Decoder>(None)?),
+                },
+                game_id: match (value & 0x01) > 0 {
+                    false => None,
+                    true => {
+                        let gid = buffer.read()?;
+                        appid = (gid & ((1 << 24) - 1)) as u32;
+
+                        Some(gid)
+                    }
+                },
+            })
         }
+    };
+
+    Ok(ServerInfo {
+        protocol_version: protocol,
+        name,
+        map,
+        folder,
+        game_mode,
+        appid,
+        players_online: players,
+        players_maximum: max_players,
+        players_bots: bots,
+        server_type,
+        environment_type,
+        has_password,
+        vac_secured,
+        the_ship,
+        game_version,
+        extra_data,
+        is_mod: false,
+        mod_data: None,
+    })
+}
+
+/// Parse the server player's response.
+/// Parse the server's players, `tv_name` being the name of the server's
+/// SourceTV (if it has one).
+fn parse_server_players(engine: &Engine, tv_name: Option<&str>, data: &[u8]) -> GDResult<Vec<ServerPlayer>> {
+    let mut buffer = Buffer::<LittleEndian>::new(data);
+
+    let count = buffer.read::<u8>()? as usize;
+    let mut players: Vec<ServerPlayer> = Vec::with_capacity(count);
 
-        Ok(players)
+    for _ in 0 .. count {
+        buffer.move_cursor(1)?; //skip the index byte
+
+        let name = buffer.read_string::<Utf8Decoder>(None)?;
+        players.push(ServerPlayer {
+            kind: player_kind(&name, tv_name),
+            name,
+            score: buffer.read()?,
+            duration: buffer.read()?,
+            deaths: match *engine == Engine::new(2400) {
+                false => None,
+                true => Some(buffer.read()?),
+            },
+            money: match *engine == Engine::new(2400) {
+                false => None,
+                true => Some(buffer.read()?),
+            },
+        });
     }
 
-    /// Get the server's rules.
-    fn get_server_rules(&mut self, engine: &Engine, protocol: u8) -> GDResult<HashMap<String, String>> {
-        let data = self.get_kind_request_data(engine, protocol, Request::Rules)?;
-        let mut buffer = Buffer::<LittleEndian>::new(&data);
+    Ok(players)
+}
 
-        let count = buffer.read::<u16>()? as usize;
-        let mut rules: HashMap<String, String> = HashMap::with_capacity(count);
+/// Names of the spectator relays of the Source engine games.
+const RELAY_NAMES: &[&str] = &["SourceTV", "GOTV", "HLTV", "Replay"];
 
-        for _ in 0 .. count {
-            let name = buffer.read_string::<Utf8Decoder>(None)?;
-            let value = buffer.read_string::<Utf8Decoder>(None)?;
+/// Tell what a player entry is by its name, `tv_name` being the name of the
+/// server's SourceTV (if it has one).
+fn player_kind(name: &str, tv_name: Option<&str>) -> PlayerKind {
+    // Some servers pad names with spaces or null characters.
+    let name = name.trim_matches(|c: char| c.is_whitespace() || c.is_control());
 
-            rules.insert(name, value);
-        }
+    if name.is_empty() {
+        PlayerKind::Unnamed
+    } else if tv_name == Some(name)
+        || RELAY_NAMES
+            .iter()
+            .any(|relay| relay.eq_ignore_ascii_case(name))
+    {
+        PlayerKind::Relay
+    } else if name == "BOT" || name.starts_with("BOT ") || name.starts_with("[BOT]") || name.ends_with(" BOT") {
+        PlayerKind::Bot
+    } else {
+        PlayerKind::Player
+    }
+}
+
+/// Leave the unnamed and relay entries out of `players`.
+fn filter_players(players: Option<Vec<ServerPlayer>>) -> Option<Vec<ServerPlayer>> {
+    players.map(|players| {
+        players
+            .into_iter()
+            .filter(|player| !matches!(player.kind, PlayerKind::Unnamed | PlayerKind::Relay))
+            .collect()
+    })
+}
+
+/// Parse the server's rules response.
+fn parse_server_rules(engine: &Engine, data: &[u8]) -> GDResult<HashMap<String, String>> {
+    let mut buffer = Buffer::<LittleEndian>::new(data);
+
+    let count = buffer.read::<u16>()? as usize;
+    let mut rules: HashMap<String, String> = HashMap::with_capacity(count);
+
+    for _ in 0 .. count {
+        let name = buffer.read_string::<Utf8Decoder>(None)?;
+        let value = buffer.read_string::<Utf8Decoder>(None)?;
+
+        rules.insert(name, value);
+    }
+
+    if *engine == Engine::new(632_360) {
+        // ROR2
+        rules.remove("Test");
+    }
 
-        if *engine == Engine::new(632_360) {
-            // ROR2
-            rules.remove("Test");
+    Ok(rules)
+}
+
+/// A rule's name and value, as sent by the server.
+pub type RawRule = (Vec<u8>, Vec<u8>);
+
+fn parse_server_rules_raw(data: &[u8]) -> GDResult<Vec<RawRule>> {
+    let mut buffer = Buffer::<LittleEndian>::new(data);
+
+    let count = buffer.read::<u16>()? as usize;
+    let mut rules = Vec::with_capacity(count);
+
+    for _ in 0 .. count {
+        let name = read_raw_string(&mut buffer)?;
+        let value = read_raw_string(&mut buffer)?;
+
+        rules.push((name, value));
+    }
+
+    Ok(rules)
+}
+
+/// Read the bytes up to the next null terminator (which is skipped).
+fn read_raw_string(buffer: &mut Buffer<LittleEndian>) -> GDResult<Vec<u8>> {
+    let remaining = buffer.remaining_bytes();
+    let length = remaining
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or_else(|| PacketUnderflow.context("Unterminated string"))?;
+    let string = remaining[.. length].to_vec();
+
+    buffer.move_cursor(length as isize + 1)?;
+
+    Ok(string)
+}
+
+/// Parse the answer to the info request.
+fn info_section(engine: &Engine, data: &[u8]) -> GDResult<ServerInfo> {
+    parse_server_info(engine, data)
+        .with_data(data)
+        .with_stage("info")
+}
+
+/// Parse the answer to the players request of the server `info` is of.
+fn players_section(engine: &Engine, info: &ServerInfo, data: &[u8]) -> GDResult<Vec<ServerPlayer>> {
+    let tv_name = info
+        .extra_data
+        .as_ref()
+        .and_then(|extra_data| extra_data.tv_name.as_deref());
+    parse_server_players(engine, tv_name, data)
+        .with_data(data)
+        .with_stage("players")
+}
+
+/// Parse the answer to the rules request.
+fn rules_section(engine: &Engine, data: &[u8]) -> GDResult<HashMap<String, String>> {
+    parse_server_rules(engine, data)
+        .with_data(data)
+        .with_stage("rules")
+}
+
+/// Parse the answer to the rules request, without decoding the rules.
+fn raw_rules_section(data: &[u8]) -> GDResult<Vec<RawRule>> {
+    parse_server_rules_raw(data)
+        .with_data(data)
+        .with_stage("rules")
+}
+
+/// Put the sections gathered as per `gather_settings` together.
+fn response(
+    info: ServerInfo,
+    (players, players_outcome): (Option<Vec<ServerPlayer>>, SectionOutcome),
+    (rules, rules_outcome): (Option<HashMap<String, String>>, SectionOutcome),
+    gather_settings: &GatheringSettings,
+    latency: Option<Duration>,
+) -> Response {
+    let players = match gather_settings.filter_players {
+        true => filter_players(players),
+        false => players,
+    };
+
+    Response {
+        info,
+        players,
+        rules,
+        latency,
+        players_outcome,
+        rules_outcome,
+    }
+}
+
+/// Check that the server's app id is one of the ids of the engine, returning
+/// a [BadGame] error when it isn't (and `check_app_id` is set).
+fn check_app_id(engine: &Engine, info: &ServerInfo, check_app_id: bool) -> GDResult<()> {
+    if let Engine::Source(Some(appids)) = engine {
+        let mut is_specified_id = false;
+
+        if appids.0 == info.appid {
+            is_specified_id = true;
+        } else if let Some(dedicated_appid) = appids.1 {
+            if dedicated_appid == info.appid {
+                is_specified_id = true;
+            }
         }
 
-        Ok(rules)
+        if !is_specified_id && check_app_id {
+            return Err(BadGame.context(format!("AppId: {}", info.appid)));
+        }
     }
+
+    Ok(())
 }
 
 /// Query a server by providing the address, the port, the app, gather and
@@ -435,48 +859,489 @@ pub fn query(
     engine: Engine,
     gather_settings: Option<GatheringSettings>,
     timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope(address, engine, gather_settings, timeout_settings, &scope)
+}
+
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(
+    address: &SocketAddr,
+    engine: Engine,
+    gather_settings: Option<GatheringSettings>,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
 ) -> GDResult<Response> {
     let response_gather_settings = gather_settings.unwrap_or_default();
-    get_response(address, engine, response_gather_settings, timeout_settings)
+    get_response(
+        address,
+        engine,
+        response_gather_settings,
+        timeout_settings,
+        scope,
+    )
+    .with_address(*address)
+    .with_protocol("valve")
 }
 
+/// Gather the info, then the players and rules sections as set, failing with
+/// the response gathered so far as the error's
+/// [partial response](crate::GDError#structfield.partial_response) if an
+/// enforced section failed.
 fn get_response(
     address: &SocketAddr,
     engine: Engine,
     gather_settings: GatheringSettings,
     timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
 ) -> GDResult<Response> {
-    let mut client = ValveProtocol::new(address, timeout_settings)?;
+    let mut client = ValveProtocol::new(address, timeout_settings, scope)?;
 
     let info = client.get_server_info(&engine)?;
+    check_app_id(&engine, &info, gather_settings.check_app_id).with_stage("info")?;
 
-    if let Engine::Source(Some(appids)) = &engine {
-        let mut is_specified_id = false;
+    let protocol = info.protocol_version;
 
-        if appids.0 == info.appid {
-            is_specified_id = true;
-        } else if let Some(dedicated_appid) = appids.1 {
-            if dedicated_appid == info.appid {
-                is_specified_id = true;
+    let mut enforced_failure = None;
+    let players = gather_section!(
+        gather_settings.players,
+        client.get_server_players(&engine, &info),
+        enforced_failure
+    );
+    let rules = gather_section!(
+        gather_settings.rules,
+        client.get_server_rules(&engine, protocol),
+        enforced_failure
+    );
+
+    enforce_sections(
+        response(info, players, rules, &gather_settings, client.latency()),
+        enforced_failure,
+    )
+}
+
+/// Async version of [ValveProtocol].
+#[cfg(feature = "async")]
+pub(crate) struct AsyncValveProtocol {
+    socket: AsyncUdpSocket,
+    retry_policy: RetryPolicy,
+}
+
+#[cfg(feature = "async")]
+impl AsyncValveProtocol {
+    pub async fn new(
+        address: &SocketAddr,
+        timeout_settings: Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<Self> {
+        let socket = AsyncUdpSocket::new(address, &timeout_settings, scope).await?;
+        let retry_policy = TimeoutSettings::get_retry_policy_or_default(&timeout_settings);
+
+        Ok(Self {
+            socket,
+            retry_policy,
+        })
+    }
+
+    /// Async version of [ValveProtocol::latency].
+    pub fn latency(&self) -> Option<Duration> { self.socket.latency() }
+
+    async fn receive(&mut self, engine: &Engine, protocol: u8, buffer_size: usize) -> GDResult<Packet> {
+        let mut reader = PacketReader::new(engine, protocol);
+        loop {
+            let data = self.socket.receive(Some(buffer_size)).await?;
+            if let Some(packet) = reader.add(&data)? {
+                return Ok(packet);
             }
         }
+    }
 
-        if !is_specified_id && gather_settings.check_app_id {
-            return Err(BadGame.context(format!("AppId: {}", info.appid)));
+    pub async fn get_kind_request_data(&mut self, engine: &Engine, protocol: u8, kind: Request) -> GDResult<Vec<u8>> {
+        self.get_request_data(engine, protocol, kind as u8, kind.get_default_payload())
+            .await
+    }
+
+    /// Ask for a specific request only.
+    /// This function will retry fetch on timeouts.
+    pub async fn get_request_data(
+        &mut self,
+        engine: &Engine,
+        protocol: u8,
+        kind: u8,
+        payload: Vec<u8>,
+    ) -> GDResult<Vec<u8>> {
+        retry_on_timeout_async!(
+            self.retry_policy,
+            self.get_request_data_impl(engine, protocol, kind, payload.clone())
+        )
+    }
+
+    /// Ask for a specific request only (without retry logic).
+    async fn get_request_data_impl(
+        &mut self,
+        engine: &Engine,
+        protocol: u8,
+        kind: u8,
+        payload: Vec<u8>,
+    ) -> GDResult<Vec<u8>> {
+        self.socket
+            .send(&Packet::new(kind, payload).to_bytes())
+            .await?;
+
+        let mut packet = self.receive(engine, protocol, PACKET_SIZE).await?;
+        loop {
+            match Answer::of(kind, packet) {
+                Answer::Data(data) => return Ok(data),
+                Answer::Challenge(reply) => {
+                    self.socket.send(&reply).await.with_stage("challenge")?;
+                    packet = self
+                        .receive(engine, protocol, PACKET_SIZE)
+                        .await
+                        .with_stage("challenge")?;
+                }
+            }
         }
     }
 
+    /// Get the server information's.
+    async fn get_server_info(&mut self, engine: &Engine) -> GDResult<ServerInfo> {
+        let data = self
+            .get_kind_request_data(engine, 0, Request::Info)
+            .await
+            .with_stage("info")?;
+        info_section(engine, &data)
+    }
+
+    /// Get the server player's.
+    async fn get_server_players(&mut self, engine: &Engine, info: &ServerInfo) -> GDResult<Vec<ServerPlayer>> {
+        let data = self
+            .get_kind_request_data(engine, info.protocol_version, Request::Players)
+            .await
+            .with_stage("players")?;
+        players_section(engine, info, &data)
+    }
+
+    /// Get the server's rules.
+    async fn get_server_rules(&mut self, engine: &Engine, protocol: u8) -> GDResult<HashMap<String, String>> {
+        let data = self
+            .get_kind_request_data(engine, protocol, Request::Rules)
+            .await
+            .with_stage("rules")?;
+        rules_section(engine, &data)
+    }
+
+    /// Async version of [ValveProtocol::get_server_rules_raw].
+    pub async fn get_server_rules_raw(&mut self, engine: &Engine, protocol: u8) -> GDResult<Vec<RawRule>> {
+        let data = self
+            .get_kind_request_data(engine, protocol, Request::Rules)
+            .await
+            .with_stage("rules")?;
+        raw_rules_section(&data)
+    }
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(
+    address: &SocketAddr,
+    engine: Engine,
+    gather_settings: Option<GatheringSettings>,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Response> {
+    let scope = QueryScope::start(&timeout_settings);
+    query_with_scope_async(address, engine, gather_settings, timeout_settings, &scope).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    address: &SocketAddr,
+    engine: Engine,
+    gather_settings: Option<GatheringSettings>,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let gather_settings = gather_settings.unwrap_or_default();
+    get_response_async(address, engine, gather_settings, timeout_settings, scope)
+        .await
+        .with_address(*address)
+        .with_protocol("valve")
+}
+
+#[cfg(feature = "async")]
+async fn get_response_async(
+    address: &SocketAddr,
+    engine: Engine,
+    gather_settings: GatheringSettings,
+    timeout_settings: Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut client = AsyncValveProtocol::new(address, timeout_settings, scope).await?;
+
+    let info = client.get_server_info(&engine).await?;
+    check_app_id(&engine, &info, gather_settings.check_app_id).with_stage("info")?;
+
     let protocol = info.protocol_version;
 
-    Ok(Response {
-        info,
-        players: maybe_gather!(
-            gather_settings.players,
-            client.get_server_players(&engine, protocol)
-        ),
-        rules: maybe_gather!(
-            gather_settings.rules,
-            client.get_server_rules(&engine, protocol)
-        ),
-    })
+    let mut enforced_failure = None;
+    let players = gather_section!(
+        gather_settings.players,
+        client.get_server_players(&engine, &info).await,
+        enforced_failure
+    );
+    let rules = gather_section!(
+        gather_settings.rules,
+        client.get_server_rules(&engine, protocol).await,
+        enforced_failure
+    );
+
+    enforce_sections(
+        response(info, players, rules, &gather_settings, client.latency()),
+        enforced_failure,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::types::{CommonPlayer, GatherToggle, GenericResponse, SectionOutcome, SocketSettings};
+    use crate::transport::Scripted;
+    use crate::GDErrorKind::PacketReceive;
+    use std::sync::Arc;
+
+    #[test]
+    fn player_kinds() {
+        assert_eq!(player_kind("Gordon", None), PlayerKind::Player);
+        assert_eq!(player_kind("", None), PlayerKind::Unnamed);
+        assert_eq!(player_kind("  \0", None), PlayerKind::Unnamed);
+        assert_eq!(player_kind("SourceTV", None), PlayerKind::Relay);
+        assert_eq!(player_kind("gotv", None), PlayerKind::Relay);
+        assert_eq!(player_kind("My TV", Some("My TV")), PlayerKind::Relay);
+        assert_eq!(player_kind("BOT Kyle", None), PlayerKind::Bot);
+        assert_eq!(player_kind("BOTANIST", None), PlayerKind::Player);
+    }
+
+    #[test]
+    fn players_filtering() {
+        let mut data = vec![4];
+        for name in ["Gordon", "", "SourceTV", "BOT Kyle"] {
+            data.push(0);
+            data.extend(name.as_bytes());
+            data.push(0);
+            data.extend(7i32.to_le_bytes());
+            data.extend(1.5f32.to_le_bytes());
+        }
+
+        let players = parse_server_players(&Engine::new(440), None, &data).unwrap();
+        let kinds: Vec<_> = players.iter().map(|player| player.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                PlayerKind::Player,
+                PlayerKind::Unnamed,
+                PlayerKind::Relay,
+                PlayerKind::Bot
+            ]
+        );
+        assert_eq!(players[0].as_json().is_bot, Some(false));
+        assert_eq!(players[3].as_json().is_bot, Some(true));
+        assert_eq!(players[2].as_json().kind, Some(PlayerKind::Relay));
+
+        let filtered = filter_players(Some(players)).unwrap();
+        let names: Vec<_> = filtered.iter().map(|player| player.name.as_str()).collect();
+        assert_eq!(names, ["Gordon", "BOT Kyle"]);
+    }
+
+    /// Split packets of a rules response, made of length (u16) prefixed
+    /// datagrams.
+    const SOURCE_BZIP2: &[u8] = include_bytes!("../../../tests/fixtures/valve/source_bzip2.bin");
+    const SOURCE_NO_SIZE: &[u8] = include_bytes!("../../../tests/fixtures/valve/source_no_size.bin");
+    const GOLDSRC: &[u8] = include_bytes!("../../../tests/fixtures/valve/goldsrc.bin");
+
+    fn datagrams(mut fixture: &[u8]) -> Vec<&[u8]> {
+        let mut datagrams = Vec::new();
+        while let [low, high, rest @ ..] = fixture {
+            let (datagram, rest) = rest.split_at(usize::from(u16::from_le_bytes([*low, *high])));
+            datagrams.push(datagram);
+            fixture = rest;
+        }
+
+        datagrams
+    }
+
+    /// A server answering with the datagrams of a fixture, in the given order.
+    fn replay(fixture: &[u8], order: &[usize]) -> Scripted {
+        let datagrams = datagrams(fixture);
+        Scripted::new().add_packets(order.iter().map(|&i| datagrams[i]))
+    }
+
+    fn query_split_rules(connector: Scripted, engine: Engine, protocol: u8) -> GDResult<HashMap<String, String>> {
+        let scope = QueryScope::default().set_socket_settings(SocketSettings::new().set_connector(Arc::new(connector)));
+        let mut client = ValveProtocol::new(&"127.0.0.1:27015".parse().unwrap(), None, &scope)?;
+        let data = client.get_kind_request_data(&engine, protocol, Request::Rules)?;
+        parse_server_rules(&engine, &data)
+    }
+
+    fn assert_rules(rules: &HashMap<String, String>) {
+        assert_eq!(rules.len(), 80);
+        assert_eq!(
+            rules["rule_00"],
+            "5feceb66ffc86f38d952786c6d696c79c2dbc239dd4e91b46729d73a27fb57e9"
+        );
+    }
+
+    #[test]
+    fn split_source_compressed() {
+        // Out of order, with a duplicate.
+        let connector = || replay(SOURCE_BZIP2, &[2, 0, 2, 1]);
+
+        assert_rules(&query_split_rules(connector(), Engine::new(440), 17).unwrap());
+        // Newer GoldSrc servers use Source's layout.
+        assert_rules(&query_split_rules(connector(), Engine::GoldSrc(false), 48).unwrap());
+    }
+
+    #[test]
+    fn split_source_without_size() {
+        let connector = replay(SOURCE_NO_SIZE, &[4, 3, 1, 0, 3, 2]);
+        assert_rules(&query_split_rules(connector, Engine::new(240), 7).unwrap());
+        // Told apart from the first fragment.
+        let in_order = replay(SOURCE_NO_SIZE, &[0, 1, 2, 3, 4]);
+        assert_rules(&query_split_rules(in_order, Engine::new(440), 17).unwrap());
+    }
+
+    #[test]
+    fn split_goldsrc() {
+        let connector = replay(GOLDSRC, &[1, 0, 4, 2, 2, 3]);
+        assert_rules(&query_split_rules(connector, Engine::GoldSrc(false), 48).unwrap());
+    }
+
+    #[test]
+    fn split_missing_fragments() {
+        let duplicates = replay(GOLDSRC, &[0; 16]);
+        let error = query_split_rules(duplicates, Engine::GoldSrc(false), 48).unwrap_err();
+        assert_eq!(error.kind, PacketBad);
+        let lost = replay(SOURCE_BZIP2, &[0, 2]);
+        let error = query_split_rules(lost, Engine::new(440), 17).unwrap_err();
+        assert_eq!(error.kind, PacketReceive);
+    }
+
+    /// A server answering the info and rules requests, but not the players
+    /// one.
+    fn no_players() -> Scripted {
+        let mut info = vec![17];
+        info.extend(b"Test server\0de_dust2\0cstrike\0Counter-Strike\0");
+        info.extend(440u16.to_le_bytes());
+        info.extend([3, 24, 0, b'd', b'l', 0, 1]);
+        info.extend(b"1.0\0");
+
+        let mut rules = 1u16.to_le_bytes().to_vec();
+        rules.extend(b"sv_cheats\x000\0");
+
+        Scripted::new()
+            .add_packet(Packet::new(0x49, info).to_bytes())
+            .add_error(PacketReceive)
+            .add_packet(Packet::new(0x45, rules).to_bytes())
+    }
+
+    #[test]
+    fn failed_section_keeps_info() {
+        let address = "127.0.0.1:27015".parse().unwrap();
+        let scope =
+            QueryScope::default().set_socket_settings(SocketSettings::new().set_connector(Arc::new(no_players())));
+
+        let response = query_with_scope(&address, Engine::new(440), None, None, &scope).unwrap();
+        assert_eq!(response.info.name, "Test server");
+        assert_eq!(response.info.players_online, 3);
+        assert!(response.players.is_none());
+        assert!(matches!(
+            response.players_outcome,
+            SectionOutcome::Failed {
+                kind: PacketReceive,
+                ..
+            }
+        ));
+        assert_eq!(response.rules.unwrap()["sv_cheats"], "0");
+        assert_eq!(response.rules_outcome, SectionOutcome::Succeeded);
+
+        // Enforced, the players' failure is the query's, carrying the info.
+        let gather_settings = GatheringSettings {
+            players: GatherToggle::Enforce,
+            ..GatheringSettings::default()
+        };
+        let error = query_with_scope(
+            &address,
+            Engine::new(440),
+            Some(gather_settings),
+            None,
+            &scope,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind, PacketReceive);
+        let Some(GenericResponse::Valve(partial)) = error.partial_response.as_ref().map(|r| r.as_original()) else {
+            panic!("expected a partial valve response");
+        };
+        assert_eq!(partial.info.name, "Test server");
+        assert!(partial.players_outcome.is_failed());
+        // The rules aren't requested past the failed enforced players.
+        assert_eq!(partial.rules_outcome, SectionOutcome::NotRequested);
+    }
+
+    #[test]
+    fn split_stray_fragments() {
+        let fragments = datagrams(SOURCE_NO_SIZE);
+        let mut packets = SplitPackets::new(&Engine::new(240), 7, fragments[0]).unwrap();
+
+        let mut stray = fragments[1].to_vec();
+        stray[4] = 0x43;
+        packets.add(&stray).unwrap();
+        packets.add(b"\xFF\xFF\xFF\xFFEstray").unwrap();
+        assert!(!packets.is_complete());
+        assert_eq!(packets.reassemble().unwrap_err().kind, PacketBad);
+    }
+
+    #[test]
+    fn split_too_big() {
+        let fragment = |number: u8| {
+            let mut data = vec![0xFE, 0xFF, 0xFF, 0xFF, 1, 0, 0, 0, 255, number, 0xE0, 0x04];
+            data.resize(6000, 0);
+            data
+        };
+
+        let mut packets = SplitPackets::new(&Engine::new(440), 17, &fragment(0)).unwrap();
+        let error = (1 .. 255)
+            .try_for_each(|number| packets.add(&fragment(number)))
+            .unwrap_err();
+        assert_eq!(error.kind, PacketOverflow);
+
+        // A compressed packet claiming to decompress to 4 GB.
+        let data = b"\xFE\xFF\xFF\xFF\x01\x00\x00\x80\x01\x00\xE0\x04\xFF\xFF\xFF\xFF\x00\x00\x00\x00BZh9";
+        let packets = SplitPackets::new(&Engine::new(440), 17, data).unwrap();
+        assert_eq!(packets.reassemble().unwrap_err().kind, PacketOverflow);
+    }
+
+    #[test]
+    fn decompression() {
+        const HELLO: &[u8] = &[
+            66, 90, 104, 57, 49, 65, 89, 38, 83, 89, 249, 44, 245, 139, 0, 0, 5, 69, 0, 192, 0, 2, 0, 2, 68, 128, 0,
+            160, 0, 49, 12, 0, 196, 194, 101, 28, 162, 157, 226, 238, 72, 167, 10, 18, 31, 37, 158, 177, 96,
+        ];
+
+        assert_eq!(
+            decompress(HELLO, 10, 0x5058_35BB).unwrap(),
+            b"\xFF\xFF\xFF\xFFEhello"
+        );
+        assert_eq!(decompress(HELLO, 10, 0).unwrap_err().kind, Decompress);
+        assert_eq!(
+            decompress(HELLO, 9, 0x5058_35BB).unwrap_err().kind,
+            Decompress
+        );
+        assert_eq!(
+            decompress(&HELLO[.. 30], 10, 0x5058_35BB).unwrap_err().kind,
+            Decompress
+        );
+        assert_eq!(
+            decompress(&[], 10, 0x5058_35BB).unwrap_err().kind,
+            Decompress
+        );
+    }
 }
@@ -1,6 +1,18 @@
 use std::collections::HashMap;
-
-use crate::protocols::types::{CommonPlayer, CommonResponse, ExtraRequestSettings, GatherToggle, GenericPlayer};
+use std::time::Duration;
+
+use crate::protocols::types::{
+    sorted_rules,
+    CommonPlayer,
+    CommonResponse,
+    ExtraRequestSettings,
+    GatherToggle,
+    GenericPlayer,
+    PlayerKind,
+    SectionOutcome,
+};
+use crate::protocols::valve::keywords::keyword_tags;
+use crate::text::{normalize_in_place, Dialect, NormalizeText, TextFormat};
 use crate::GDErrorKind::UnknownEnumCast;
 use crate::GDResult;
 use crate::{buffer::Buffer, protocols::GenericResponse};
@@ -46,6 +58,15 @@ impl Environment {
             _ => Err(UnknownEnumCast)?,
         })
     }
+
+    /// The operating system's name, in lowercase.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Linux => "linux",
+            Self::Windows => "windows",
+            Self::Mac => "mac",
+        }
+    }
 }
 
 /// A query response.
@@ -55,10 +76,19 @@ pub struct Response {
     pub info: ServerInfo,
     pub players: Option<Vec<ServerPlayer>>,
     pub rules: Option<HashMap<String, String>>,
+    /// Round-trip time of the first request/response exchange.
+    pub latency: Option<Duration>,
+    /// What happened to the players request.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub players_outcome: SectionOutcome,
+    /// What happened to the rules request.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rules_outcome: SectionOutcome,
 }
 
 impl CommonResponse for Response {
     fn as_original(&self) -> GenericResponse { GenericResponse::Valve(self) }
+    fn latency(&self) -> Option<Duration> { self.latency }
 
     fn name(&self) -> Option<&str> { Some(&self.info.name) }
     fn game_mode(&self) -> Option<&str> { Some(&self.info.game_mode) }
@@ -68,6 +98,16 @@ impl CommonResponse for Response {
     fn players_online(&self) -> u32 { self.info.players_online.into() }
     fn players_bots(&self) -> Option<u32> { Some(self.info.players_bots.into()) }
     fn has_password(&self) -> Option<bool> { Some(self.info.has_password) }
+    fn is_secured(&self) -> Option<bool> { Some(self.info.vac_secured) }
+    fn server_os(&self) -> Option<&str> { Some(self.info.environment_type.as_str()) }
+    fn game_port(&self) -> Option<u16> { self.info.extra_data.as_ref()?.port }
+
+    fn tags(&self) -> Option<Vec<&str>> {
+        let keywords = self.info.extra_data.as_ref()?.keywords.as_deref()?;
+        Some(keyword_tags(keywords))
+    }
+
+    fn rules(&self) -> Option<Vec<(&str, &str)>> { self.rules.as_ref().map(|rules| sorted_rules(rules.iter())) }
 
     fn players(&self) -> Option<Vec<&dyn CommonPlayer>> {
         self.players
@@ -76,6 +116,17 @@ impl CommonResponse for Response {
     }
 }
 
+impl NormalizeText for Response {
+    fn normalize_text(&mut self, format: TextFormat) {
+        normalize_in_place(&mut self.info.name, Dialect::None, format);
+        if let Some(players) = &mut self.players {
+            for player in players {
+                normalize_in_place(&mut player.name, Dialect::None, format);
+            }
+        }
+    }
+}
+
 /// General server information's.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -132,12 +183,20 @@ pub struct ServerPlayer {
     pub deaths: Option<u32>, // the_ship
     /// Only for [the ship](https://developer.valvesoftware.com/wiki/The_Ship): money amount
     pub money: Option<u32>, // the_ship
+    /// What the entry is, bots being recognized by the `BOT` tag in their
+    /// name (the protocol doesn't tell them apart).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kind: PlayerKind,
 }
 
 impl CommonPlayer for ServerPlayer {
     fn as_original(&self) -> GenericPlayer { GenericPlayer::Valve(self) }
     fn name(&self) -> &str { &self.name }
     fn score(&self) -> Option<i32> { Some(self.score) }
+    fn time_connected(&self) -> Option<Duration> { Duration::try_from_secs_f32(self.duration).ok() }
+    fn deaths(&self) -> Option<u32> { self.deaths }
+    fn is_bot(&self) -> Option<bool> { Some(self.kind == PlayerKind::Bot) }
+    fn kind(&self) -> Option<PlayerKind> { Some(self.kind) }
 }
 
 /// Only present for [the ship](https://developer.valvesoftware.com/wiki/The_Ship).
@@ -167,6 +226,17 @@ pub struct ExtraData {
     pub game_id: Option<u64>,
 }
 
+impl ExtraData {
+    /// The [keywords](ExtraData::keywords) split into tags (see
+    /// [keywords](super::keywords) to decode them).
+    pub fn tags(&self) -> Vec<&str> {
+        self.keywords
+            .as_deref()
+            .map(keyword_tags)
+            .unwrap_or_default()
+    }
+}
+
 /// Data related to GoldSrc Mod response.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -286,6 +356,9 @@ pub struct GatheringSettings {
     pub players: GatherToggle,
     pub rules: GatherToggle,
     pub check_app_id: bool,
+    /// Whether to leave the [unnamed](PlayerKind::Unnamed) and
+    /// [relay](PlayerKind::Relay) entries out of the players.
+    pub filter_players: bool,
 }
 
 impl GatheringSettings {
@@ -296,6 +369,7 @@ impl GatheringSettings {
             players: GatherToggle::Try,
             rules: GatherToggle::Try,
             check_app_id: true,
+            filter_players: false,
         }
     }
 
@@ -306,6 +380,8 @@ impl GatheringSettings {
             gather_players: Some(self.players),
             gather_rules: Some(self.rules),
             check_app_id: Some(self.check_app_id),
+            name_format: None,
+            filter_players: Some(self.filter_players),
         }
     }
 }
@@ -321,6 +397,7 @@ impl From<ExtraRequestSettings> for GatheringSettings {
             players: value.gather_players.unwrap_or(default.players),
             rules: value.gather_rules.unwrap_or(default.rules),
             check_app_id: value.check_app_id.unwrap_or(default.check_app_id),
+            filter_players: value.filter_players.unwrap_or(default.filter_players),
         }
     }
 }
@@ -330,6 +407,8 @@ impl From<ExtraRequestSettings> for GatheringSettings {
 /// fields).
 pub mod game {
     use super::{Server, ServerPlayer};
+    use crate::protocols::types::PlayerKind;
+    use crate::protocols::valve::keywords::keyword_tags;
     use crate::protocols::valve::types::get_optional_extracted_data;
     use std::collections::HashMap;
 
@@ -346,6 +425,9 @@ pub mod game {
         pub score: i32,
         /// How long a player has been in the server (seconds).
         pub duration: f32,
+        /// What the entry is.
+        #[cfg_attr(feature = "serde", serde(default))]
+        pub kind: PlayerKind,
     }
 
     impl Player {
@@ -354,6 +436,7 @@ pub mod game {
                 name: player.name.clone(),
                 score: player.score,
                 duration: player.duration,
+                kind: player.kind,
             }
         }
     }
@@ -433,5 +516,13 @@ pub mod game {
                 rules: response.rules.unwrap_or_default(),
             }
         }
+
+        /// The [keywords](Response::keywords) split into tags.
+        pub fn tags(&self) -> Vec<&str> {
+            self.keywords
+                .as_deref()
+                .map(keyword_tags)
+                .unwrap_or_default()
+        }
     }
 }
@@ -1,13 +1,48 @@
 use crate::http::HttpClient;
 use crate::minetest_master_server::types::Response;
+use crate::protocols::types::QueryScope;
 use crate::{GDResult, TimeoutSettings};
 
+#[cfg(feature = "async")]
+use crate::http::AsyncHttpClient;
+
 pub fn query(timeout_settings: TimeoutSettings) -> GDResult<Response> {
+    let timeout_settings = Some(timeout_settings);
+    query_with_scope(&timeout_settings, &QueryScope::start(&timeout_settings))
+}
+
+/// [query] as part of a larger query, sharing its deadline.
+pub(crate) fn query_with_scope(timeout_settings: &Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Response> {
     let mut client = HttpClient::from_url(
         "https://servers.minetest.net",
-        &Some(timeout_settings),
+        timeout_settings,
         None,
+        scope,
     )?;
 
     client.get_json("/list", None)
 }
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(timeout_settings: TimeoutSettings) -> GDResult<Response> {
+    let timeout_settings = Some(timeout_settings);
+    query_with_scope_async(&timeout_settings, &QueryScope::start(&timeout_settings)).await
+}
+
+/// Async version of [query_with_scope].
+#[cfg(feature = "async")]
+pub(crate) async fn query_with_scope_async(
+    timeout_settings: &Option<TimeoutSettings>,
+    scope: &QueryScope,
+) -> GDResult<Response> {
+    let mut client = AsyncHttpClient::from_url(
+        "https://servers.minetest.net",
+        timeout_settings,
+        None,
+        scope,
+    )
+    .await?;
+
+    client.get_json("/list", None).await
+}
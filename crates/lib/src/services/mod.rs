@@ -6,3 +6,7 @@ pub mod valve_master_server;
 /// Reference: [Node-GameDig](https://github.com/gamedig/node-gamedig/blob/master/protocols/minetest.js)
 #[cfg(all(feature = "serde", feature = "tls"))]
 pub mod minetest_master_server;
+
+/// Reference: [IGameServersService](https://steamapi.xpaw.me/#IGameServersService/GetServerList)
+#[cfg(all(feature = "serde", feature = "tls"))]
+pub mod valve_web_api;
@@ -1,5 +1,6 @@
 use crate::{
     buffer::Buffer,
+    protocols::types::QueryScope,
     socket::{Socket, UdpSocket},
     valve_master_server::{Region, SearchFilters},
     GDErrorKind::PacketBad,
@@ -49,7 +50,7 @@ pub struct ValveMasterServer {
 impl ValveMasterServer {
     /// Construct a new struct.
     pub fn new(master_address: &SocketAddr) -> GDResult<Self> {
-        let socket = UdpSocket::new(master_address, &None)?;
+        let socket = UdpSocket::new(master_address, &None, &QueryScope::default())?;
 
         Ok(Self { socket })
     }
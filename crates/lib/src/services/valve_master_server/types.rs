@@ -216,6 +216,14 @@ impl SearchFilters {
         bytes.extend([0x00]);
         bytes
     }
+
+    /// The filters as a string (the Steam Web API's `filter` parameter).
+    #[cfg(all(feature = "serde", feature = "tls"))]
+    pub(crate) fn to_filter_string(&self) -> String {
+        let bytes = self.to_bytes();
+        // Without the ending NULL byte.
+        String::from_utf8_lossy(&bytes[.. bytes.len() - 1]).into_owned()
+    }
 }
 
 /// The region that you want to query server for.
@@ -0,0 +1,7 @@
+/// The implementation.
+pub mod service;
+/// All types used by the implementation.
+pub mod types;
+
+pub use service::*;
+pub use types::*;
@@ -0,0 +1,180 @@
+use crate::http::HttpClient;
+use crate::protocols::types::QueryScope;
+use crate::valve_master_server::SearchFilters;
+use crate::valve_web_api::types::{ApiResponse, Server, ServerListSettings};
+use crate::{GDResult, TimeoutSettings};
+
+#[cfg(feature = "async")]
+use crate::http::AsyncHttpClient;
+
+const SERVER_LIST_PATH: &str = "/IGameServersService/GetServerList/v1/";
+
+/// The query parameters of a server list request.
+fn parameters(settings: &ServerListSettings, search_filters: Option<&SearchFilters>) -> Vec<(&'static str, String)> {
+    let mut parameters = vec![("key", settings.key.clone())];
+
+    let filter = search_filters.map_or_else(String::new, SearchFilters::to_filter_string);
+    if !filter.is_empty() {
+        parameters.push(("filter", filter));
+    }
+
+    if let Some(limit) = settings.limit {
+        parameters.push(("limit", limit.to_string()));
+    }
+
+    parameters
+}
+
+fn as_pairs<'a>(parameters: &'a [(&'static str, String)]) -> Vec<(&'a str, &'a str)> {
+    parameters
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .collect()
+}
+
+/// List the servers matching `search_filters` (the same ones as the master
+/// server's), up to the settings' limit.
+pub fn query(
+    settings: &ServerListSettings,
+    search_filters: Option<SearchFilters>,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Vec<Server>> {
+    let mut client = HttpClient::from_url(
+        settings.api_url.as_str(),
+        &timeout_settings,
+        None,
+        &QueryScope::start(&timeout_settings),
+    )?;
+
+    let parameters = parameters(settings, search_filters.as_ref());
+    let response: ApiResponse = client.get_json_with_query(SERVER_LIST_PATH, &as_pairs(&parameters), None)?;
+
+    Ok(response.response.servers)
+}
+
+/// Async version of [query].
+#[cfg(feature = "async")]
+pub async fn query_async(
+    settings: &ServerListSettings,
+    search_filters: Option<SearchFilters>,
+    timeout_settings: Option<TimeoutSettings>,
+) -> GDResult<Vec<Server>> {
+    let mut client = AsyncHttpClient::from_url(
+        settings.api_url.as_str(),
+        &timeout_settings,
+        None,
+        &QueryScope::start(&timeout_settings),
+    )
+    .await?;
+
+    let parameters = parameters(settings, search_filters.as_ref());
+    let response: ApiResponse = client
+        .get_json_with_query(SERVER_LIST_PATH, &as_pairs(&parameters), None)
+        .await?;
+
+    Ok(response.response.servers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::valve_master_server::Filter;
+    use crate::GDErrorKind::PacketSend;
+
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    const SERVERS: &str = r#"{"response":{"servers":[{"addr":"127.0.0.1:27015","gameport":27015,
+        "steamid":"90185938946281483","name":"Test server","appid":440,"gamedir":"tf","version":"9317463",
+        "product":"tf","region":255,"players":20,"max_players":24,"bots":2,"map":"ctf_2fort","secure":true,
+        "dedicated":true,"os":"l","gametype":"alltalk,increased_maxplayers"},{"addr":"127.0.0.2:27016"}]}}"#;
+
+    /// A stand-in for the API answering a single request, returning the
+    /// request's line.
+    fn stand_in(status: &'static str, body: &'static str) -> (ServerListSettings, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let server_thread = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            // Skip the headers.
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+
+            request_line
+        });
+
+        let settings = ServerListSettings::new("SECRET").set_api_url(format!("http://{address}"));
+        (settings, server_thread)
+    }
+
+    #[test]
+    fn server_list() {
+        let (settings, server_thread) = stand_in("200 OK", SERVERS);
+        let filters = SearchFilters::new().insert(Filter::RunsAppID(440));
+
+        let servers = query(&settings.set_limit(2), Some(filters), None).unwrap();
+        let request_line = server_thread.join().unwrap();
+
+        assert!(request_line
+            .starts_with("GET /IGameServersService/GetServerList/v1/?key=SECRET&filter=%5Cappid%5C440&limit=2 "));
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0].name, "Test server");
+        assert_eq!(servers[0].bots, 2);
+        assert_eq!(servers[0].tags(), ["alltalk", "increased_maxplayers"]);
+        assert_eq!(
+            servers[1].query_address(),
+            Some("127.0.0.2:27016".parse().unwrap())
+        );
+        assert_eq!(servers[1].app_id, 0);
+    }
+
+    #[test]
+    fn no_servers() {
+        let (settings, server_thread) = stand_in("200 OK", r#"{"response":{}}"#);
+
+        let servers = query(&settings, None, None).unwrap();
+        let request_line = server_thread.join().unwrap();
+
+        assert!(request_line.starts_with("GET /IGameServersService/GetServerList/v1/?key=SECRET "));
+        assert!(servers.is_empty());
+    }
+
+    #[test]
+    fn forbidden() {
+        let (settings, server_thread) = stand_in("403 Forbidden", "");
+
+        let error = query(&settings, None, None).unwrap_err();
+        server_thread.join().unwrap();
+
+        assert_eq!(error.kind, PacketSend);
+        assert!(format!("{error:?}").contains("Status code 403 (Forbidden)"));
+        assert!(!format!("{error:?}").contains("SECRET"));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn server_list_async() {
+        let (settings, server_thread) = stand_in("200 OK", SERVERS);
+
+        let servers = query_async(&settings, None, None).await.unwrap();
+        server_thread.join().unwrap();
+
+        assert_eq!(servers.len(), 2);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::protocols::valve::keywords::keyword_tags;
+
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+/// The URL of the Steam Web API.
+pub const DEFAULT_API_URL: &str = "https://api.steampowered.com";
+
+/// Settings of the server list requests.
+///
+/// ```
+/// use gamedig::valve_web_api::ServerListSettings;
+///
+/// let settings = ServerListSettings::new("STEAM_WEB_API_KEY").set_limit(1000);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerListSettings {
+    /// The Steam Web API key (get one at <https://steamcommunity.com/dev/apikey>).
+    pub key: String,
+    /// How many servers to list at most, the API lists 100 without one.
+    pub limit: Option<u32>,
+    /// The URL of the API, [DEFAULT_API_URL] by default.
+    pub api_url: String,
+}
+
+impl ServerListSettings {
+    /// Settings using the API key `key`.
+    pub fn new<S: Into<String>>(key: S) -> Self {
+        Self {
+            key: key.into(),
+            limit: None,
+            api_url: DEFAULT_API_URL.to_string(),
+        }
+    }
+
+    /// Set the maximum number of servers to list.
+    pub const fn set_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Set the URL of the API (such as a proxy's).
+    pub fn set_api_url<S: Into<String>>(mut self, api_url: S) -> Self {
+        self.api_url = api_url.into();
+        self
+    }
+}
+
+/// A server, as listed by the API.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Server {
+    /// The address to query the server on (`ip:port`).
+    #[serde(rename = "addr")]
+    pub address: String,
+    /// The port to connect to the game on.
+    #[serde(rename = "gameport")]
+    pub game_port: u16,
+    #[serde(rename = "steamid")]
+    pub steam_id: String,
+    pub name: String,
+    #[serde(rename = "appid")]
+    pub app_id: u32,
+    #[serde(rename = "gamedir")]
+    pub game_dir: String,
+    pub version: String,
+    pub product: String,
+    /// The region (see [Region](crate::valve_master_server::Region)), 255 if
+    /// it has none.
+    pub region: i32,
+    pub players: u32,
+    pub max_players: u32,
+    pub bots: u32,
+    pub map: String,
+    pub secure: bool,
+    pub dedicated: bool,
+    /// The operating system: `l` (Linux), `w` (Windows) or `m` (Mac).
+    pub os: String,
+    /// The keywords, see [tags](Self::tags).
+    #[serde(rename = "gametype")]
+    pub keywords: String,
+}
+
+impl Server {
+    /// The address to query the server on, if it's a valid one.
+    pub fn query_address(&self) -> Option<SocketAddr> { self.address.parse().ok() }
+
+    /// The keywords split into tags, see
+    /// [keywords](crate::protocols::valve::keywords).
+    pub fn tags(&self) -> Vec<&str> { keyword_tags(&self.keywords) }
+}
+
+/// The API's answer.
+#[derive(Deserialize, Debug)]
+pub(crate) struct ApiResponse {
+    pub response: ServerList,
+}
+
+/// There are no servers (instead of them being empty) when none matched.
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct ServerList {
+    #[serde(default)]
+    pub servers: Vec<Server>,
+}
@@ -1,27 +1,111 @@
 use crate::{
-    protocols::types::TimeoutSettings,
+    protocols::types::{QueryScope, SocketSettings, TimeoutSettings},
+    socks5::{self, Command, UdpAssociation},
+    transport::{Transport, TransportKind},
     GDErrorKind::{PacketReceive, PacketSend, SocketBind, SocketConnect},
     GDResult,
 };
 
 use std::{
-    io::{Read, Write},
-    net::{self, SocketAddr},
+    io::ErrorKind,
+    net::{self, IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::{Duration, Instant},
 };
 
+#[cfg(feature = "games")]
+use std::io::{Read, Write};
+
 const DEFAULT_PACKET_SIZE: usize = 1024;
 
+/// The unspecified address of a family, with port 0.
+fn unspecified(ipv4: bool) -> SocketAddr {
+    if ipv4 {
+        SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0)
+    } else {
+        SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 0)
+    }
+}
+
+/// Bind a socket with `bind` to the local address and ports of the socket
+/// settings, by default an ephemeral port on the unspecified address of the
+/// target's family (`ipv4` tells which one that is).
+pub(crate) fn bind_local<T>(
+    socket_settings: &SocketSettings,
+    ipv4: bool,
+    mut bind: impl FnMut(SocketAddr) -> std::io::Result<T>,
+) -> GDResult<T> {
+    let ip: IpAddr = match socket_settings.get_bind_address() {
+        Some(ip) if ip.is_ipv4() != ipv4 => {
+            return Err(SocketBind.context(format!(
+                "Bind address {ip} isn't of the target's address family"
+            )));
+        }
+        Some(ip) => ip,
+        None if ipv4 => Ipv4Addr::UNSPECIFIED.into(),
+        None => Ipv6Addr::UNSPECIFIED.into(),
+    };
+
+    let Some(ports) = socket_settings.get_bind_ports() else {
+        return bind(SocketAddr::new(ip, 0)).map_err(|e| SocketBind.context(e));
+    };
+
+    for port in ports.iter() {
+        match bind(SocketAddr::new(ip, port)) {
+            Ok(socket) => return Ok(socket),
+            Err(e) if e.kind() == ErrorKind::AddrInUse => continue,
+            Err(e) => return Err(SocketBind.context(e)),
+        }
+    }
+
+    Err(SocketBind.context(format!(
+        "No free local port in {}-{}",
+        ports.get_start(),
+        ports.get_end()
+    )))
+}
+
+/// Times the first request/response exchange made on a socket.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct LatencyTimer {
+    sent_at: Option<Instant>,
+    latency: Option<Duration>,
+}
+
+impl LatencyTimer {
+    /// Record a request being sent, only the last one before the first
+    /// response counts (so retries aren't included).
+    pub(crate) fn sent(&mut self) {
+        if self.latency.is_none() {
+            self.sent_at = Some(Instant::now());
+        }
+    }
+
+    /// Record a response being received.
+    pub(crate) fn received(&mut self) {
+        if self.latency.is_none() {
+            self.latency = self.sent_at.map(|sent_at| sent_at.elapsed());
+        }
+    }
+
+    /// The round-trip time of the first exchange, if there was one.
+    pub(crate) const fn latency(&self) -> Option<Duration> { self.latency }
+}
+
 /// A trait defining the basic functionalities of a network socket.
 pub trait Socket {
+    /// The kind of transport the socket is.
+    const KIND: TransportKind;
+
     /// Create a new socket and connect to the remote address.
     ///
     /// # Arguments
     /// * `address` - The address to connect the socket to.
     /// * `timeout_settings` - Optional timeout settings for the socket.
+    /// * `scope` - The query the socket is used for.
     ///
     /// # Returns
     /// A result containing the socket instance or an error.
-    fn new(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>) -> GDResult<Self>
+    fn new(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self>
     where Self: Sized;
 
     /// Apply read and write timeouts to the socket.
@@ -55,8 +139,15 @@ pub trait Socket {
     ///
     /// # Returns
     /// The port number.
+    #[cfg(feature = "games")]
     fn port(&self) -> u16;
 
+    /// Get the round-trip time of the first request/response exchange.
+    ///
+    /// # Returns
+    /// The latency, or None if no response has been received yet.
+    fn latency(&self) -> Option<Duration>;
+
     /// Get the local SocketAddr.
     ///
     /// # Returns
@@ -65,28 +156,67 @@ pub trait Socket {
     fn local_addr(&self) -> std::io::Result<SocketAddr>;
 }
 
+/// Connect a TCP stream to `address`, bound as per the socket settings.
+fn connect_tcp(
+    address: &SocketAddr,
+    timeout_settings: &Option<TimeoutSettings>,
+    socket_settings: &SocketSettings,
+) -> GDResult<net::TcpStream> {
+    let socket = bind_local(socket_settings, address.is_ipv4(), |local| {
+        let socket = socket2::Socket::new(
+            socket2::Domain::for_address(local),
+            socket2::Type::STREAM,
+            Some(socket2::Protocol::TCP),
+        )?;
+        socket.bind(&local.into())?;
+
+        Ok(socket)
+    })?;
+
+    let connected = TimeoutSettings::get_connect_or_default(timeout_settings).map_or_else(
+        || socket.connect(&(*address).into()),
+        |timeout| socket.connect_timeout(&(*address).into(), timeout),
+    );
+    connected.map_err(|e| SocketConnect.context(e))?;
+
+    Ok(socket.into())
+}
+
 /// Implementation of a TCP socket.
+#[cfg(feature = "games")]
 pub struct TcpSocketImpl {
     /// The underlying TCP socket stream.
     socket: net::TcpStream,
     /// The address of the remote host.
     address: SocketAddr,
+    /// Timer of the first exchange.
+    timer: LatencyTimer,
 }
 
+#[cfg(feature = "games")]
 impl Socket for TcpSocketImpl {
-    fn new(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>) -> GDResult<Self> {
-        let socket = TimeoutSettings::get_connect_or_default(timeout_settings).map_or_else(
-            || net::TcpStream::connect(address),
-            |timeout| net::TcpStream::connect_timeout(address, timeout),
-        );
+    const KIND: TransportKind = TransportKind::Tcp;
 
-        let socket = Self {
-            socket: socket.map_err(|e| SocketConnect.context(e))?,
+    fn new(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self> {
+        let socket_settings = scope.socket_settings();
+        let proxy = socket_settings.get_proxy();
+
+        let mut socket = Self {
+            socket: connect_tcp(
+                &proxy.unwrap_or(*address),
+                timeout_settings,
+                socket_settings,
+            )?,
             address: *address,
+            timer: LatencyTimer::default(),
         };
 
         socket.apply_timeout(timeout_settings)?;
 
+        if proxy.is_some() {
+            socks5::handshake(&mut socket.socket, Command::Connect, address)?;
+        }
+
         Ok(socket)
     }
 
@@ -100,6 +230,7 @@ impl Socket for TcpSocketImpl {
 
     fn send(&mut self, data: &[u8]) -> GDResult<()> {
         self.socket.write(data).map_err(|e| PacketSend.context(e))?;
+        self.timer.sent();
         Ok(())
     }
 
@@ -108,12 +239,16 @@ impl Socket for TcpSocketImpl {
         self.socket
             .read_to_end(&mut buf)
             .map_err(|e| PacketReceive.context(e))?;
+        self.timer.received();
 
         Ok(buf)
     }
 
+    #[cfg(feature = "games")]
     fn port(&self) -> u16 { self.address.port() }
 
+    fn latency(&self) -> Option<Duration> { self.timer.latency() }
+
     #[cfg(feature = "packet_capture")]
     fn local_addr(&self) -> std::io::Result<SocketAddr> { self.socket.local_addr() }
 }
@@ -124,15 +259,47 @@ pub struct UdpSocketImpl {
     socket: net::UdpSocket,
     /// The address of the remote host.
     address: SocketAddr,
+    /// Timer of the first exchange.
+    timer: LatencyTimer,
+    /// The SOCKS5 association datagrams are relayed through, if proxied.
+    association: Option<UdpAssociation<net::TcpStream>>,
 }
 
 impl Socket for UdpSocketImpl {
-    fn new(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>) -> GDResult<Self> {
-        let socket = net::UdpSocket::bind("0.0.0.0:0").map_err(|e| SocketBind.context(e))?;
+    const KIND: TransportKind = TransportKind::Udp;
+
+    fn new(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self> {
+        let socket_settings = scope.socket_settings();
+        let association = match socket_settings.get_proxy() {
+            Some(proxy) => {
+                let mut control = connect_tcp(&proxy, timeout_settings, socket_settings)?;
+                let (read, write) = TimeoutSettings::get_read_and_write_or_defaults(timeout_settings);
+                control.set_read_timeout(read).unwrap(); // unwrapping because TimeoutSettings::new
+                control.set_write_timeout(write).unwrap(); // checks if these are 0 and throws an error
+
+                let bound = socks5::handshake(
+                    &mut control,
+                    Command::UdpAssociate,
+                    &unspecified(proxy.is_ipv4()),
+                )?;
+
+                Some(UdpAssociation {
+                    relay: socks5::relay_address(bound, &proxy),
+                    control,
+                })
+            }
+            None => None,
+        };
+
+        let ipv4 = association
+            .as_ref()
+            .map_or(address.is_ipv4(), |association| association.relay.is_ipv4());
 
         let socket = Self {
-            socket,
+            socket: bind_local(socket_settings, ipv4, net::UdpSocket::bind)?,
             address: *address,
+            timer: LatencyTimer::default(),
+            association,
         };
 
         socket.apply_timeout(timeout_settings)?;
@@ -149,45 +316,977 @@ impl Socket for UdpSocketImpl {
     }
 
     fn send(&mut self, data: &[u8]) -> GDResult<()> {
-        self.socket
-            .send_to(data, self.address)
-            .map_err(|e| PacketSend.context(e))?;
+        match &self.association {
+            Some(association) => {
+                self.socket.send_to(
+                    &socks5::wrap_datagram(&self.address, data),
+                    association.relay,
+                )
+            }
+            None => self.socket.send_to(data, self.address),
+        }
+        .map_err(|e| PacketSend.context(e))?;
+        self.timer.sent();
 
         Ok(())
     }
 
     fn receive(&mut self, size: Option<usize>) -> GDResult<Vec<u8>> {
-        let mut buf: Vec<u8> = vec![0; size.unwrap_or(DEFAULT_PACKET_SIZE)];
-        let (number_of_bytes_received, _) = self
-            .socket
-            .recv_from(&mut buf)
-            .map_err(|e| PacketReceive.context(e))?;
+        let size = size.unwrap_or(DEFAULT_PACKET_SIZE);
+        let header_size = self
+            .association
+            .as_ref()
+            .map_or(0, |_| socks5::MAX_HEADER_SIZE);
+
+        let mut buf: Vec<u8> = vec![0; size + header_size];
+        let data = loop {
+            let (number_of_bytes_received, _) = self
+                .socket
+                .recv_from(&mut buf)
+                .map_err(|e| PacketReceive.context(e))?;
+
+            let data = &buf[.. number_of_bytes_received];
+            let data = match self.association {
+                // Datagrams relayed from other hosts are dropped.
+                Some(_) => {
+                    match socks5::unwrap_datagram(data, &self.address)? {
+                        Some(data) => data,
+                        None => continue,
+                    }
+                }
+                None => data,
+            };
+
+            break data[.. data.len().min(size)].to_vec();
+        };
+        self.timer.received();
 
-        Ok(buf[.. number_of_bytes_received].to_vec())
+        Ok(data)
     }
 
+    #[cfg(feature = "games")]
     fn port(&self) -> u16 { self.address.port() }
 
+    fn latency(&self) -> Option<Duration> { self.timer.latency() }
+
     #[cfg(feature = "packet_capture")]
     fn local_addr(&self) -> std::io::Result<SocketAddr> { self.socket.local_addr() }
 }
 
+/// An async version of [Socket], implemented on top of tokio.
+///
+/// Read and write timeouts from [TimeoutSettings] are applied to every
+/// individual send and receive.
+#[cfg(feature = "async")]
+pub trait AsyncSocket {
+    /// The kind of transport the socket is.
+    const KIND: TransportKind;
+
+    /// Create a new socket and connect to the remote address.
+    ///
+    /// # Arguments
+    /// * `address` - The address to connect the socket to.
+    /// * `timeout_settings` - Optional timeout settings for the socket.
+    /// * `scope` - The query the socket is used for.
+    ///
+    /// # Returns
+    /// A result containing the socket instance or an error.
+    async fn new(
+        address: &SocketAddr,
+        timeout_settings: &Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<Self>
+    where
+        Self: Sized;
+
+    /// Apply read and write timeouts to the socket.
+    ///
+    /// # Arguments
+    /// * `timeout_settings` - Optional timeout settings to apply.
+    ///
+    /// # Returns
+    /// A result indicating success or error in applying timeouts.
+    fn apply_timeout(&mut self, timeout_settings: &Option<TimeoutSettings>) -> GDResult<()>;
+
+    /// Send data over the socket.
+    ///
+    /// # Arguments
+    /// * `data` - Data to be sent.
+    ///
+    /// # Returns
+    /// A result indicating success or error in sending data.
+    async fn send(&mut self, data: &[u8]) -> GDResult<()>;
+
+    /// Receive data from the socket.
+    ///
+    /// # Arguments
+    /// * `size` - Optional size of data to receive.
+    ///
+    /// # Returns
+    /// A result containing received data or an error.
+    async fn receive(&mut self, size: Option<usize>) -> GDResult<Vec<u8>>;
+
+    /// Get the remote port of the socket.
+    ///
+    /// # Returns
+    /// The port number.
+    #[cfg(feature = "games")]
+    fn port(&self) -> u16;
+
+    /// Get the round-trip time of the first request/response exchange.
+    ///
+    /// # Returns
+    /// The latency, or None if no response has been received yet.
+    fn latency(&self) -> Option<Duration>;
+}
+
+/// Run an IO future, failing with [std::io::ErrorKind::TimedOut] if it doesn't
+/// complete within the timeout (if any).
+#[cfg(feature = "async")]
+async fn with_timeout<T>(
+    timeout: Option<std::time::Duration>,
+    future: impl std::future::Future<Output = std::io::Result<T>>,
+) -> std::io::Result<T> {
+    match timeout {
+        None => future.await,
+        Some(timeout) => {
+            tokio::time::timeout(timeout, future)
+                .await
+                .unwrap_or_else(|_| Err(std::io::ErrorKind::TimedOut.into()))
+        }
+    }
+}
+
+/// Async version of [connect_tcp].
+#[cfg(feature = "async")]
+async fn connect_tcp_async(
+    address: &SocketAddr,
+    timeout_settings: &Option<TimeoutSettings>,
+    socket_settings: &SocketSettings,
+) -> GDResult<tokio::net::TcpStream> {
+    let socket = bind_local(socket_settings, address.is_ipv4(), |local| {
+        let socket = if local.is_ipv4() {
+            tokio::net::TcpSocket::new_v4()
+        } else {
+            tokio::net::TcpSocket::new_v6()
+        }?;
+        socket.bind(local)?;
+
+        Ok(socket)
+    })?;
+
+    with_timeout(
+        TimeoutSettings::get_connect_or_default(timeout_settings),
+        socket.connect(*address),
+    )
+    .await
+    .map_err(|e| SocketConnect.context(e))
+}
+
+/// [socks5::handshake_async] within the connect timeout.
+#[cfg(feature = "async")]
+async fn socks5_handshake_async(
+    stream: &mut tokio::net::TcpStream,
+    command: Command,
+    address: &SocketAddr,
+    timeout_settings: &Option<TimeoutSettings>,
+) -> GDResult<SocketAddr> {
+    let handshake = socks5::handshake_async(stream, command, address);
+
+    match TimeoutSettings::get_connect_or_default(timeout_settings) {
+        Some(timeout) => {
+            tokio::time::timeout(timeout, handshake)
+                .await
+                .map_err(|e| SocketConnect.context(e))?
+        }
+        None => handshake.await,
+    }
+}
+
+/// Implementation of an async TCP socket.
+#[cfg(all(feature = "async", feature = "games"))]
+pub struct AsyncTcpSocketImpl {
+    /// The underlying TCP socket stream.
+    socket: tokio::net::TcpStream,
+    /// The address of the remote host.
+    address: SocketAddr,
+    /// Timeout for each read.
+    read_timeout: Option<std::time::Duration>,
+    /// Timeout for each write.
+    write_timeout: Option<std::time::Duration>,
+    /// Timer of the first exchange.
+    timer: LatencyTimer,
+}
+
+#[cfg(all(feature = "async", feature = "games"))]
+impl AsyncSocket for AsyncTcpSocketImpl {
+    const KIND: TransportKind = TransportKind::Tcp;
+
+    async fn new(
+        address: &SocketAddr,
+        timeout_settings: &Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<Self> {
+        let socket_settings = scope.socket_settings();
+        let proxy = socket_settings.get_proxy();
+
+        let mut socket = connect_tcp_async(
+            &proxy.unwrap_or(*address),
+            timeout_settings,
+            socket_settings,
+        )
+        .await?;
+        if proxy.is_some() {
+            socks5_handshake_async(&mut socket, Command::Connect, address, timeout_settings).await?;
+        }
+
+        let (read_timeout, write_timeout) = TimeoutSettings::get_read_and_write_or_defaults(timeout_settings);
+
+        Ok(Self {
+            socket,
+            address: *address,
+            read_timeout,
+            write_timeout,
+            timer: LatencyTimer::default(),
+        })
+    }
+
+    fn apply_timeout(&mut self, timeout_settings: &Option<TimeoutSettings>) -> GDResult<()> {
+        (self.read_timeout, self.write_timeout) = TimeoutSettings::get_read_and_write_or_defaults(timeout_settings);
+
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> GDResult<()> {
+        use tokio::io::AsyncWriteExt;
+
+        with_timeout(self.write_timeout, self.socket.write_all(data))
+            .await
+            .map_err(|e| PacketSend.context(e))?;
+        self.timer.sent();
+
+        Ok(())
+    }
+
+    async fn receive(&mut self, size: Option<usize>) -> GDResult<Vec<u8>> {
+        use tokio::io::AsyncReadExt;
+
+        let mut buf = Vec::with_capacity(size.unwrap_or(DEFAULT_PACKET_SIZE));
+        with_timeout(self.read_timeout, self.socket.read_to_end(&mut buf))
+            .await
+            .map_err(|e| PacketReceive.context(e))?;
+        self.timer.received();
+
+        Ok(buf)
+    }
+
+    #[cfg(feature = "games")]
+    fn port(&self) -> u16 { self.address.port() }
+
+    fn latency(&self) -> Option<Duration> { self.timer.latency() }
+}
+
+/// Implementation of an async UDP socket.
+///
+/// When created inside [UdpSocketPool::scope], the socket sends and receives
+/// through one of the pool's shared sockets instead of binding its own.
+#[cfg(feature = "async")]
+pub struct AsyncUdpSocketImpl {
+    /// The underlying UDP socket (or route on a pooled socket).
+    socket: AsyncUdpTransport,
+    /// The address of the remote host.
+    address: SocketAddr,
+    /// Timeout for each read.
+    read_timeout: Option<std::time::Duration>,
+    /// Timeout for each write.
+    write_timeout: Option<std::time::Duration>,
+    /// Timer of the first exchange.
+    timer: LatencyTimer,
+    /// The SOCKS5 association datagrams are relayed through, if proxied.
+    association: Option<UdpAssociation<tokio::net::TcpStream>>,
+}
+
+#[cfg(feature = "async")]
+enum AsyncUdpTransport {
+    Owned(tokio::net::UdpSocket),
+    Pooled(PooledUdpRoute),
+}
+
+#[cfg(feature = "async")]
+impl AsyncSocket for AsyncUdpSocketImpl {
+    const KIND: TransportKind = TransportKind::Udp;
+
+    async fn new(
+        address: &SocketAddr,
+        timeout_settings: &Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<Self> {
+        let socket_settings = scope.socket_settings();
+        let association = match socket_settings.get_proxy() {
+            Some(proxy) => {
+                let mut control = connect_tcp_async(&proxy, timeout_settings, socket_settings).await?;
+                let bound = socks5_handshake_async(
+                    &mut control,
+                    Command::UdpAssociate,
+                    &unspecified(proxy.is_ipv4()),
+                    timeout_settings,
+                )
+                .await?;
+
+                Some(UdpAssociation {
+                    relay: socks5::relay_address(bound, &proxy),
+                    control,
+                })
+            }
+            None => None,
+        };
+
+        // Proxied datagrams all come from the relay, so they can't be pooled.
+        let pooled = match association {
+            Some(_) => None,
+            None => {
+                UDP_SOCKET_POOL
+                    .try_with(|pool| pool.route(address))
+                    .ok()
+                    .flatten()
+            }
+        };
+
+        let socket = match pooled {
+            Some(route) => AsyncUdpTransport::Pooled(route),
+            None => {
+                let ipv4 = association
+                    .as_ref()
+                    .map_or(address.is_ipv4(), |association| association.relay.is_ipv4());
+
+                AsyncUdpTransport::Owned(bind_local(socket_settings, ipv4, bind_async_udp)?)
+            }
+        };
+
+        let (read_timeout, write_timeout) = TimeoutSettings::get_read_and_write_or_defaults(timeout_settings);
+
+        Ok(Self {
+            socket,
+            address: *address,
+            read_timeout,
+            write_timeout,
+            timer: LatencyTimer::default(),
+            association,
+        })
+    }
+
+    fn apply_timeout(&mut self, timeout_settings: &Option<TimeoutSettings>) -> GDResult<()> {
+        (self.read_timeout, self.write_timeout) = TimeoutSettings::get_read_and_write_or_defaults(timeout_settings);
+
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> GDResult<()> {
+        let socket = match &self.socket {
+            AsyncUdpTransport::Owned(socket) => socket,
+            AsyncUdpTransport::Pooled(route) => &route.socket.socket,
+        };
+
+        let sent = match &self.association {
+            Some(association) => {
+                let datagram = socks5::wrap_datagram(&self.address, data);
+                with_timeout(
+                    self.write_timeout,
+                    socket.send_to(&datagram, association.relay),
+                )
+                .await
+            }
+            None => with_timeout(self.write_timeout, socket.send_to(data, self.address)).await,
+        };
+        sent.map_err(|e| PacketSend.context(e))?;
+        self.timer.sent();
+
+        Ok(())
+    }
+
+    async fn receive(&mut self, size: Option<usize>) -> GDResult<Vec<u8>> {
+        let size = size.unwrap_or(DEFAULT_PACKET_SIZE);
+
+        let data = match &mut self.socket {
+            AsyncUdpTransport::Owned(socket) => {
+                let header_size = self
+                    .association
+                    .as_ref()
+                    .map_or(0, |_| socks5::MAX_HEADER_SIZE);
+
+                let mut buf: Vec<u8> = vec![0; size + header_size];
+                loop {
+                    let (number_of_bytes_received, _) = with_timeout(self.read_timeout, socket.recv_from(&mut buf))
+                        .await
+                        .map_err(|e| PacketReceive.context(e))?;
+
+                    let data = &buf[.. number_of_bytes_received];
+                    let data = match self.association {
+                        // Datagrams relayed from other hosts are dropped.
+                        Some(_) => {
+                            match socks5::unwrap_datagram(data, &self.address)? {
+                                Some(data) => data,
+                                None => continue,
+                            }
+                        }
+                        None => data,
+                    };
+
+                    break data[.. data.len().min(size)].to_vec();
+                }
+            }
+            AsyncUdpTransport::Pooled(route) => {
+                let mut buf = with_timeout(self.read_timeout, async {
+                    route
+                        .receiver
+                        .recv()
+                        .await
+                        .ok_or_else(|| std::io::ErrorKind::BrokenPipe.into())
+                })
+                .await
+                .map_err(|e| PacketReceive.context(e))?;
+
+                buf.truncate(size);
+
+                buf
+            }
+        };
+        self.timer.received();
+
+        Ok(data)
+    }
+
+    #[cfg(feature = "games")]
+    fn port(&self) -> u16 { self.address.port() }
+
+    fn latency(&self) -> Option<Duration> { self.timer.latency() }
+}
+
+/// Bind a tokio UDP socket, done through std so it can be used with
+/// [bind_local].
+#[cfg(feature = "async")]
+fn bind_async_udp(local: SocketAddr) -> std::io::Result<tokio::net::UdpSocket> {
+    let socket = net::UdpSocket::bind(local)?;
+    socket.set_nonblocking(true)?;
+
+    tokio::net::UdpSocket::from_std(socket)
+}
+
+#[cfg(feature = "async")]
+tokio::task_local! {
+    /// The pool used by [AsyncUdpSocketImpl]s created in the current task.
+    static UDP_SOCKET_POOL: std::sync::Arc<UdpSocketPool>;
+}
+
+/// Largest UDP datagram the pool's receivers will read.
+#[cfg(all(feature = "async", feature = "games"))]
+const MAX_DATAGRAM_SIZE: usize = 65535;
+
+/// A small set of shared UDP sockets that many async queries are multiplexed
+/// over, responses are demultiplexed by their source address.
+#[cfg(feature = "async")]
+pub(crate) struct UdpSocketPool {
+    sockets: Vec<std::sync::Arc<PooledUdpSocket>>,
+    next: std::sync::atomic::AtomicUsize,
+}
+
+#[cfg(feature = "async")]
+struct PooledUdpSocket {
+    socket: tokio::net::UdpSocket,
+    routes: std::sync::Mutex<std::collections::HashMap<SocketAddr, tokio::sync::mpsc::UnboundedSender<Vec<u8>>>>,
+}
+
+/// A registration of a remote address on a pooled socket, removed on drop.
+#[cfg(feature = "async")]
+struct PooledUdpRoute {
+    socket: std::sync::Arc<PooledUdpSocket>,
+    address: SocketAddr,
+    receiver: tokio::sync::mpsc::UnboundedReceiver<Vec<u8>>,
+}
+
+#[cfg(feature = "async")]
+impl Drop for PooledUdpRoute {
+    fn drop(&mut self) {
+        if let Ok(mut routes) = self.socket.routes.lock() {
+            routes.remove(&self.address);
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl UdpSocketPool {
+    /// Bind `count` sockets for each of the requested address families, to
+    /// the local address and ports of the socket settings.
+    #[cfg(feature = "games")]
+    pub(crate) async fn bind(
+        count: usize,
+        ipv4: bool,
+        ipv6: bool,
+        socket_settings: &SocketSettings,
+    ) -> GDResult<std::sync::Arc<Self>> {
+        let mut sockets = Vec::new();
+        for (enabled, is_ipv4) in [(ipv4, true), (ipv6, false)] {
+            if !enabled {
+                continue;
+            }
+
+            for _ in 0 .. count.max(1) {
+                let socket = bind_local(socket_settings, is_ipv4, bind_async_udp)?;
+
+                sockets.push(std::sync::Arc::new(PooledUdpSocket {
+                    socket,
+                    routes: Default::default(),
+                }));
+            }
+        }
+
+        Ok(std::sync::Arc::new(Self {
+            sockets,
+            next: Default::default(),
+        }))
+    }
+
+    /// Run a future with the pool used by every async UDP socket it creates.
+    #[cfg(feature = "games")]
+    pub(crate) async fn scope<F: std::future::Future>(self: &std::sync::Arc<Self>, future: F) -> F::Output {
+        UDP_SOCKET_POOL.scope(self.clone(), future).await
+    }
+
+    /// Spawn a receiver task per socket which forwards each datagram to the
+    /// route registered for its source address (unrouted ones are dropped).
+    /// A receiver stops on errors concerning the socket itself, leaving the
+    /// routes on it to time out.
+    #[cfg(feature = "games")]
+    pub(crate) fn spawn_receivers(&self) -> Vec<tokio::task::JoinHandle<()>> {
+        self.sockets
+            .iter()
+            .map(|pooled| {
+                let pooled = pooled.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0; MAX_DATAGRAM_SIZE];
+                    loop {
+                        let (size, source) = match pooled.socket.recv_from(&mut buf).await {
+                            Ok(received) => received,
+                            // Errors such as ICMP port unreachable only concern one
+                            // remote, the route's read timeout will handle it.
+                            Err(e)
+                                if matches!(
+                                    e.kind(),
+                                    std::io::ErrorKind::ConnectionRefused
+                                        | std::io::ErrorKind::ConnectionReset
+                                        | std::io::ErrorKind::Interrupted
+                                ) =>
+                            {
+                                continue;
+                            }
+                            Err(_) => break,
+                        };
+
+                        if let Ok(routes) = pooled.routes.lock() {
+                            if let Some(sender) = routes.get(&source) {
+                                let _ = sender.send(buf[.. size].to_vec());
+                            }
+                        }
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Register an address on a socket of the same family that isn't already
+    /// talking to it, returns [None] if there isn't one.
+    fn route(&self, address: &SocketAddr) -> Option<PooledUdpRoute> {
+        let start = self.next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        for i in 0 .. self.sockets.len() {
+            let pooled = &self.sockets[(start + i) % self.sockets.len()];
+            if pooled.socket.local_addr().ok()?.is_ipv4() != address.is_ipv4() {
+                continue;
+            }
+
+            let mut routes = pooled.routes.lock().ok()?;
+            if routes.contains_key(address) {
+                continue;
+            }
+
+            let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+            routes.insert(*address, sender);
+
+            return Some(PooledUdpRoute {
+                socket: pooled.clone(),
+                address: *address,
+                receiver,
+            });
+        }
+
+        None
+    }
+}
+
+/// A socket whose transport was created by the
+/// [Connector](crate::transport::Connector) of the socket settings.
+pub struct CustomSocket {
+    /// The transport, only missing if an async operation using it was
+    /// cancelled.
+    transport: Option<Box<dyn Transport>>,
+    /// The address of the remote host.
+    #[cfg(feature = "games")]
+    address: SocketAddr,
+    /// Timer of the first exchange.
+    timer: LatencyTimer,
+}
+
+impl CustomSocket {
+    /// Connect with the connector of the query's socket settings, if there's
+    /// one, giving it the timeout settings of the first operation.
+    fn connect(
+        kind: TransportKind,
+        address: &SocketAddr,
+        scope: &QueryScope,
+        operation_timeout: &Option<TimeoutSettings>,
+    ) -> Option<GDResult<Self>> {
+        let connector = scope.socket_settings().get_connector()?;
+
+        Some(
+            connector
+                .connect(kind, address, operation_timeout)
+                .map(|transport| {
+                    Self {
+                        transport: Some(transport),
+                        #[cfg(feature = "games")]
+                        address: *address,
+                        timer: LatencyTimer::default(),
+                    }
+                }),
+        )
+    }
+
+    fn transport(&mut self) -> GDResult<&mut Box<dyn Transport>> {
+        self.transport
+            .as_mut()
+            .ok_or_else(|| SocketConnect.context("The transport was lost by a cancelled operation"))
+    }
+
+    fn send(&mut self, data: &[u8]) -> GDResult<()> {
+        self.transport()?.send(data)?;
+        self.timer.sent();
+
+        Ok(())
+    }
+
+    fn receive(&mut self, size: Option<usize>) -> GDResult<Vec<u8>> {
+        let data = self.transport()?.receive(size)?;
+        self.timer.received();
+
+        Ok(data)
+    }
+
+    /// Run `operation` on tokio's blocking threads, as transports are
+    /// blocking.
+    #[cfg(feature = "async")]
+    async fn blocking<T: Send + 'static>(
+        &mut self,
+        operation: impl FnOnce(&mut dyn Transport) -> GDResult<T> + Send + 'static,
+    ) -> GDResult<T> {
+        let mut transport = self
+            .transport
+            .take()
+            .ok_or_else(|| SocketConnect.context("The transport was lost by a cancelled operation"))?;
+
+        let (transport, result) = tokio::task::spawn_blocking(move || {
+            let result = operation(transport.as_mut());
+            (transport, result)
+        })
+        .await
+        .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+        self.transport = Some(transport);
+
+        result
+    }
+}
+
+/// A socket created by the [Connector](crate::transport::Connector) of the
+/// socket settings if there is one, else by the built-in implementation `S`.
+///
+/// The read and write timeouts of built-in sockets follow the timeout factor
+/// of the try being made by a [RetryPolicy](crate::RetryPolicy) and are
+/// shortened to fit the query deadline, operations failing once it expired.
+pub struct PluggableSocket<S> {
+    socket: Plugged<S>,
+    /// The settings the socket was created with.
+    timeout_settings: Option<TimeoutSettings>,
+    /// The query the socket is used for, holding its deadline.
+    scope: QueryScope,
+    /// The factor the timeouts currently applied are multiplied by.
+    timeout_factor: u32,
+}
+
+enum Plugged<S> {
+    /// A socket of the built-in implementation.
+    Builtin(S),
+    /// A socket of the connector.
+    Custom(CustomSocket),
+}
+
+impl<S> PluggableSocket<S> {
+    fn plugged(
+        socket: Plugged<S>,
+        timeout_settings: &Option<TimeoutSettings>,
+        scope: &QueryScope,
+        timeout_factor: u32,
+    ) -> Self {
+        Self {
+            socket,
+            timeout_settings: *timeout_settings,
+            scope: scope.clone(),
+            timeout_factor,
+        }
+    }
+
+    /// The timeout settings to apply before the next operation, if they
+    /// changed from the ones currently applied (custom transports are given
+    /// the settings once, when they are connected, only the query deadline is
+    /// checked for them).
+    fn operation_timeout(&mut self, timeout_factor: u32) -> GDResult<Option<Option<TimeoutSettings>>> {
+        let deadline = self.scope.remaining()?;
+        if matches!(self.socket, Plugged::Custom(_)) || (deadline.is_none() && timeout_factor == self.timeout_factor) {
+            return Ok(None);
+        }
+
+        self.timeout_factor = timeout_factor;
+        self.scope
+            .operation_timeout(&self.timeout_settings, timeout_factor)
+            .map(Some)
+    }
+
+    /// Report operations that failed because they were cut short by the
+    /// query deadline as such.
+    fn check_deadline<T>(&self, result: GDResult<T>) -> GDResult<T> {
+        match result {
+            Err(_) => self.scope.remaining().and(result),
+            Ok(_) => result,
+        }
+    }
+}
+
+impl<S: Socket> PluggableSocket<S> {
+    fn follow_timeout(&mut self) -> GDResult<()> {
+        match self.operation_timeout(crate::utils::timeout_factor())? {
+            Some(timeout_settings) => self.apply_timeout(&timeout_settings),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<S: Socket> Socket for PluggableSocket<S> {
+    const KIND: TransportKind = S::KIND;
+
+    fn new(address: &SocketAddr, timeout_settings: &Option<TimeoutSettings>, scope: &QueryScope) -> GDResult<Self> {
+        let timeout_factor = crate::utils::timeout_factor();
+        let operation_timeout = scope.operation_timeout(timeout_settings, timeout_factor)?;
+
+        let socket = match CustomSocket::connect(S::KIND, address, scope, &operation_timeout) {
+            Some(socket) => Plugged::Custom(socket?),
+            None => Plugged::Builtin(S::new(address, &operation_timeout, scope)?),
+        };
+
+        Ok(Self::plugged(
+            socket,
+            timeout_settings,
+            scope,
+            timeout_factor,
+        ))
+    }
+
+    fn apply_timeout(&self, timeout_settings: &Option<TimeoutSettings>) -> GDResult<()> {
+        match &self.socket {
+            Plugged::Builtin(socket) => socket.apply_timeout(timeout_settings),
+            // Given to the connector, for the transport to apply.
+            Plugged::Custom(_) => Ok(()),
+        }
+    }
+
+    fn send(&mut self, data: &[u8]) -> GDResult<()> {
+        self.follow_timeout()?;
+
+        let result = match &mut self.socket {
+            Plugged::Builtin(socket) => socket.send(data),
+            Plugged::Custom(socket) => socket.send(data),
+        };
+
+        self.check_deadline(result)
+    }
+
+    fn receive(&mut self, size: Option<usize>) -> GDResult<Vec<u8>> {
+        self.follow_timeout()?;
+
+        let result = match &mut self.socket {
+            Plugged::Builtin(socket) => socket.receive(size),
+            Plugged::Custom(socket) => socket.receive(size),
+        };
+
+        self.check_deadline(result)
+    }
+
+    #[cfg(feature = "games")]
+    fn port(&self) -> u16 {
+        match &self.socket {
+            Plugged::Builtin(socket) => socket.port(),
+            Plugged::Custom(socket) => socket.address.port(),
+        }
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        match &self.socket {
+            Plugged::Builtin(socket) => socket.latency(),
+            Plugged::Custom(socket) => socket.timer.latency(),
+        }
+    }
+
+    #[cfg(feature = "packet_capture")]
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        match &self.socket {
+            Plugged::Builtin(socket) => socket.local_addr(),
+            Plugged::Custom(_) => Err(ErrorKind::Unsupported.into()),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncSocket> PluggableSocket<S> {
+    fn follow_async_timeout(&mut self) -> GDResult<()> {
+        match self.operation_timeout(crate::utils::async_timeout_factor())? {
+            Some(timeout_settings) => self.apply_timeout(&timeout_settings),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncSocket> AsyncSocket for PluggableSocket<S> {
+    const KIND: TransportKind = S::KIND;
+
+    async fn new(
+        address: &SocketAddr,
+        timeout_settings: &Option<TimeoutSettings>,
+        scope: &QueryScope,
+    ) -> GDResult<Self> {
+        let timeout_factor = crate::utils::async_timeout_factor();
+        let operation_timeout = scope.operation_timeout(timeout_settings, timeout_factor)?;
+
+        let socket = if scope.socket_settings().get_connector().is_none() {
+            Plugged::Builtin(S::new(address, &operation_timeout, scope).await?)
+        } else {
+            let (address, connect_scope) = (*address, scope.clone());
+            let socket = tokio::task::spawn_blocking(move || {
+                CustomSocket::connect(S::KIND, &address, &connect_scope, &operation_timeout)
+            })
+            .await
+            .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+            .expect("the connector is set")?;
+
+            Plugged::Custom(socket)
+        };
+
+        Ok(Self::plugged(
+            socket,
+            timeout_settings,
+            scope,
+            timeout_factor,
+        ))
+    }
+
+    fn apply_timeout(&mut self, timeout_settings: &Option<TimeoutSettings>) -> GDResult<()> {
+        match &mut self.socket {
+            Plugged::Builtin(socket) => socket.apply_timeout(timeout_settings),
+            // Given to the connector, for the transport to apply.
+            Plugged::Custom(_) => Ok(()),
+        }
+    }
+
+    async fn send(&mut self, data: &[u8]) -> GDResult<()> {
+        self.follow_async_timeout()?;
+
+        let result = match &mut self.socket {
+            Plugged::Builtin(socket) => socket.send(data).await,
+            Plugged::Custom(socket) => {
+                let data = data.to_vec();
+                let result = socket
+                    .blocking(move |transport| transport.send(&data))
+                    .await;
+                if result.is_ok() {
+                    socket.timer.sent();
+                }
+
+                result
+            }
+        };
+
+        self.check_deadline(result)
+    }
+
+    async fn receive(&mut self, size: Option<usize>) -> GDResult<Vec<u8>> {
+        self.follow_async_timeout()?;
+
+        let result = match &mut self.socket {
+            Plugged::Builtin(socket) => socket.receive(size).await,
+            Plugged::Custom(socket) => {
+                let result = socket
+                    .blocking(move |transport| transport.receive(size))
+                    .await;
+                if result.is_ok() {
+                    socket.timer.received();
+                }
+
+                result
+            }
+        };
+
+        self.check_deadline(result)
+    }
+
+    #[cfg(feature = "games")]
+    fn port(&self) -> u16 {
+        match &self.socket {
+            Plugged::Builtin(socket) => socket.port(),
+            Plugged::Custom(socket) => socket.address.port(),
+        }
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        match &self.socket {
+            Plugged::Builtin(socket) => socket.latency(),
+            Plugged::Custom(socket) => socket.timer.latency(),
+        }
+    }
+}
+
 #[cfg(not(feature = "packet_capture"))]
-pub type UdpSocket = UdpSocketImpl;
-#[cfg(not(feature = "packet_capture"))]
-pub type TcpSocket = TcpSocketImpl;
+pub type UdpSocket = PluggableSocket<UdpSocketImpl>;
+#[cfg(all(not(feature = "packet_capture"), feature = "games"))]
+pub type TcpSocket = PluggableSocket<TcpSocketImpl>;
 
 #[cfg(feature = "packet_capture")]
-pub(crate) type UdpSocket = crate::capture::socket::CapturedUdpSocket;
-#[cfg(feature = "packet_capture")]
-pub(crate) type TcpSocket = crate::capture::socket::CapturedTcpSocket;
+pub(crate) type UdpSocket = PluggableSocket<crate::capture::socket::CapturedUdpSocket>;
+#[cfg(all(feature = "packet_capture", feature = "games"))]
+pub(crate) type TcpSocket = PluggableSocket<crate::capture::socket::CapturedTcpSocket>;
+
+#[cfg(feature = "async")]
+pub type AsyncUdpSocket = PluggableSocket<AsyncUdpSocketImpl>;
+#[cfg(all(feature = "async", feature = "games"))]
+pub type AsyncTcpSocket = PluggableSocket<AsyncTcpSocketImpl>;
 
 #[cfg(test)]
 mod tests {
+    use std::io::{Read, Write};
     use std::thread;
 
     use super::*;
+    use crate::transport::Scripted;
+    use crate::GDErrorKind::DeadlineExceeded;
+    use std::sync::Arc;
 
+    #[cfg(feature = "games")]
     #[test]
     fn test_tcp_socket_send_and_receive() {
         // Spawn a thread to run the server
@@ -201,7 +1300,7 @@ mod tests {
         });
 
         // Create a TCP socket and send a message to the server
-        let mut socket = TcpSocket::new(&bound_address, &None).unwrap();
+        let mut socket = TcpSocket::new(&bound_address, &None, &QueryScope::default()).unwrap();
         let message = b"hello, world!";
         socket.send(message).unwrap();
 
@@ -232,7 +1331,7 @@ mod tests {
         });
 
         // Create a UDP socket and send a message to the server
-        let mut socket = UdpSocket::new(&bound_address, &None).unwrap();
+        let mut socket = UdpSocket::new(&bound_address, &None, &QueryScope::default()).unwrap();
         let message = b"hello, world!";
         socket.send(message).unwrap();
 
@@ -250,4 +1349,283 @@ mod tests {
 
         assert_eq!(message, &received_message[..]);
     }
+
+    #[test]
+    fn test_udp_socket_query_deadline() {
+        // Never answers.
+        let server = net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let bound_address = server.local_addr().unwrap();
+
+        let timeout_settings = Some(TimeoutSettings::default().set_query_deadline(Duration::from_millis(200)));
+        let started = std::time::Instant::now();
+
+        let scope = QueryScope::start(&timeout_settings);
+        let mut socket = UdpSocket::new(&bound_address, &timeout_settings, &scope).unwrap();
+        socket.send(b"hello").unwrap();
+        let error = socket.receive(None).unwrap_err();
+
+        assert_eq!(error.kind, DeadlineExceeded);
+        // Well before the 4 seconds of the read timeout.
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(socket.send(b"hello").unwrap_err().kind, DeadlineExceeded);
+    }
+
+    #[test]
+    fn test_udp_socket_latency() {
+        let socket = net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let bound_address = socket.local_addr().unwrap();
+        let server_thread = thread::spawn(move || {
+            let mut buf = [0; 16];
+            for delay in [50, 0] {
+                let (_, src_addr) = socket.recv_from(&mut buf).unwrap();
+                thread::sleep(Duration::from_millis(delay));
+                socket.send_to(&buf, src_addr).unwrap();
+            }
+        });
+
+        let mut socket = UdpSocket::new(&bound_address, &None, &QueryScope::default()).unwrap();
+        assert_eq!(socket.latency(), None);
+
+        socket.send(b"first").unwrap();
+        socket.receive(None).unwrap();
+        let latency = socket.latency().unwrap();
+        assert!(latency >= Duration::from_millis(50));
+
+        // Only the first exchange is measured.
+        socket.send(b"second").unwrap();
+        socket.receive(None).unwrap();
+        assert_eq!(socket.latency(), Some(latency));
+
+        server_thread.join().expect("server thread panicked");
+    }
+
+    #[test]
+    fn test_udp_socket_local_bind() {
+        let socket = net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let bound_address = socket.local_addr().unwrap();
+        let server_thread = thread::spawn(move || {
+            let mut buf = [0; 16];
+            let (_, src_addr) = socket.recv_from(&mut buf).unwrap();
+            src_addr
+        });
+
+        // Occupy the first port of the range so the next one gets used.
+        let occupied = net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = occupied.local_addr().unwrap().port();
+        let scope = QueryScope::default().set_socket_settings(
+            SocketSettings::new()
+                .set_bind_address(IpAddr::V4(Ipv4Addr::LOCALHOST))
+                .set_bind_ports(crate::PortRange::new(port, port.saturating_add(10)).unwrap()),
+        );
+
+        let mut socket = UdpSocket::new(&bound_address, &None, &scope).unwrap();
+        socket.send(b"hello").unwrap();
+
+        let src_addr = server_thread.join().expect("server thread panicked");
+        assert_eq!(src_addr.ip(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert!(src_addr.port() > port && src_addr.port() <= port.saturating_add(10));
+    }
+
+    #[test]
+    fn test_bind_address_family_mismatch() {
+        let scope = QueryScope::default()
+            .set_socket_settings(SocketSettings::new().set_bind_address(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+
+        let error = UdpSocket::new(&"127.0.0.1:27015".parse().unwrap(), &None, &scope)
+            .err()
+            .unwrap();
+        assert_eq!(error, SocketBind.into());
+    }
+
+    /// Accept one SOCKS5 client on `listener`, returning the stream once a
+    /// request for `command` has been answered with `bound`.
+    fn accept_socks5(listener: &net::TcpListener, command: u8, bound: SocketAddr) -> net::TcpStream {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut greeting = [0; 3];
+        stream.read_exact(&mut greeting).unwrap();
+        stream.write_all(&[5, 0]).unwrap();
+
+        let mut request = [0; 10];
+        stream.read_exact(&mut request).unwrap();
+        assert_eq!(request[.. 2], [5, command]);
+
+        let SocketAddr::V4(bound) = bound else { unreachable!() };
+        let mut reply = vec![5, 0, 0, 1];
+        reply.extend(bound.ip().octets());
+        reply.extend(bound.port().to_be_bytes());
+        stream.write_all(&reply).unwrap();
+
+        stream
+    }
+
+    #[cfg(feature = "games")]
+    #[test]
+    fn test_tcp_socket_socks5_proxy() {
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy = listener.local_addr().unwrap();
+        let proxy_thread = thread::spawn(move || {
+            // Pretend to be the target once connected.
+            let mut stream = accept_socks5(&listener, 1, "0.0.0.0:0".parse().unwrap());
+            let mut buf = [0; 5];
+            stream.read_exact(&mut buf).unwrap();
+            stream.write_all(&buf).unwrap();
+        });
+
+        let scope = QueryScope::default().set_socket_settings(SocketSettings::new().set_proxy(proxy));
+        let mut socket = TcpSocket::new(&"192.0.2.1:25565".parse().unwrap(), &None, &scope).unwrap();
+        socket.send(b"hello").unwrap();
+        assert_eq!(socket.receive(None).unwrap(), b"hello");
+
+        proxy_thread.join().expect("proxy thread panicked");
+    }
+
+    #[test]
+    fn test_udp_socket_socks5_proxy() {
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy = listener.local_addr().unwrap();
+        let relay = net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let relay_address = relay.local_addr().unwrap();
+        let target: SocketAddr = "192.0.2.1:27015".parse().unwrap();
+        let proxy_thread = thread::spawn(move || {
+            let _control = accept_socks5(&listener, 3, relay_address);
+
+            let mut buf = [0; 64];
+            let (size, client) = relay.recv_from(&mut buf).unwrap();
+            assert_eq!(buf[.. size], socks5::wrap_datagram(&target, b"ping")[..]);
+            // A datagram from another host, which is dropped.
+            let other = "192.0.2.2:27015".parse().unwrap();
+            relay
+                .send_to(&socks5::wrap_datagram(&other, b"spam"), client)
+                .unwrap();
+            relay
+                .send_to(&socks5::wrap_datagram(&target, b"pong"), client)
+                .unwrap();
+        });
+
+        let scope = QueryScope::default().set_socket_settings(SocketSettings::new().set_proxy(proxy));
+        let mut socket = UdpSocket::new(&target, &None, &scope).unwrap();
+        socket.send(b"ping").unwrap();
+        assert_eq!(socket.receive(None).unwrap(), b"pong");
+
+        proxy_thread.join().expect("proxy thread panicked");
+    }
+
+    #[test]
+    fn test_custom_transport() {
+        // Nothing listens there, the connector is used instead.
+        let address: SocketAddr = "192.0.2.1:27015".parse().unwrap();
+        let connector = Arc::new(Scripted::new().add_packet(*b"pong"));
+        let scope = QueryScope::default().set_socket_settings(SocketSettings::new().set_connector(connector.clone()));
+
+        let mut socket = UdpSocket::new(&address, &None, &scope).unwrap();
+        socket.send(b"ping").unwrap();
+        assert_eq!(socket.receive(None).unwrap(), b"pong");
+        assert!(socket.latency().is_some());
+        assert!(connector
+            .connections()
+            .contains(&(TransportKind::Udp, address)));
+
+        // TCP sockets (and the port) are only used by games.
+        #[cfg(feature = "games")]
+        {
+            assert_eq!(socket.port(), 27015);
+
+            let mut socket = TcpSocket::new(&address, &None, &scope).unwrap();
+            socket.send(b"hello").unwrap();
+            assert_eq!(socket.receive(None).unwrap(), b"pong");
+            assert_eq!(socket.receive(None).unwrap_err().kind, PacketReceive);
+
+            assert!(connector
+                .connections()
+                .contains(&(TransportKind::Tcp, address)));
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_custom_transport() {
+        let address: SocketAddr = "192.0.2.2:27015".parse().unwrap();
+        let connector = Arc::new(Scripted::new().add_packet(*b"pong"));
+        let scope = QueryScope::default().set_socket_settings(SocketSettings::new().set_connector(connector));
+
+        let mut socket = AsyncUdpSocket::new(&address, &None, &scope).await.unwrap();
+        socket.send(b"ping").await.unwrap();
+        assert_eq!(socket.receive(None).await.unwrap(), b"pong");
+        assert!(socket.latency().is_some());
+    }
+
+    #[cfg(all(feature = "async", feature = "games"))]
+    #[tokio::test]
+    async fn test_async_tcp_socket_send_and_receive() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let bound_address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let size = stream.read(&mut buf).await.unwrap();
+            stream.write_all(&buf[.. size]).await.unwrap();
+        });
+
+        let mut socket = AsyncTcpSocket::new(&bound_address, &None, &QueryScope::default())
+            .await
+            .unwrap();
+        let message = b"hello, world!";
+        socket.send(message).await.unwrap();
+
+        let received_message = socket.receive(None).await.unwrap();
+
+        server.await.expect("server task panicked");
+
+        assert_eq!(message, &received_message[..]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_udp_socket_send_and_receive() {
+        let server_socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let bound_address = server_socket.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut buf = [0; 1024];
+            let (size, src_addr) = server_socket.recv_from(&mut buf).await.unwrap();
+            server_socket
+                .send_to(&buf[.. size], src_addr)
+                .await
+                .unwrap();
+        });
+
+        let mut socket = AsyncUdpSocket::new(&bound_address, &None, &QueryScope::default())
+            .await
+            .unwrap();
+        let message = b"hello, world!";
+        socket.send(message).await.unwrap();
+
+        let received_message = socket.receive(None).await.unwrap();
+
+        server.await.expect("server task panicked");
+
+        assert_eq!(message, &received_message[..]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_udp_socket_receive_timeout() {
+        let server_socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let bound_address = server_socket.local_addr().unwrap();
+
+        let timeout_settings = TimeoutSettings::new(Some(std::time::Duration::from_millis(50)), None, None, 0).unwrap();
+
+        let mut socket = AsyncUdpSocket::new(
+            &bound_address,
+            &Some(timeout_settings),
+            &QueryScope::default(),
+        )
+        .await
+        .unwrap();
+        socket.send(b"hello").await.unwrap();
+
+        let error = socket.receive(None).await.unwrap_err();
+        assert_eq!(error, PacketReceive.into());
+    }
 }
@@ -0,0 +1,279 @@
+//! Minimal SOCKS5 client (no authentication), used to tunnel the sockets.
+//!
+//! [Reference](https://datatracker.ietf.org/doc/html/rfc1928)
+
+use crate::{
+    GDErrorKind::{PacketBad, SocketConnect},
+    GDResult,
+};
+
+use std::{
+    io::{Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+
+const VERSION: u8 = 5;
+const NO_AUTHENTICATION: u8 = 0;
+
+/// Commands that can be requested from the proxy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub(crate) enum Command {
+    #[cfg(feature = "games")]
+    Connect = 1,
+    UdpAssociate = 3,
+}
+
+/// The greeting, offering no authentication.
+const GREETING: [u8; 3] = [VERSION, 1, NO_AUTHENTICATION];
+
+fn check_method(reply: [u8; 2]) -> GDResult<()> {
+    match reply {
+        [VERSION, NO_AUTHENTICATION] => Ok(()),
+        [VERSION, _] => Err(SocketConnect.context("SOCKS5 proxy requires authentication")),
+        _ => Err(SocketConnect.context("Not a SOCKS5 proxy")),
+    }
+}
+
+/// Append the address type, address and port of `address`.
+fn write_address(buf: &mut Vec<u8>, address: &SocketAddr) {
+    match address.ip() {
+        IpAddr::V4(ip) => {
+            buf.push(1);
+            buf.extend(ip.octets());
+        }
+        IpAddr::V6(ip) => {
+            buf.push(4);
+            buf.extend(ip.octets());
+        }
+    }
+
+    buf.extend(address.port().to_be_bytes());
+}
+
+fn request(command: Command, address: &SocketAddr) -> Vec<u8> {
+    let mut buf = vec![VERSION, command as u8, 0];
+    write_address(&mut buf, address);
+
+    buf
+}
+
+/// Check the fixed part of a reply, along with the first byte of the bound
+/// address (the length of a domain name), returning how many bytes of address
+/// and port follow it.
+fn check_reply_head(head: [u8; 5]) -> GDResult<usize> {
+    if head[0] != VERSION {
+        return Err(SocketConnect.context("Not a SOCKS5 proxy"));
+    }
+
+    if head[1] != 0 {
+        return Err(SocketConnect.context(format!(
+            "SOCKS5 proxy refused the request (reply {})",
+            head[1]
+        )));
+    }
+
+    match head[3] {
+        1 => Ok(3 + 2),
+        3 => Ok(usize::from(head[4]) + 2),
+        4 => Ok(15 + 2),
+        kind => Err(SocketConnect.context(format!("Unsupported SOCKS5 address type {kind}"))),
+    }
+}
+
+/// Parse the bound address of a reply from its [head](check_reply_head) and
+/// the rest of it, a domain name being taken as the proxy's own host (an
+/// unspecified address, see [relay_address]).
+fn parse_bound_address(head: [u8; 5], rest: &[u8]) -> SocketAddr {
+    let (address, port) = rest.split_at(rest.len() - 2);
+    let mut octets = vec![head[4]];
+    octets.extend_from_slice(address);
+
+    let ip: IpAddr = match head[3] {
+        1 => Ipv4Addr::from(<[u8; 4]>::try_from(octets).unwrap_or_default()).into(),
+        4 => Ipv6Addr::from(<[u8; 16]>::try_from(octets).unwrap_or_default()).into(),
+        _ => Ipv4Addr::UNSPECIFIED.into(),
+    };
+
+    SocketAddr::new(ip, u16::from_be_bytes([port[0], port[1]]))
+}
+
+/// Negotiate `command` for `address` over a connection to the proxy,
+/// returning the address bound by the proxy.
+pub(crate) fn handshake<S: Read + Write>(
+    stream: &mut S,
+    command: Command,
+    address: &SocketAddr,
+) -> GDResult<SocketAddr> {
+    let io = |e| SocketConnect.context(e);
+
+    stream.write_all(&GREETING).map_err(io)?;
+    let mut method = [0; 2];
+    stream.read_exact(&mut method).map_err(io)?;
+    check_method(method)?;
+
+    stream.write_all(&request(command, address)).map_err(io)?;
+    let mut head = [0; 5];
+    stream.read_exact(&mut head).map_err(io)?;
+    let mut rest = vec![0; check_reply_head(head)?];
+    stream.read_exact(&mut rest).map_err(io)?;
+
+    Ok(parse_bound_address(head, &rest))
+}
+
+/// Async version of [handshake].
+#[cfg(feature = "async")]
+pub(crate) async fn handshake_async<S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin>(
+    stream: &mut S,
+    command: Command,
+    address: &SocketAddr,
+) -> GDResult<SocketAddr> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let io = |e| SocketConnect.context(e);
+
+    stream.write_all(&GREETING).await.map_err(io)?;
+    let mut method = [0; 2];
+    stream.read_exact(&mut method).await.map_err(io)?;
+    check_method(method)?;
+
+    stream
+        .write_all(&request(command, address))
+        .await
+        .map_err(io)?;
+    let mut head = [0; 5];
+    stream.read_exact(&mut head).await.map_err(io)?;
+    let mut rest = vec![0; check_reply_head(head)?];
+    stream.read_exact(&mut rest).await.map_err(io)?;
+
+    Ok(parse_bound_address(head, &rest))
+}
+
+/// Largest header of a relayed datagram (for the longest domain name).
+pub(crate) const MAX_HEADER_SIZE: usize = 4 + 1 + 255 + 2;
+
+/// A UDP association, which lasts as long as its control connection is open.
+pub(crate) struct UdpAssociation<C> {
+    /// The connection the association was requested on, only held to keep
+    /// the association alive.
+    #[allow(dead_code)]
+    pub(crate) control: C,
+    /// Where datagrams are to be sent.
+    pub(crate) relay: SocketAddr,
+}
+
+/// The address datagrams should be sent to, proxies commonly answer an
+/// unspecified address meaning "the proxy's own".
+pub(crate) fn relay_address(bound: SocketAddr, proxy: &SocketAddr) -> SocketAddr {
+    if bound.ip().is_unspecified() {
+        SocketAddr::new(proxy.ip(), bound.port())
+    } else {
+        bound
+    }
+}
+
+/// Wrap a datagram to `address` for the UDP relay.
+pub(crate) fn wrap_datagram(address: &SocketAddr, data: &[u8]) -> Vec<u8> {
+    let mut buf = vec![0, 0, 0];
+    write_address(&mut buf, address);
+    buf.extend_from_slice(data);
+
+    buf
+}
+
+/// Remove the relay's header of a datagram, `None` if it was relayed from
+/// another address than `source` (or from a domain name), fragmented ones are
+/// rejected.
+pub(crate) fn unwrap_datagram<'a>(datagram: &'a [u8], source: &SocketAddr) -> GDResult<Option<&'a [u8]>> {
+    let address_size = match datagram {
+        [0, 0, 0, 1, ..] => 4,
+        [0, 0, 0, 3, length, ..] => 1 + usize::from(*length),
+        [0, 0, 0, 4, ..] => 16,
+        [0, 0, 0, kind, ..] => return Err(PacketBad.context(format!("Unsupported SOCKS5 address type {kind}"))),
+        _ => return Err(PacketBad.context("Fragmented or malformed SOCKS5 datagram")),
+    };
+
+    let header_size = 4 + address_size + 2;
+    if datagram.len() < header_size {
+        return Err(PacketBad.context("SOCKS5 datagram too short"));
+    }
+
+    let (header, data) = datagram.split_at(header_size);
+    let mut expected = vec![0, 0, 0];
+    write_address(&mut expected, source);
+
+    Ok((header == expected).then_some(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn datagram_round_trip() {
+        let address = "192.168.1.2:27015".parse().unwrap();
+        let datagram = wrap_datagram(&address, b"data");
+        assert_eq!(
+            datagram,
+            [0, 0, 0, 1, 192, 168, 1, 2, 0x69, 0x87, b'd', b'a', b't', b'a']
+        );
+        assert_eq!(unwrap_datagram(&datagram, &address), Ok(Some(&b"data"[..])));
+
+        assert!(unwrap_datagram(&[0, 0, 1, 1, 0, 0], &address).is_err());
+        assert!(unwrap_datagram(&datagram[.. 8], &address).is_err());
+    }
+
+    #[test]
+    fn datagram_from_other_sources() {
+        let address = "192.168.1.2:27015".parse().unwrap();
+        let other_port = wrap_datagram(&"192.168.1.2:27016".parse().unwrap(), b"data");
+        let other_host = wrap_datagram(&"192.168.1.3:27015".parse().unwrap(), b"data");
+        let domain = [&[0, 0, 0, 3, 7][..], b"a.b.com", &[0x69, 0x87], b"data"].concat();
+
+        assert_eq!(unwrap_datagram(&other_port, &address), Ok(None));
+        assert_eq!(unwrap_datagram(&other_host, &address), Ok(None));
+        assert_eq!(unwrap_datagram(&domain, &address), Ok(None));
+    }
+
+    #[test]
+    fn domain_bound_address() {
+        let head = [5, 0, 0, 3, 9];
+        let mut rest = vec![0; check_reply_head(head).unwrap()];
+        assert_eq!(rest.len(), 9 + 2);
+        rest[.. 9].copy_from_slice(b"localhost");
+        rest[9 ..].copy_from_slice(&[0x04, 0x38]);
+
+        let bound = parse_bound_address(head, &rest);
+        assert_eq!(bound, "0.0.0.0:1080".parse().unwrap());
+        assert_eq!(
+            relay_address(bound, &"10.0.0.1:1080".parse().unwrap()),
+            "10.0.0.1:1080".parse().unwrap()
+        );
+    }
+
+    #[cfg(feature = "games")]
+    #[test]
+    fn handshake_connect() {
+        struct Mock(std::io::Cursor<Vec<u8>>, Vec<u8>);
+        impl Read for Mock {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> { self.0.read(buf) }
+        }
+        impl Write for Mock {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> { self.1.write(buf) }
+            fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+        }
+
+        // Greeting reply, then a success reply bound to 10.0.0.1:1080.
+        let replies = vec![5, 0, 5, 0, 0, 1, 10, 0, 0, 1, 0x04, 0x38];
+        let mut mock = Mock(std::io::Cursor::new(replies), Vec::new());
+        let bound = handshake(
+            &mut mock,
+            Command::Connect,
+            &"127.0.0.1:25565".parse().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(bound, "10.0.0.1:1080".parse().unwrap());
+        assert_eq!(mock.1, [5, 1, 0, 5, 1, 0, 1, 127, 0, 0, 1, 0x63, 0xdd]);
+    }
+}
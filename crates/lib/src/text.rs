@@ -0,0 +1,609 @@
+//! Normalization of the formatting codes that servers embed in names.
+//!
+//! Most engines let server owners and players color their names: Quake uses
+//! `^1`-style codes, Unreal 2 inlines `0x1b R G B` sequences, Minecraft has `§`
+//! codes and JSON chat components and Mindustry uses `[red]` markup. This
+//! module parses those into styled [Segment]s which can then be rendered as
+//! plain text, ANSI terminal colors or HTML spans.
+//!
+//! ```
+//! use gamedig::text::{normalize, Dialect, TextFormat};
+//!
+//! assert_eq!(normalize("^1Red ^7Server", Dialect::Quake, TextFormat::Plain), "Red Server");
+//! ```
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How names containing formatting codes should be returned.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum TextFormat {
+    /// Leave the text exactly as the server sent it.
+    #[default]
+    Raw,
+    /// Strip all formatting codes.
+    Plain,
+    /// Replace the formatting codes with ANSI (24-bit color) escape sequences.
+    Ansi,
+    /// Replace the formatting codes with HTML `<span>`s, the text is escaped.
+    Html,
+}
+
+/// The formatting code flavour a piece of text uses.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dialect {
+    /// No formatting codes, the text is taken as is.
+    None,
+    /// Quake 3 style `^0`-`^9` codes.
+    Quake,
+    /// Unreal 2 `0x1b R G B` sequences.
+    Unreal2,
+    /// Minecraft `§` codes or a JSON chat component.
+    Minecraft,
+    /// Mindustry `[color]`, `[#rrggbb]` and `[]` markup.
+    Mindustry,
+}
+
+/// A 24-bit color.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self { Self { r, g, b } }
+
+    const fn from_hex(hex: u32) -> Self { Self::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8) }
+
+    /// Parse `rrggbb` (an extra alpha component is ignored).
+    fn parse_hex(hex: &str) -> Option<Self> {
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        u32::from_str_radix(&hex[.. 6], 16).ok().map(Self::from_hex)
+    }
+}
+
+/// The style applied to a [Segment].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underlined: bool,
+    pub strikethrough: bool,
+}
+
+impl Style {
+    const fn color(color: Color) -> Self {
+        Self {
+            color: Some(color),
+            bold: false,
+            italic: false,
+            underlined: false,
+            strikethrough: false,
+        }
+    }
+
+    fn is_plain(&self) -> bool { *self == Self::default() }
+}
+
+/// A run of text sharing the same style.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Segment {
+    pub text: String,
+    pub style: Style,
+}
+
+/// Text split into styled segments.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct FormattedText {
+    pub segments: Vec<Segment>,
+}
+
+impl FormattedText {
+    /// Parse the formatting codes of `text`.
+    pub fn parse(text: &str, dialect: Dialect) -> Self {
+        let mut formatted = Self::default();
+        match dialect {
+            Dialect::None => formatted.push(text, Style::default()),
+            Dialect::Quake => formatted.parse_quake(text),
+            Dialect::Unreal2 => formatted.parse_unreal2(text),
+            Dialect::Minecraft => formatted.parse_minecraft(text),
+            Dialect::Mindustry => formatted.parse_mindustry(text),
+        }
+
+        formatted
+    }
+
+    /// The text without any formatting.
+    pub fn to_plain(&self) -> String { self.segments.iter().map(|s| s.text.as_str()).collect() }
+
+    /// The text with its styles as ANSI escape sequences.
+    pub fn to_ansi(&self) -> String {
+        let mut ansi = String::new();
+        for segment in &self.segments {
+            if segment.style.is_plain() {
+                ansi.push_str(&segment.text);
+                continue;
+            }
+
+            let style = &segment.style;
+            let mut codes = Vec::new();
+            if style.bold {
+                codes.push("1".to_string());
+            }
+            if style.italic {
+                codes.push("3".to_string());
+            }
+            if style.underlined {
+                codes.push("4".to_string());
+            }
+            if style.strikethrough {
+                codes.push("9".to_string());
+            }
+            if let Some(color) = style.color {
+                codes.push(format!("38;2;{};{};{}", color.r, color.g, color.b));
+            }
+
+            ansi.push_str(&format!("\x1b[{}m{}\x1b[0m", codes.join(";"), segment.text));
+        }
+
+        ansi
+    }
+
+    /// The text escaped for HTML, styled segments are wrapped in `<span>`s.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        for segment in &self.segments {
+            let text = escape_html(&segment.text);
+            if segment.style.is_plain() {
+                html.push_str(&text);
+                continue;
+            }
+
+            let style = &segment.style;
+            let mut css = Vec::new();
+            if let Some(color) = style.color {
+                css.push(format!(
+                    "color:#{:02x}{:02x}{:02x}",
+                    color.r, color.g, color.b
+                ));
+            }
+            if style.bold {
+                css.push("font-weight:bold".to_string());
+            }
+            if style.italic {
+                css.push("font-style:italic".to_string());
+            }
+            match (style.underlined, style.strikethrough) {
+                (true, true) => css.push("text-decoration:underline line-through".to_string()),
+                (true, false) => css.push("text-decoration:underline".to_string()),
+                (false, true) => css.push("text-decoration:line-through".to_string()),
+                (false, false) => {}
+            }
+
+            html.push_str(&format!(
+                "<span style=\"{}\">{}</span>",
+                css.join(";"),
+                text
+            ));
+        }
+
+        html
+    }
+
+    /// Render the text in the given format, [TextFormat::Raw] gives the
+    /// segments back without any formatting (as the original codes are lost).
+    pub fn render(&self, format: TextFormat) -> String {
+        match format {
+            TextFormat::Raw | TextFormat::Plain => self.to_plain(),
+            TextFormat::Ansi => self.to_ansi(),
+            TextFormat::Html => self.to_html(),
+        }
+    }
+
+    /// Append text, merging it with the last segment if the style matches.
+    fn push(&mut self, text: &str, style: Style) {
+        if text.is_empty() {
+            return;
+        }
+
+        match self.segments.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(text),
+            _ => {
+                self.segments.push(Segment {
+                    text: text.to_string(),
+                    style,
+                })
+            }
+        }
+    }
+
+    fn push_char(&mut self, c: char, style: Style) { self.push(c.encode_utf8(&mut [0; 4]), style) }
+
+    fn parse_quake(&mut self, text: &str) {
+        let mut style = Style::default();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match chars.peek() {
+                // Same as ioquake3's Q_IsColorString, the index wraps around the 8 colors.
+                Some(&code) if c == '^' && code.is_ascii_alphanumeric() => {
+                    chars.next();
+                    let index = (code as u8).wrapping_sub(b'0') & 7;
+                    style = Style::color(QUAKE_COLORS[usize::from(index)]);
+                }
+                _ => self.push_char(c, style),
+            }
+        }
+    }
+
+    fn parse_unreal2(&mut self, text: &str) {
+        let mut style = Style::default();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                // 0x1b is followed by the red, green and blue components as characters.
+                '\x1b' => {
+                    let mut component = || {
+                        chars
+                            .next()
+                            .map(|c| u8::try_from(u32::from(c)).unwrap_or(u8::MAX))
+                    };
+                    if let (Some(r), Some(g), Some(b)) = (component(), component(), component()) {
+                        style = Style::color(Color::new(r, g, b));
+                    }
+                }
+                // Other control characters are never printed.
+                '\x01' ..= '\x1a' => {}
+                _ => self.push_char(c, style),
+            }
+        }
+    }
+
+    fn parse_minecraft(&mut self, text: &str) {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with(['{', '[', '"']) {
+            if let Ok(component) = serde_json::from_str::<Value>(trimmed) {
+                self.parse_minecraft_component(&component, Style::default());
+                return;
+            }
+        }
+
+        self.parse_minecraft_legacy(text, Style::default());
+    }
+
+    fn parse_minecraft_component(&mut self, component: &Value, parent: Style) {
+        match component {
+            Value::String(text) => self.parse_minecraft_legacy(text, parent),
+            Value::Array(components) => {
+                for component in components {
+                    self.parse_minecraft_component(component, parent);
+                }
+            }
+            Value::Object(object) => {
+                let mut style = parent;
+                if let Some(color) = object
+                    .get("color")
+                    .and_then(Value::as_str)
+                    .and_then(minecraft_color)
+                {
+                    style.color = Some(color);
+                }
+                let flag = |name: &str, inherited: bool| {
+                    object
+                        .get(name)
+                        .and_then(Value::as_bool)
+                        .unwrap_or(inherited)
+                };
+                style.bold = flag("bold", style.bold);
+                style.italic = flag("italic", style.italic);
+                style.underlined = flag("underlined", style.underlined);
+                style.strikethrough = flag("strikethrough", style.strikethrough);
+
+                if let Some(text) = object
+                    .get("text")
+                    .or_else(|| object.get("translate"))
+                    .and_then(Value::as_str)
+                {
+                    self.parse_minecraft_legacy(text, style);
+                }
+
+                if let Some(extra) = object.get("extra") {
+                    self.parse_minecraft_component(extra, style);
+                }
+            }
+            Value::Null => {}
+            other => self.push(&other.to_string(), parent),
+        }
+    }
+
+    fn parse_minecraft_legacy(&mut self, text: &str, base: Style) {
+        let mut style = base;
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c != '§' {
+                self.push_char(c, style);
+                continue;
+            }
+
+            // Like the vanilla client, unknown codes are dropped.
+            match chars.next().map(|code| code.to_ascii_lowercase()) {
+                Some(code @ ('0' ..= '9' | 'a' ..= 'f')) => {
+                    let index = code.to_digit(16).unwrap_or_default() as usize;
+                    style = Style::color(MINECRAFT_COLORS[index].1);
+                }
+                Some('l') => style.bold = true,
+                Some('m') => style.strikethrough = true,
+                Some('n') => style.underlined = true,
+                Some('o') => style.italic = true,
+                Some('r') => style = base,
+                _ => {}
+            }
+        }
+    }
+
+    fn parse_mindustry(&mut self, text: &str) {
+        let mut stack: Vec<Style> = Vec::new();
+        let mut style = Style::default();
+        let mut rest = text;
+        while let Some(start) = rest.find('[') {
+            self.push(&rest[.. start], style);
+            rest = &rest[start ..];
+
+            // `[[` is an escaped bracket.
+            if rest.starts_with("[[") {
+                self.push("[", style);
+                rest = &rest[2 ..];
+                continue;
+            }
+
+            let Some(end) = rest.find(']') else {
+                break;
+            };
+
+            let tag = &rest[1 .. end];
+            if tag.is_empty() {
+                style = stack.pop().unwrap_or_default();
+            } else if let Some(color) = mindustry_color(tag) {
+                stack.push(style);
+                style = Style::color(color);
+            } else {
+                // Unknown tags are not markup, keep them as text.
+                self.push(&rest[..= end], style);
+            }
+
+            rest = &rest[end + 1 ..];
+        }
+
+        self.push(rest, style);
+    }
+}
+
+/// Parse `text` and render it in `format` (with [TextFormat::Raw] the text is
+/// returned untouched).
+pub fn normalize(text: &str, dialect: Dialect, format: TextFormat) -> String {
+    match format {
+        TextFormat::Raw => text.to_string(),
+        format => FormattedText::parse(text, dialect).render(format),
+    }
+}
+
+/// In place version of [normalize].
+pub(crate) fn normalize_in_place(text: &mut String, dialect: Dialect, format: TextFormat) {
+    if format != TextFormat::Raw {
+        *text = normalize(text, dialect, format);
+    }
+}
+
+/// Types holding server or player names that can be normalized.
+pub trait NormalizeText {
+    /// Rewrite the server and player names (from their raw form) using
+    /// `format`.
+    fn normalize_text(&mut self, format: TextFormat);
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+const QUAKE_COLORS: [Color; 8] = [
+    Color::from_hex(0x000000),
+    Color::from_hex(0xff0000),
+    Color::from_hex(0x00ff00),
+    Color::from_hex(0xffff00),
+    Color::from_hex(0x0000ff),
+    Color::from_hex(0x00ffff),
+    Color::from_hex(0xff00ff),
+    Color::from_hex(0xffffff),
+];
+
+/// [Reference](https://minecraft.wiki/w/Formatting_codes#Color_codes)
+const MINECRAFT_COLORS: [(&str, Color); 16] = [
+    ("black", Color::from_hex(0x000000)),
+    ("dark_blue", Color::from_hex(0x0000aa)),
+    ("dark_green", Color::from_hex(0x00aa00)),
+    ("dark_aqua", Color::from_hex(0x00aaaa)),
+    ("dark_red", Color::from_hex(0xaa0000)),
+    ("dark_purple", Color::from_hex(0xaa00aa)),
+    ("gold", Color::from_hex(0xffaa00)),
+    ("gray", Color::from_hex(0xaaaaaa)),
+    ("dark_gray", Color::from_hex(0x555555)),
+    ("blue", Color::from_hex(0x5555ff)),
+    ("green", Color::from_hex(0x55ff55)),
+    ("aqua", Color::from_hex(0x55ffff)),
+    ("red", Color::from_hex(0xff5555)),
+    ("light_purple", Color::from_hex(0xff55ff)),
+    ("yellow", Color::from_hex(0xffff55)),
+    ("white", Color::from_hex(0xffffff)),
+];
+
+fn minecraft_color(name: &str) -> Option<Color> {
+    match name.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 => Color::parse_hex(hex),
+        Some(_) => None,
+        None => {
+            MINECRAFT_COLORS
+                .iter()
+                .find(|(color_name, _)| *color_name == name)
+                .map(|(_, color)| *color)
+        }
+    }
+}
+
+/// [Reference](https://github.com/Anuken/Arc/blob/master/arc-core/src/arc/graphics/Colors.java)
+const MINDUSTRY_COLORS: [(&str, Color); 37] = [
+    ("black", Color::from_hex(0x000000)),
+    ("white", Color::from_hex(0xffffff)),
+    ("lightgray", Color::from_hex(0xbfbfbf)),
+    ("light_gray", Color::from_hex(0xbfbfbf)),
+    ("gray", Color::from_hex(0x7f7f7f)),
+    ("darkgray", Color::from_hex(0x3f3f3f)),
+    ("dark_gray", Color::from_hex(0x3f3f3f)),
+    ("blue", Color::from_hex(0x0000ff)),
+    ("navy", Color::from_hex(0x00007f)),
+    ("royal", Color::from_hex(0x4169e1)),
+    ("slate", Color::from_hex(0x708090)),
+    ("sky", Color::from_hex(0x87ceeb)),
+    ("cyan", Color::from_hex(0x00ffff)),
+    ("teal", Color::from_hex(0x007f7f)),
+    ("green", Color::from_hex(0x00ff00)),
+    ("acid", Color::from_hex(0x7fff00)),
+    ("lime", Color::from_hex(0x32cd32)),
+    ("forest", Color::from_hex(0x228b22)),
+    ("olive", Color::from_hex(0x6b8e23)),
+    ("yellow", Color::from_hex(0xffff00)),
+    ("gold", Color::from_hex(0xffd700)),
+    ("goldenrod", Color::from_hex(0xdaa520)),
+    ("orange", Color::from_hex(0xffa500)),
+    ("brown", Color::from_hex(0x8b4513)),
+    ("tan", Color::from_hex(0xd2b48c)),
+    ("brick", Color::from_hex(0xb22222)),
+    ("red", Color::from_hex(0xff0000)),
+    ("scarlet", Color::from_hex(0xff341c)),
+    ("crimson", Color::from_hex(0xdc143c)),
+    ("coral", Color::from_hex(0xff7f50)),
+    ("salmon", Color::from_hex(0xfa8072)),
+    ("pink", Color::from_hex(0xff69b4)),
+    ("magenta", Color::from_hex(0xff00ff)),
+    ("purple", Color::from_hex(0xa020f0)),
+    ("violet", Color::from_hex(0xee82ee)),
+    ("maroon", Color::from_hex(0xb03060)),
+    ("accent", Color::from_hex(0xffd37f)),
+];
+
+fn mindustry_color(tag: &str) -> Option<Color> {
+    match tag.strip_prefix('#') {
+        Some(hex) => Color::parse_hex(hex),
+        None => {
+            MINDUSTRY_COLORS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(tag))
+                .map(|(_, color)| *color)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quake_codes() {
+        let text = FormattedText::parse("^1Red^7 White 100^", Dialect::Quake);
+        assert_eq!(text.to_plain(), "Red White 100^");
+        assert_eq!(text.segments[0].style.color, Some(Color::new(255, 0, 0)));
+        assert_eq!(
+            text.segments[1].style.color,
+            Some(Color::new(255, 255, 255))
+        );
+    }
+
+    #[test]
+    fn unreal2_sequences() {
+        let text = FormattedText::parse(
+            "\x1b\u{ff}\x01\x01Red\x1b\x01\x01\u{ff}Blue\x02",
+            Dialect::Unreal2,
+        );
+        assert_eq!(text.to_plain(), "RedBlue");
+        assert_eq!(text.segments[0].style.color, Some(Color::new(255, 1, 1)));
+        assert_eq!(text.segments[1].style.color, Some(Color::new(1, 1, 255)));
+    }
+
+    #[test]
+    fn minecraft_legacy_codes() {
+        let text = FormattedText::parse("§aGreen §lBold§r plain", Dialect::Minecraft);
+        assert_eq!(text.to_plain(), "Green Bold plain");
+        assert_eq!(
+            text.to_ansi(),
+            "\x1b[38;2;85;255;85mGreen \x1b[0m\x1b[1;38;2;85;255;85mBold\x1b[0m plain"
+        );
+    }
+
+    #[test]
+    fn minecraft_json_component() {
+        let component = r##"{"text":"A ","extra":[{"text":"Minecraft","color":"gold","bold":true},"§cServer"]}"##;
+        let text = FormattedText::parse(component, Dialect::Minecraft);
+        assert_eq!(text.to_plain(), "A MinecraftServer");
+        assert_eq!(
+            text.to_html(),
+            "A <span style=\"color:#ffaa00;font-weight:bold\">Minecraft</span><span \
+             style=\"color:#ff5555\">Server</span>"
+        );
+
+        assert_eq!(
+            normalize("\"Just text\"", Dialect::Minecraft, TextFormat::Plain),
+            "Just text"
+        );
+    }
+
+    #[test]
+    fn mindustry_markup() {
+        let text = FormattedText::parse(
+            "[red]Red[] [[not] [#00ff00]Green[unknown]",
+            Dialect::Mindustry,
+        );
+        assert_eq!(text.to_plain(), "Red [not] Green[unknown]");
+        assert_eq!(text.segments[0].style.color, Some(Color::new(255, 0, 0)));
+        assert_eq!(text.segments[2].style.color, Some(Color::new(0, 255, 0)));
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        assert_eq!(
+            normalize("<b>&\"'", Dialect::None, TextFormat::Html),
+            "&lt;b&gt;&amp;&quot;&#39;"
+        );
+    }
+
+    #[test]
+    fn raw_is_untouched() {
+        assert_eq!(
+            normalize("^1Name", Dialect::Quake, TextFormat::Raw),
+            "^1Name"
+        );
+    }
+}
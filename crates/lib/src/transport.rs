@@ -0,0 +1,201 @@
+//! Pluggable transports, to send the queries over something else than the
+//! built-in sockets (rate-limited, instrumented or in-memory ones, for
+//! example).
+//!
+//! Set a [Connector] with
+//! [SocketSettings::set_connector](crate::SocketSettings::set_connector) and
+//! every UDP and TCP socket of the queries given these settings is created by
+//! it.
+//!
+//! The HTTP client doesn't use it: the HTTP based games, protocols and
+//! services (Eco, Epic, Minetest, the Valve Web API...) keep connecting on
+//! their own.
+//!
+//! ```
+//! use gamedig::transport::{Connector, Transport, TransportKind};
+//! use gamedig::{GDResult, SocketSettings, TimeoutSettings, GAMES};
+//! use std::net::SocketAddr;
+//! use std::sync::Arc;
+//!
+//! /// Answers every request with a fixed packet.
+//! struct Canned(&'static [u8]);
+//!
+//! impl Transport for Canned {
+//!     fn send(&mut self, _data: &[u8]) -> GDResult<()> { Ok(()) }
+//!
+//!     fn receive(&mut self, _size: Option<usize>) -> GDResult<Vec<u8>> { Ok(self.0.to_vec()) }
+//! }
+//!
+//! struct CannedConnector;
+//!
+//! impl Connector for CannedConnector {
+//!     fn connect(
+//!         &self,
+//!         _kind: TransportKind,
+//!         _address: &SocketAddr,
+//!         _timeout_settings: &Option<TimeoutSettings>,
+//!     ) -> GDResult<Box<dyn Transport>> {
+//!         Ok(Box::new(Canned(b"\xFF\xFF\xFF\xFFn\\hostname\\Test\\map\\e1m1\\maxclients\\8\n")))
+//!     }
+//! }
+//!
+//! let socket_settings = SocketSettings::new().set_connector(Arc::new(CannedConnector));
+//! let response = gamedig::query_with_socket_settings(
+//!     GAMES.get("quake1").unwrap(),
+//!     &"127.0.0.1".parse().unwrap(),
+//!     None,
+//!     None,
+//!     None,
+//!     &socket_settings,
+//! )
+//! .unwrap();
+//! assert_eq!(response.name(), Some("Test"));
+//! ```
+
+use crate::{protocols::types::TimeoutSettings, GDResult};
+
+use std::net::SocketAddr;
+
+/// The kind of socket a protocol asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransportKind {
+    /// Datagrams, each [Transport::receive] returns one packet.
+    Udp,
+    /// A stream, [Transport::receive] returns everything the server sent until
+    /// it closed the connection.
+    Tcp,
+}
+
+/// A connection to a server, as created by a [Connector].
+///
+/// Errors should use the same kinds as the built-in sockets (mainly
+/// GDErrorKind::[PacketSend](crate::GDErrorKind::PacketSend) and
+/// GDErrorKind::[PacketReceive](crate::GDErrorKind::PacketReceive), timeouts
+/// being the later with an [std::io::ErrorKind::TimedOut] source) so the
+/// protocols retry and report them the usual way.
+pub trait Transport: Send {
+    /// Send data to the server.
+    fn send(&mut self, data: &[u8]) -> GDResult<()>;
+
+    /// Receive data from the server, `size` being the expected size if the
+    /// protocol knows it.
+    fn receive(&mut self, size: Option<usize>) -> GDResult<Vec<u8>>;
+}
+
+/// Creates the [Transport]s used by the queries.
+///
+/// Async queries call it and the transports on tokio's blocking threads.
+pub trait Connector: Send + Sync {
+    /// Open a transport of `kind` to `address`, the timeout settings are the
+    /// ones of the query (for the transport to apply as it sees fit).
+    fn connect(
+        &self,
+        kind: TransportKind,
+        address: &SocketAddr,
+        timeout_settings: &Option<TimeoutSettings>,
+    ) -> GDResult<Box<dyn Transport>>;
+}
+
+/// A [Connector] for tests: its transports answer with the queued packets
+/// (whatever was sent to them) and then fail with the final error, by default
+/// a [PacketReceive](crate::GDErrorKind::PacketReceive) as if the server
+/// stopped answering.
+#[cfg(test)]
+pub(crate) struct Scripted {
+    results: Vec<Result<Vec<u8>, crate::GDErrorKind>>,
+    final_error: crate::GDErrorKind,
+    port: Option<u16>,
+    connections: std::sync::Mutex<Vec<(TransportKind, SocketAddr)>>,
+}
+
+#[cfg(test)]
+impl Scripted {
+    pub(crate) fn new() -> Self {
+        Self {
+            results: Vec::new(),
+            final_error: crate::GDErrorKind::PacketReceive,
+            port: None,
+            connections: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Queue a packet to answer with.
+    pub(crate) fn add_packet(mut self, packet: impl Into<Vec<u8>>) -> Self {
+        self.results.push(Ok(packet.into()));
+        self
+    }
+
+    /// Queue packets to answer with.
+    pub(crate) fn add_packets<P: Into<Vec<u8>>>(self, packets: impl IntoIterator<Item = P>) -> Self {
+        packets.into_iter().fold(self, Self::add_packet)
+    }
+
+    /// Queue an error, the packets queued after it are still answered.
+    pub(crate) fn add_error(mut self, kind: crate::GDErrorKind) -> Self {
+        self.results.push(Err(kind));
+        self
+    }
+
+    /// Set the error of every receive once the queue is exhausted.
+    pub(crate) const fn set_final_error(mut self, kind: crate::GDErrorKind) -> Self {
+        self.final_error = kind;
+        self
+    }
+
+    /// Only answer on `port`, the transports to other ports never answer
+    /// (failing with a [PacketReceive](crate::GDErrorKind::PacketReceive)).
+    #[cfg_attr(not(feature = "games"), allow(dead_code))]
+    pub(crate) const fn set_port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// The transports created so far.
+    pub(crate) fn connections(&self) -> Vec<(TransportKind, SocketAddr)> { self.connections.lock().unwrap().clone() }
+}
+
+#[cfg(test)]
+impl Connector for Scripted {
+    fn connect(
+        &self,
+        kind: TransportKind,
+        address: &SocketAddr,
+        _timeout_settings: &Option<TimeoutSettings>,
+    ) -> GDResult<Box<dyn Transport>> {
+        self.connections.lock().unwrap().push((kind, *address));
+
+        Ok(Box::new(match self.port {
+            Some(port) if port != address.port() => {
+                ScriptedTransport {
+                    results: std::collections::VecDeque::new(),
+                    final_error: crate::GDErrorKind::PacketReceive,
+                }
+            }
+            _ => {
+                ScriptedTransport {
+                    results: self.results.iter().cloned().collect(),
+                    final_error: self.final_error,
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+struct ScriptedTransport {
+    results: std::collections::VecDeque<Result<Vec<u8>, crate::GDErrorKind>>,
+    final_error: crate::GDErrorKind,
+}
+
+#[cfg(test)]
+impl Transport for ScriptedTransport {
+    fn send(&mut self, _data: &[u8]) -> GDResult<()> { Ok(()) }
+
+    fn receive(&mut self, _size: Option<usize>) -> GDResult<Vec<u8>> {
+        match self.results.pop_front() {
+            Some(Ok(packet)) => Ok(packet),
+            Some(Err(kind)) => Err(kind.context("Scripted error")),
+            None => Err(self.final_error.context("End of the script")),
+        }
+    }
+}
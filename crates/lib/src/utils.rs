@@ -1,6 +1,11 @@
-use crate::GDErrorKind::{PacketOverflow, PacketReceive, PacketSend, PacketUnderflow};
-use crate::GDResult;
+use crate::protocols::types::{CommonResponse, RetryPolicy};
+use crate::GDErrorKind::{DeadlineExceeded, PacketOverflow, PacketUnderflow};
+use crate::{GDError, GDResult};
+use std::cell::Cell;
 use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
 
 pub fn error_by_expected_size(expected: usize, size: usize) -> GDResult<()> {
     match size.cmp(&expected) {
@@ -12,81 +17,267 @@ pub fn error_by_expected_size(expected: usize, size: usize) -> GDResult<()> {
 
 pub const fn u8_lower_upper(n: u8) -> (u8, u8) { (n & 15, n >> 4) }
 
-/// Run a closure `retry_count+1` times while it returns [PacketReceive] or
-/// [PacketSend] errors, returning the first success, other Error, or after
-/// `retry_count+1` tries the last [PacketReceive] or [PacketSend] error.
-pub fn retry_on_timeout<T>(mut retry_count: usize, mut fetch: impl FnMut() -> GDResult<T>) -> GDResult<T> {
-    let mut last_err = PacketReceive.context("Retry count was 0");
-    retry_count += 1;
-    while retry_count > 0 {
-        last_err = match fetch() {
+thread_local! {
+    /// The factor the timeouts of the sockets used by the current try of
+    /// [retry_on_timeout] are multiplied by.
+    static TIMEOUT_FACTOR: Cell<u32> = const { Cell::new(1) };
+}
+
+#[cfg(feature = "async")]
+tokio::task_local! {
+    /// Async version of [TIMEOUT_FACTOR], for [retry_on_timeout_async].
+    pub(crate) static ASYNC_TIMEOUT_FACTOR: u32;
+}
+
+/// The factor the timeouts of sockets should currently be multiplied by, as
+/// per the [RetryPolicy] of the try being made.
+pub(crate) fn timeout_factor() -> u32 { TIMEOUT_FACTOR.with(Cell::get) }
+
+/// Async version of [timeout_factor].
+#[cfg(feature = "async")]
+pub(crate) fn async_timeout_factor() -> u32 { ASYNC_TIMEOUT_FACTOR.try_with(|factor| *factor).unwrap_or(1) }
+
+/// The tries made following a [RetryPolicy].
+pub(crate) struct Retries {
+    policy: RetryPolicy,
+    started: Instant,
+    attempt: usize,
+}
+
+impl Retries {
+    pub(crate) fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            started: Instant::now(),
+            attempt: 1,
+        }
+    }
+
+    /// The number of the current try, starting at 1.
+    pub(crate) const fn attempt(&self) -> usize { self.attempt }
+
+    /// The factor the timeouts of the current try are multiplied by.
+    pub(crate) fn timeout_factor(&self) -> u32 { self.policy.get_attempt_timeout_factor(self.attempt) }
+
+    /// Decide what to do after the current try failed with `error`: retry
+    /// after the returned delay or give up (always the case once the query
+    /// deadline expired).
+    pub(crate) fn next_delay(&mut self, error: &GDError) -> Option<Duration> {
+        if error.kind == DeadlineExceeded
+            || !self.policy.is_retryable(&error.kind)
+            || self.attempt > self.policy.get_retries()
+        {
+            return None;
+        }
+
+        let delay = self
+            .policy
+            .get_delay(self.attempt)
+            .map_or(Duration::ZERO, |delay| {
+                match self.policy.get_jitter() {
+                    true => jittered(delay),
+                    false => delay,
+                }
+            });
+
+        if let Some(deadline) = self.policy.get_deadline() {
+            if self.started.elapsed() + delay >= deadline {
+                return None;
+            }
+        }
+
+        self.attempt += 1;
+        Some(delay)
+    }
+}
+
+/// A random number, not suited for cryptography.
+pub(crate) fn random() -> u64 {
+    // Each RandomState is seeded differently, which is random enough here.
+    RandomState::new().build_hasher().finish()
+}
+
+/// A random duration between half and all of `delay`.
+fn jittered(delay: Duration) -> Duration { delay.mul_f64(0.5 + (random() as f64 / u64::MAX as f64) / 2.0) }
+
+/// Run a closure while it fails with errors the [RetryPolicy] retries (by
+/// default [PacketReceive] or [PacketSend]), up to its number of retries,
+/// returning the first success, other Error, or the last error once out of
+/// retries.
+///
+/// Returned errors have the number of the try they happened on as context.
+pub fn retry_on_timeout<T>(retry_policy: RetryPolicy, mut fetch: impl FnMut() -> GDResult<T>) -> GDResult<T> {
+    let mut retries = Retries::new(retry_policy);
+    loop {
+        let previous_factor = TIMEOUT_FACTOR.with(|factor| factor.replace(retries.timeout_factor()));
+        let result = fetch();
+        TIMEOUT_FACTOR.with(|factor| factor.set(previous_factor));
+
+        let error = match result {
             Ok(r) => return Ok(r),
-            Err(e) if e.kind == PacketReceive || e.kind == PacketSend => e,
-            Err(e) => return Err(e),
+            Err(e) => e.with_attempt(retries.attempt()),
         };
-        retry_count -= 1;
+
+        match retries.next_delay(&error) {
+            Some(delay) if !delay.is_zero() => std::thread::sleep(delay),
+            Some(_) => {}
+            None => return Err(error),
+        }
     }
-    Err(last_err)
 }
 
-/// Run gather_fn based on the value of gather_toggle.
+/// Async version of [retry_on_timeout]: await `fetch` while it fails with
+/// errors the [RetryPolicy] retries.
+///
+/// This is a macro (rather than a function taking a closure) so that `fetch`
+/// can borrow from its surroundings, e.g. `self.get_request_data_impl()`.
+#[cfg(feature = "async")]
+macro_rules! retry_on_timeout_async {
+    ($retry_policy: expr, $fetch: expr) => {{
+        let mut retries = crate::utils::Retries::new($retry_policy);
+        loop {
+            let result = crate::utils::ASYNC_TIMEOUT_FACTOR
+                .scope(retries.timeout_factor(), $fetch)
+                .await;
+
+            let error = match result {
+                Ok(r) => break Ok(r),
+                Err(e) => e.with_attempt(retries.attempt()),
+            };
+
+            match retries.next_delay(&error) {
+                Some(delay) if !delay.is_zero() => tokio::time::sleep(delay).await,
+                Some(_) => {}
+                None => break Err(error),
+            }
+        }
+    }};
+}
+
+#[cfg(feature = "async")]
+pub(crate) use retry_on_timeout_async;
+
+/// Run gather_fn based on the value of gather_toggle, returning the gathered
+/// section (if any) and what happened to it.
 ///
 /// # Parameters
 /// - `gather_toggle` should be an expression resolving to a
 ///   [crate::protocols::types::GatherToggle].
 /// - `gather_fn` should be an expression that returns a [crate::GDResult].
+/// - `enforced_failure` should be a mutable `Option<GDError>` binding, shared
+///   by the sections of a response, holding the error of a failed
+///   [Enforce](crate::protocols::types::GatherToggle::Enforce) section (pass it
+///   and the response to [enforce_sections] once the response is built).
 ///
 /// # States
-/// - [DontGather](crate::protocols::types::GatherToggle::DontGather) - Don't
-///   run gather function, returns None.
-/// - [AttemptGather](crate::protocols::types::GatherToggle::AttemptGather) -
-///   Runs the gather function, if it returns an error return None, else return
-///   Some.
-/// - [Required](crate::protocols::types::GatherToggle::Required) - Runs the
-///   gather function, if it returns an error propagate it using the `?`
-///   operator, else return Some.
+/// - [Skip](crate::protocols::types::GatherToggle::Skip) - Don't run gather
+///   function, returns None and
+///   [NotRequested](crate::protocols::types::SectionOutcome::NotRequested).
+/// - [Try](crate::protocols::types::GatherToggle::Try) - Runs the gather
+///   function, if it returns an error return None and the
+///   [Failed](crate::protocols::types::SectionOutcome::Failed) outcome, else
+///   return Some.
+/// - [Enforce](crate::protocols::types::GatherToggle::Enforce) - Runs the
+///   gather function, if it returns an error keep it in `enforced_failure` and
+///   return None and the
+///   [Failed](crate::protocols::types::SectionOutcome::Failed) outcome, else
+///   return Some.
+///
+/// Once an enforced section failed, the next ones aren't gathered (returning
+/// None and [NotRequested](crate::protocols::types::SectionOutcome::NotRequested)).
 ///
 /// # Examples
 ///
 /// ```ignore,Doctests cannot access private items
-/// use gamedig::protocols::types::GatherToggle;
-/// use gamedig::utils::maybe_gather;
+/// use gamedig::protocols::types::{GatherToggle, SectionOutcome};
+/// use gamedig::utils::gather_section;
 ///
 /// let query_fn = || { Err("Query error") };
+/// let mut enforced_failure = None;
 ///
 /// // query_fn() is not called
-/// let response = maybe_gather!(GatherToggle::DontGather, query_fn());
+/// let (response, outcome) = gather_section!(GatherToggle::Skip, query_fn(), enforced_failure);
 /// assert!(response.is_none());
+/// assert_eq!(outcome, SectionOutcome::NotRequested);
 ///
 /// // query_fn() is called but Err is converted to None
-/// let response = maybe_gather!(GatherToggle::AttemptGather, query_fn());
+/// let (response, outcome) = gather_section!(GatherToggle::Try, query_fn(), enforced_failure);
 /// assert!(response.is_none());
+/// assert!(outcome.is_failed());
 ///
-/// // query_fn() is called and Err is propagated.
-/// let response = maybe_gather!(GatherToggle::Required, query_fn());
-/// unreachable!();
+/// // query_fn() is called and Err is kept to fail the query with.
+/// let (response, outcome) = gather_section!(GatherToggle::Enforce, query_fn(), enforced_failure);
+/// assert!(response.is_none());
+/// assert!(outcome.is_failed());
+/// assert!(enforced_failure.is_some());
 /// ```
-macro_rules! maybe_gather {
-    ($gather_toggle: expr, $gather_fn: expr) => {
+macro_rules! gather_section {
+    ($gather_toggle: expr, $gather_fn: expr, $enforced_failure: ident) => {
         match $gather_toggle {
-            crate::protocols::types::GatherToggle::Skip => None,
-            crate::protocols::types::GatherToggle::Try => $gather_fn.ok(),
-            crate::protocols::types::GatherToggle::Enforce => Some($gather_fn?),
+            crate::protocols::types::GatherToggle::Skip => {
+                (None, crate::protocols::types::SectionOutcome::NotRequested)
+            }
+            _ if $enforced_failure.is_some() => (None, crate::protocols::types::SectionOutcome::NotRequested),
+            crate::protocols::types::GatherToggle::Try => {
+                match $gather_fn {
+                    Ok(section) => {
+                        (
+                            Some(section),
+                            crate::protocols::types::SectionOutcome::Succeeded,
+                        )
+                    }
+                    Err(e) => {
+                        (
+                            None,
+                            crate::protocols::types::SectionOutcome::from_error(&e),
+                        )
+                    }
+                }
+            }
+            crate::protocols::types::GatherToggle::Enforce => {
+                match $gather_fn {
+                    Ok(section) => {
+                        (
+                            Some(section),
+                            crate::protocols::types::SectionOutcome::Succeeded,
+                        )
+                    }
+                    Err(e) => {
+                        let outcome = crate::protocols::types::SectionOutcome::from_error(&e);
+                        $enforced_failure = Some(e);
+                        (None, outcome)
+                    }
+                }
+            }
         }
     };
 }
 
-pub(crate) use maybe_gather;
+pub(crate) use gather_section;
+
+/// Fail with the error of a section gathered with
+/// [Enforce](crate::protocols::types::GatherToggle::Enforce) (see
+/// [gather_section]), if one failed, carrying the response gathered so far.
+pub fn enforce_sections<R: CommonResponse + Send + Sync + 'static>(
+    response: R,
+    enforced_failure: Option<GDError>,
+) -> GDResult<R> {
+    match enforced_failure {
+        Some(error) => Err(error.with_partial_response(response)),
+        None => Ok(response),
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::retry_on_timeout;
     use crate::{
-        protocols::types::GatherToggle,
+        protocols::types::{GatherToggle, RetryPolicy, SectionOutcome},
         GDError,
         GDErrorKind::{self, PacketBad, PacketReceive, PacketSend},
         GDResult,
     };
+    use std::time::{Duration, Instant};
 
     #[test]
     fn u8_lower_upper() {
@@ -102,13 +293,13 @@ mod tests {
 
     #[test]
     fn retry_success_on_first() {
-        let r = retry_on_timeout(0, || Ok(()));
+        let r = retry_on_timeout(RetryPolicy::new(0), || Ok(()));
         assert!(r.is_ok());
     }
 
     #[test]
     fn retry_no_success() {
-        let r: GDResult<()> = retry_on_timeout(100, || Err(PacketSend.context("test")));
+        let r: GDResult<()> = retry_on_timeout(RetryPolicy::new(100), || Err(PacketSend.context("test")));
         assert!(r.is_err());
         assert_eq!(r.unwrap_err().kind, PacketSend);
     }
@@ -116,7 +307,7 @@ mod tests {
     #[test]
     fn retry_success_on_third() {
         let mut i = 0u8;
-        let r = retry_on_timeout(2, || {
+        let r = retry_on_timeout(RetryPolicy::new(2), || {
             i += 1;
             if i < 3 {
                 Err(PacketReceive.context("test"))
@@ -130,7 +321,7 @@ mod tests {
     #[test]
     fn retry_success_on_third_but_less_retries() {
         let mut i = 0u8;
-        let r = retry_on_timeout(1, || {
+        let r = retry_on_timeout(RetryPolicy::new(1), || {
             i += 1;
             if i < 3 {
                 Err(PacketReceive.context("test"))
@@ -139,13 +330,15 @@ mod tests {
             }
         });
         assert!(r.is_err());
-        assert_eq!(r.unwrap_err().kind, PacketReceive);
+        let err = r.unwrap_err();
+        assert_eq!(err.kind, PacketReceive);
+        assert_eq!(err.context.attempt, Some(2));
     }
 
     #[test]
     fn retry_with_non_timeout_error() {
         let mut i = 0u8;
-        let r = retry_on_timeout(50, || {
+        let r = retry_on_timeout(RetryPolicy::new(50), || {
             i += 1;
             match i {
                 1 => Err(PacketSend.context("test")),
@@ -154,6 +347,125 @@ mod tests {
             }
         });
         assert!(r.is_err());
+        let err = r.unwrap_err();
+        assert_eq!(err.kind, PacketBad);
+        assert_eq!(err.context.attempt, Some(2));
+    }
+
+    #[test]
+    fn retry_only_retryable_kinds() {
+        let policy = RetryPolicy::new(2).set_retryable(&[PacketBad]);
+
+        let mut i = 0u8;
+        let r: GDResult<()> = retry_on_timeout(policy, || {
+            i += 1;
+            Err(PacketBad.context("test"))
+        });
+        assert_eq!(r.unwrap_err().kind, PacketBad);
+        assert_eq!(i, 3);
+
+        let mut i = 0u8;
+        let r: GDResult<()> = retry_on_timeout(policy, || {
+            i += 1;
+            Err(PacketReceive.context("test"))
+        });
+        assert_eq!(r.unwrap_err().kind, PacketReceive);
+        assert_eq!(i, 1);
+    }
+
+    #[test]
+    fn retry_with_backoff() {
+        let policy = RetryPolicy::new(2).set_backoff(Duration::from_millis(20));
+
+        let started = Instant::now();
+        let r: GDResult<()> = retry_on_timeout(policy, || Err(PacketReceive.context("test")));
+        assert_eq!(r.unwrap_err().context.attempt, Some(3));
+        // 20ms then 40ms.
+        assert!(started.elapsed() >= Duration::from_millis(60));
+    }
+
+    #[test]
+    fn retry_stops_at_deadline() {
+        let policy = RetryPolicy::new(5)
+            .set_backoff(Duration::from_millis(50))
+            .set_deadline(Duration::from_millis(10));
+
+        let r: GDResult<()> = retry_on_timeout(policy, || Err(PacketReceive.context("test")));
+        assert_eq!(r.unwrap_err().context.attempt, Some(1));
+    }
+
+    #[test]
+    fn retry_timeout_factor() {
+        let policy = RetryPolicy::new(2).set_timeout_factor(2);
+
+        let mut factors = Vec::new();
+        let r: GDResult<()> = retry_on_timeout(policy, || {
+            factors.push(super::timeout_factor());
+            Err(PacketReceive.context("test"))
+        });
+        assert!(r.is_err());
+        assert_eq!(factors, [1, 2, 4]);
+        assert_eq!(super::timeout_factor(), 1);
+    }
+
+    #[test]
+    fn jittered() {
+        let delay = Duration::from_millis(100);
+        for _ in 0 .. 100 {
+            let jittered = super::jittered(delay);
+            assert!(jittered >= delay / 2 && jittered <= delay);
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn retry_async_success_on_third() {
+        let mut i = 0u8;
+        let mut fetch = || {
+            i += 1;
+            let current = i;
+            async move {
+                if current < 3 {
+                    Err(PacketReceive.context("test"))
+                } else {
+                    Ok(())
+                }
+            }
+        };
+        let r: GDResult<()> = retry_on_timeout_async!(RetryPolicy::new(2), fetch());
+        assert!(r.is_ok());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn retry_async_timeout_factor() {
+        let policy = RetryPolicy::new(2).set_timeout_factor(3);
+
+        let mut factors = Vec::new();
+        let r: GDResult<()> = retry_on_timeout_async!(policy, async {
+            factors.push(super::async_timeout_factor());
+            Err(PacketReceive.context("test"))
+        });
+        assert!(r.is_err());
+        assert_eq!(factors, [1, 3, 9]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn retry_async_with_non_timeout_error() {
+        let mut i = 0u8;
+        let mut fetch = || {
+            i += 1;
+            let current = i;
+            async move {
+                match current {
+                    1 => Err(PacketSend.context("test")),
+                    2 => Err(PacketBad.context("test")),
+                    _ => Ok(()),
+                }
+            }
+        };
+        let r: GDResult<()> = retry_on_timeout_async!(RetryPolicy::new(50), fetch());
         assert_eq!(r.unwrap_err().kind, PacketBad);
     }
 
@@ -163,46 +475,88 @@ mod tests {
 
     #[test]
     fn gather_success_dont_gather() -> GDResult<()> {
-        let result = maybe_gather!(GatherToggle::Skip, gather_success(5));
+        let mut enforced_failure: Option<GDError> = None;
+        let (result, _) = gather_section!(GatherToggle::Skip, gather_success(5), enforced_failure);
         assert!(result.is_none());
+        assert!(enforced_failure.is_none());
         Ok(())
     }
 
     #[test]
     fn gather_success_attempt_gather() -> GDResult<()> {
-        let result = maybe_gather!(GatherToggle::Try, gather_success(10));
+        let mut enforced_failure: Option<GDError> = None;
+        let (result, _) = gather_section!(GatherToggle::Try, gather_success(10), enforced_failure);
         assert_eq!(result, Some(10));
+        assert!(enforced_failure.is_none());
         Ok(())
     }
 
     #[test]
     fn gather_success_required() -> GDResult<()> {
-        let result = maybe_gather!(GatherToggle::Enforce, gather_success(15));
+        let mut enforced_failure: Option<GDError> = None;
+        let (result, _) = gather_section!(GatherToggle::Enforce, gather_success(15), enforced_failure);
         assert_eq!(result, Some(15));
+        assert!(enforced_failure.is_none());
         Ok(())
     }
 
     #[test]
     fn gather_fail_dont_gather() -> GDResult<()> {
-        let result = maybe_gather!(GatherToggle::Skip, gather_fail("dont"));
+        let mut enforced_failure: Option<GDError> = None;
+        let (result, _) = gather_section!(GatherToggle::Skip, gather_fail("dont"), enforced_failure);
         assert!(result.is_none());
+        assert!(enforced_failure.is_none());
         Ok(())
     }
 
     #[test]
     fn gather_fail_attempt_gather() -> GDResult<()> {
-        let result = maybe_gather!(GatherToggle::Try, gather_fail("attempt"));
+        let mut enforced_failure: Option<GDError> = None;
+        let (result, _) = gather_section!(GatherToggle::Try, gather_fail("attempt"), enforced_failure);
         assert!(result.is_none());
+        assert!(enforced_failure.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn gather_section_outcomes() -> GDResult<()> {
+        let mut enforced_failure: Option<GDError> = None;
+        let (result, outcome) = gather_section!(GatherToggle::Skip, gather_success(10), enforced_failure);
+        assert_eq!((result, outcome), (None, SectionOutcome::NotRequested));
+
+        let (result, outcome) = gather_section!(GatherToggle::Try, gather_success(10), enforced_failure);
+        assert_eq!((result, outcome), (Some(10), SectionOutcome::Succeeded));
+
+        let (result, outcome) = gather_section!(GatherToggle::Try, gather_fail("attempt"), enforced_failure);
+        assert!(result.is_none());
+        assert_eq!(
+            outcome,
+            SectionOutcome::Failed {
+                kind: PacketSend,
+                message: "attempt".to_string(),
+            }
+        );
+        assert!(outcome.is_failed());
+
+        assert!(enforced_failure.is_none());
         Ok(())
     }
 
     #[test]
     fn gather_fail_required() {
-        let inner = || {
-            let result = maybe_gather!(GatherToggle::Enforce, gather_fail("required"));
-            assert_eq!(result, Some(10));
-            Ok::<(), GDError>(())
-        };
-        assert!(inner().is_err());
+        let mut enforced_failure: Option<GDError> = None;
+        let (result, outcome) = gather_section!(
+            GatherToggle::Enforce,
+            gather_fail("required"),
+            enforced_failure
+        );
+        assert!(result.is_none());
+        assert!(outcome.is_failed());
+        assert_eq!(enforced_failure.as_ref().unwrap().kind, PacketSend);
+
+        // The sections after a failed enforced one aren't gathered.
+        let (result, outcome) = gather_section!(GatherToggle::Try, gather_success(10), enforced_failure);
+        assert_eq!((result, outcome), (None, SectionOutcome::NotRequested));
+        assert_eq!(enforced_failure.unwrap().kind, PacketSend);
     }
 }